@@ -112,6 +112,8 @@ const EMPTY: NodeTags = NodeTags {
 pub struct Annotations {
     pub tags: HashMap<NodeQId, NodeTags>,
     pub profile_summary: Option<crate::profile::ProfileSummary>,
+    /// Per iteration spans, only collected when a trace is requested.
+    pub trace: Option<Vec<crate::profile::Span>>,
 }
 
 impl Annotations {
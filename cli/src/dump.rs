@@ -23,9 +23,19 @@ pub fn handle(
             .tract_model
             .downcast_ref::<TypedModel>()
             .context("Can only profile typed models")?;
+        if sub_matches.is_present("trace") {
+            annotations.trace = Some(vec![]);
+        }
         crate::profile::profile(model, bench_limits, &mut annotations)?;
     }
 
+    if let Some(path) = sub_matches.value_of("trace") {
+        let trace = crate::export::ChromeTrace::from(annotations.trace.as_deref().unwrap_or(&[]));
+        let file =
+            std::fs::File::create(path).with_context(|| format!("Creating trace file {}", path))?;
+        serde_json::to_writer(std::io::BufWriter::new(file), &trace)?;
+    }
+
     if let Some(asserts) = &params.assertions.assert_output_facts {
         let outputs_facts: Vec<InferenceFact> = model
             .output_outlets()
@@ -80,6 +90,20 @@ pub fn handle(
         }
     }
 
+    if let Some(path) = sub_matches.value_of("onnx") {
+        #[cfg(feature = "onnx")]
+        {
+            if let Some(typed) = model.downcast_ref::<TypedModel>() {
+                let file = std::fs::File::create(path)?;
+                tract_onnx::onnx().write(typed, std::io::BufWriter::new(file))?;
+            } else {
+                bail!("Only typed model can be dumped")
+            }
+        }
+        #[cfg(not(feature = "onnx"))]
+        bail!("tract is built without ONNX support, can not dump to {}", path)
+    }
+
     if options.cost {
         let total = annotations.tags.values().sum::<NodeTags>();
         let assert =
@@ -1,4 +1,5 @@
 use crate::annotations::Annotations;
+use crate::profile::Span;
 use std::collections::HashMap;
 use tract_core::internal::*;
 
@@ -56,3 +57,151 @@ impl GraphPerfInfo {
         GraphPerfInfo { nodes, profiling_info }
     }
 }
+
+/// Chrome Trace Event Format (JSON object form), as read by chrome://tracing
+/// and Perfetto.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChromeTrace {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+    other_data: TraceMetadata,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceMetadata {
+    tract_version: &'static str,
+    iterations: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceEvent {
+    name: String,
+    cat: String,
+    ph: &'static str,
+    /// Microseconds.
+    ts: f64,
+    pid: usize,
+    tid: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<TraceArgs>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceArgs {
+    iteration: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qualified_id: Option<NodeQIdSer>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    output_shapes: Vec<Vec<usize>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    profiled_separately: bool,
+}
+
+impl ChromeTrace {
+    pub fn from(spans: &[Span]) -> ChromeTrace {
+        fn events(span: &Span, trace: &mut Vec<TraceEvent>) {
+            let event = |ph, ts: std::time::Duration, args| TraceEvent {
+                name: span.name.clone(),
+                cat: span.op.clone(),
+                ph,
+                ts: ts.as_secs_f64() * 1e6,
+                pid: 0,
+                tid: 0,
+                args,
+            };
+            let args = TraceArgs {
+                iteration: span.iteration,
+                qualified_id: span
+                    .qid
+                    .as_ref()
+                    .map(|id| NodeQIdSer(id.0.iter().cloned().collect(), id.1)),
+                output_shapes: span.output_shapes.iter().map(|s| s.to_vec()).collect(),
+                profiled_separately: span.profiled_separately,
+            };
+            trace.push(event("B", span.begin, Some(args)));
+            for child in &span.children {
+                events(child, trace);
+            }
+            trace.push(event("E", span.end, None));
+        }
+        let mut trace_events = vec![];
+        for span in spans {
+            events(span, &mut trace_events);
+        }
+        ChromeTrace {
+            trace_events,
+            display_time_unit: "ns",
+            other_data: TraceMetadata {
+                tract_version: env!("CARGO_PKG_VERSION"),
+                iterations: spans.iter().filter(|s| s.qid.is_none()).count(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::params::BenchLimits;
+    use tract_core::ops::logic::If;
+    use tract_core::ops::math;
+
+    // if cond { abs(x) } else { neg(x) }, cond being true
+    fn model_with_if() -> TractResult<TypedModel> {
+        let fact = TypedFact::dt_shape(f32::datum_type(), &[3]);
+        let mut then_body = TypedModel::default();
+        let x = then_body.add_source("x", fact.clone())?;
+        let y = then_body.wire_node("abs", math::abs(), &[x])?;
+        then_body.set_output_outlets(&y)?;
+        let mut else_body = TypedModel::default();
+        let x = else_body.add_source("x", fact.clone())?;
+        let y = else_body.wire_node("neg", math::neg(), &[x])?;
+        else_body.set_output_outlets(&y)?;
+        let mut model = TypedModel::default();
+        let cond = model.add_source("cond", TypedFact::from(tensor0(true)))?;
+        let x = model.add_source("x", fact)?;
+        let op = If::new(then_body, vec![1], else_body, vec![1], &model.symbol_table)?;
+        let y = model.wire_node("if", op, &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn trace_json() -> TractResult<()> {
+        let model = model_with_if()?;
+        let mut annotations = Annotations::from_model(&model)?;
+        annotations.trace = Some(vec![]);
+        let limits = BenchLimits { max_iters: 2, max_time: std::time::Duration::from_secs(60) };
+        crate::profile::profile(&model, &limits, &mut annotations)?;
+        let trace = ChromeTrace::from(annotations.trace.as_deref().unwrap());
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&trace)?)?;
+        assert_eq!(json["otherData"]["iterations"], 2);
+        let mut stack = vec![];
+        let mut last_ts = 0f64;
+        let mut bodies = vec![];
+        for event in json["traceEvents"].as_array().unwrap() {
+            let name = event["name"].as_str().unwrap();
+            let ts = event["ts"].as_f64().unwrap();
+            assert!(ts >= last_ts, "{} goes back in time", name);
+            last_ts = ts;
+            match event["ph"].as_str().unwrap() {
+                "B" => {
+                    if event["args"]["profiled_separately"] == true && event["cat"] == "If" {
+                        // a branch body, within the span of its If node
+                        assert_eq!(stack.last(), Some(&"if"));
+                        bodies.push(name.to_string());
+                    }
+                    stack.push(name);
+                }
+                "E" => assert_eq!(stack.pop(), Some(name)),
+                ph => panic!("unexpected event type {}", ph),
+            }
+        }
+        assert!(stack.is_empty());
+        assert_eq!(bodies, vec!["if then", "if then"]);
+        let else_branch = tvec!((model.node_by_name("if")?.id, "else".to_string()));
+        assert!(annotations.tags.keys().all(|qid| qid.0 != else_branch));
+        Ok(())
+    }
+}
@@ -218,6 +218,13 @@ fn main() -> tract_core::anyhow::Result<()> {
         .long_about("Dumps the Tensorflow graph in human readable form.")
         .arg(Arg::with_name("cost").long("cost").help("Include const information"))
         .arg(Arg::with_name("profile").long("profile").help("Include results for profile run"))
+        .arg(
+            Arg::with_name("trace")
+            .takes_value(true)
+            .long("trace")
+            .requires("profile")
+            .help("Write a Chrome trace of the profile run (Trace Event Format JSON, for chrome://tracing or Perfetto)"),
+            )
         .arg(
             Arg::with_name("assert-cost")
             .takes_value(true)
@@ -248,6 +255,12 @@ fn main() -> tract_core::anyhow::Result<()> {
             .long("nnef-graph")
             .help("Dump the network definition (without the weights) as a graph.nnef-like file"),
             )
+        .arg(
+            Arg::with_name("onnx")
+            .takes_value(true)
+            .long("onnx")
+            .help("Dump the network in ONNX format (typed models only)"),
+            )
         .arg(
             Arg::with_name("assert-output")
             .takes_value(true)
@@ -66,6 +66,12 @@ pub trait Model:
             self.node_op(id).downcast_ref::<tract_hir::ops::scan::InferenceScan>()
         {
             vec![("loop".into(), &hir.body)]
+        } else if let Some(op) = self.node_op(id).downcast_ref::<tract_core::ops::scan::Loop>() {
+            vec![("loop".into(), &op.body)]
+        } else if let Some(op) = self.node_op(id).downcast_ref::<tract_core::ops::logic::If>() {
+            vec![("then".into(), &op.then_body), ("else".into(), &op.else_body)]
+        } else if let Some(op) = self.node_op(id).downcast_ref::<tract_core::ops::logic::LirIf>() {
+            vec![("then".into(), op.then_plan.model()), ("else".into(), op.else_plan.model())]
         } else {
             vec![]
         }
@@ -82,14 +88,35 @@ pub trait Model:
         {
             // if we have typefact, we hopefully have type ops
             unreachable!();
+        } else if let Some(op) = self.node_op(id).downcast_ref::<tract_core::ops::scan::Loop>() {
+            vec![op.iteration_count(input).or(Some(1.into()))]
+        } else if self.node_op(id).downcast_ref::<tract_core::ops::logic::If>().is_some()
+            || self.node_op(id).downcast_ref::<tract_core::ops::logic::LirIf>().is_some()
+        {
+            // one run of a branch, if it is taken
+            vec![Some(1.into()), Some(1.into())]
         } else {
             vec![]
         }
     }
 
+    /// Subnets of a node that an evaluation on `inputs` actually runs
+    fn nested_models_taken(&self, id: usize, inputs: &[Arc<Tensor>]) -> TractResult<Vec<bool>> {
+        if self.node_op(id).downcast_ref::<tract_core::ops::logic::If>().is_some()
+            || self.node_op(id).downcast_ref::<tract_core::ops::logic::LirIf>().is_some()
+        {
+            let cond = inputs[0].cast_to_scalar::<bool>()?;
+            Ok(vec![cond, !cond])
+        } else {
+            Ok(vec![true; self.nested_models(id).len()])
+        }
+    }
+
     fn auto_outputs(&mut self) -> TractResult<()>;
 
     fn properties(&self) -> &HashMap<String, Arc<Tensor>>;
+
+    fn symbol_table(&self) -> &SymbolTable;
 }
 
 downcast_rs::impl_downcast!(Model);
@@ -192,4 +219,8 @@ where
     fn properties(&self) -> &HashMap<String, Arc<Tensor>> {
         &self.properties
     }
+
+    fn symbol_table(&self) -> &SymbolTable {
+        &self.symbol_table
+    }
 }
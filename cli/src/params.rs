@@ -292,7 +292,8 @@ impl Parameters {
                         .nth(0)
                         .unwrap()
                         .parse::<usize>()?;
-                    let (name, tensor) = tensor::for_data(file.path().to_str().unwrap())?;
+                    let (name, tensor) =
+                        tensor::for_data(&raw_model.symbol_table, file.path().to_str().unwrap())?;
                     Ok(Some((ix, filename.starts_with("input_"), filename, name.unwrap(), tensor)))
                 } else {
                     Ok(None)
@@ -350,7 +351,7 @@ impl Parameters {
 
         if let Some(inputs) = matches.values_of("input") {
             for (ix, v) in inputs.enumerate() {
-                let (name, t) = tensor::for_string(v)?;
+                let (name, t) = tensor::for_string(&raw_model.symbol_table, v)?;
                 let fact = t.clone().without_value();
                 let fact: F = (&fact).try_into().unwrap();
                 let outlet = if let Some(name) = name.filter(|s| s.len() > 0) {
@@ -542,7 +543,7 @@ impl Parameters {
         #[cfg(feature = "pulse")]
         {
             if let Some(dim) = concretize_stream_dim {
-                stage!("concretize-stream-dim", typed_model -> typed_model, |m:TypedModel| Ok(m.concretize_dims(&SymbolValues::default().with(stream_symbol(), dim as _))?));
+                stage!("concretize-stream-dim", typed_model -> typed_model, |m:TypedModel| Ok(m.concretize_dims(&SymbolValues::default().with(stream_symbol(&m.symbol_table), dim as _))?));
                 stage!("concretize-stream-dim-declutter", typed_model -> typed_model, |m:TypedModel| Ok(m.declutter()?));
             } else if let Some(pulse) = pulse {
                 stage!("pulse", typed_model -> pulsed_model, |m:TypedModel| Ok(PulsedModel::new(&m, pulse)?));
@@ -610,7 +611,7 @@ impl Parameters {
 
         if let Some(inputs) = matches.values_of("input") {
             let names = inputs
-                .map(|t| Ok(tensor::for_string(t)?.0))
+                .map(|t| Ok(tensor::for_string(raw_model.symbol_table(), t)?.0))
                 .collect::<CliResult<Vec<Option<String>>>>()?;
             if names.iter().all(|s| s.is_some() && s.as_ref().unwrap().len() > 0) {
                 let names: Vec<&str> = names.iter().map(|s| &**s.as_ref().unwrap()).collect();
@@ -630,7 +631,7 @@ impl Parameters {
 
         if let Some(override_facts) = matches.values_of("override_fact") {
             for fact in override_facts {
-                let (name, fact) = tensor::for_string(fact)?;
+                let (name, fact) = tensor::for_string(raw_model.symbol_table(), fact)?;
                 let node = raw_model.node_id_by_name(&name.unwrap())?;
                 if let Some(inf) = raw_model.downcast_mut::<InferenceModel>() {
                     inf.set_outlet_fact(OutletId::new(node, 0), fact)?;
@@ -652,7 +653,8 @@ impl Parameters {
             })
             .collect();
 
-        let mut assertions = Assertions::from_clap(matches, &*output_names_and_labels)?;
+        let mut assertions =
+            Assertions::from_clap(matches, raw_model.symbol_table(), &*output_names_and_labels)?;
 
         if let Some(sub) = matches.value_of("kaldi_downsample") {
             dispatch_model_mut_no_pulse!(raw_model, |m| Self::kaldi_downsample(m, sub.parse()?))?;
@@ -769,12 +771,14 @@ pub struct Assertions {
 impl Assertions {
     fn from_clap(
         matches: &clap::ArgMatches,
+        symbol_table: &SymbolTable,
         output_names: &[Vec<String>],
     ) -> CliResult<Assertions> {
         let mut assert_outputs: Vec<Option<Arc<Tensor>>> = vec![None; output_names.len()];
         if let Some(values) = matches.values_of("assert-output") {
             for (ix, o) in values.enumerate() {
-                assert_outputs[ix] = tensor::for_string(o).unwrap().1.value.concretize();
+                assert_outputs[ix] =
+                    tensor::for_string(symbol_table, o).unwrap().1.value.concretize();
             }
         }
 
@@ -797,7 +801,7 @@ impl Assertions {
 
         let assert_output_facts: Option<Vec<InferenceFact>> = matches
             .values_of("assert-output-fact")
-            .map(|vs| vs.map(|v| tensor::for_string(v).unwrap().1).collect());
+            .map(|vs| vs.map(|v| tensor::for_string(symbol_table, v).unwrap().1).collect());
         Ok(Assertions { assert_outputs, assert_output_facts })
     }
 }
@@ -16,6 +16,25 @@ impl Scalable for std::time::Duration {
     }
 }
 
+/// A timed evaluation, kept for trace export. Times are relative to the
+/// beginning of the profile run.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub name: String,
+    pub op: String,
+    /// None for the spans covering a whole iteration.
+    pub qid: Option<NodeQId>,
+    pub iteration: usize,
+    pub output_shapes: Vec<TVec<usize>>,
+    pub begin: Duration,
+    pub end: Duration,
+    pub children: Vec<Span>,
+    /// Nested model bodies are timed in their own runs, after the runs of
+    /// the outer model. Their spans are laid out from the beginning of the
+    /// span of their outer node, and cut at its end.
+    pub profiled_separately: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct ProfileSummary {
     pub max: Duration,
@@ -33,20 +52,57 @@ pub fn profile(
     let plan = SimplePlan::new(model)?;
     let mut state = SimpleState::new(&plan)?;
     let mut iters = 0usize;
+    // nested models run by each node evaluation, by (node, iteration)
+    let mut taken = HashMap::<(usize, usize), Vec<bool>>::new();
     let start = Instant::now();
     while iters < bench_limits.max_iters && start.elapsed() < bench_limits.max_time {
+        let mut spans = vec![];
+        let tracing = dg.trace.is_some();
+        let run_start = start.elapsed();
         let _ = state.run_plan_with_eval(
             crate::tensor::make_inputs_for_model(model)?,
             |session_state, state, node, input| {
-                let start = Instant::now();
+                if let Ok(nested) = (model as &dyn Model).nested_models_taken(node.id, &input) {
+                    if !nested.is_empty() {
+                        taken.insert((node.id, iters), nested);
+                    }
+                }
+                let node_start = Instant::now();
                 let r = tract_core::plan::eval(session_state, state, node, input);
-                let elapsed = start.elapsed();
+                let elapsed = node_start.elapsed();
                 *dg.node_mut(NodeQId(tvec!(), node.id))
                     .profile
                     .get_or_insert(Duration::default()) += elapsed;
+                if tracing {
+                    let begin = node_start - start;
+                    spans.push(Span {
+                        name: node.name.clone(),
+                        op: node.op.name().to_string(),
+                        qid: Some(NodeQId(tvec!(), node.id)),
+                        iteration: iters,
+                        output_shapes: output_shapes(&r),
+                        begin,
+                        end: begin + elapsed,
+                        children: vec![],
+                        profiled_separately: false,
+                    });
+                }
                 r
             },
         )?;
+        if let Some(trace) = dg.trace.as_mut() {
+            trace.push(Span {
+                name: format!("iteration {}", iters),
+                op: "run".to_string(),
+                qid: None,
+                iteration: iters,
+                output_shapes: vec![],
+                begin: run_start,
+                end: start.elapsed(),
+                children: spans,
+                profiled_separately: false,
+            });
+        }
         iters += 1;
     }
     let entire = start.elapsed();
@@ -64,23 +120,33 @@ pub fn profile(
                 .map(|&i| i.to_typed_fact())
                 .collect::<TractResult<_>>()?;
             let ref_inputs: TVec<&TypedFact> = inputs.iter().collect();
-            for ((inner_model_name, inner_model), multiplier) in model
+            for (model_ix, ((inner_model_name, inner_model), multiplier)) in model
                 .nested_models(outer_node.id)
                 .iter()
                 .zip(model.nested_models_iters(outer_node.id, &ref_inputs).iter())
+                .enumerate()
             {
-                let multi = multiplier.as_ref().unwrap().to_isize().unwrap();
+                let multi = multiplier.as_ref().and_then(|m| m.to_isize().ok()).unwrap_or(1);
                 let prefix = tvec!((outer_node.id, inner_model_name.to_string()));
                 if let Some(inner_model) = inner_model.downcast_ref::<TypedModel>() {
-                    for _ in 0..iters {
+                    for iteration in 0..iters {
+                        // only time the bodies the outer run went through
+                        if taken.get(&(outer_node.id, iteration)).map(|t| t[model_ix]) != Some(true)
+                        {
+                            continue;
+                        }
                         let inner_plan = SimplePlan::new(inner_model)?;
                         let mut state = SimpleState::new(inner_plan)?;
+                        let mut spans = vec![];
+                        let tracing = dg.trace.is_some();
+                        let inner_start = Instant::now();
                         let _ = state.run_plan_with_eval(
                             crate::tensor::make_inputs_for_model(inner_model)?,
                             |session_state, state, node, input| {
-                                let start = Instant::now();
+                                let node_start = Instant::now();
                                 let r = tract_core::plan::eval(session_state, state, node, input);
-                                let elapsed = start.elapsed().scale(multi as _);
+                                let real_elapsed = node_start.elapsed();
+                                let elapsed = real_elapsed.scale(multi as _);
                                 *dg.node_mut(NodeQId(prefix.clone(), node.id))
                                     .profile
                                     .get_or_insert(Duration::default()) += elapsed;
@@ -89,9 +155,52 @@ pub fn profile(
                                     .profile
                                     .get_or_insert(Duration::default());
                                 *parent -= elapsed.min(*parent);
+                                if tracing {
+                                    // relative to the body run, until laid out in the outer span
+                                    let begin = node_start - inner_start;
+                                    spans.push(Span {
+                                        name: node.name.clone(),
+                                        op: node.op.name().to_string(),
+                                        qid: Some(NodeQId(prefix.clone(), node.id)),
+                                        iteration,
+                                        output_shapes: output_shapes(&r),
+                                        begin,
+                                        end: begin + real_elapsed,
+                                        children: vec![],
+                                        profiled_separately: true,
+                                    });
+                                }
                                 r
                             },
                         )?;
+                        let inner_elapsed = inner_start.elapsed();
+                        let outer_qid = Some(NodeQId(tvec!(), outer_node.id));
+                        if let Some(parent) = dg
+                            .trace
+                            .iter_mut()
+                            .flatten()
+                            .filter(|s| s.qid.is_none() && s.iteration == iteration)
+                            .flat_map(|s| s.children.iter_mut())
+                            .find(|s| s.qid == outer_qid)
+                        {
+                            let (begin, end) = (parent.begin, parent.end);
+                            let shift = |d: Duration| (begin + d).min(end);
+                            for span in &mut spans {
+                                span.begin = shift(span.begin);
+                                span.end = shift(span.end);
+                            }
+                            parent.children.push(Span {
+                                name: format!("{} {}", outer_node.name, inner_model_name),
+                                op: outer_node.op.name().to_string(),
+                                qid: None,
+                                iteration,
+                                output_shapes: vec![],
+                                begin,
+                                end: shift(inner_elapsed),
+                                children: spans,
+                                profiled_separately: true,
+                            });
+                        }
                     }
                 }
             }
@@ -109,3 +218,10 @@ pub fn profile(
     dg.profile_summary = Some(ProfileSummary { max, sum, entire, iters });
     Ok(())
 }
+
+fn output_shapes(outputs: &TractResult<TVec<Arc<Tensor>>>) -> Vec<TVec<usize>> {
+    outputs
+        .as_ref()
+        .map(|outputs| outputs.iter().map(|t| t.shape().into()).collect())
+        .unwrap_or_default()
+}
@@ -22,6 +22,7 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
         .downcast_ref::<TypedModel>()
         .context("Final model is not Typed. (using --pass ?)")?;
 
+    let stream_sym = stream_symbol(&decl.symbol_table);
     let decl_input_fact = decl.input_fact(0)?;
     let pulsed_input_fact = pulsed.input_fact(0)?;
     let input_pulse = pulsed_input_fact.pulse();
@@ -53,12 +54,17 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
 
             let stream_dim = delay + 3 * input_pulse + input_pulse / 2;
 
-            let fixed_input = crate::tensor::tensor_for_fact(decl_input_fact, Some(stream_dim))?;
+            let fixed_input = crate::tensor::tensor_for_fact(
+                decl_input_fact,
+                Some((stream_sym.clone(), stream_dim)),
+            )?;
 
             let decl = (*decl).clone();
             let fixed_result = decl
                 .with_output_outlets(&[decl_outlet])?
-                .concretize_dims(&SymbolValues::default().with(stream_symbol(), stream_dim as _))?
+                .concretize_dims(
+                    &SymbolValues::default().with(stream_sym.clone(), stream_dim as _),
+                )?
                 .into_runnable()?
                 .run(tvec!(fixed_input.clone()))?
                 .remove(output_slot);
@@ -86,7 +92,8 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
                 };
                 if offset + input_pulse > stream_dim {
                     debug!("Set known_stream_len: {}", stream_dim);
-                    state.session_state.resolved_symbols[stream_symbol()] = Some(stream_dim as _);
+                    state.session_state.resolved_symbols[stream_sym.clone()] =
+                        Some(stream_dim as _);
                 };
 
                 let output = state.run(tvec!(pulsed_input.into()))?.remove(output_slot);
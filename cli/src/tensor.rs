@@ -6,89 +6,22 @@ use crate::model::Model;
 use crate::CliResult;
 use tract_hir::internal::*;
 
-fn parse_dt(dt: &str) -> CliResult<DatumType> {
-    Ok(match dt.to_lowercase().as_ref() {
-        "f16" => DatumType::F16,
-        "f32" => DatumType::F32,
-        "f64" => DatumType::F64,
-        "i8" => DatumType::I8,
-        "i16" => DatumType::I16,
-        "i32" => DatumType::I32,
-        "i64" => DatumType::I64,
-        "u8" => DatumType::U8,
-        "u16" => DatumType::U16,
-        "u32" => DatumType::U32,
-        "u64" => DatumType::U64,
-        _ => bail!(
-            "Type of the input should be f16, f32, f64, i8, i16, i16, i32, u8, u16, u32, u64."
-        ),
-    })
-}
-
-pub fn parse_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_spec(symbol_table: &SymbolTable, size: &str) -> CliResult<InferenceFact> {
     if size.len() == 0 {
         return Ok(InferenceFact::default());
     }
     if size.contains("x") && !size.contains(",") {
-        parse_x_spec(size)
+        parse_x_spec(symbol_table, size)
     } else {
-        parse_coma_spec(size)
+        parse_coma_spec(symbol_table, size)
     }
 }
 
-pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
-    let splits = size.split(",").collect::<Vec<_>>();
-
-    if splits.len() < 1 {
-        bail!("The <size> argument should be formatted as {size},{...},{type}.");
-    }
-
-    let last = splits.last().unwrap();
-    let (datum_type, shape) = if let Ok(dt) = parse_dt(last) {
-        (Some(dt), &splits[0..splits.len() - 1])
-    } else {
-        (None, &*splits)
-    };
-
-    let shape = ShapeFactoid::closed(
-        shape
-            .iter()
-            .map(|&s| {
-                Ok(if s == "_" { GenericFactoid::Any } else { GenericFactoid::Only(parse_dim(s)?) })
-            })
-            .collect::<CliResult<TVec<DimFact>>>()?,
-    );
-
-    if let Some(dt) = datum_type {
-        Ok(InferenceFact::dt_shape(dt, shape))
-    } else {
-        Ok(InferenceFact::shape(shape))
-    }
-}
-
-pub fn parse_dim(i: &str) -> CliResult<TDim> {
-    // ensure the magic S is pre-registered
-    #[cfg(feature = "pulse")]
-    let _ = tract_pulse::internal::stream_symbol();
-
-    if i.len() == 0 {
-        bail!("Can not parse empty string as Dim")
-    }
-    let number_len = i.chars().take_while(|c| c.is_digit(10)).count();
-    let symbol_len = i.len() - number_len;
-    if symbol_len > 1 {
-        bail!("Can not parse {} as Dim", i)
-    }
-    let number: i64 = if number_len > 0 { i[..number_len].parse()? } else { 1 };
-    if symbol_len == 0 {
-        return Ok(number.to_dim());
-    }
-    let symbol = i.chars().last().unwrap();
-    let symbol = Symbol::from(symbol);
-    Ok(symbol.to_dim() * number)
+pub fn parse_coma_spec(symbol_table: &SymbolTable, size: &str) -> CliResult<InferenceFact> {
+    InferenceFact::parse(symbol_table, size)
 }
 
-pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_x_spec(symbol_table: &SymbolTable, size: &str) -> CliResult<InferenceFact> {
     warn!(
         "Deprecated \"x\" syntax for shape : please use the comma as separator, x is now a symbol."
     );
@@ -102,7 +35,7 @@ pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
     let (datum_type, shape) = if last.ends_with("S") || last.parse::<i32>().is_ok() {
         (None, &*splits)
     } else {
-        let datum_type = parse_dt(splits.last().unwrap())?;
+        let datum_type = parse_datum_type(splits.last().unwrap())?;
         (Some(datum_type), &splits[0..splits.len() - 1])
     };
 
@@ -113,7 +46,7 @@ pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
                 Ok(if s == "_" {
                     GenericFactoid::Any
                 } else {
-                    GenericFactoid::Only(parse_dim_stream(s)?)
+                    GenericFactoid::Only(parse_dim(symbol_table, s)?)
                 })
             })
             .collect::<CliResult<TVec<DimFact>>>()?,
@@ -134,14 +67,14 @@ fn parse_values<'a, T: Datum + FromStr>(shape: &[usize], it: Vec<&'a str>) -> Cl
     Ok(tract_ndarray::Array::from_shape_vec(shape, values)?.into())
 }
 
-fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
+fn tensor_for_text_data(symbol_table: &SymbolTable, filename: &str) -> CliResult<Tensor> {
     let mut file = fs::File::open(filename)
         .map_err(|e| format_err!("Reading tensor from {}, {:?}", filename, e))?;
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
     let mut lines = data.lines();
-    let proto = parse_spec(lines.next().context("Empty data file")?)?;
+    let proto = parse_spec(symbol_table, lines.next().context("Empty data file")?)?;
     let shape = proto.shape.concretize().unwrap();
 
     let values = lines.flat_map(|l| l.split_whitespace()).collect::<Vec<&str>>();
@@ -156,7 +89,10 @@ fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
 }
 
 /// Parses the `data` command-line argument.
-pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_data(
+    symbol_table: &SymbolTable,
+    filename: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     #[allow(unused_imports)]
     use std::convert::TryFrom;
     if filename.ends_with(".pb") {
@@ -177,7 +113,7 @@ pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
         let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(filename)?)?;
         Ok((None, for_npz(&mut npz, inner)?.into()))
     } else {
-        Ok((None, tensor_for_text_data(filename)?.into()))
+        Ok((None, tensor_for_text_data(symbol_table, filename)?.into()))
     }
 }
 
@@ -222,9 +158,12 @@ pub fn for_npz(npz: &mut ndarray_npy::NpzReader<fs::File>, name: &str) -> CliRes
     bail!("Can not extract tensor from {}", name);
 }
 
-pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_string(
+    symbol_table: &SymbolTable,
+    value: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     if value.starts_with("@") {
-        for_data(&value[1..])
+        for_data(symbol_table, &value[1..])
     } else {
         let (name, value) = if value.contains(":") {
             let mut splits = value.split(":");
@@ -234,7 +173,7 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
         };
         if value.contains("=") {
             let mut split = value.split("=");
-            let spec = parse_spec(split.next().unwrap())?;
+            let spec = parse_spec(symbol_table, split.next().unwrap())?;
             let value = split.next().unwrap().split(",");
             let dt = spec
                 .datum_type
@@ -247,30 +186,11 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
             let tensor = dispatch_datum!(parse_values(dt)(&*shape, value.collect()))?;
             Ok((name, tensor.into()))
         } else {
-            Ok((name, parse_spec(value)?))
+            Ok((name, parse_spec(symbol_table, value)?))
         }
     }
 }
 
-#[cfg(feature = "pulse")]
-fn parse_dim_stream(s: &str) -> CliResult<TDim> {
-    use tract_pulse::internal::stream_dim;
-    if s == "S" {
-        Ok(stream_dim())
-    } else if s.ends_with("S") {
-        let number: String = s.chars().take_while(|c| c.is_digit(10)).collect();
-        let number: i64 = number.parse::<i64>().map(|i| i.into())?;
-        Ok(stream_dim() * number)
-    } else {
-        Ok(s.parse::<i64>().map(|i| i.into())?)
-    }
-}
-
-#[cfg(not(feature = "pulse"))]
-fn parse_dim_stream(s: &str) -> CliResult<TDim> {
-    Ok(s.parse::<i64>().map(|i| i.into())?)
-}
-
 pub fn make_inputs(values: &[impl std::borrow::Borrow<TypedFact>]) -> CliResult<TVec<Tensor>> {
     values.iter().map(|v| tensor_for_fact(v.borrow(), None)).collect()
 }
@@ -286,27 +206,28 @@ pub fn make_inputs_for_model(model: &dyn Model) -> CliResult<TVec<Tensor>> {
 }
 
 #[allow(unused_variables)]
-pub fn tensor_for_fact(fact: &TypedFact, streaming_dim: Option<usize>) -> CliResult<Tensor> {
+pub fn tensor_for_fact(
+    fact: &TypedFact,
+    streaming_dim: Option<(Symbol, usize)>,
+) -> CliResult<Tensor> {
     if let Some(value) = &fact.konst {
         return Ok(value.clone().into_tensor());
     }
     #[cfg(pulse)]
     {
-        if fact.shape.stream_info().is_some() {
+        if let Some((s, dim)) = streaming_dim {
             use tract_pulse::fact::StreamFact;
-            use tract_pulse::internal::stream_symbol;
-            let s = stream_symbol();
-            if let Some(dim) = streaming_dim {
+            if fact.shape.stream_info(&s).is_some() {
                 let shape = fact
                     .shape
                     .iter()
                     .map(|d| {
-                        d.eval(&SymbolValues::default().with(s, dim as i64)).to_usize().unwrap()
+                        d.eval(&SymbolValues::default().with(s.clone(), dim as i64))
+                            .to_usize()
+                            .unwrap()
                     })
                     .collect::<TVec<_>>();
                 return Ok(random(&shape, fact.datum_type));
-            } else {
-                bail!("random tensor requires a streaming dim")
             }
         }
     }
@@ -40,6 +40,9 @@ where
     /// model properties
     #[educe(Hash(method = "hash_properties"))]
     pub properties: HashMap<String, Arc<Tensor>>,
+    /// scope of the symbols used in the model dimensions
+    #[educe(Hash(ignore))]
+    pub symbol_table: SymbolTable,
 }
 
 fn hash_outlet_labels<H: std::hash::Hasher>(it: &HashMap<OutletId, String>, state: &mut H) {
@@ -72,6 +75,7 @@ where
             outputs: vec![],
             outlet_labels: HashMap::new(),
             properties: HashMap::new(),
+            symbol_table: SymbolTable::default(),
         }
     }
 }
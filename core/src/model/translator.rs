@@ -28,6 +28,7 @@ where
         source: &Graph<TI1, O1>,
     ) -> TractResult<(Graph<TI2, O2>, HashMap<OutletId, OutletId>)> {
         let mut target = Graph::default();
+        target.symbol_table = source.symbol_table.clone();
         let mut mapping = HashMap::new();
         for old_id in source.eval_order()? {
             let node = source.node(old_id);
@@ -133,6 +133,7 @@ impl TypedModel {
                 node.op.concretize_dims(source, node, target, mapping, self)
             }
         }
+        self.symbol_table.check(values)?;
         values.translate_model(&self)
     }
 
@@ -155,4 +156,19 @@ mod test {
         fn is_sync<T: Sync>() {}
         is_sync::<TypedModel>();
     }
+
+    #[test]
+    fn concretize_checks_assertions() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let n = model.symbol_table.sym("N");
+        model.symbol_table.add_assertion(Assertion::GreaterOrEqual(n.clone(), 1))?;
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [n.to_dim()]))?;
+        model.set_output_outlets(&[source])?;
+        let concrete = model.concretize_dims(&SymbolValues::default().with(n.clone(), 3))?;
+        assert_eq!(concrete.input_fact(0)?.shape.to_tvec(), tvec!(3.to_dim()));
+        let err = model.concretize_dims(&SymbolValues::default().with(n, 0)).unwrap_err();
+        assert_eq!(err.to_string(), "Symbol value N=0 violates assertion N>=1");
+        Ok(())
+    }
 }
@@ -2,8 +2,8 @@ use crate::internal::*;
 
 #[derive(Debug, Clone, new, Hash)]
 pub struct ConstantOfShape {
-    shape: TVec<TDim>,
-    scalar: Arc<Tensor>,
+    pub shape: TVec<TDim>,
+    pub scalar: Arc<Tensor>,
 }
 
 impl_dyn_hash!(ConstantOfShape);
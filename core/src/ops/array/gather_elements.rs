@@ -0,0 +1,120 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Gather single elements along an axis. Output has the shape of the
+/// indices, indices can be negative.
+#[derive(Debug, Clone, new, Hash)]
+pub struct GatherElements {
+    pub axis: usize,
+}
+
+impl_dyn_hash!(GatherElements);
+
+/// Checks indices have the rank of data, and are not bigger than data on the
+/// axes other than `axis`. Symbolic dimensions are assumed to match.
+pub(crate) fn check_indices_shape<D: DimLike>(
+    op: &str,
+    axis: usize,
+    data: &[D],
+    indices: &[D],
+) -> TractResult<()> {
+    if data.len() != indices.len() {
+        bail!("{}: data and indices must have the same rank", op);
+    }
+    if axis >= data.len() {
+        bail!("{}: axis {} is invalid for data of rank {}", op, axis, data.len());
+    }
+    for (ix, (d, i)) in data.iter().zip(indices.iter()).enumerate() {
+        if let (Ok(d), Ok(i)) = (d.to_usize(), i.to_usize()) {
+            if ix != axis && i > d {
+                bail!("{}: indices have {} elements on axis {}, data only {}", op, i, ix, d);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Op for GatherElements {
+    fn name(&self) -> Cow<str> {
+        "GatherElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl GatherElements {
+    fn eval_t<T: Datum>(&self, data: &Tensor, indices: &ArrayViewD<i64>) -> TractResult<Tensor> {
+        let data_view = data.to_array_view::<T>()?;
+        let dim = data.shape()[self.axis] as i64;
+        if let Some(ix) = indices.iter().find(|&&ix| ix < -dim || ix >= dim) {
+            bail!("GatherElements: index {} out of range for axis of size {}", ix, dim);
+        }
+        let output = ArrayD::from_shape_fn(indices.shape(), |mut coords| {
+            let ix = indices[&coords];
+            coords[self.axis] = if ix < 0 { ix + dim } else { ix } as usize;
+            data_view[coords].clone()
+        });
+        Ok(output.into_tensor())
+    }
+}
+
+impl EvalOp for GatherElements {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices) = args_2!(inputs);
+        check_indices_shape("GatherElements", self.axis, data.shape(), indices.shape())?;
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let output = dispatch_datum!(Self::eval_t(data.datum_type())(self, &data, &indices))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for GatherElements {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        check_indices_shape(
+            "GatherElements",
+            self.axis,
+            &inputs[0].shape.to_tvec(),
+            &inputs[1].shape.to_tvec(),
+        )?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[1].shape.clone())))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gather_elements_negative() -> TractResult<()> {
+        let op = GatherElements::new(1);
+        let data = rctensor2(&[[1, 2], [3, 4]]);
+        let indices = rctensor2(&[[0i64, 0], [-1, 0]]);
+        let output = op.eval(tvec!(data, indices))?;
+        assert_eq!(*output[0], tensor2(&[[1, 1], [4, 3]]));
+        Ok(())
+    }
+
+    #[test]
+    fn gather_elements_bad_shapes() {
+        let op = GatherElements::new(1);
+        let data = rctensor2(&[[1, 2], [3, 4]]);
+        assert!(op.eval(tvec!(data.clone(), rctensor2(&[[0i64], [1], [0]]))).is_err());
+        assert!(op.eval(tvec!(data.clone(), rctensor1(&[0i64]))).is_err());
+        assert!(GatherElements::new(2).eval(tvec!(data, rctensor2(&[[0i64]]))).is_err());
+        let data = TypedFact::dt_shape(i32::datum_type(), &[2, 2]);
+        let indices = TypedFact::dt_shape(i64::datum_type(), &[3, 1]);
+        assert!(op.output_facts(&[&data, &indices]).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Gather slices which coordinates are given in the last axis of the indices.
+/// The first `batch_dims` axes of data and indices are shared.
+#[derive(Debug, Clone, new, Hash)]
+pub struct GatherNd {
+    pub batch_dims: usize,
+}
+
+impl_dyn_hash!(GatherNd);
+
+impl GatherNd {
+    pub fn compute_shape<D: DimLike>(
+        &self,
+        data_shape: &[D],
+        indices_shape: &[D],
+    ) -> TractResult<TVec<D>> {
+        if indices_shape.len() <= self.batch_dims {
+            bail!(
+                "GatherNd: indices of rank {} can not have {} batch dims",
+                indices_shape.len(),
+                self.batch_dims
+            );
+        }
+        if data_shape.len() < self.batch_dims {
+            bail!(
+                "GatherNd: data of rank {} can not have {} batch dims",
+                data_shape.len(),
+                self.batch_dims
+            );
+        }
+        for axis in 0..self.batch_dims {
+            if let (Ok(d), Ok(i)) = (data_shape[axis].to_usize(), indices_shape[axis].to_usize()) {
+                if d != i {
+                    bail!("GatherNd: batch axis {} is {} in data, but {} in indices", axis, d, i);
+                }
+            }
+        }
+        let mut shape: TVec<D> = indices_shape.into();
+        let n = shape.pop().unwrap().to_usize()?;
+        if self.batch_dims + n > data_shape.len() {
+            bail!(
+                "GatherNd: indices last axis ({}) too big for data of rank {}",
+                n,
+                data_shape.len()
+            );
+        }
+        shape.extend(data_shape[self.batch_dims + n..].iter().cloned());
+        Ok(shape)
+    }
+
+    fn eval_t<T: Datum>(
+        &self,
+        output: &mut Tensor,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+    ) -> TractResult<()> {
+        let data = data.to_array_view::<T>()?;
+        let mut output = output.to_array_view_mut::<T>()?;
+        for prefix in tract_ndarray::indices(&indices.shape()[0..indices.ndim() - 1]) {
+            let mut dst = output.view_mut();
+            let mut coords = indices.view();
+            for &x in prefix.slice().iter() {
+                dst.index_axis_inplace(Axis(0), x);
+                coords.index_axis_inplace(Axis(0), x);
+            }
+            let mut src = data.view();
+            for &x in &prefix.slice()[..self.batch_dims] {
+                src.index_axis_inplace(Axis(0), x);
+            }
+            for &x in coords.iter() {
+                let dim = src.shape()[0] as i64;
+                if x < -dim || x >= dim {
+                    bail!("GatherNd: index {} out of range for axis of size {}", x, dim);
+                }
+                src.index_axis_inplace(Axis(0), if x < 0 { x + dim } else { x } as usize);
+            }
+            dst.assign(&src);
+        }
+        Ok(())
+    }
+}
+
+impl Op for GatherNd {
+    fn name(&self) -> Cow<str> {
+        "GatherNd".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("batch_dims: {}", self.batch_dims)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for GatherNd {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices) = args_2!(inputs);
+        let shape = self.compute_shape(&data.shape(), &indices.shape())?;
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let mut output = Tensor::zero_dt(data.datum_type(), &*shape)?;
+        dispatch_datum!(Self::eval_t(data.datum_type())(self, &mut output, &data, &indices))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for GatherNd {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape = self.compute_shape(&inputs[0].shape.to_tvec(), &inputs[1].shape.to_tvec())?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if self.batch_dims > 0 {
+            return Ok(None);
+        }
+        if let Some(indices) = &model.outlet_fact(node.inputs[1])?.konst {
+            if indices.rank() == 2 && indices.shape()[0] == 1 {
+                let data_shape = model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+                let mut patch = TypedModelPatch::default();
+                let mut wire = patch.tap_model(model, node.inputs[0])?;
+                for (axis, &i) in indices.cast_to::<i64>()?.as_slice::<i64>()?.iter().enumerate() {
+                    let start = if i < 0 { data_shape[axis].clone() + i } else { i.to_dim() };
+                    wire = patch.wire_node(
+                        format!("{}-slice-axis-{}", node.name, axis),
+                        crate::ops::array::Slice { axis, start: start.clone(), end: start + 1 },
+                        &[wire],
+                    )?[0];
+                }
+                for i in (0..indices.shape()[1]).rev() {
+                    wire = patch.wire_node(
+                        format!("{}-remove_axis_{}", node.name, i),
+                        AxisOp::Rm(i),
+                        &[wire],
+                    )?[0];
+                }
+                wire =
+                    patch.wire_node(format!("{}-add_axis", node.name), AxisOp::Add(0), &[wire])?[0];
+                patch.shunt_outside(model, node.id.into(), wire)?;
+                return Ok(Some(patch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://www.tensorflow.org/api_docs/python/tf/gather_nd
+    #[test]
+    fn simple_indexing() {
+        let g = GatherNd::new(0);
+        assert_eq!(
+            g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[0, 0], [1, 1]]))).unwrap(),
+            tvec!(rctensor1(&[1, 4]))
+        );
+    }
+
+    #[test]
+    fn slice_indexing() {
+        let g = GatherNd::new(0);
+        assert_eq!(
+            g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[1], [0]]))).unwrap(),
+            tvec!(rctensor2(&[[3, 4], [1, 2]]))
+        );
+    }
+
+    #[test]
+    fn tensor_3d_1() {
+        let g = GatherNd::new(0);
+        let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
+        assert_eq!(
+            g.eval(tvec!(t.clone(), rctensor2(&[[1]]))).unwrap(),
+            tvec!(rctensor3(&[[[11, 21], [31, 41]]]))
+        );
+    }
+
+    #[test]
+    fn tensor_3d_2() {
+        let g = GatherNd::new(0);
+        let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
+        assert_eq!(
+            g.eval(tvec!(t.clone(), rctensor2(&[[0, 1], [1, 0]]))).unwrap(),
+            tvec!(rctensor2(&[[30, 40], [11, 21]]))
+        );
+    }
+
+    #[test]
+    fn tensor_3d_3() {
+        let g = GatherNd::new(0);
+        let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
+        assert_eq!(
+            g.eval(tvec!(t.clone(), rctensor2(&[[0, 0, 1], [1, 0, 1]]))).unwrap(),
+            tvec!(rctensor1(&[20, 21]))
+        );
+    }
+
+    #[test]
+    fn batch_dims_and_negative() {
+        let g = GatherNd::new(1);
+        let t = rctensor3(&[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]);
+        assert_eq!(
+            g.eval(tvec!(t, rctensor2(&[[1i64], [-2]]))).unwrap(),
+            tvec!(rctensor2(&[[2, 3], [4, 5]]))
+        );
+    }
+
+    #[test]
+    fn bad_shapes() {
+        let t = rctensor3(&[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]);
+        assert!(GatherNd::new(1).eval(tvec!(t.clone(), rctensor2(&[[1i64], [0], [1]]))).is_err());
+        assert!(GatherNd::new(1).eval(tvec!(t.clone(), rctensor1(&[1i64]))).is_err());
+        assert!(GatherNd::new(0).eval(tvec!(t.clone(), rctensor0(1i64))).is_err());
+        assert!(GatherNd::new(0).eval(tvec!(t, rctensor2(&[[0i64, 0, 0, 0]]))).is_err());
+    }
+}
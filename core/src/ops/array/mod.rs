@@ -3,18 +3,28 @@ mod broadcast;
 pub(crate) mod concat;
 mod constant_of_shape;
 mod gather;
+mod gather_elements;
+mod gather_nd;
 mod one_hot;
 mod pad;
 mod reshape;
+mod scatter_elements;
+mod scatter_nd;
 mod slice;
 mod tile;
+mod topk;
 
 pub use self::broadcast::MultiBroadcastTo;
 pub use self::concat::{ConcatSlice, TypedConcat};
 pub use self::constant_of_shape::ConstantOfShape;
 pub use self::gather::Gather;
+pub use self::gather_elements::GatherElements;
+pub use self::gather_nd::GatherNd;
 pub use self::one_hot::OneHot;
 pub use self::pad::{Pad, PadMode};
 pub use self::reshape::FiniteReshape;
+pub use self::scatter_elements::{ScatterElements, ScatterReduction};
+pub use self::scatter_nd::ScatterNd;
 pub use self::slice::Slice;
 pub use self::tile::Tile;
+pub use self::topk::TopK;
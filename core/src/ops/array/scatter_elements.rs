@@ -0,0 +1,227 @@
+use super::gather_elements::check_indices_shape;
+use crate::internal::*;
+use ndarray::*;
+
+/// How updates are combined with the existing data by the scatter operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScatterReduction {
+    None,
+    Add,
+    Mul,
+    Max,
+    Min,
+}
+
+impl Default for ScatterReduction {
+    fn default() -> ScatterReduction {
+        ScatterReduction::None
+    }
+}
+
+impl ScatterReduction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScatterReduction::None => "none",
+            ScatterReduction::Add => "add",
+            ScatterReduction::Mul => "mul",
+            ScatterReduction::Max => "max",
+            ScatterReduction::Min => "min",
+        }
+    }
+
+    pub fn parse(s: &str) -> TractResult<ScatterReduction> {
+        Ok(match s {
+            "none" => ScatterReduction::None,
+            "add" => ScatterReduction::Add,
+            "mul" => ScatterReduction::Mul,
+            "max" => ScatterReduction::Max,
+            "min" => ScatterReduction::Min,
+            _ => bail!("Unsupported scatter reduction: {}", s),
+        })
+    }
+
+    pub(crate) fn combine<T>(&self) -> impl Fn(&mut T, &T)
+    where
+        T: Datum + Copy + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        let red = *self;
+        move |a: &mut T, b: &T| match red {
+            ScatterReduction::None => *a = *b,
+            ScatterReduction::Add => *a = *a + *b,
+            ScatterReduction::Mul => *a = *a * *b,
+            ScatterReduction::Max => {
+                if *b > *a {
+                    *a = *b
+                }
+            }
+            ScatterReduction::Min => {
+                if *b < *a {
+                    *a = *b
+                }
+            }
+        }
+    }
+}
+
+/// Write updates in a copy of data, at the positions given by indices along
+/// an axis. Indices can be negative.
+#[derive(Debug, Clone, new, Hash)]
+pub struct ScatterElements {
+    pub axis: usize,
+    pub reduction: ScatterReduction,
+}
+
+impl_dyn_hash!(ScatterElements);
+
+impl Op for ScatterElements {
+    fn name(&self) -> Cow<str> {
+        "ScatterElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} reduction: {}", self.axis, self.reduction.as_str())])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl ScatterElements {
+    fn eval_t<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+        combine: &dyn Fn(&mut T, &T),
+    ) -> TractResult<Tensor> {
+        let mut data = data.to_array_view::<T>()?.to_owned();
+        let updates = updates.to_array_view::<T>()?;
+        let dim = data.shape()[self.axis] as i64;
+        for (mut coords, &ix) in indices.indexed_iter() {
+            if ix < -dim || ix >= dim {
+                bail!("ScatterElements: index {} out of range for axis of size {}", ix, dim);
+            }
+            let update = &updates[&coords];
+            coords[self.axis] = if ix < 0 { ix + dim } else { ix } as usize;
+            combine(&mut data[coords], update);
+        }
+        Ok(data.into_tensor())
+    }
+
+    fn eval_assign<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Tensor> {
+        self.eval_t::<T>(data, indices, updates, &|a, b| *a = b.clone())
+    }
+
+    fn eval_reduce<T>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Tensor>
+    where
+        T: Datum + Copy + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        self.eval_t::<T>(data, indices, updates, &self.reduction.combine::<T>())
+    }
+}
+
+impl EvalOp for ScatterElements {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        check_indices_shape("ScatterElements", self.axis, data.shape(), indices.shape())?;
+        if indices.shape() != updates.shape() {
+            bail!(
+                "ScatterElements: inconsistent shapes data:{:?} indices:{:?} updates:{:?}",
+                data.shape(),
+                indices.shape(),
+                updates.shape()
+            );
+        }
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let output = if self.reduction == ScatterReduction::None {
+            dispatch_datum!(Self::eval_assign(data.datum_type())(self, &data, &indices, &updates))?
+        } else {
+            dispatch_numbers!(Self::eval_reduce(data.datum_type())(
+                self, &data, &indices, &updates
+            ))?
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for ScatterElements {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let indices_shape = inputs[1].shape.to_tvec();
+        check_indices_shape(
+            "ScatterElements",
+            self.axis,
+            &inputs[0].shape.to_tvec(),
+            &indices_shape,
+        )?;
+        if let (Some(indices), Some(updates)) =
+            (inputs[1].shape.as_concrete(), inputs[2].shape.as_concrete())
+        {
+            if indices != updates {
+                bail!("ScatterElements: indices {:?} and updates {:?} differ", indices, updates);
+            }
+        } else if inputs[1].rank() != inputs[2].rank() {
+            bail!("ScatterElements: indices and updates must have the same rank");
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scatter_elements() -> TractResult<()> {
+        let op = ScatterElements::new(1, ScatterReduction::None);
+        let data = rctensor2(&[[1.0f32, 2.0, 3.0, 4.0, 5.0]]);
+        let indices = rctensor2(&[[1i64, -2]]);
+        let updates = rctensor2(&[[1.1f32, 2.1]]);
+        let output = op.eval(tvec!(data, indices, updates))?;
+        assert_eq!(*output[0], tensor2(&[[1.0f32, 1.1, 3.0, 2.1, 5.0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn scatter_elements_add() -> TractResult<()> {
+        let op = ScatterElements::new(1, ScatterReduction::Add);
+        let data = rctensor2(&[[1.0f32, 2.0, 3.0, 4.0, 5.0]]);
+        let indices = rctensor2(&[[1i64, 1]]);
+        let updates = rctensor2(&[[1.1f32, 2.1]]);
+        let output = op.eval(tvec!(data, indices, updates))?;
+        assert_eq!(*output[0], tensor2(&[[1.0f32, 5.2, 3.0, 4.0, 5.0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn scatter_elements_bad_shapes() {
+        let op = ScatterElements::new(1, ScatterReduction::None);
+        let data = rctensor2(&[[1.0f32, 2.0, 3.0, 4.0, 5.0]]);
+        let indices = rctensor2(&[[1i64], [0]]);
+        let updates = rctensor2(&[[1.1f32], [2.1]]);
+        assert!(op.eval(tvec!(data, indices, updates)).is_err());
+        let data = TypedFact::dt_shape(f32::datum_type(), &[1, 5]);
+        let indices = TypedFact::dt_shape(i64::datum_type(), &[1, 2]);
+        let updates = TypedFact::dt_shape(f32::datum_type(), &[1, 3]);
+        assert!(op.output_facts(&[&data, &indices, &updates]).is_err());
+        let indices = TypedFact::dt_shape(i64::datum_type(), &[2, 2]);
+        let updates = TypedFact::dt_shape(f32::datum_type(), &[2, 2]);
+        assert!(op.output_facts(&[&data, &indices, &updates]).is_err());
+    }
+}
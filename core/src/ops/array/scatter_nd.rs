@@ -0,0 +1,150 @@
+use super::ScatterReduction;
+use crate::internal::*;
+use ndarray::*;
+
+/// Write slices of updates in a copy of data, at the coordinates given in the
+/// last axis of indices. Indices can be negative.
+#[derive(Debug, Clone, new, Hash)]
+pub struct ScatterNd {
+    pub reduction: ScatterReduction,
+}
+
+impl_dyn_hash!(ScatterNd);
+
+impl Op for ScatterNd {
+    fn name(&self) -> Cow<str> {
+        "ScatterNd".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("reduction: {}", self.reduction.as_str())])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl ScatterNd {
+    fn eval_t<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+        combine: &dyn Fn(&mut T, &T),
+    ) -> TractResult<Tensor> {
+        let mut data = data.to_array_view::<T>()?.to_owned();
+        let updates = updates.to_array_view::<T>()?;
+        let prefix_rank = indices.ndim() - 1;
+        for prefix in tract_ndarray::indices(&indices.shape()[..prefix_rank]) {
+            let mut coords = indices.view();
+            let mut update = updates.view();
+            for &x in prefix.slice().iter() {
+                coords.index_axis_inplace(Axis(0), x);
+                update.index_axis_inplace(Axis(0), x);
+            }
+            let mut dst = data.view_mut();
+            for &x in coords.iter() {
+                let dim = dst.shape()[0] as i64;
+                if x < -dim || x >= dim {
+                    bail!("ScatterNd: index {} out of range for axis of size {}", x, dim);
+                }
+                dst.index_axis_inplace(Axis(0), if x < 0 { x + dim } else { x } as usize);
+            }
+            dst.zip_mut_with(&update, |a, b| combine(a, b));
+        }
+        Ok(data.into_tensor())
+    }
+
+    fn eval_assign<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Tensor> {
+        self.eval_t::<T>(data, indices, updates, &|a, b| *a = b.clone())
+    }
+
+    fn eval_reduce<T>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Tensor>
+    where
+        T: Datum + Copy + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        self.eval_t::<T>(data, indices, updates, &self.reduction.combine::<T>())
+    }
+}
+
+impl EvalOp for ScatterNd {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        if indices.rank() == 0 {
+            bail!("ScatterNd: indices must have at least one axis");
+        }
+        let k = indices.shape()[indices.rank() - 1];
+        let expected: TVec<usize> = indices.shape()[..indices.rank() - 1]
+            .iter()
+            .chain(data.shape().iter().skip(k))
+            .cloned()
+            .collect();
+        if k > data.rank() || updates.shape() != &*expected {
+            bail!(
+                "ScatterNd: inconsistent shapes data:{:?} indices:{:?} updates:{:?}",
+                data.shape(),
+                indices.shape(),
+                updates.shape()
+            );
+        }
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let output = if self.reduction == ScatterReduction::None {
+            dispatch_datum!(Self::eval_assign(data.datum_type())(self, &data, &indices, &updates))?
+        } else {
+            dispatch_numbers!(Self::eval_reduce(data.datum_type())(
+                self, &data, &indices, &updates
+            ))?
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for ScatterNd {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scatter_nd_slices() -> TractResult<()> {
+        let op = ScatterNd::new(ScatterReduction::None);
+        let data = rctensor2(&[[1, 2], [3, 4], [5, 6]]);
+        let indices = rctensor2(&[[2i64], [-3]]);
+        let updates = rctensor2(&[[7, 8], [9, 10]]);
+        let output = op.eval(tvec!(data, indices, updates))?;
+        assert_eq!(*output[0], tensor2(&[[9, 10], [3, 4], [7, 8]]));
+        Ok(())
+    }
+
+    #[test]
+    fn scatter_nd_mul() -> TractResult<()> {
+        let op = ScatterNd::new(ScatterReduction::Mul);
+        let data = rctensor1(&[1, 2, 3, 4]);
+        let indices = rctensor2(&[[1i64], [1], [3]]);
+        let updates = rctensor1(&[2, 3, 10]);
+        let output = op.eval(tvec!(data, indices, updates))?;
+        assert_eq!(*output[0], tensor1(&[1, 12, 3, 40]));
+        Ok(())
+    }
+}
@@ -63,6 +63,9 @@ impl EvalOp for Slice {
 impl TypedOp for Slice {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut fact = inputs[0].clone();
+        if (self.start.clone() - &self.end).prove_strict_positive() {
+            bail!("Invalid slice {}..{} on axis {}", self.start, self.end, self.axis);
+        }
         fact.shape.set(self.axis, (self.end.clone() - &self.start).to_dim());
         Ok(tvec!(fact))
     }
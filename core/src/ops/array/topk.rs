@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+
+use crate::internal::*;
+use ndarray::*;
+
+/// Extracts the `k` largest (or smallest) elements along an axis.
+///
+/// Inputs are the data and `k`, as an integer scalar. Outputs are the
+/// values and their indices (as i64) along the axis. Ties are broken by
+/// taking the lowest index first. If `sorted` is false, the selected values
+/// come out in their input order instead of sorted.
+#[derive(Debug, Clone, new, Hash)]
+pub struct TopK {
+    pub axis: usize,
+    pub largest: bool,
+    pub sorted: bool,
+    /// Output dimension along `axis` when `k` is not a constant, usually a
+    /// symbol of the model.
+    pub k_dim_hint: Option<TDim>,
+}
+
+impl_dyn_hash!(TopK);
+
+impl Op for TopK {
+    fn name(&self) -> Cow<str> {
+        "TopK".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} largest: {} sorted: {}", self.axis, self.largest, self.sorted)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl TopK {
+    fn eval_t<T: Datum + PartialOrd>(
+        &self,
+        input: &Tensor,
+        k: usize,
+    ) -> TractResult<(Tensor, Tensor)> {
+        let input = input.to_array_view::<T>()?;
+        if k > input.shape()[self.axis] {
+            bail!(
+                "TopK: k is {} but axis {} has only {} elements",
+                k,
+                self.axis,
+                input.shape()[self.axis]
+            );
+        }
+        let mut shape: TVec<usize> = input.shape().into();
+        shape[self.axis] = k;
+        let mut values = ArrayD::<T>::default(&*shape);
+        let mut indices = ArrayD::<i64>::default(&*shape);
+        let mut buffer: Vec<usize> = Vec::with_capacity(input.shape()[self.axis]);
+        for ((lane, mut values), mut indices) in input
+            .lanes(Axis(self.axis))
+            .into_iter()
+            .zip(values.lanes_mut(Axis(self.axis)))
+            .zip(indices.lanes_mut(Axis(self.axis)))
+        {
+            buffer.clear();
+            buffer.extend(0..lane.len());
+            // sort is stable, so equal values keep their index order
+            buffer.sort_by(|a, b| {
+                let order = lane[*a].partial_cmp(&lane[*b]).unwrap_or(Ordering::Equal);
+                if self.largest {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+            let selected = &mut buffer[..k];
+            if !self.sorted {
+                selected.sort();
+            }
+            for (i, ix) in selected.iter().enumerate() {
+                values[i] = lane[*ix].clone();
+                indices[i] = *ix as i64;
+            }
+        }
+        Ok((values.into_tensor(), indices.into_tensor()))
+    }
+}
+
+impl EvalOp for TopK {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let k = inputs[1].cast_to_scalar::<i64>()?;
+        if k < 0 {
+            bail!("TopK: k must be positive, got {}", k);
+        }
+        let (values, indices) =
+            dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0], k as usize))?;
+        Ok(tvec!(values.into_arc_tensor(), indices.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for TopK {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if self.axis >= inputs[0].rank() {
+            bail!("TopK axis {} is invalid for input {:?}", self.axis, inputs[0]);
+        }
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[self.axis] = if let Some(k) = &inputs[1].konst {
+            k.cast_to_scalar::<i64>()?.to_dim()
+        } else if let Some(hint) = &self.k_dim_hint {
+            hint.clone()
+        } else {
+            bail!("TopK with a non-constant k requires a k_dim_hint")
+        };
+        Ok(tvec!(
+            TypedFact::dt_shape(inputs[0].datum_type, &*shape),
+            TypedFact::dt_shape(i64::datum_type(), &*shape)
+        ))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        let axes = (0..rank)
+            .filter(|&ix| ix != self.axis)
+            .map(|ix| AxisInfo {
+                inputs: tvec!(Some(ix), None),
+                outputs: tvec!(Some(ix), Some(ix)),
+                period: 1,
+                disposable: true,
+            })
+            .collect::<Vec<_>>();
+        Ok(axes.into_iter().collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn topk_largest() -> TractResult<()> {
+        let op = TopK::new(1, true, true, None);
+        let input = tensor2(&[[1.0f32, 3.0, 2.0, 3.0], [4.0, 0.0, 5.0, 1.0]]);
+        let outputs = op.eval(tvec!(input.into_arc_tensor(), rctensor0(2i64)))?;
+        assert_eq!(*outputs[0], tensor2(&[[3.0f32, 3.0], [5.0, 4.0]]));
+        assert_eq!(*outputs[1], tensor2(&[[1i64, 3], [2, 0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_smallest_axis_0() -> TractResult<()> {
+        let op = TopK::new(0, false, true, None);
+        let input = tensor2(&[[1i32, 3], [0, 5], [2, 4]]);
+        let outputs = op.eval(tvec!(input.into_arc_tensor(), rctensor0(1i64)))?;
+        assert_eq!(*outputs[0], tensor2(&[[0i32, 3]]));
+        assert_eq!(*outputs[1], tensor2(&[[1i64, 0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_unsorted() -> TractResult<()> {
+        let op = TopK::new(0, true, false, None);
+        let input = tensor1(&[1.0f32, 4.0, 2.0, 5.0, 3.0]);
+        let outputs = op.eval(tvec!(input.into_arc_tensor(), rctensor0(3i64)))?;
+        assert_eq!(*outputs[0], tensor1(&[4.0f32, 5.0, 3.0]));
+        assert_eq!(*outputs[1], tensor1(&[1i64, 3, 4]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_dynamic_k() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let input = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[5]))?;
+        let k = model.add_source("k", TypedFact::dt_shape(i64::datum_type(), &[0usize; 0]))?;
+        let k_dim = model.symbol_table.new_with_prefix("K");
+        let op = TopK::new(0, true, true, Some(k_dim.clone().into()));
+        let outputs = model.wire_node("topk", op, &[input, k])?;
+        assert_eq!(model.outlet_fact(outputs[0])?.shape.to_tvec(), tvec!(k_dim.into()));
+        let op = TopK::new(0, true, true, None);
+        assert!(model.wire_node("topk-no-hint", op, &[input, k]).is_err());
+        Ok(())
+    }
+}
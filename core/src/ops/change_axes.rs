@@ -429,6 +429,14 @@ impl TypedOp for AxisOp {
         Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, shape)))
     }
 
+    fn in_place_input(&self, _inputs: &[&TypedFact]) -> Option<usize> {
+        if let Move(..) = self.canonical().as_ref() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
     fn invariants(&self, _model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
         let mut axes = vec![];
         for i in 0..node.outputs[0].fact.shape.rank() {
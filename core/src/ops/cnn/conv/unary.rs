@@ -166,6 +166,7 @@ impl ConvUnary {
         if let Some(q) = self.q_params.as_ref() {
             q.inject_into_mmm(&mut *mmm)?;
         }
+        mmm.set_threads(tract_linalg::ops().mmm_threads_for(m, k, n));
 
         trace!(
             "Gemm iters={} m={} k={} n={}",
@@ -644,6 +645,7 @@ impl TypedOp for ConvUnary {
                     && (0..spatial_rank)
                         .all(|i| self.pool_spec.stride(i) == 1 && self.pool_spec.dilation(i) == 1)
                     && self.group == 1
+                    && (self.q_params.is_none() || self.bias.is_none())
                 {
                     use crate::ops::matmul::MatMulUnary;
                     let mut patch = TypedModelPatch::default();
@@ -728,6 +730,7 @@ impl TypedOp for ConvUnary {
                 } else if self.group != 1
                     && self.group == self.output_channels()
                     && self.group == self.input_channels()
+                    && self.q_params.is_none()
                 {
                     let op = dispatch_floatlike!(Self::to_depth_wise(dt)(self, &shape))
                         .context("in to_depth_wise")?;
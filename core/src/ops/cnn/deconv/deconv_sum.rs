@@ -0,0 +1,137 @@
+use crate::internal::*;
+use crate::ops::cnn::PoolSpec;
+use ndarray::*;
+
+/// Accumulates kernel-sized patches into the output image of a transposed
+/// convolution (aka col2im).
+///
+/// Input is the product of the kernel by the deconvolution input, shaped as
+/// [N?, group, output channels per group * kernel spatial size, input spatial
+/// size]. Output is in the pool spec data format, and starts from the bias
+/// if any.
+#[derive(Clone, Debug, new, Hash)]
+pub struct DeconvSum {
+    pub pool_spec: PoolSpec,
+    /// shape of the deconvolution input
+    pub input_shape: TVec<usize>,
+    pub adjustments: TVec<usize>,
+    pub bias: Option<Arc<Tensor>>,
+    pub group: usize,
+}
+
+impl_dyn_hash!(DeconvSum);
+
+impl Op for DeconvSum {
+    fn name(&self) -> Cow<str> {
+        "DeconvSum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.pool_spec.info();
+        info.push(format!("Adjustments: {:?}, group: {}", self.adjustments, self.group));
+        Ok(info)
+    }
+
+    op_core_lir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvSum {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_floatlike!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0]))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for DeconvSum {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape = super::output_shape(&self.pool_spec, &*self.input_shape, &*self.adjustments)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    as_op!();
+}
+
+impl DeconvSum {
+    fn eval_t<T: Datum + Copy + num_traits::Zero>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let input_shape = self.pool_spec.data_format.shape(&*self.input_shape)?;
+        let output_shape = self.pool_spec.data_format.shape(super::output_shape(
+            &self.pool_spec,
+            &*self.input_shape,
+            &*self.adjustments,
+        )?)?;
+        let spatial = self.pool_spec.padding.compute_for_deconv(
+            input_shape.hw_dims(),
+            &self.pool_spec.kernel_shape,
+            &self.pool_spec.dilations(),
+            &self.pool_spec.strides(),
+            &self.adjustments,
+        );
+        let mut output = if let Some(bias) = &self.bias {
+            let mut bias_shape = tvec!(1; output_shape.rank());
+            bias_shape[output_shape.c_axis()] = bias.len();
+            let bias = bias.cast_to::<T>()?;
+            let bias = bias.to_array_view::<T>()?.into_shape(&*bias_shape)?;
+            bias.broadcast(&*output_shape.shape).unwrap().to_owned()
+        } else {
+            ArrayD::<T>::zeros(&*output_shape.shape)
+        };
+        let output_slice = output.as_slice_mut().unwrap();
+        let input = input.as_slice::<T>()?;
+
+        // offsets of the kernel taps and of the input items in the output
+        // spatial space, relatively to the (cropped) output origin
+        let strides = self.pool_spec.strides();
+        let dilations = self.pool_spec.dilations();
+        let kernel_offsets: Vec<TVec<isize>> = indices(&*self.pool_spec.kernel_shape)
+            .into_iter()
+            .map(|k| {
+                (0..spatial.len())
+                    .map(|ax| (k[ax] * dilations[ax]) as isize - spatial[ax].pad_before as isize)
+                    .collect()
+            })
+            .collect();
+        let input_offsets: Vec<TVec<isize>> = indices(input_shape.hw_dims())
+            .into_iter()
+            .map(|x| (0..spatial.len()).map(|ax| (x[ax] * strides[ax]) as isize).collect())
+            .collect();
+        let output_spatial = output_shape.hw_dims();
+        let output_spatial_strides = output_shape.hw_strides();
+
+        let n = *input_shape.n().unwrap_or(&1);
+        if input.len() != n * *output_shape.c() * kernel_offsets.len() * input_offsets.len() {
+            bail!("Unexpected input size {} for {:?}", input.len(), self);
+        }
+        let mut input_ptr = 0;
+        for n in 0..n {
+            let output_n = n * output_shape.n_stride().unwrap_or(&0);
+            for co in 0..*output_shape.c() {
+                let output_c = output_n + co * output_shape.c_stride();
+                for k in &kernel_offsets {
+                    for x in &input_offsets {
+                        let mut offset = output_c as isize;
+                        let mut valid = true;
+                        for ax in 0..spatial.len() {
+                            let coord = k[ax] + x[ax];
+                            if coord < 0 || coord >= output_spatial[ax] as isize {
+                                valid = false;
+                                break;
+                            }
+                            offset += coord * output_spatial_strides[ax] as isize;
+                        }
+                        if valid {
+                            output_slice[offset as usize] =
+                                output_slice[offset as usize] + input[input_ptr];
+                        }
+                        input_ptr += 1;
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
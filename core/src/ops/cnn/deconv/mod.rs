@@ -0,0 +1,34 @@
+use crate::internal::*;
+use crate::ops::cnn::PoolSpec;
+
+mod deconv_sum;
+mod unary;
+
+pub use self::deconv_sum::DeconvSum;
+pub use self::unary::DeconvUnary;
+
+/// Full output shape of a transposed convolution, in the pool spec data
+/// format.
+pub fn output_shape<D: DimLike>(
+    pool_spec: &PoolSpec,
+    x_shape: &[D],
+    adjustments: &[usize],
+) -> TractResult<TVec<D>> {
+    let x_shape = pool_spec.data_format.shape(x_shape)?;
+    let spatial = pool_spec.padding.compute_for_deconv(
+        x_shape.hw_dims(),
+        &pool_spec.kernel_shape,
+        &pool_spec.dilations(),
+        &pool_spec.strides(),
+        adjustments,
+    );
+    let channels = pool_spec
+        .output_channel_override
+        .ok_or_else(|| format_err!("Deconvolution requires an explicit output channel count"))?;
+    let shape = pool_spec.data_format.from_n_c_hw(
+        x_shape.n().cloned().unwrap_or_else(|| 1.into()),
+        channels.into(),
+        spatial.into_iter().map(|d| d.output).collect::<TVec<D>>(),
+    )?;
+    Ok(shape.shape)
+}
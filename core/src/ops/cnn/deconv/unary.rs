@@ -0,0 +1,310 @@
+use crate::internal::*;
+use crate::ops::cnn::{KernelFormat, PoolSpec};
+use crate::ops::matmul::MatMulUnary;
+use crate::ops::nn::DataFormat;
+
+use super::DeconvSum;
+
+/// Transposed convolution (aka deconvolution) with a constant kernel.
+///
+/// The kernel is laid out as for the matching forward convolution, so input
+/// and output channels are swapped compared to ConvUnary: OIHW kernels are
+/// [input channels, output channels / group, H, W], HWIO kernels are
+/// [H, W, output channels / group, input channels].
+///
+/// The padding spec describes how much of the full-size output is cropped,
+/// while adjustments (ONNX output_padding) extend it at the end.
+#[derive(Clone, Debug, new, Hash)]
+pub struct DeconvUnary {
+    pub pool_spec: PoolSpec,
+    pub kernel_format: KernelFormat,
+    pub kernel: Arc<Tensor>,
+    pub bias: Option<Arc<Tensor>>,
+    pub adjustments: TVec<usize>,
+    pub group: usize,
+}
+
+impl_dyn_hash!(DeconvUnary);
+
+impl DeconvUnary {
+    pub fn input_channels(&self) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => self.kernel.shape()[0],
+            KernelFormat::HWIO => self.kernel.shape()[self.kernel.rank() - 1],
+        }
+    }
+
+    pub fn output_channels(&self) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => self.kernel.shape()[1] * self.group,
+            KernelFormat::HWIO => self.kernel.shape()[self.kernel.rank() - 2] * self.group,
+        }
+    }
+
+    /// Kernel as [input channels, output channels / group, H, W]
+    pub fn kernel_as_oihw(&self) -> TractResult<Arc<Tensor>> {
+        match self.kernel_format {
+            KernelFormat::OIHW => Ok(self.kernel.clone()),
+            KernelFormat::HWIO => {
+                let rank = self.kernel.rank();
+                let mut permutation = vec![rank - 1, rank - 2];
+                permutation.extend(0..rank - 2);
+                Ok(self.kernel.clone().into_tensor().permute_axes(&permutation)?.into_arc_tensor())
+            }
+        }
+    }
+
+    /// Wire the deconvolution as a matrix product between the kernel and the
+    /// input, followed by a DeconvSum. Input shape must be known.
+    pub fn wire_with_deconv_sum(
+        &self,
+        name: &str,
+        target: &mut TypedModel,
+        input: OutletId,
+    ) -> TractResult<TVec<OutletId>> {
+        let input_shape: TVec<usize> = target
+            .outlet_fact(input)?
+            .shape
+            .as_concrete()
+            .ok_or_else(|| format_err!("Deconvolution requires a concrete input shape"))?
+            .into();
+        let shape = self.pool_spec.data_format.shape(&*input_shape)?;
+        let geo_dim: usize = shape.hw_dims().iter().product();
+        let geo_dims: TVec<TDim> = shape.hw_dims().iter().map(|d| d.to_dim()).collect();
+        let ci = self.input_channels();
+        let group_split = tvec!(self.group.to_dim(), (ci / self.group).to_dim());
+        let mut wire = input;
+        let b_trans = match self.pool_spec.data_format {
+            DataFormat::NCHW | DataFormat::CHW => {
+                // [N?, C, H, W] -> [N?, G, C/G, HW]
+                wire = target.wire_node(
+                    format!("{}.split_group", name),
+                    AxisOp::Reshape(shape.c_axis(), tvec!(ci.to_dim()), group_split),
+                    &[wire],
+                )?[0];
+                wire = target.wire_node(
+                    format!("{}.flatten_geo", name),
+                    AxisOp::Reshape(shape.c_axis() + 2, geo_dims, tvec!(geo_dim.to_dim())),
+                    &[wire],
+                )?[0];
+                false
+            }
+            DataFormat::NHWC | DataFormat::HWC => {
+                // [N?, H, W, C] -> [N?, G, HW, C/G]
+                wire = target.wire_node(
+                    format!("{}.flatten_geo", name),
+                    AxisOp::Reshape(shape.h_axis(), geo_dims, tvec!(geo_dim.to_dim())),
+                    &[wire],
+                )?[0];
+                wire = target.wire_node(
+                    format!("{}.split_group", name),
+                    AxisOp::Reshape(shape.h_axis() + 1, tvec!(ci.to_dim()), group_split),
+                    &[wire],
+                )?[0];
+                wire = target.wire_node(
+                    format!("{}.move_group", name),
+                    AxisOp::Move(shape.h_axis(), shape.h_axis() + 1),
+                    &[wire],
+                )?[0];
+                true
+            }
+        };
+        let b_rank = target.outlet_fact(wire)?.rank();
+        let kernel = self.kernel_as_oihw()?;
+        // [G, C/G, CO/G * KHW], used transposed
+        let a = kernel
+            .into_tensor()
+            .into_shape(&[self.group, ci / self.group, self.kernel.len() / ci])?
+            .broadcast_into_rank(b_rank)?;
+        wire = target.wire_node(
+            format!("{}.matmul", name),
+            MatMulUnary::new(a.into_arc_tensor(), true, b_trans, false, None),
+            &[wire],
+        )?[0];
+        target.wire_node(
+            name,
+            DeconvSum::new(
+                self.pool_spec.clone(),
+                input_shape,
+                self.adjustments.clone(),
+                self.bias.clone(),
+                self.group,
+            ),
+            &[wire],
+        )
+    }
+}
+
+impl Op for DeconvUnary {
+    fn name(&self) -> Cow<str> {
+        "Deconv".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.pool_spec.info();
+        info.push(format!(
+            "Kernel shape, {:?}: {:?} (groups:{}), adjustments: {:?}",
+            self.kernel_format,
+            self.kernel.shape(),
+            self.group,
+            self.adjustments
+        ));
+        if let Some(b) = &self.bias {
+            info.push(format!("Bias: {:?}", b))
+        }
+        Ok(info)
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvUnary {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut model = TypedModel::default();
+        let source = model
+            .add_source("source", TypedFact::dt_shape(inputs[0].datum_type(), inputs[0].shape()))?;
+        let output = self.wire_with_deconv_sum("adhoc", &mut model, source)?;
+        model.set_output_outlets(&*output)?;
+        SimplePlan::new(model)?.run(inputs.into_iter().map(|t| t.into_tensor()).collect())
+    }
+}
+
+impl TypedOp for DeconvUnary {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let x_shape = self.pool_spec.data_format.shape(inputs[0].shape.to_tvec())?;
+        if x_shape.c() != &self.input_channels().to_dim() {
+            bail!(
+                "Inconsistent deconvolution: input is {:?}, kernel expects {} input channels, {:?}",
+                inputs[0],
+                self.input_channels(),
+                self
+            );
+        }
+        if self.pool_spec.output_channel_override != Some(self.output_channels()) {
+            bail!(
+                "Inconsistent deconvolution: output channels from pool spec is {:?}, kernel expects {} output channels",
+                self.pool_spec.output_channel_override,
+                self.output_channels(),
+            );
+        }
+        if let Some(bias) = &self.bias {
+            if bias.len() != self.output_channels() {
+                bail!("Bias should have one value per output channel, got:{:?}", bias);
+            }
+        }
+        let shape = super::output_shape(&self.pool_spec, &*x_shape.shape, &*self.adjustments)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let fact = model.outlet_fact(node.inputs[0])?;
+        let shape = self.pool_spec.data_format.shape(fact.shape.to_tvec())?;
+        let mut axes = vec![];
+        if let Some(n_axis) = shape.n_axis() {
+            axes.push(AxisInfo::simple(n_axis).disposable(true));
+        }
+        Ok(axes.into_iter().collect())
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let kernel_surface = self.pool_spec.kernel_shape.iter().product::<usize>();
+        let fma = inputs[0].shape.iter().maybe_product()?
+            * (self.output_channels() / self.group * kernel_surface);
+        Ok(tvec!(
+            (
+                Cost::Params(inputs[0].datum_type),
+                (self.kernel.len() + self.bias.as_ref().map(|b| b.len()).unwrap_or(0)).to_dim()
+            ),
+            (Cost::FMA(inputs[0].datum_type), fma)
+        ))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !model.outlet_fact(node.inputs[0])?.shape.is_concrete() {
+            return Ok(None);
+        }
+        let mut patch = TypedModelPatch::default();
+        let wire = patch.tap_model(model, node.inputs[0])?;
+        let wire = self.wire_with_deconv_sum(&node.name, &mut patch, wire)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+
+    fn deconv(
+        data_format: DataFormat,
+        kernel: Tensor,
+        padding: PaddingSpec,
+        strides: TVec<usize>,
+        group: usize,
+    ) -> DeconvUnary {
+        let kernel_shape: TVec<usize> = kernel.shape()[2..].into();
+        let op = DeconvUnary::new(
+            PoolSpec::new(data_format, kernel_shape, padding, None, Some(strides), None),
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            None,
+            tvec!(0, 0),
+            group,
+        );
+        let pool_spec =
+            PoolSpec { output_channel_override: Some(op.output_channels()), ..op.pool_spec };
+        DeconvUnary { pool_spec, ..op }
+    }
+
+    #[test]
+    fn deconv_stride_2() -> TractResult<()> {
+        let kernel = Tensor::from(tract_ndarray::arr2(&[[1.0f32, 2.0], [3.0, 4.0]]))
+            .into_shape(&[1, 1, 2, 2])?;
+        let op = deconv(DataFormat::NCHW, kernel, PaddingSpec::Valid, tvec!(2, 2), 1);
+        let input = tensor4(&[[[[1.0f32, 10.0]]]]);
+        let output = op.eval(tvec!(input.into_arc_tensor()))?.remove(0);
+        let expected = tensor4(&[[[[1.0f32, 2.0, 10.0, 20.0], [3.0, 4.0, 30.0, 40.0]]]]);
+        output.close_enough(&expected, false)
+    }
+
+    #[test]
+    fn deconv_same_kernel_1_stride_2() -> TractResult<()> {
+        let kernel = tensor4(&[[[[2.0f32]]]]);
+        let op = deconv(DataFormat::NCHW, kernel, PaddingSpec::SameUpper, tvec!(1, 2), 1);
+        let input = tensor4(&[[[[1.0f32, 2.0, 3.0]]]]);
+        let output = op.eval(tvec!(input.into_arc_tensor()))?.remove(0);
+        let expected = tensor4(&[[[[2.0f32, 0.0, 4.0, 0.0, 6.0, 0.0]]]]);
+        output.close_enough(&expected, false)
+    }
+
+    #[test]
+    fn deconv_overlap_groups_nhwc() -> TractResult<()> {
+        // two groups, each mapping one channel to one channel, kernel 3, stride 2
+        let kernel =
+            tensor3(&[[[1.0f32, 1.0, 1.0]], [[1.0, 2.0, 3.0]]]).into_shape(&[2, 1, 1, 3])?;
+        let op = deconv(
+            DataFormat::NHWC,
+            kernel,
+            PaddingSpec::Explicit(tvec!(0, 1), tvec!(0, 1), false),
+            tvec!(1, 2),
+            2,
+        );
+        let input = tensor4(&[[[[1.0f32, 1.0], [2.0, 2.0]]]]);
+        let output = op.eval(tvec!(input.into_arc_tensor()))?.remove(0);
+        // full outputs: [1, 1, 3, 2, 2] and [1, 2, 5, 4, 6], cropped by 1 on each side
+        let expected = tensor4(&[[[[1.0f32, 2.0], [3.0, 5.0], [2.0, 4.0]]]]);
+        output.close_enough(&expected, false)
+    }
+}
@@ -81,6 +81,76 @@ impl PaddingSpec {
         }
     }
 
+    /// Output and cropping of a transposed convolution (deconvolution).
+    ///
+    /// `pad_before` and `pad_after` are the number of items to remove from
+    /// the full-size output. With SAME padding, the output may also be longer
+    /// than the full-size output if the kernel field is narrower than the
+    /// stride: the extra items at the end get no contribution.
+    pub fn compute_for_deconv<D: DimLike>(
+        &self,
+        input_spatial_shape: &[D],
+        kernel_spatial_shape: &[usize],
+        dilations: &[usize],
+        strides: &[usize],
+        adjustments: &[usize],
+    ) -> TVec<ComputedPaddedDim<D>> {
+        (0..input_spatial_shape.len())
+            .map(|d| {
+                self.compute_one_for_deconv(
+                    d,
+                    &input_spatial_shape[d],
+                    kernel_spatial_shape[d],
+                    dilations[d],
+                    strides[d],
+                    adjustments[d],
+                )
+            })
+            .collect()
+    }
+
+    pub fn compute_one_for_deconv<D: DimLike>(
+        &self,
+        axis: usize,
+        input: &D,
+        kernel: usize,
+        dilation: usize,
+        stride: usize,
+        adjustment: usize,
+    ) -> ComputedPaddedDim<D> {
+        let kernel_field = (kernel - 1) * dilation + 1;
+        let full = (input.clone() - 1) * stride + kernel_field + adjustment;
+        match self {
+            PaddingSpec::Valid => ComputedPaddedDim::new(full, 0.into(), 0.into()),
+            PaddingSpec::Explicit(ref bef, ref aft, _) => ComputedPaddedDim::new(
+                full - bef[axis] - aft[axis],
+                bef[axis].into(),
+                aft[axis].into(),
+            ),
+            PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+                // output is input * stride. A kernel field narrower than the stride
+                // leaves (stride - kernel_field - adjustment) empty items at the end
+                // of the full output: they are kept as an implicit adjustment instead
+                // of a negative crop.
+                let crop = (kernel_field + adjustment).saturating_sub(stride);
+                let (lower, higher) = (crop / 2, crop - crop / 2);
+                let (before, after) =
+                    if self == &PaddingSpec::SameUpper { (lower, higher) } else { (higher, lower) };
+                ComputedPaddedDim::new(input.clone() * stride, before.into(), after.into())
+            }
+        }
+    }
+
+    /// Symbolic counterpart of the saturating subtractions of the integer case: symbols
+    /// are assumed big enough unless their assertions prove the value negative.
+    fn saturate<D: DimLike>(d: D) -> D {
+        if (-d.to_dim()).prove_strict_positive() {
+            D::zero()
+        } else {
+            d
+        }
+    }
+
     fn valid<D: DimLike>(
         input: &D,
         kernel: usize,
@@ -91,7 +161,7 @@ impl PaddingSpec {
         let output = if let Ok(int) = input.to_usize() {
             D::from((int + 1).saturating_sub(kernel_field).div_ceil(stride))
         } else {
-            (input.clone() + 1 - kernel_field).div_ceil(stride)
+            Self::saturate(input.clone() + 1 - kernel_field).div_ceil(stride)
         };
         ComputedPaddedDim::new(output, 0.into(), 0.into())
     }
@@ -109,7 +179,7 @@ impl PaddingSpec {
         let dividend = if let Ok(int) = input.to_usize() {
             D::from((int + bef + aft).saturating_sub(kernel_field))
         } else {
-            input.clone() + bef + aft - kernel_field
+            Self::saturate(input.clone() + bef + aft - kernel_field)
         };
         let output = if ceil_mode { dividend.div_ceil(stride) } else { dividend.div(stride) } + 1;
         ComputedPaddedDim::new(output, bef.into(), aft.into())
@@ -129,7 +199,7 @@ impl PaddingSpec {
                 .saturating_sub(input);
             pad.into()
         } else {
-            (output.clone() - 1) * stride + kernel_field - input
+            Self::saturate((output.clone() - 1) * stride + kernel_field - input)
         };
         let lower_pad = pad.clone() / 2;
         let higher_pad = pad - &lower_pad;
@@ -142,6 +212,24 @@ impl PaddingSpec {
 mod tests {
     use super::*;
 
+    #[test]
+    fn same_symbolic_with_assertions() {
+        let table = SymbolTable::default();
+        let n = table.sym("N");
+        table.add_assertion(Assertion::MultipleOf(n.clone(), 2)).unwrap();
+        let s = PaddingSpec::SameUpper.compute(&[n.to_dim()], &[1], &[1], &[2]);
+        assert_eq!(s, tvec!(ComputedPaddedDim::new(n.to_dim() / 2, 0.into(), 0.into())));
+    }
+
+    #[test]
+    fn valid_symbolic_with_assertions() {
+        let table = SymbolTable::default();
+        let n = table.sym("N");
+        table.add_assertion(Assertion::LessOrEqual(n.clone(), 1)).unwrap();
+        let s = PaddingSpec::Valid.compute(&[n.to_dim()], &[3], &[1], &[1]);
+        assert_eq!(s, tvec!(ComputedPaddedDim::new(0.into(), 0.into(), 0.into())));
+    }
+
     #[test]
     fn same_stride_1() {
         assert_eq!(PaddingSpec::same(&1usize, 2usize, 1, 1, true), ComputedPaddedDim::new(1, 0, 1));
@@ -192,6 +280,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deconv_valid() {
+        assert_eq!(
+            PaddingSpec::Valid.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(7, 0, 0)
+        );
+        assert_eq!(
+            PaddingSpec::Valid.compute_one_for_deconv(0, &3usize, 3, 2, 1, 1),
+            ComputedPaddedDim::new(8, 0, 0)
+        );
+    }
+
+    #[test]
+    fn deconv_explicit() {
+        let spec = PaddingSpec::Explicit(tvec!(1), tvec!(2), false);
+        assert_eq!(
+            spec.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(4, 1, 2)
+        );
+    }
+
+    #[test]
+    fn deconv_same() {
+        assert_eq!(
+            PaddingSpec::SameUpper.compute_one_for_deconv(0, &3usize, 4, 1, 2, 0),
+            ComputedPaddedDim::new(6, 1, 1)
+        );
+        assert_eq!(
+            PaddingSpec::SameUpper.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(6, 0, 1)
+        );
+        assert_eq!(
+            PaddingSpec::SameLower.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(6, 1, 0)
+        );
+    }
+
+    #[test]
+    fn deconv_same_kernel_narrower_than_stride() {
+        assert_eq!(
+            PaddingSpec::SameUpper.compute_one_for_deconv(0, &3usize, 1, 1, 2, 0),
+            ComputedPaddedDim::new(6, 0, 0)
+        );
+        assert_eq!(
+            PaddingSpec::SameLower.compute_one_for_deconv(0, &3usize, 1, 1, 3, 1),
+            ComputedPaddedDim::new(9, 0, 0)
+        );
+    }
+
     #[test]
     fn same_upper() {
         assert_eq!(PaddingSpec::same(&7usize, 1usize, 1, 2, true), ComputedPaddedDim::new(4, 0, 0));
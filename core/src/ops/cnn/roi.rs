@@ -0,0 +1,197 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// How a region of interest is reduced to the fixed output size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoiMode {
+    /// Average of the input cells covered by each output bin.
+    AvgPool,
+    /// Maximum of the input cells covered by each output bin.
+    MaxPool,
+    /// Bilinear sampling at the output positions.
+    Resample(ResampleMethod),
+}
+
+/// Mapping of output positions to input coordinates for resampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResampleMethod {
+    /// Pixel centers are aligned: x_in = begin + (x_out + 0.5) * scale - 0.5.
+    Symmetric,
+    /// x_in = begin + x_out * scale
+    Asymmetric,
+    /// First and last output pixels are on the region boundaries.
+    Aligned,
+}
+
+/// Extract fixed size patches from regions of interest of a NCHW input.
+///
+/// Inputs are the data, the regions (one [top, left, bottom, right] row per
+/// region, in input pixels) and the batch entry of each region. Output has
+/// shape [regions, C, output_size...].
+#[derive(Debug, Clone, new, Hash)]
+pub struct RoiPool {
+    pub output_size: TVec<usize>,
+    pub mode: RoiMode,
+}
+
+impl_dyn_hash!(RoiPool);
+
+impl Op for RoiPool {
+    fn name(&self) -> Cow<str> {
+        "RoiPool".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("output_size: {:?} mode: {:?}", self.output_size, self.mode)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl RoiPool {
+    fn bin(&self, begin: f32, end: f32, ix: usize, len: usize, dim: usize) -> (usize, usize) {
+        let scale = (end - begin) / len as f32;
+        let start = (begin + ix as f32 * scale).floor().max(0.0) as usize;
+        let end = (begin + (ix + 1) as f32 * scale).ceil().max(0.0) as usize;
+        (start.min(dim), end.min(dim))
+    }
+
+    fn coord(method: ResampleMethod, begin: f32, end: f32, ix: usize, len: usize) -> f32 {
+        match method {
+            ResampleMethod::Symmetric => {
+                begin + (ix as f32 + 0.5) * (end - begin) / len as f32 - 0.5
+            }
+            ResampleMethod::Asymmetric => begin + ix as f32 * (end - begin) / len as f32,
+            ResampleMethod::Aligned if len > 1 => {
+                begin + ix as f32 * (end - begin) / (len - 1) as f32
+            }
+            ResampleMethod::Aligned => (begin + end) / 2.0,
+        }
+    }
+
+    fn bilinear(plane: &ArrayView2<f32>, y: f32, x: f32) -> f32 {
+        let (h, w) = plane.dim();
+        // like an empty pooling bin, an empty plane samples as zero
+        if h == 0 || w == 0 {
+            return 0.0;
+        }
+        let y = y.max(0.0).min((h - 1) as f32);
+        let x = x.max(0.0).min((w - 1) as f32);
+        let (y0, x0) = (y.floor() as usize, x.floor() as usize);
+        let (y1, x1) = ((y0 + 1).min(h - 1), (x0 + 1).min(w - 1));
+        let (dy, dx) = (y - y0 as f32, x - x0 as f32);
+        let top = plane[(y0, x0)] * (1.0 - dx) + plane[(y0, x1)] * dx;
+        let bottom = plane[(y1, x0)] * (1.0 - dx) + plane[(y1, x1)] * dx;
+        top * (1.0 - dy) + bottom * dy
+    }
+}
+
+impl EvalOp for RoiPool {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, rois, batch_index) = args_3!(inputs);
+        let dt = input.datum_type();
+        let input = input.cast_to::<f32>()?;
+        let input = input.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?;
+        let rois = rois.cast_to::<f32>()?;
+        let rois = rois.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let batch_index = batch_index.cast_to::<i64>()?;
+        let batch_index = batch_index.as_slice::<i64>()?;
+        let (n, c, h, w) = input.dim();
+        let (oh, ow) = (self.output_size[0], self.output_size[1]);
+        let mut output = Array4::<f32>::zeros((rois.nrows(), c, oh, ow));
+        for (r, roi) in rois.outer_iter().enumerate() {
+            let b = batch_index[r];
+            if b < 0 || b as usize >= n {
+                bail!("RoiPool: batch index {} out of range for batch of size {}", b, n);
+            }
+            let image = input.index_axis(Axis(0), b as usize);
+            for ((ch, y, x), v) in output.index_axis_mut(Axis(0), r).indexed_iter_mut() {
+                let plane = image.index_axis(Axis(0), ch);
+                *v = match self.mode {
+                    RoiMode::Resample(method) => {
+                        let y = Self::coord(method, roi[0], roi[2], y, oh);
+                        let x = Self::coord(method, roi[1], roi[3], x, ow);
+                        Self::bilinear(&plane, y, x)
+                    }
+                    pool => {
+                        let (y0, y1) = self.bin(roi[0], roi[2], y, oh, h);
+                        let (x0, x1) = self.bin(roi[1], roi[3], x, ow, w);
+                        let cells = plane.slice(s![y0..y1, x0..x1]);
+                        if cells.is_empty() {
+                            0.0
+                        } else if pool == RoiMode::AvgPool {
+                            cells.sum() / cells.len() as f32
+                        } else {
+                            cells.fold(std::f32::MIN, |acc, &v| acc.max(v))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(tvec!(output.into_tensor().cast_to_dt(dt)?.into_owned().into_arc_tensor()))
+    }
+}
+
+impl TypedOp for RoiPool {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() != 4 || self.output_size.len() != 2 {
+            bail!("RoiPool only supports 2D NCHW inputs, got {:?}", inputs[0]);
+        }
+        if inputs[1].rank() != 2 || inputs[2].rank() != 1 {
+            bail!("RoiPool expects rois as [R, 4] and batch index as [R]");
+        }
+        let mut shape = tvec!(inputs[1].shape[0].clone(), inputs[0].shape[1].clone());
+        shape.extend(self.output_size.iter().map(|d| d.to_dim()));
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(mode: RoiMode, output_size: usize) -> TractResult<Tensor> {
+        let input = Array4::from_shape_fn((1, 1, 4, 4), |(_, _, y, x)| (y * 4 + x) as f32);
+        let op = RoiPool::new(tvec!(output_size, output_size), mode);
+        let rois = rctensor2(&[[0f32, 0., 4., 4.], [1., 1., 3., 3.]]);
+        let output = op.eval(tvec!(input.into_arc_tensor(), rois, rctensor1(&[0i64, 0])))?;
+        Ok(output[0].clone().into_tensor())
+    }
+
+    #[test]
+    fn pools() -> TractResult<()> {
+        let max = run(RoiMode::MaxPool, 2)?;
+        assert_eq!(max, tensor4(&[[[[5f32, 7.], [13., 15.]]], [[[5., 6.], [9., 10.]]]]));
+        let avg = run(RoiMode::AvgPool, 1)?;
+        assert_eq!(avg, tensor4(&[[[[7.5f32]]], [[[7.5]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn resample() -> TractResult<()> {
+        let output = run(RoiMode::Resample(ResampleMethod::Aligned), 3)?;
+        let expected = tensor4(&[
+            [[[0f32, 2., 3.], [8., 10., 11.], [12., 14., 15.]]],
+            [[[5., 6., 7.], [9., 10., 11.], [13., 14., 15.]]],
+        ]);
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn resample_empty_plane() -> TractResult<()> {
+        let op = RoiPool::new(tvec!(2, 2), RoiMode::Resample(ResampleMethod::Symmetric));
+        let input = Array4::<f32>::zeros((1, 1, 0, 3));
+        let rois = rctensor2(&[[0f32, 0., 1., 1.]]);
+        let output = op.eval(tvec!(input.into_arc_tensor(), rois, rctensor1(&[0i64])))?;
+        assert_eq!(*output[0], Tensor::zero::<f32>(&[1, 1, 2, 2])?);
+        Ok(())
+    }
+}
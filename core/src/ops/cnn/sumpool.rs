@@ -133,10 +133,8 @@ impl SumPoolFixed {
                             .map(|v| *input_ptr.offset(v + input_offset as isize))
                             .sum::<T>();
 
-                        if let Some(div) = div {
-                            *values_ptr.offset(output_offset as isize + visitor.output_offset) =
-                                sum * div;
-                        }
+                        *values_ptr.offset(output_offset as isize + visitor.output_offset) =
+                            if let Some(div) = div { sum * div } else { sum };
                     }
                 }
             });
@@ -175,3 +173,36 @@ impl TypedOp for SumPoolFixed {
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+    use crate::ops::nn::DataFormat;
+
+    fn sum_pool(normalize: bool) -> TractResult<Tensor> {
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2),
+            PaddingSpec::Valid,
+            None,
+            Some(tvec!(2)),
+            None,
+        );
+        let op = SumPool::new(pool_spec, false, normalize);
+        let input = tensor3(&[[[1f32, 2., 3., 5.]]]);
+        Ok(op.eval(tvec!(input.into_arc_tensor()))?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn sums() -> TractResult<()> {
+        assert_eq!(sum_pool(false)?, tensor3(&[[[3f32, 8.]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn averages() -> TractResult<()> {
+        assert_eq!(sum_pool(true)?, tensor3(&[[[1.5f32, 4.]]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,552 @@
+use std::fmt;
+use std::ops::{Add, Mul};
+
+use num_traits::{One, Zero};
+use tract_itertools::Itertools;
+
+use crate::internal::*;
+use crate::ops::matmul::MatMul;
+use crate::ops::nn::{Reduce, Reducer};
+use ndarray::*;
+
+/// An einsum equation, with one label per axis of each input and of the
+/// output. Ellipsis are expanded at parsing time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Expr {
+    pub inputs: TVec<TVec<char>>,
+    pub output: TVec<char>,
+}
+
+impl Expr {
+    /// Parse an equation like "bij,bjk->bik". Input ranks are required to
+    /// expand the ellipsis ("...") if any.
+    pub fn parse(equation: &str, input_ranks: &[usize]) -> TractResult<Expr> {
+        let equation: String = equation.chars().filter(|c| !c.is_whitespace()).collect();
+        let (inputs, output) = if let Some(pos) = equation.find("->") {
+            (&equation[..pos], Some(&equation[pos + 2..]))
+        } else {
+            (&*equation, None)
+        };
+        let inputs: Vec<&str> = inputs.split(',').collect();
+        if inputs.len() != input_ranks.len() {
+            bail!(
+                "Einsum equation {} expects {} inputs, got {}",
+                equation,
+                inputs.len(),
+                input_ranks.len()
+            );
+        }
+        let mut ellipsis_rank = 0;
+        for (input, &rank) in inputs.iter().zip(input_ranks.iter()) {
+            let explicit = input.replace("...", "").chars().count();
+            if input.contains("...") {
+                if explicit > rank {
+                    bail!("Einsum input {} is too long for rank {}", input, rank);
+                }
+                ellipsis_rank = ellipsis_rank.max(rank - explicit);
+            } else if explicit != rank {
+                bail!("Einsum input {} does not match rank {}", input, rank);
+            }
+        }
+        // ellipsis axes get labels out of the ascii range
+        let ellipsis: TVec<char> =
+            (0..ellipsis_rank).map(|i| std::char::from_u32(0x3b1 + i as u32).unwrap()).collect();
+        let expand = |labels: &str, ellipsis_len: usize| -> TractResult<TVec<char>> {
+            let mut result = tvec!();
+            let mut parts = labels.split("...");
+            result.extend(parts.next().unwrap().chars());
+            if let Some(after) = parts.next() {
+                result.extend(ellipsis[ellipsis_rank - ellipsis_len..].iter().cloned());
+                result.extend(after.chars());
+            }
+            if parts.next().is_some() {
+                bail!("Einsum: multiple ellipsis in {}", labels);
+            }
+            if let Some(c) = result.iter().find(|c| !c.is_alphabetic()) {
+                bail!("Einsum: invalid label {:?} in {}", c, labels);
+            }
+            Ok(result)
+        };
+        let inputs = inputs
+            .iter()
+            .zip(input_ranks.iter())
+            .map(|(input, &rank)| {
+                let explicit = input.replace("...", "").chars().count();
+                expand(input, rank.saturating_sub(explicit))
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let output = if let Some(output) = output {
+            expand(output, ellipsis_rank)?
+        } else {
+            // implicit mode: ellipsis, then labels appearing once, sorted
+            let once = inputs
+                .iter()
+                .flatten()
+                .filter(|c| !ellipsis.contains(c))
+                .filter(|c| inputs.iter().flatten().filter(|x| x == c).count() == 1)
+                .cloned()
+                .sorted();
+            ellipsis.iter().cloned().chain(once).collect()
+        };
+        if let Some(c) = output.iter().find(|c| !inputs.iter().any(|i| i.contains(c))) {
+            bail!("Einsum: output label {} not found in inputs", c);
+        }
+        if output.iter().unique().count() != output.len() {
+            bail!("Einsum: repeated label in output");
+        }
+        Ok(Expr { inputs, output })
+    }
+
+    /// All labels, output ones first.
+    fn labels(&self) -> TVec<char> {
+        self.output.iter().chain(self.inputs.iter().flatten()).unique().cloned().collect()
+    }
+
+    fn label_dim<D: DimLike>(&self, label: char, shapes: &[&[D]]) -> TractResult<D> {
+        let mut dim: Option<D> = None;
+        for (labels, shape) in self.inputs.iter().zip(shapes.iter()) {
+            for (l, d) in labels.iter().zip(shape.iter()) {
+                if *l == label && d != &D::one() {
+                    if let Some(previous) = &dim {
+                        if previous != d {
+                            bail!(
+                                "Einsum: inconsistent dimensions for {}: {} and {}",
+                                label,
+                                previous,
+                                d
+                            );
+                        }
+                    } else {
+                        dim = Some(d.clone());
+                    }
+                }
+            }
+        }
+        Ok(dim.unwrap_or_else(D::one))
+    }
+
+    pub fn output_shape<D: DimLike>(&self, shapes: &[&[D]]) -> TractResult<TVec<D>> {
+        self.output.iter().map(|&l| self.label_dim(l, shapes)).collect()
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}->{}",
+            self.inputs.iter().map(|i| i.iter().collect::<String>()).join(","),
+            self.output.iter().collect::<String>()
+        )
+    }
+}
+
+/// Einstein summation. Mostly a front: it declutters to MatMul, AxisOp and
+/// Reduce when the expression allows it.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Einsum {
+    pub expr: Expr,
+}
+
+impl_dyn_hash!(Einsum);
+
+impl Op for Einsum {
+    fn name(&self) -> Cow<str> {
+        "Einsum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![self.expr.to_string()])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl Einsum {
+    fn eval_t<T: Datum + Copy + Zero + One + Add<Output = T> + Mul<Output = T>>(
+        &self,
+        inputs: &[Arc<Tensor>],
+    ) -> TractResult<Tensor> {
+        let shapes: TVec<&[usize]> = inputs.iter().map(|i| i.shape()).collect();
+        let labels = self.expr.labels();
+        let dims = labels
+            .iter()
+            .map(|&l| self.expr.label_dim(l, &shapes))
+            .collect::<TractResult<TVec<usize>>>()?;
+        let views = inputs
+            .iter()
+            .map(|i| i.to_array_view::<T>())
+            .collect::<TractResult<TVec<ArrayViewD<T>>>>()?;
+        // position of each input axis in the labels (and coords) list
+        let positions: TVec<TVec<usize>> = self
+            .expr
+            .inputs
+            .iter()
+            .map(|i| i.iter().map(|l| labels.iter().position(|x| x == l).unwrap()).collect())
+            .collect();
+        let mut output = ArrayD::<T>::zeros(&dims[..self.expr.output.len()]);
+        let mut coords: TVec<usize> = tvec!();
+        for full in indices(&*dims) {
+            let mut product = T::one();
+            for (view, positions) in views.iter().zip(positions.iter()) {
+                coords.clear();
+                coords.extend(positions.iter().zip(view.shape()).map(|(&p, &d)| {
+                    if d == 1 {
+                        0
+                    } else {
+                        full[p]
+                    }
+                }));
+                product = product * view[&*coords];
+            }
+            let cell = &mut output[&full.slice()[..self.expr.output.len()]];
+            *cell = *cell + product;
+        }
+        Ok(output.into_tensor())
+    }
+
+    fn declutter_unary(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let labels = &self.expr.inputs[0];
+        if labels.iter().unique().count() != labels.len() {
+            return Ok(None);
+        }
+        let mut patch = TypedModelPatch::default();
+        let mut wire = patch.tap_model(model, node.inputs[0])?;
+        let mut current = labels.clone();
+        wire = sum_over(
+            &mut patch,
+            &format!("{}.sum", node.name),
+            wire,
+            &mut current,
+            &self.expr.output,
+        )?;
+        wire = permute(&mut patch, &node.name, wire, &mut current, &self.expr.output)?;
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+
+    fn declutter_binary(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let (a_labels, b_labels) = (&self.expr.inputs[0], &self.expr.inputs[1]);
+        if a_labels.iter().unique().count() != a_labels.len()
+            || b_labels.iter().unique().count() != b_labels.len()
+        {
+            return Ok(None);
+        }
+        let output = &self.expr.output;
+        let batch: TVec<char> = output
+            .iter()
+            .filter(|l| a_labels.contains(l) && b_labels.contains(l))
+            .cloned()
+            .collect();
+        let m: TVec<char> = output.iter().filter(|l| !b_labels.contains(l)).cloned().collect();
+        let n: TVec<char> = output.iter().filter(|l| !a_labels.contains(l)).cloned().collect();
+        let k: TVec<char> = a_labels
+            .iter()
+            .filter(|l| b_labels.contains(l) && !output.contains(l))
+            .cloned()
+            .collect();
+        let a_fact = model.outlet_fact(node.inputs[0])?;
+        let b_fact = model.outlet_fact(node.inputs[1])?;
+        let dt = a_fact.datum_type;
+        if b_fact.datum_type != dt || tract_linalg::ops().mmm(dt, dt, dt, 1, 1, 1).is_none() {
+            return Ok(None);
+        }
+        let dim = |fact: &TypedFact, labels: &[char], l: &char| -> TDim {
+            fact.shape[labels.iter().position(|x| x == l).unwrap()].clone()
+        };
+        let m_dims: TVec<TDim> = m.iter().map(|l| dim(a_fact, a_labels, l)).collect();
+        let n_dims: TVec<TDim> = n.iter().map(|l| dim(b_fact, b_labels, l)).collect();
+        let k_dims: TVec<TDim> = k.iter().map(|l| dim(a_fact, a_labels, l)).collect();
+        if k.iter().any(|l| dim(a_fact, a_labels, l) != dim(b_fact, b_labels, l)) {
+            return Ok(None);
+        }
+        let product = |dims: &[TDim]| -> TractResult<TDim> { dims.iter().maybe_product() };
+        if product(&m_dims).is_err() || product(&n_dims).is_err() || product(&k_dims).is_err() {
+            return Ok(None);
+        }
+
+        let mut patch = TypedModelPatch::default();
+        let name = &node.name;
+        let mut a = patch.tap_model(model, node.inputs[0])?;
+        let mut a_current = a_labels.clone();
+        let a_keep: TVec<char> = batch.iter().chain(m.iter()).chain(k.iter()).cloned().collect();
+        a = sum_over(&mut patch, &format!("{}.a.sum", name), a, &mut a_current, &a_keep)?;
+        a = permute(&mut patch, &format!("{}.a", name), a, &mut a_current, &a_keep)?;
+        a = merge(&mut patch, &format!("{}.a.m", name), a, batch.len(), &m_dims)?;
+        a = merge(&mut patch, &format!("{}.a.k", name), a, batch.len() + 1, &k_dims)?;
+
+        let mut b = patch.tap_model(model, node.inputs[1])?;
+        let mut b_current = b_labels.clone();
+        let b_keep: TVec<char> = batch.iter().chain(k.iter()).chain(n.iter()).cloned().collect();
+        b = sum_over(&mut patch, &format!("{}.b.sum", name), b, &mut b_current, &b_keep)?;
+        b = permute(&mut patch, &format!("{}.b", name), b, &mut b_current, &b_keep)?;
+        b = merge(&mut patch, &format!("{}.b.k", name), b, batch.len(), &k_dims)?;
+        b = merge(&mut patch, &format!("{}.b.n", name), b, batch.len() + 1, &n_dims)?;
+
+        let mut wire = patch.wire_node(format!("{}.matmul", name), MatMul::default(), &[a, b])?[0];
+        wire = split(&mut patch, &format!("{}.n", name), wire, batch.len() + 1, &n_dims)?;
+        wire = split(&mut patch, &format!("{}.m", name), wire, batch.len(), &m_dims)?;
+        let mut current: TVec<char> =
+            batch.iter().chain(m.iter()).chain(n.iter()).cloned().collect();
+        wire = permute(&mut patch, name, wire, &mut current, output)?;
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+
+    fn declutter_nary(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        // contract the first two inputs, keeping the labels still needed
+        let needed: TVec<char> = self
+            .expr
+            .output
+            .iter()
+            .chain(self.expr.inputs[2..].iter().flatten())
+            .cloned()
+            .collect();
+        let first_output: TVec<char> = self.expr.inputs[0]
+            .iter()
+            .chain(self.expr.inputs[1].iter())
+            .unique()
+            .filter(|l| needed.contains(l))
+            .cloned()
+            .collect();
+        let first = Expr {
+            inputs: self.expr.inputs[0..2].iter().cloned().collect(),
+            output: first_output.clone(),
+        };
+        let rest = Expr {
+            inputs: std::iter::once(first_output)
+                .chain(self.expr.inputs[2..].iter().cloned())
+                .collect(),
+            output: self.expr.output.clone(),
+        };
+        let mut patch = TypedModelPatch::default();
+        let taps = node
+            .inputs
+            .iter()
+            .map(|i| patch.tap_model(model, *i))
+            .collect::<TractResult<TVec<_>>>()?;
+        let wire = patch.wire_node(format!("{}.0", node.name), Einsum::new(first), &taps[0..2])?[0];
+        let mut inputs = tvec!(wire);
+        inputs.extend(taps[2..].iter().cloned());
+        let wire = patch.wire_node(&node.name, Einsum::new(rest), &inputs)?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+}
+
+/// Sum over and remove the axes which labels are not in `keep`.
+fn sum_over(
+    patch: &mut TypedModelPatch,
+    name: &str,
+    mut wire: OutletId,
+    current: &mut TVec<char>,
+    keep: &[char],
+) -> TractResult<OutletId> {
+    let axes: TVec<usize> = (0..current.len()).filter(|&ix| !keep.contains(&current[ix])).collect();
+    if !axes.is_empty() {
+        wire = patch.wire_node(name, Reduce::new(axes.clone(), Reducer::Sum), &[wire])?[0];
+        for (ix, axis) in axes.iter().rev().enumerate() {
+            wire = patch.wire_node(format!("{}.rm-{}", name, ix), AxisOp::Rm(*axis), &[wire])?[0];
+            current.remove(*axis);
+        }
+    }
+    Ok(wire)
+}
+
+/// Move axes around until they match `target` order.
+fn permute(
+    patch: &mut TypedModelPatch,
+    name: &str,
+    mut wire: OutletId,
+    current: &mut TVec<char>,
+    target: &[char],
+) -> TractResult<OutletId> {
+    for (ix, label) in target.iter().enumerate() {
+        let pos = current.iter().position(|l| l == label).unwrap();
+        if pos != ix {
+            wire =
+                patch.wire_node(format!("{}.move-{}", name, ix), AxisOp::Move(pos, ix), &[wire])?
+                    [0];
+            let label = current.remove(pos);
+            current.insert(ix, label);
+        }
+    }
+    Ok(wire)
+}
+
+/// Merge dims starting at `at` to a single axis, or add one if dims is empty.
+fn merge(
+    patch: &mut TypedModelPatch,
+    name: &str,
+    wire: OutletId,
+    at: usize,
+    dims: &[TDim],
+) -> TractResult<OutletId> {
+    match dims.len() {
+        0 => Ok(patch.wire_node(name, AxisOp::Add(at), &[wire])?[0]),
+        1 => Ok(wire),
+        _ => {
+            let product = dims.iter().maybe_product()?;
+            let op = AxisOp::Reshape(at, dims.into(), tvec!(product));
+            Ok(patch.wire_node(name, op, &[wire])?[0])
+        }
+    }
+}
+
+/// Opposite of merge.
+fn split(
+    patch: &mut TypedModelPatch,
+    name: &str,
+    wire: OutletId,
+    at: usize,
+    dims: &[TDim],
+) -> TractResult<OutletId> {
+    match dims.len() {
+        0 => Ok(patch.wire_node(name, AxisOp::Rm(at), &[wire])?[0]),
+        1 => Ok(wire),
+        _ => {
+            let product = dims.iter().maybe_product()?;
+            let op = AxisOp::Reshape(at, tvec!(product), dims.into());
+            Ok(patch.wire_node(name, op, &[wire])?[0])
+        }
+    }
+}
+
+impl EvalOp for Einsum {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &*inputs))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Einsum {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs.len() != self.expr.inputs.len() {
+            bail!(
+                "Einsum {} expects {} inputs, got {}",
+                self.expr,
+                self.expr.inputs.len(),
+                inputs.len()
+            );
+        }
+        for (fact, labels) in inputs.iter().zip(self.expr.inputs.iter()) {
+            if fact.rank() != labels.len() {
+                bail!("Einsum {}: input {:?} has wrong rank", self.expr, fact);
+            }
+        }
+        let shapes: TVec<TVec<TDim>> = inputs.iter().map(|i| i.shape.to_tvec()).collect();
+        let shapes: TVec<&[TDim]> = shapes.iter().map(|s| &**s).collect();
+        let shape = self.expr.output_shape(&shapes)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        match self.expr.inputs.len() {
+            1 => self.declutter_unary(model, node),
+            2 => self.declutter_binary(model, node),
+            _ => self.declutter_nary(model, node),
+        }
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_ellipsis_and_implicit() -> TractResult<()> {
+        let expr = Expr::parse("...ij,...jk", &[4, 3])?;
+        assert_eq!(expr.inputs[0], tvec!('α', 'β', 'i', 'j'));
+        assert_eq!(expr.inputs[1], tvec!('β', 'j', 'k'));
+        assert_eq!(expr.output, tvec!('α', 'β', 'i', 'k'));
+        Ok(())
+    }
+
+    fn check(equation: &str, inputs: TVec<Tensor>) -> TractResult<()> {
+        let ranks: TVec<usize> = inputs.iter().map(|i| i.rank()).collect();
+        let op = Einsum::new(Expr::parse(equation, &ranks)?);
+        let expected = op.eval(inputs.iter().cloned().map(|t| t.into_arc_tensor()).collect())?;
+        let mut model = TypedModel::default();
+        let sources = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, i)| {
+                model.add_source(
+                    format!("s{}", ix),
+                    TypedFact::dt_shape(f32::datum_type(), i.shape()),
+                )
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let output = model.wire_node("einsum", op, &sources)?;
+        model.set_output_outlets(&output)?;
+        let model = model.declutter()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<Einsum>()));
+        let found = model.into_runnable()?.run(inputs)?;
+        found[0].close_enough(&expected[0], true)
+    }
+
+    fn t(shape: &[usize]) -> Tensor {
+        let len = shape.iter().product::<usize>();
+        tensor1(&(0..len).map(|i| i as f32 * 0.5 - 1.0).collect::<Vec<_>>())
+            .into_shape(shape)
+            .unwrap()
+    }
+
+    #[test]
+    fn eval_matmul() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2.0], [3.0, 4.0]]);
+        let b = tensor2(&[[1f32, 0.0], [1.0, 1.0]]);
+        let op = Einsum::new(Expr::parse("ij,jk->ik", &[2, 2])?);
+        let c = op.eval(tvec!(a.into_arc_tensor(), b.into_arc_tensor()))?;
+        assert_eq!(*c[0], tensor2(&[[3f32, 2.0], [7.0, 4.0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn declutter_batched_attention() -> TractResult<()> {
+        check("bhqd,bhkd->bhqk", tvec!(t(&[2, 3, 4, 5]), t(&[2, 3, 6, 5])))
+    }
+
+    #[test]
+    fn declutter_multiple_k_and_transposed_output() -> TractResult<()> {
+        check("abc,cbd->da", tvec!(t(&[2, 3, 4]), t(&[4, 3, 5])))
+    }
+
+    #[test]
+    fn declutter_outer_and_sum() -> TractResult<()> {
+        check("ij,k->kj", tvec!(t(&[2, 3]), t(&[4])))
+    }
+
+    #[test]
+    fn declutter_unary() -> TractResult<()> {
+        check("ijk->ki", tvec!(t(&[2, 3, 4])))
+    }
+
+    #[test]
+    fn declutter_three_inputs() -> TractResult<()> {
+        check("ij,jk,kl->il", tvec!(t(&[2, 3]), t(&[3, 4]), t(&[4, 5])))
+    }
+}
@@ -131,6 +131,14 @@ impl TypedOp for ElementWiseOp {
         Invariants::new_element_wise(model, node)
     }
 
+    fn in_place_input(&self, inputs: &[&TypedFact]) -> Option<usize> {
+        if self.0.output_type(inputs[0].datum_type).is_none() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
     fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
         let count: TDim = inputs[0].shape.iter().maybe_product()?;
         Ok(self
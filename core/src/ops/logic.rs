@@ -93,3 +93,317 @@ impl TypedOp for Iff {
         Ok(tvec!(TypedFact::dt_shape(inputs[1].datum_type, shape)))
     }
 }
+
+/// Conditional evaluation of one of two models.
+///
+/// The first input is the boolean condition. Each body input is fed by the op
+/// input designated by its mapping. Both bodies must have outputs of the same
+/// types and ranks. Dimensions the branches disagree on are symbols in the
+/// output facts, only known once a branch is taken.
+#[derive(Debug, Clone, Hash)]
+pub struct If {
+    pub then_body: TypedModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: TypedModel,
+    pub else_input_mapping: Vec<usize>,
+    pub output_facts: TVec<TypedFact>,
+    decluttered: bool,
+}
+
+impl_dyn_hash!(If);
+
+impl If {
+    /// Build the op, creating the symbols for mismatching output dimensions
+    /// in `symbol_table`.
+    pub fn new(
+        then_body: TypedModel,
+        then_input_mapping: Vec<usize>,
+        else_body: TypedModel,
+        else_input_mapping: Vec<usize>,
+        symbol_table: &SymbolTable,
+    ) -> TractResult<If> {
+        let then_outputs = then_body.output_outlets()?.len();
+        let else_outputs = else_body.output_outlets()?.len();
+        if then_outputs != else_outputs {
+            bail!("If branches have {} and {} outputs", then_outputs, else_outputs)
+        }
+        let output_facts = (0..then_outputs)
+            .map(|ix| {
+                let then_fact = then_body.output_fact(ix)?;
+                let else_fact = else_body.output_fact(ix)?;
+                if then_fact.datum_type != else_fact.datum_type
+                    || then_fact.rank() != else_fact.rank()
+                {
+                    bail!(
+                        "If branches output #{} mismatch: {:?} and {:?}",
+                        ix,
+                        then_fact,
+                        else_fact
+                    )
+                }
+                let shape = then_fact
+                    .shape
+                    .iter()
+                    .zip(else_fact.shape.iter())
+                    .map(|(t, e)| if t == e { t } else { symbol_table.new_with_prefix("I").into() })
+                    .collect::<TVec<TDim>>();
+                Ok(TypedFact::dt_shape(then_fact.datum_type, shape))
+            })
+            .collect::<TractResult<_>>()?;
+        Ok(If {
+            then_body,
+            then_input_mapping,
+            else_body,
+            else_input_mapping,
+            output_facts,
+            decluttered: false,
+        })
+    }
+
+    fn branch(&self, cond: bool) -> (&TypedModel, &[usize]) {
+        if cond {
+            (&self.then_body, &self.then_input_mapping)
+        } else {
+            (&self.else_body, &self.else_input_mapping)
+        }
+    }
+
+    fn declutter_bodies(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.decluttered {
+            let mut new = self.clone();
+            new.then_body = self.then_body.declutter()?;
+            new.else_body = self.else_body.declutter()?;
+            new.decluttered = true;
+            Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Optimize both branch bodies, and build their plans.
+    pub fn to_codegen_op(&self) -> TractResult<LirIf> {
+        Ok(LirIf {
+            then_plan: Arc::new(SimplePlan::new(self.then_body.clone().optimize()?)?),
+            then_input_mapping: self.then_input_mapping.clone(),
+            else_plan: Arc::new(SimplePlan::new(self.else_body.clone().optimize()?)?),
+            else_input_mapping: self.else_input_mapping.clone(),
+            output_facts: self.output_facts.clone(),
+        })
+    }
+
+    /// Wire the nodes of a branch body in `target`, feeding its inputs from
+    /// `inputs` according to `input_mapping`.
+    pub fn wire_body(
+        prefix: &str,
+        body: &TypedModel,
+        input_mapping: &[usize],
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut mapping = HashMap::<OutletId, OutletId>::new();
+        for (ix, outlet) in body.input_outlets()?.iter().enumerate() {
+            mapping.insert(*outlet, inputs[input_mapping[ix]]);
+        }
+        for id in body.eval_order()? {
+            let node = body.node(id);
+            if mapping.contains_key(&OutletId::new(id, 0)) {
+                continue;
+            }
+            let node_inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+            let wires = target.wire_node(
+                format!("{}.{}", prefix, node.name),
+                node.op.clone(),
+                &node_inputs,
+            )?;
+            for (ix, wire) in wires.into_iter().enumerate() {
+                mapping.insert(OutletId::new(id, ix), wire);
+            }
+        }
+        body.output_outlets()?.iter().map(|o| Ok(mapping[o])).collect()
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("Then branch inputs: {:?}", self.then_input_mapping),
+            format!("Else branch inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for If {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let cond = inputs[0].cast_to_scalar::<bool>()?;
+        let (body, input_mapping) = self.branch(cond);
+        let body_inputs =
+            input_mapping.iter().map(|ix| inputs[*ix].clone().into_tensor()).collect();
+        SimplePlan::new(body)?.run(body_inputs)
+    }
+}
+
+impl TypedOp for If {
+    as_op!();
+
+    fn output_facts(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(self.output_facts.clone())
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let cond = if let Some(cond) = &model.outlet_fact(node.inputs[0])?.konst {
+            cond.cast_to_scalar::<bool>()?
+        } else {
+            return self.declutter_bodies(model, node);
+        };
+        let mut patch = TypedModelPatch::default();
+        let inputs = node
+            .inputs
+            .iter()
+            .map(|i| patch.tap_model(model, *i))
+            .collect::<TractResult<TVec<_>>>()?;
+        let (body, input_mapping) = self.branch(cond);
+        let outputs = Self::wire_body(&node.name, body, input_mapping, &mut patch, &inputs)?;
+        for (ix, output) in outputs.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), output)?;
+        }
+        Ok(Some(patch))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        Ok(Some(TypedModelPatch::replace_single_op(
+            model,
+            node,
+            &node.inputs,
+            self.to_codegen_op()?,
+        )?))
+    }
+}
+
+/// Codegen form of `If`, running the plans of the optimized branch bodies.
+#[derive(Debug, Clone, Hash)]
+pub struct LirIf {
+    pub then_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub then_input_mapping: Vec<usize>,
+    pub else_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub else_input_mapping: Vec<usize>,
+    pub output_facts: TVec<TypedFact>,
+}
+
+impl_dyn_hash!(LirIf);
+
+impl Op for LirIf {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("Then branch inputs: {:?}", self.then_input_mapping),
+            format!("Else branch inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    op_core_lir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for LirIf {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (plan, input_mapping) = if inputs[0].cast_to_scalar::<bool>()? {
+            (&self.then_plan, &self.then_input_mapping)
+        } else {
+            (&self.else_plan, &self.else_input_mapping)
+        };
+        let body_inputs =
+            input_mapping.iter().map(|ix| inputs[*ix].clone().into_tensor()).collect();
+        plan.run(body_inputs)
+    }
+}
+
+impl TypedOp for LirIf {
+    as_op!();
+
+    fn output_facts(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(self.output_facts.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::array::Slice;
+
+    // then: x, else: x[..2]
+    fn if_op(symbol_table: &SymbolTable) -> TractResult<If> {
+        let mut then_body = TypedModel::default();
+        let x = then_body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        then_body.set_output_outlets(&[x])?;
+        let mut else_body = TypedModel::default();
+        let x = else_body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let x = else_body.wire_node("slice", Slice::new(0, 0, 2), &[x])?[0];
+        else_body.set_output_outlets(&[x])?;
+        If::new(then_body, vec![1], else_body, vec![1], symbol_table)
+    }
+
+    #[test]
+    fn if_branches_with_different_shapes() -> TractResult<()> {
+        let symbol_table = SymbolTable::default();
+        let op = if_op(&symbol_table)?;
+        let i = symbol_table.get("I").unwrap();
+        assert_eq!(
+            op.output_facts[0],
+            TypedFact::dt_shape(f32::datum_type(), tvec!(TDim::from(i)))
+        );
+        let x = rctensor1(&[1f32, 2., 3.]);
+        let then = op.eval(tvec!(rctensor0(true), x.clone()))?;
+        assert_eq!(*then[0], tensor1(&[1f32, 2., 3.]));
+        let other = op.eval(tvec!(rctensor0(false), x))?;
+        assert_eq!(*other[0], tensor1(&[1f32, 2.]));
+        Ok(())
+    }
+
+    #[test]
+    fn if_codegen() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let cond =
+            model.add_source("cond", TypedFact::dt_shape(bool::datum_type(), &[0usize; 0]))?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let op = if_op(&model.symbol_table)?;
+        let y = model.wire_node("if", op, &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        let model = model.into_optimized()?;
+        assert!(model.node(model.output_outlets()?[0].node).op_is::<LirIf>());
+        let plan = SimplePlan::new(model)?;
+        let x = tensor1(&[1f32, 2., 3.]);
+        assert_eq!(*plan.run(tvec!(tensor0(true), x.clone()))?[0], x);
+        assert_eq!(*plan.run(tvec!(tensor0(false), x))?[0], tensor1(&[1f32, 2.]));
+        Ok(())
+    }
+}
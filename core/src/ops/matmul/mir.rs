@@ -206,6 +206,20 @@ mod test {
         c.close_enough(&c_found, true).unwrap();
     }
 
+    #[test]
+    fn bin_half_floats() -> TractResult<()> {
+        let a = tensor2(&[[0f32, 1.0, 2.0], [3.0, 4.0, 5.0]]);
+        let b = tensor2(&[[0f32], [1.0], [2.0]]);
+        for dt in &[DatumType::F16, DatumType::BF16] {
+            let a = a.cast_to_dt(*dt)?.into_owned().into_arc_tensor();
+            let b = b.cast_to_dt(*dt)?.into_owned().into_arc_tensor();
+            let c_found = MatMul::default().eval(tvec!(a, b))?.pop().unwrap();
+            assert_eq!(c_found.datum_type(), *dt);
+            tensor2(&[[5f32], [14.0]]).close_enough(&c_found, true)?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn batch_input() -> TractResult<()> {
         crate::setup_test_logger();
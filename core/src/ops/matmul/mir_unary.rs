@@ -179,10 +179,7 @@ impl TypedOp for MatMulUnary {
                             patch.tap_model(model, concat_node.inputs[input - 1])?
                         }
                     };
-                    let mut a = self.a.slice(k_axis, offsets[ix], offsets[ix + 1])?;
-                    while a.rank() > 0 && a.shape()[0] == 1 {
-                        a.remove_axis(0)?;
-                    }
+                    let a = self.a.slice(k_axis, offsets[ix], offsets[ix + 1])?;
                     let wire = patch.wire_node(
                         format!("{}.k-{}-{}", node.name, offsets[ix], offsets[ix + 1]),
                         MatMulUnary { a: a.into_arc_tensor(), ..self.clone() },
@@ -323,6 +320,7 @@ impl MatMulUnary {
                 q.inject_into_mmm(&mut *mm)?;
             }
         }
+        mm.set_threads(tract_linalg::ops().mmm_threads_for(m, k, n));
         let rank = c_shape.len();
         if n > 1 {
             let mut packed_b_shape: TVec<usize> = b_shape[..b_shape.len() - 2].into();
@@ -365,7 +363,7 @@ impl MatMulUnary {
                 packed_as,
                 fused_ops: None,
                 mmm: mm,
-                k
+                k,
             },
             &[wire],
         )?[0];
@@ -373,3 +371,62 @@ impl MatMulUnary {
         Ok(patch)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::array::concat::ConcatSlice;
+    use crate::ops::array::TypedConcat;
+
+    fn split_over_concatenated_k(a: Tensor, b_shape: &[usize]) -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let mut var_shape: TVec<usize> = b_shape.into();
+        let k = var_shape.pop().unwrap();
+        let const_shape: TVec<usize> = var_shape.iter().cloned().chain(Some(2)).collect();
+        var_shape.push(k - 2);
+        let source = model.add_source("b", TypedFact::dt_shape(f32::datum_type(), &*var_shape))?;
+        let konst: Tensor = ArrayD::from_shape_vec(
+            &*const_shape,
+            (0..const_shape.iter().product()).map(|x| x as f32).collect(),
+        )?
+        .into();
+        let concat = model.wire_node(
+            "concat",
+            TypedConcat::new(
+                b_shape.len() - 1,
+                tvec!(ConcatSlice::Var, ConcatSlice::Const(konst.into_arc_tensor())),
+            ),
+            &[source],
+        )?;
+        let mm = model.wire_node(
+            "mm",
+            MatMulUnary::new(a.into_arc_tensor(), false, true, false, None),
+            &concat,
+        )?;
+        model.set_output_outlets(&mm)?;
+        let input: Tensor = ArrayD::from_shape_vec(
+            &*var_shape,
+            (0..var_shape.iter().product()).map(|x| x as f32 / 10.0).collect(),
+        )?
+        .into();
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        assert!(decluttered.nodes().iter().all(|n| !n.op_is::<TypedConcat>()));
+        assert_eq!(decluttered.output_fact(0)?, model.output_fact(0)?);
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        expected[0].close_enough(&found[0], true)
+    }
+
+    #[test]
+    fn split_over_concatenated_k_rank_2() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2.0, 3.0, 4.0, 5.0, 6.0]]);
+        split_over_concatenated_k(a, &[3, 6])
+    }
+
+    #[test]
+    fn split_over_concatenated_k_rank_3() -> TractResult<()> {
+        let a: Tensor =
+            ArrayD::from_shape_vec(&[1, 2, 6][..], (0..12).map(|x| x as f32).collect())?.into();
+        split_over_concatenated_k(a, &[1, 3, 6])
+    }
+}
@@ -20,6 +20,7 @@ pub mod change_axes;
 pub mod cnn;
 pub mod downsample;
 pub mod dummy;
+pub mod einsum;
 pub mod identity;
 pub mod konst;
 pub mod logic;
@@ -247,6 +248,14 @@ pub trait TypedOp:
         Ok(None)
     }
 
+    /// Input whose buffer the (single) output can take over, by computing in place.
+    ///
+    /// Used by the memory planner to have both values share the same region.
+    #[allow(unused_variables)]
+    fn in_place_input(&self, inputs: &[&TypedFact]) -> Option<usize> {
+        None
+    }
+
     /// Nested model multipliers, with label (for profiling).
     #[allow(unused_variables)]
     fn nested_model_multipliers(&self, inputs: &[&TypedFact]) -> Vec<(Cow<str>, f64)> {
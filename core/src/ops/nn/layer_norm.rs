@@ -0,0 +1,240 @@
+use crate::internal::*;
+use crate::ops::math::{Add, Mul, Pow, Rsqrt, Square, Sub};
+use num_traits::Float;
+
+use super::softmax::{
+    is_bin, is_element_wise, is_reduce, map_lanes, single_successor, successors, unary_scalar,
+};
+use super::Reducer;
+
+/// Normalizes the input to zero mean and unit variance over `axes`.
+///
+/// Scale and bias, if any, are left to the surrounding graph.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct LayerNorm {
+    pub axes: TVec<usize>,
+    #[educe(Hash(method = "hash_f32"))]
+    pub epsilon: f32,
+}
+
+impl_dyn_hash!(LayerNorm);
+
+impl Op for LayerNorm {
+    fn name(&self) -> Cow<str> {
+        "LayerNorm".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axes: {:?} epsilon: {}", self.axes, self.epsilon)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+fn layer_norm_t<T: Float>(x: &mut [T], epsilon: T) {
+    let len = T::from(x.len()).unwrap();
+    let mean = x.iter().fold(T::zero(), |acc, &x| acc + x) / len;
+    let var = x.iter().fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean)) / len;
+    let factor = (var + epsilon).sqrt().recip();
+    x.iter_mut().for_each(|px| *px = (*px - mean) * factor);
+}
+
+impl EvalOp for LayerNorm {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F32 => {
+                let kernel = (tract_linalg::ops().layer_norm_f32)();
+                map_lanes::<f32>(&input, &self.axes, |x| kernel.run(x, self.epsilon))?
+            }
+            DatumType::F64 => {
+                map_lanes::<f64>(&input, &self.axes, |x| layer_norm_t(x, self.epsilon as f64))?
+            }
+            DatumType::F16 => {
+                map_lanes::<f16>(&input, &self.axes, |x| layer_norm_t(x, f16::from(self.epsilon)))?
+            }
+            dt => bail!("LayerNorm does not support {:?}", dt),
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for LayerNorm {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        let axes = (0..rank)
+            .filter(|axis| !self.axes.contains(axis))
+            .map(|axis| AxisInfo::simple(axis))
+            .collect::<TVec<_>>();
+        Ok(axes.into())
+    }
+
+    fn change_axes(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        _io: InOut,
+        change: &AxisOp,
+    ) -> TractResult<Option<AxisChangeConsequence>> {
+        let mut axes = tvec!();
+        for axis in &self.axes {
+            if let Some(axis) = change.transform_axis(*axis) {
+                axes.push(axis);
+            } else {
+                return Ok(None);
+            }
+        }
+        let op = Some(Box::new(LayerNorm { axes, ..self.clone() }) as _);
+        Ok(Some(AxisChangeConsequence::new(model, node, op, change)))
+    }
+
+    as_op!();
+}
+
+/// Is `node` a Mul by the uniform 1/n, turning a sum over `n` values into a
+/// mean?
+fn is_mean_scaling(node: &TypedNode, n: usize) -> TractResult<bool> {
+    Ok(unary_scalar::<Mul>(node)?.map(|c| (c * n as f64 - 1.0).abs() < 1e-4).unwrap_or(false))
+}
+
+fn is_square_of(model: &TypedModel, node: &TypedNode, d: OutletId) -> TractResult<bool> {
+    if node.inputs.len() == 1 {
+        return Ok(node.inputs[0] == d && is_element_wise::<Square>(node));
+    }
+    if is_bin::<Mul>(node) {
+        return Ok(node.inputs == [d, d]);
+    }
+    if is_bin::<Pow>(node) && node.inputs[0] == d {
+        if let Some(exp) = &model.outlet_fact(node.inputs[1])?.konst {
+            return Ok(exp.len() == 1 && exp.cast_to_scalar::<f64>()? == 2.0);
+        }
+    }
+    Ok(false)
+}
+
+/// Recognizes the mean, sub, square, mean, add epsilon, rsqrt and mul chain
+/// of a layer normalization, starting from the first sum reduction.
+pub(super) fn declutter_layer_norm_pattern(
+    model: &TypedModel,
+    sum: &TypedNode,
+    axes: &[usize],
+) -> TractResult<Option<TypedModelPatch>> {
+    macro_rules! next {
+        ($node: expr) => {
+            if let Some(succ) = single_successor(model, $node) {
+                succ
+            } else {
+                return Ok(None);
+            }
+        };
+    }
+    let input = sum.inputs[0];
+    let fact = model.outlet_fact(input)?;
+    if !fact.datum_type.is_float() {
+        return Ok(None);
+    }
+    let n = if let Ok(n) = axes.iter().map(|&ax| fact.shape[ax].to_usize()).product() {
+        n
+    } else {
+        return Ok(None);
+    };
+    let mean = next!(sum);
+    if !is_mean_scaling(mean, n)? {
+        return Ok(None);
+    }
+    let sub = next!(mean);
+    if !is_bin::<Sub>(sub) || sub.inputs != [input, mean.id.into()] {
+        return Ok(None);
+    }
+    let d: OutletId = sub.id.into();
+    let d_succs = successors(model, sub);
+    if d_succs.len() != 2 {
+        return Ok(None);
+    }
+    let (square, out) = if is_square_of(model, d_succs[0], d)? {
+        (d_succs[0], d_succs[1])
+    } else if is_square_of(model, d_succs[1], d)? {
+        (d_succs[1], d_succs[0])
+    } else {
+        return Ok(None);
+    };
+    let var_sum = next!(square);
+    if !is_reduce(var_sum, Reducer::Sum, axes) {
+        return Ok(None);
+    }
+    let var = next!(var_sum);
+    if !is_mean_scaling(var, n)? {
+        return Ok(None);
+    }
+    let add_eps = next!(var);
+    let epsilon = if let Some(eps) = unary_scalar::<Add>(add_eps)? {
+        eps
+    } else {
+        return Ok(None);
+    };
+    let rsqrt = next!(add_eps);
+    if !is_element_wise::<Rsqrt>(rsqrt) || next!(rsqrt).id != out.id {
+        return Ok(None);
+    }
+    if !is_bin::<Mul>(out)
+        || (out.inputs != [d, rsqrt.id.into()] && out.inputs != [rsqrt.id.into(), d])
+    {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let wire = patch.tap_model(model, input)?;
+    let op = LayerNorm::new(axes.into(), epsilon as f32);
+    let wire = patch.wire_node(&out.name, op, &[wire])?[0];
+    patch.shunt_outside(model, out.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+    use crate::ops::nn::Reduce;
+
+    #[test]
+    fn layer_norm_leading_axis() -> TractResult<()> {
+        let input = tensor2(&[[1f32, 4.0], [3.0, 0.0]]);
+        let output = LayerNorm::new(tvec!(0), 0.0).eval(tvec!(input.into_arc_tensor()))?;
+        output[0].close_enough(&tensor2(&[[-1f32, 1.0], [1.0, -1.0]]), true)
+    }
+
+    #[test]
+    fn declutter_decomposed_layer_norm() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2, 4]))?;
+        let quarter = model.add_const("quarter", tensor2(&[[0.25f32]]))?;
+        let eps = model.add_const("eps", tensor2(&[[1e-5f32]]))?;
+        let two = model.add_const("two", tensor2(&[[2f32]]))?;
+        let sum = model.wire_node("sum", Reduce::new(tvec!(1), Reducer::Sum), &[x])?[0];
+        let mean = model.wire_node("mean", math::mul::bin_typed(), &[sum, quarter])?[0];
+        let d = model.wire_node("d", math::sub::bin_typed(), &[x, mean])?[0];
+        let sq = model.wire_node("sq", math::pow::bin_typed(), &[d, two])?[0];
+        let var_sum = model.wire_node("var_sum", Reduce::new(tvec!(1), Reducer::Sum), &[sq])?[0];
+        let var = model.wire_node("var", math::mul::bin_typed(), &[var_sum, quarter])?[0];
+        let var_eps = model.wire_node("var_eps", math::add::bin_typed(), &[var, eps])?[0];
+        let std = model.wire_node("std", math::sqrt(), &[var_eps])?[0];
+        let out = model.wire_node("out", math::div::bin_typed(), &[d, std])?;
+        model.set_output_outlets(&out)?;
+        let decluttered = model.clone().declutter()?;
+        assert!(decluttered.nodes().iter().any(|n| n.op_is::<LayerNorm>()));
+        assert_eq!(decluttered.nodes().len(), 2);
+        let input = tensor2(&[[0f32, 1.0, 2.0, 5.0], [-1.0, 0.5, 3.0, 3.0]]);
+        let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        found[0].close_enough(&expected[0], true)
+    }
+}
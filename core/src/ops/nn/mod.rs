@@ -1,8 +1,12 @@
 mod data_formats;
+mod layer_norm;
 mod reduce;
+mod softmax;
 
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape};
-pub use self::reduce::{Reduce, Reducer};
+pub use self::layer_norm::LayerNorm;
+pub use self::reduce::{CumulativeReduce, Reduce, Reducer};
+pub use self::softmax::Softmax;
 
 pub use crate::internal::*;
 
@@ -211,6 +211,21 @@ impl EvalOp for Reduce {
 
 impl TypedOp for Reduce {
     as_op!();
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        match self.reducer {
+            Reducer::Max => super::softmax::declutter_softmax_pattern(model, node, &self.axes),
+            Reducer::Sum => {
+                super::layer_norm::declutter_layer_norm_pattern(model, node, &self.axes)
+            }
+            _ => Ok(None),
+        }
+    }
+
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut shape: TVec<_> = inputs[0].shape.to_tvec();
         for &ax in &self.axes {
@@ -253,3 +268,89 @@ impl TypedOp for Reduce {
         Ok(Some(AxisChangeConsequence::new(model, node, op, change)))
     }
 }
+
+/// Running reduction along an axis: each output item is the reduction of the input
+/// items up to and including it, so the output has the input shape.
+#[derive(Clone, Debug, new, Hash)]
+pub struct CumulativeReduce {
+    pub axis: usize,
+    pub reducer: Reducer,
+}
+
+impl_dyn_hash!(CumulativeReduce);
+
+impl CumulativeReduce {
+    /// Fold `item`, a slice of length one along the axis, into the running value.
+    pub fn accumulate(&self, acc: Option<Tensor>, item: Tensor) -> TractResult<Tensor> {
+        if let Some(acc) = acc {
+            let both = Tensor::stack_tensors(self.axis, &[acc, item])?;
+            self.reducer.reduce(&[self.axis], &both)
+        } else {
+            Ok(item)
+        }
+    }
+}
+
+impl Op for CumulativeReduce {
+    fn name(&self) -> Cow<str> {
+        format!("CumulativeReduce<{:?}>", self.reducer).into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for CumulativeReduce {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let len = input.shape()[self.axis];
+        if len == 0 {
+            return Ok(tvec!(input));
+        }
+        let mut acc = None;
+        let mut items = Vec::with_capacity(len);
+        for i in 0..len {
+            let running = self.accumulate(acc.take(), input.slice(self.axis, i, i + 1)?)?;
+            items.push(running.clone());
+            acc = Some(running);
+        }
+        Ok(tvec!(Tensor::stack_tensors(self.axis, &items)?.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for CumulativeReduce {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if let Reducer::ArgMax(_) | Reducer::ArgMin(_) = self.reducer {
+            bail!("CumulativeReduce does not support {:?}", self.reducer);
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cumulative_reduce() -> TractResult<()> {
+        let input = rctensor2(&[[1f32, 4.], [3., 2.], [2., 5.]]);
+        let run = |reducer| -> TractResult<Arc<Tensor>> {
+            Ok(CumulativeReduce::new(0, reducer).eval(tvec!(input.clone()))?.remove(0))
+        };
+        assert_eq!(run(Reducer::Sum)?, rctensor2(&[[1f32, 4.], [4., 6.], [6., 11.]]));
+        assert_eq!(run(Reducer::Prod)?, rctensor2(&[[1f32, 4.], [3., 8.], [6., 40.]]));
+        assert_eq!(run(Reducer::Max)?, rctensor2(&[[1f32, 4.], [3., 4.], [3., 5.]]));
+        assert_eq!(run(Reducer::Min)?, rctensor2(&[[1f32, 4.], [1., 2.], [1., 2.]]));
+        Ok(())
+    }
+}
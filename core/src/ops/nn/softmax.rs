@@ -0,0 +1,269 @@
+use crate::internal::*;
+use crate::ops::binary::{BinMiniOp, TypedBinOp, UnaryOp};
+use crate::ops::element_wise::{ElementWiseMiniOp, ElementWiseOp};
+use crate::ops::math::{Div, Exp, Mul, Recip, Sub};
+use ndarray::*;
+use num_traits::Float;
+
+use super::{Reduce, Reducer};
+
+/// Softmax over one or several axes. Each lane takes three passes: max,
+/// exponentiation and sum, then scaling.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Softmax {
+    pub axes: TVec<usize>,
+}
+
+impl_dyn_hash!(Softmax);
+
+impl Op for Softmax {
+    fn name(&self) -> Cow<str> {
+        "Softmax".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axes: {:?}", self.axes)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+fn softmax_t<T: Float>(x: &mut [T]) {
+    let max = x.iter().fold(T::neg_infinity(), |acc, &x| acc.max(x));
+    let mut sum = T::zero();
+    for px in x.iter_mut() {
+        *px = (*px - max).exp();
+        sum = sum + *px;
+    }
+    x.iter_mut().for_each(|px| *px = *px / sum);
+}
+
+impl EvalOp for Softmax {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F32 => {
+                let kernel = (tract_linalg::ops().softmax_f32)();
+                map_lanes::<f32>(&input, &self.axes, |x| kernel.run(x))?
+            }
+            DatumType::F64 => map_lanes::<f64>(&input, &self.axes, softmax_t)?,
+            DatumType::F16 => map_lanes::<f16>(&input, &self.axes, softmax_t)?,
+            dt => bail!("Softmax does not support {:?}", dt),
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Softmax {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        let axes = (0..rank)
+            .filter(|axis| !self.axes.contains(axis))
+            .map(|axis| AxisInfo::simple(axis))
+            .collect::<TVec<_>>();
+        Ok(axes.into())
+    }
+
+    fn change_axes(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        _io: InOut,
+        change: &AxisOp,
+    ) -> TractResult<Option<AxisChangeConsequence>> {
+        let mut axes = tvec!();
+        for axis in &self.axes {
+            if let Some(axis) = change.transform_axis(*axis) {
+                axes.push(axis);
+            } else {
+                return Ok(None);
+            }
+        }
+        let op = Some(Box::new(Softmax { axes }) as _);
+        Ok(Some(AxisChangeConsequence::new(model, node, op, change)))
+    }
+
+    as_op!();
+}
+
+/// Apply `f` in place to each contiguous group of values spanning `axes`.
+pub(super) fn map_lanes<T: Datum + Copy>(
+    input: &Tensor,
+    axes: &[usize],
+    mut f: impl FnMut(&mut [T]),
+) -> TractResult<Tensor> {
+    let rank = input.rank();
+    let mut axes: TVec<usize> = axes.into();
+    axes.sort();
+    let len: usize = axes.iter().map(|&ax| input.shape()[ax]).product();
+    let mut perm: TVec<usize> = (0..rank).filter(|ax| !axes.contains(ax)).collect();
+    perm.extend(axes.iter().cloned());
+    if len == 0 {
+        return Ok(input.clone());
+    }
+    if perm.iter().enumerate().all(|(ix, &ax)| ix == ax) {
+        let mut output = input.clone();
+        output.as_slice_mut::<T>()?.chunks_mut(len).for_each(f);
+        return Ok(output);
+    }
+    let view = input.to_array_view::<T>()?.permuted_axes(&*perm);
+    let mut data: Vec<T> = view.iter().cloned().collect();
+    data.chunks_mut(len).for_each(&mut f);
+    let permuted = ArrayD::from_shape_vec(view.shape(), data)?;
+    let mut inverse: TVec<usize> = tvec!(0; rank);
+    for (ix, &ax) in perm.iter().enumerate() {
+        inverse[ax] = ix;
+    }
+    let output = permuted.permuted_axes(&*inverse);
+    Ok(ArrayD::from_shape_vec(output.shape(), output.iter().cloned().collect())?.into_tensor())
+}
+
+pub(super) fn successors<'m>(model: &'m TypedModel, node: &TypedNode) -> TVec<&'m TypedNode> {
+    let mut succs: TVec<&TypedNode> = tvec!();
+    for outlet in &node.outputs[0].successors {
+        if !succs.iter().any(|s| s.id == outlet.node) {
+            succs.push(model.node(outlet.node));
+        }
+    }
+    succs
+}
+
+pub(super) fn single_successor<'m>(
+    model: &'m TypedModel,
+    node: &TypedNode,
+) -> Option<&'m TypedNode> {
+    let succs = successors(model, node);
+    if succs.len() == 1 && node.outputs.len() == 1 {
+        Some(succs[0])
+    } else {
+        None
+    }
+}
+
+pub(super) fn is_bin<O: BinMiniOp>(node: &TypedNode) -> bool {
+    node.op_as::<TypedBinOp>().map(|op| op.0.is::<O>()).unwrap_or(false)
+}
+
+pub(super) fn is_element_wise<O: ElementWiseMiniOp>(node: &TypedNode) -> bool {
+    node.op_as::<ElementWiseOp>().map(|op| op.0.is::<O>()).unwrap_or(false)
+}
+
+/// The uniform constant operand of a UnaryOp of the given kind.
+pub(super) fn unary_scalar<O: BinMiniOp>(node: &TypedNode) -> TractResult<Option<f64>> {
+    if let Some(op) = node.op_as::<UnaryOp>() {
+        if op.mini_op.is::<O>() && op.a.is_uniform()? {
+            return Ok(Some(op.a.cast_to_scalar::<f64>()?));
+        }
+    }
+    Ok(None)
+}
+
+pub(super) fn is_reduce(node: &TypedNode, reducer: Reducer, axes: &[usize]) -> bool {
+    node.op_as::<Reduce>().map(|op| op.reducer == reducer && &*op.axes == axes).unwrap_or(false)
+}
+
+/// Recognizes max, sub, exp, sum and div (or mul by recip) chains, starting
+/// from the max reduction.
+pub(super) fn declutter_softmax_pattern(
+    model: &TypedModel,
+    max: &TypedNode,
+    axes: &[usize],
+) -> TractResult<Option<TypedModelPatch>> {
+    let input = max.inputs[0];
+    if !model.outlet_fact(input)?.datum_type.is_float() {
+        return Ok(None);
+    }
+    let sub = if let Some(sub) = single_successor(model, max) {
+        sub
+    } else {
+        return Ok(None);
+    };
+    if !is_bin::<Sub>(sub) || sub.inputs != [input, max.id.into()] {
+        return Ok(None);
+    }
+    let exp = if let Some(exp) = single_successor(model, sub) {
+        exp
+    } else {
+        return Ok(None);
+    };
+    if !is_element_wise::<Exp>(exp) {
+        return Ok(None);
+    }
+    let exp_succs = successors(model, exp);
+    if exp_succs.len() != 2 {
+        return Ok(None);
+    }
+    let (sum, out) = if is_reduce(exp_succs[0], Reducer::Sum, axes) {
+        (exp_succs[0], exp_succs[1])
+    } else if is_reduce(exp_succs[1], Reducer::Sum, axes) {
+        (exp_succs[1], exp_succs[0])
+    } else {
+        return Ok(None);
+    };
+    let sum_succ = if let Some(s) = single_successor(model, sum) {
+        s
+    } else {
+        return Ok(None);
+    };
+    let matched = if sum_succ.id == out.id {
+        is_bin::<Div>(out) && out.inputs == [exp.id.into(), sum.id.into()]
+    } else if is_element_wise::<Recip>(sum_succ) {
+        single_successor(model, sum_succ).map(|s| s.id) == Some(out.id)
+            && is_bin::<Mul>(out)
+            && (out.inputs == [exp.id.into(), sum_succ.id.into()]
+                || out.inputs == [sum_succ.id.into(), exp.id.into()])
+    } else {
+        false
+    };
+    if !matched {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let wire = patch.tap_model(model, input)?;
+    let wire = patch.wire_node(&out.name, Softmax::new(axes.into()), &[wire])?[0];
+    patch.shunt_outside(model, out.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn softmax_non_trailing_axis() -> TractResult<()> {
+        let input = tensor2(&[[0f32, 1.0], [0.0, 3.0]]);
+        let output = Softmax::new(tvec!(0)).eval(tvec!(input.into_arc_tensor()))?;
+        let e2 = 2f32.exp();
+        let expected = tensor2(&[[0.5f32, 1.0 / (1.0 + e2)], [0.5, e2 / (e2 + 1.0)]]);
+        output[0].close_enough(&expected, true)
+    }
+
+    #[test]
+    fn declutter_decomposed_softmax() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2, 3]))?;
+        let max = model.wire_node("max", Reduce::new(tvec!(1), Reducer::Max), &[x])?[0];
+        let sub = model.wire_node("sub", math::sub::bin_typed(), &[x, max])?[0];
+        let exp = model.wire_node("exp", math::exp(), &[sub])?[0];
+        let sum = model.wire_node("sum", Reduce::new(tvec!(1), Reducer::Sum), &[exp])?[0];
+        let div = model.wire_node("div", math::div::bin_typed(), &[exp, sum])?;
+        model.set_output_outlets(&div)?;
+        let decluttered = model.clone().declutter()?;
+        assert!(decluttered.nodes().iter().any(|n| n.op_is::<Softmax>()));
+        assert_eq!(decluttered.nodes().len(), 2);
+        let input = tensor2(&[[0f32, 1.0, 2.0], [-1.0, 0.5, 3.0]]);
+        let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        found[0].close_enough(&expected[0], true)
+    }
+}
@@ -0,0 +1,358 @@
+use super::*;
+use tract_data::internal::*;
+
+/// A loop running its body until its condition turns false, or a maximum
+/// trip count is reached.
+///
+/// The body receives the iteration number (i64 scalar) and the current
+/// condition (bool scalar) as its two first inputs, then the inputs described
+/// by `input_mapping`. Its first output is the condition for the next
+/// iteration, followed by the outputs described by `output_mapping`.
+///
+/// Input mappings can only be `State` or `Full`. State outputs feed the
+/// states of the next iteration, in order. Full outputs are concatenated
+/// along their axis across all iterations.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Loop {
+    pub body: TypedModel,
+    decluttered: bool,
+    pub trip_count_slot: Option<usize>,
+    pub cond_slot: Option<usize>,
+    pub input_mapping: Vec<InputMapping>,
+    pub output_mapping: Vec<OutputMapping<TDim>>,
+}
+
+impl_dyn_hash!(Loop);
+
+impl Loop {
+    pub fn new(
+        body: TypedModel,
+        trip_count_slot: Option<usize>,
+        cond_slot: Option<usize>,
+        input_mapping: Vec<InputMapping>,
+        output_mapping: Vec<OutputMapping<TDim>>,
+    ) -> TractResult<Loop> {
+        if trip_count_slot.is_none() && cond_slot.is_none() {
+            bail!("Loop needs a trip count or a condition, or it would never end")
+        }
+        if input_mapping.iter().any(|im| im.as_scan().is_some()) {
+            bail!("Loop does not support scanning inputs")
+        }
+        if body.input_outlets()?.len() != input_mapping.len() + 2 {
+            bail!(
+                "Loop body expects {} inputs, mappings expect {}",
+                body.input_outlets()?.len(),
+                input_mapping.len() + 2
+            )
+        }
+        if body.output_outlets()?.len() != output_mapping.len() + 1 {
+            bail!(
+                "Loop body has {} outputs, mappings expect {}",
+                body.output_outlets()?.len(),
+                output_mapping.len() + 1
+            )
+        }
+        let states = input_mapping.iter().filter(|im| im.as_state().is_some()).count();
+        if output_mapping.iter().filter(|om| om.state).count() != states {
+            bail!("Loop state inputs and outputs do not match")
+        }
+        Ok(Loop {
+            body,
+            decluttered: false,
+            trip_count_slot,
+            cond_slot,
+            input_mapping,
+            output_mapping,
+        })
+    }
+
+    /// Number of iterations, as far as it can be known from the input facts.
+    pub fn iteration_count(&self, inputs: &[&TypedFact]) -> Option<TDim> {
+        if let Ok(Some(iters)) = self.static_iteration_count(inputs) {
+            Some(iters.into())
+        } else {
+            self.output_mapping.iter().filter_map(|om| om.full_dim_hint.clone()).next()
+        }
+    }
+
+    /// Trip count, if it is known and the loop has no early exit.
+    fn static_iteration_count(&self, inputs: &[&TypedFact]) -> TractResult<Option<usize>> {
+        if self.cond_slot.is_some() {
+            return Ok(None);
+        }
+        if let Some(k) = self.trip_count_slot.and_then(|slot| inputs[slot].konst.as_ref()) {
+            Ok(Some(k.cast_to_scalar::<i64>()?.max(0) as usize))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn declutter_body(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.decluttered {
+            let mut new = self.clone();
+            new.body = self.body.clone().declutter()?;
+            new.decluttered = true;
+            Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut lines = vec![];
+        if let Some(slot) = self.trip_count_slot {
+            lines.push(format!("Trip count: inlet {}", slot));
+        }
+        if let Some(slot) = self.cond_slot {
+            lines.push(format!("Condition: inlet {}", slot));
+        }
+        for (ix, im) in self.input_mapping.iter().enumerate() {
+            lines.push(format!("Model input  #{}: {:?}", ix + 2, im));
+        }
+        for (ix, om) in self.output_mapping.iter().enumerate() {
+            lines.push(format!("Model output #{}: {:?}", ix + 1, om));
+        }
+        Ok(lines)
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let trip_count = if let Some(slot) = self.trip_count_slot {
+            Some(inputs[slot].cast_to_scalar::<i64>()?)
+        } else {
+            None
+        };
+        let mut cond = if let Some(slot) = self.cond_slot {
+            inputs[slot].cast_to_scalar::<bool>()?
+        } else {
+            true
+        };
+        let mut states: TVec<Tensor> = self
+            .input_mapping
+            .iter()
+            .filter_map(|im| im.as_state())
+            .map(|init| match init {
+                StateInitializer::FromInput(slot) => (*inputs[*slot]).clone(),
+                StateInitializer::Value(v) => (**v).clone(),
+            })
+            .collect();
+        let mut fulls: Vec<Vec<Tensor>> = vec![vec![]; self.output_mapping.len()];
+        let mut last_values: Vec<Option<Tensor>> = vec![None; self.output_mapping.len()];
+
+        let plan = SimplePlan::new(&self.body)?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut iter = 0i64;
+        while cond && trip_count.map(|max| iter < max).unwrap_or(true) {
+            let mut body_inputs = tvec!(tensor0(iter), tensor0(cond));
+            let mut state_ix = 0;
+            for im in &self.input_mapping {
+                match im {
+                    InputMapping::State { .. } => {
+                        body_inputs.push(states[state_ix].clone());
+                        state_ix += 1;
+                    }
+                    InputMapping::Full { slot } => body_inputs.push((*inputs[*slot]).clone()),
+                    InputMapping::Scan { .. } => unreachable!(),
+                }
+            }
+            let mut outputs = state
+                .run(body_inputs)
+                .with_context(|| format!("Evaluating loop body #{}", iter))?;
+            let cond_output = outputs.remove(0);
+            if self.cond_slot.is_some() {
+                cond = cond_output.cast_to_scalar::<bool>()?;
+            }
+            let mut state_ix = 0;
+            for (ix, (om, value)) in self.output_mapping.iter().zip(outputs.into_iter()).enumerate()
+            {
+                let value = value.into_tensor();
+                if om.full_slot.is_some() {
+                    fulls[ix].push(value.clone());
+                }
+                if om.state {
+                    states[state_ix] = value.clone();
+                    state_ix += 1;
+                }
+                last_values[ix] = Some(value);
+            }
+            iter += 1;
+        }
+
+        let mut outputs = tvec!();
+        let mut state_ix = 0;
+        for (ix, om) in self.output_mapping.iter().enumerate() {
+            if let Some(slot) = om.last_value_slot {
+                let value = if om.state {
+                    states[state_ix].clone()
+                } else if let Some(value) = last_values[ix].take() {
+                    value
+                } else {
+                    bail!("Loop ran no iteration, output #{} has no last value", ix + 1)
+                };
+                outputs.push((slot, value));
+            }
+            if let Some(slot) = om.full_slot {
+                let value = if fulls[ix].len() > 0 {
+                    Tensor::stack_tensors(om.axis, &fulls[ix])?
+                } else {
+                    let fact = self.body.output_fact(ix + 1)?;
+                    let mut shape: TVec<usize> = fact
+                        .shape
+                        .as_concrete()
+                        .ok_or_else(|| {
+                            format_err!("Loop ran no iteration, can not shape output #{}", ix + 1)
+                        })?
+                        .into();
+                    shape[om.axis] = 0;
+                    Tensor::zero_dt(fact.datum_type, &shape)?
+                };
+                outputs.push((slot, value));
+            }
+            if om.state {
+                state_ix += 1;
+            }
+        }
+        outputs.sort_by_key(|a| a.0);
+        Ok(outputs.into_iter().map(|(_slot, v)| v.into_arc_tensor()).collect())
+    }
+}
+
+impl TypedOp for Loop {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let iters = self.static_iteration_count(inputs)?;
+        let mut outputs = tvec!();
+        for (ix, output) in self.output_mapping.iter().enumerate() {
+            let fact = self.body.output_fact(ix + 1)?;
+            if let Some(slot) = output.last_value_slot {
+                outputs.push((slot, TypedFact::dt_shape(fact.datum_type, fact.shape.clone())));
+            }
+            if let Some(slot) = output.full_slot {
+                let mut shape = fact.shape.clone();
+                let dim = if let Some(hint) = &output.full_dim_hint {
+                    hint.clone()
+                } else if let Some(iters) = iters {
+                    shape[output.axis].clone() * iters
+                } else {
+                    bail!("Loop output #{} has a dynamic length and no dimension hint", ix + 1)
+                };
+                shape.set(output.axis, dim);
+                outputs.push((slot, TypedFact::dt_shape(fact.datum_type, shape)));
+            }
+        }
+        outputs.sort_by_key(|a| a.0);
+        Ok(outputs.into_iter().map(|(_slot, v)| v).collect())
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.declutter_body(model, node)
+    }
+
+    fn concretize_dims(
+        &self,
+        _source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        values: &SymbolValues,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|o| mapping[&o]).collect::<TVec<_>>();
+        let op = Self {
+            output_mapping: self
+                .output_mapping
+                .iter()
+                .map(|om| om.concretize_dims(values))
+                .collect::<TractResult<Vec<_>>>()?,
+            ..self.clone()
+        };
+        target.wire_node(&node.name, op, &inputs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    // for i in 0..n { acc = acc * 2; emit(acc); if !(acc < 10) { break } }
+    fn doubling(with_cond: bool) -> TractResult<Loop> {
+        let mut body = TypedModel::default();
+        let _iter =
+            body.add_source("iter", TypedFact::dt_shape(i64::datum_type(), &[0usize; 0]))?;
+        let _cond =
+            body.add_source("cond", TypedFact::dt_shape(bool::datum_type(), &[0usize; 0]))?;
+        let acc = body.add_source("acc", TypedFact::dt_shape(i64::datum_type(), &[1]))?;
+        let two = body.add_const("two", tensor1(&[2i64]))?;
+        let acc = body.wire_node("double", math::mul::bin_typed(), &[acc, two])?[0];
+        let limit = body.add_const("limit", tensor1(&[10i64]))?;
+        let cond =
+            body.wire_node("cond_out", crate::ops::logic::lesser::bin_typed(), &[acc, limit])?[0];
+        let cond = body.wire_node("squeeze", AxisOp::Rm(0), &[cond])?[0];
+        body.set_output_outlets(&[cond, acc, acc])?;
+        Loop::new(
+            body,
+            Some(0),
+            if with_cond { Some(1) } else { None },
+            vec![InputMapping::State { initializer: StateInitializer::FromInput(2) }],
+            vec![
+                OutputMapping {
+                    state: true,
+                    last_value_slot: Some(0),
+                    full_slot: None,
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                },
+                OutputMapping {
+                    state: false,
+                    last_value_slot: None,
+                    full_slot: Some(1),
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn loop_trip_count() -> TractResult<()> {
+        let op = doubling(false)?;
+        let outputs = op.eval(tvec!(rctensor0(5i64), rctensor0(true), rctensor1(&[1i64])))?;
+        assert_eq!(*outputs[0], tensor1(&[32i64]));
+        assert_eq!(*outputs[1], tensor1(&[2i64, 4, 8, 16, 32]));
+        Ok(())
+    }
+
+    #[test]
+    fn loop_condition() -> TractResult<()> {
+        let op = doubling(true)?;
+        let outputs = op.eval(tvec!(rctensor0(100i64), rctensor0(true), rctensor1(&[1i64])))?;
+        assert_eq!(*outputs[0], tensor1(&[16i64]));
+        assert_eq!(*outputs[1], tensor1(&[2i64, 4, 8, 16]));
+        Ok(())
+    }
+}
@@ -2,9 +2,11 @@ use crate::internal::*;
 use std::fmt;
 
 mod lir;
+mod loops;
 mod mir;
 
 pub use lir::LirScan;
+pub use loops::Loop;
 pub use mir::Scan;
 
 #[derive(Clone, new, Hash)]
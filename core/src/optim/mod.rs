@@ -6,6 +6,7 @@ pub mod change_axes;
 mod op_optim;
 mod prop_const;
 mod push_split_down;
+pub mod quantize;
 
 use self::change_axes::ChangeAxes;
 use self::prop_const::PropConst;
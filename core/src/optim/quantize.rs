@@ -0,0 +1,341 @@
+//! Post-training static quantization.
+//!
+//! A `Calibration` is built by running representative inputs through a f32
+//! model and recording the range of every f32 tensor. `quantize_static` then
+//! uses these ranges to swap MatMulUnary and ConvUnary for their i8
+//! counterparts, surrounded by quantize and dequantize ops. Decluttering the
+//! result folds consecutive dequantize/quantize pairs (and the element-wise ops
+//! between them) into lookup tables, keeping chains of quantized operators in
+//! i8.
+//!
+//! Weights are quantized symmetrically per tensor, activations
+//! asymmetrically.
+use crate::internal::*;
+use crate::model::translator::Translate;
+use crate::ops::cnn::ConvUnary;
+use crate::ops::matmul::MatMulUnary;
+use crate::ops::quant::{quantize_linear_i8, DequantizeLinearF32, QParams};
+
+/// Observed value ranges of the f32 tensors of a model.
+#[derive(Clone, Debug, Default)]
+pub struct Calibration {
+    pub ranges: HashMap<OutletId, (f32, f32)>,
+}
+
+impl Calibration {
+    /// Run all samples through the model, recording the min and max value
+    /// of each f32 outlet.
+    pub fn collect(model: &TypedModel, samples: &[TVec<Tensor>]) -> TractResult<Calibration> {
+        let plan = SimplePlan::new(model)?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut calibration = Calibration::default();
+        for sample in samples {
+            state.run_plan_with_eval(sample.clone(), |session_state, op_state, node, inputs| {
+                let outputs = crate::plan::eval(session_state, op_state, node, inputs)?;
+                for (ix, output) in outputs.iter().enumerate() {
+                    calibration.observe(OutletId::new(node.id, ix), output)?;
+                }
+                TractResult::Ok(outputs)
+            })?;
+        }
+        Ok(calibration)
+    }
+
+    /// Extend the range recorded for an outlet with a tensor value. Non-f32
+    /// tensors are ignored.
+    pub fn observe(&mut self, outlet: OutletId, value: &Tensor) -> TractResult<()> {
+        if value.datum_type() != f32::datum_type() || value.len() == 0 {
+            return Ok(());
+        }
+        let (min, max) = min_max(value.as_slice::<f32>()?);
+        let range = self.ranges.entry(outlet).or_insert((min, max));
+        range.0 = range.0.min(min);
+        range.1 = range.1.max(max);
+        Ok(())
+    }
+
+    pub fn range(&self, outlet: OutletId) -> Option<(f32, f32)> {
+        self.ranges.get(&outlet).cloned()
+    }
+}
+
+/// Rewrite MatMulUnary and ConvUnary nodes operating on f32 as i8 ops, using
+/// the calibration ranges of their input and output.
+///
+/// Nodes with no recorded range, or whose requantization factor could not be
+/// expressed by the matrix multipliers, are left untouched.
+pub fn quantize_static(model: &TypedModel, calibration: &Calibration) -> TractResult<TypedModel> {
+    StaticQuantizer(calibration).translate_model(model)?.declutter()
+}
+
+/// Affine mapping between f32 values and i8: x = (q - zero_point) * scale
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct QuantI8 {
+    scale: f32,
+    zero_point: i8,
+}
+
+impl QuantI8 {
+    fn from_range(min: f32, max: f32) -> QuantI8 {
+        let min = min.min(0.0);
+        let max = max.max(0.0);
+        let scale = (max - min) / 255.0;
+        let scale = if scale > 0.0 && scale.is_normal() { scale } else { 1.0 };
+        let zero_point = (-128.0 - min / scale).round().max(-128.0).min(127.0) as i8;
+        QuantI8 { scale, zero_point }
+    }
+
+    fn symmetric(t: &Tensor) -> TractResult<(Tensor, f32)> {
+        let values = t.as_slice::<f32>()?;
+        let max = values.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+        let scale = if max > 0.0 && max.is_normal() { max / 127.0 } else { 1.0 };
+        let q: Vec<i8> =
+            values.iter().map(|x| (x / scale).round().max(-127.0).min(127.0) as i8).collect();
+        Ok((tensor1(&q).into_shape(t.shape())?, scale))
+    }
+}
+
+fn min_max(values: &[f32]) -> (f32, f32) {
+    values.iter().fold((std::f32::MAX, std::f32::MIN), |(min, max), &x| (min.min(x), max.max(x)))
+}
+
+#[derive(Debug)]
+struct StaticQuantizer<'a>(&'a Calibration);
+
+impl<'a> StaticQuantizer<'a> {
+    /// Input and output quantization of a single-input node, if it operates
+    /// on f32 and has been calibrated.
+    fn io_quant(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<(QuantI8, QuantI8)>> {
+        if node.inputs.len() != 1
+            || source.outlet_fact(node.inputs[0])?.datum_type != f32::datum_type()
+            || node.outputs[0].fact.datum_type != f32::datum_type()
+        {
+            return Ok(None);
+        }
+        let input = self.0.range(node.inputs[0]);
+        let output = self.0.range(OutletId::new(node.id, 0));
+        Ok(input.and_then(|i| {
+            output.map(|o| (QuantI8::from_range(i.0, i.1), QuantI8::from_range(o.0, o.1)))
+        }))
+    }
+
+    fn q_params(weight_scale: f32, input: QuantI8, output: QuantI8) -> Option<QParams> {
+        let factor = weight_scale * input.scale / output.scale;
+        // the multipliers only implement downscaling requantization
+        if !(factor > 0.0 && factor < 1.0) {
+            return None;
+        }
+        Some(
+            QParams::new(i8::datum_type())
+                .with_zero_point_b(&rctensor0(input.zero_point))
+                .with_zero_point_c(&rctensor0(output.zero_point))
+                .with_scale_factor(factor),
+        )
+    }
+
+    fn quantized_op(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<(Box<dyn TypedOp>, QuantI8, QuantI8)>> {
+        let (input, output) = if let Some(io) = self.io_quant(source, node)? {
+            io
+        } else {
+            return Ok(None);
+        };
+        if let Some(op) = node.op_as::<MatMulUnary>() {
+            if op.q_params.is_some() || op.a.datum_type() != f32::datum_type() {
+                return Ok(None);
+            }
+            let (a, scale) = QuantI8::symmetric(&op.a)?;
+            if let Some(qp) = Self::q_params(scale, input, output) {
+                let op = MatMulUnary::new(
+                    a.into_arc_tensor(),
+                    op.a_trans,
+                    op.b_trans,
+                    op.c_trans,
+                    Some(qp),
+                );
+                return Ok(Some((Box::new(op), input, output)));
+            }
+        } else if let Some(op) = node.op_as::<ConvUnary>() {
+            if op.q_params.is_some() || op.kernel.datum_type() != f32::datum_type() {
+                return Ok(None);
+            }
+            let (kernel, scale) = QuantI8::symmetric(&op.kernel)?;
+            if let Some(qp) = Self::q_params(scale, input, output) {
+                let bias = if let Some(bias) = &op.bias {
+                    let bias_scale = scale * input.scale;
+                    let bias: Vec<i32> = bias
+                        .cast_to::<f32>()?
+                        .as_slice::<f32>()?
+                        .iter()
+                        .map(|b| (b / bias_scale).round() as i32)
+                        .collect();
+                    Some(rctensor1(&bias))
+                } else {
+                    None
+                };
+                let op = ConvUnary::new(
+                    op.pool_spec.clone(),
+                    op.kernel_fmt,
+                    kernel.into_arc_tensor(),
+                    op.group,
+                    bias,
+                    Some(qp),
+                );
+                return Ok(Some((Box::new(op), input, output)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>>
+    for StaticQuantizer<'a>
+{
+    fn translate_node(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        if let Some((op, input, output)) = self.quantized_op(source, node)? {
+            let wire = target.wire_node(
+                format!("{}.quant", node.name),
+                quantize_linear_i8(input.scale.recip(), input.zero_point),
+                &inputs,
+            )?;
+            let wire = target.wire_node(&node.name, op, &wire)?;
+            target.wire_node(
+                format!("{}.dequant", node.name),
+                DequantizeLinearF32::new(output.scale, output.zero_point as i32),
+                &wire,
+            )
+        } else {
+            target.wire_node(&node.name, node.op.clone(), &inputs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::nn::sigmoid;
+
+    #[test]
+    fn quantize_matmul_chain() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[4, 2]))?;
+        let a1 =
+            tensor2(&[[0.5f32, -0.25, 0.75, 0.1], [-0.6, 0.2, 0.3, -0.9], [0.4, 0.4, -0.1, 0.2]]);
+        let wire = model.wire_node(
+            "mm1",
+            MatMulUnary::new(a1.into_arc_tensor(), false, false, false, None),
+            &[source],
+        )?;
+        let wire = model.wire_node("sigmoid", sigmoid(), &wire)?;
+        let a2 = tensor2(&[[0.3f32, -0.8, 0.5], [0.9, 0.1, -0.4]]);
+        let wire = model.wire_node(
+            "mm2",
+            MatMulUnary::new(a2.into_arc_tensor(), false, false, false, None),
+            &wire,
+        )?;
+        model.set_output_outlets(&wire)?;
+
+        let samples: Vec<TVec<Tensor>> = (0..8)
+            .map(|i| {
+                let v: Vec<f32> = (0..8).map(|j| ((i * 8 + j) as f32 * 0.37).sin()).collect();
+                tvec!(tensor1(&v).into_shape(&[4, 2]).unwrap())
+            })
+            .collect();
+        let calibration = Calibration::collect(&model, &samples)?;
+        let quantized = quantize_static(&model, &calibration)?;
+        let quantized_mm = quantized
+            .nodes()
+            .iter()
+            .filter_map(|n| n.op_as::<MatMulUnary>())
+            .filter(|op| op.q_params.is_some())
+            .count();
+        assert_eq!(quantized_mm, 2);
+
+        let reference = SimplePlan::new(&model)?;
+        let plan = SimplePlan::new(&quantized)?;
+        for sample in samples {
+            let expected = reference.run(sample.clone())?.remove(0);
+            let found = plan.run(sample)?.remove(0);
+            let (min, max) = min_max(expected.as_slice::<f32>()?);
+            let tolerance = (max - min) / 20.0;
+            for (e, f) in expected.as_slice::<f32>()?.iter().zip(found.as_slice::<f32>()?) {
+                assert!((e - f).abs() <= tolerance, "expected {:?} found {:?}", expected, found);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn quantize_conv_with_bias() -> TractResult<()> {
+        use crate::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+        use crate::ops::nn::DataFormat;
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[1, 2, 8]))?;
+        let kernel = tract_ndarray::Array3::from_shape_fn((3, 2, 3), |(o, i, k)| {
+            ((o * 6 + i * 3 + k) as f32 * 1.3).cos() * 0.5
+        });
+        let pool_spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(3), PaddingSpec::Valid, None, None, Some(3));
+        let conv = ConvUnary::new(
+            pool_spec,
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            1,
+            Some(rctensor1(&[0.2f32, -0.1, 0.05])),
+            None,
+        );
+        let wire = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&wire)?;
+
+        let samples: Vec<TVec<Tensor>> = (0..8)
+            .map(|i| {
+                let v: Vec<f32> = (0..16).map(|j| ((i * 16 + j) as f32 * 0.37).sin()).collect();
+                tvec!(tensor1(&v).into_shape(&[1, 2, 8]).unwrap())
+            })
+            .collect();
+        let calibration = Calibration::collect(&model, &samples)?;
+        let quantized = quantize_static(&model, &calibration)?;
+        let conv = quantized
+            .nodes()
+            .iter()
+            .filter_map(|n| n.op_as::<ConvUnary>())
+            .find(|op| op.q_params.is_some())
+            .expect("quantized convolution");
+        assert_eq!(conv.bias.as_ref().unwrap().datum_type(), i32::datum_type());
+
+        let reference = SimplePlan::new(&model)?;
+        let plan = SimplePlan::new(&quantized)?;
+        let optimized = SimplePlan::new(quantized.declutter()?.optimize()?)?;
+        for sample in samples {
+            let expected = reference.run(sample.clone())?.remove(0);
+            let (min, max) = min_max(expected.as_slice::<f32>()?);
+            let tolerance = (max - min) / 20.0;
+            for found in &[plan.run(sample.clone())?.remove(0), optimized.run(sample)?.remove(0)] {
+                for (e, f) in expected.as_slice::<f32>()?.iter().zip(found.as_slice::<f32>()?) {
+                    assert!(
+                        (e - f).abs() <= tolerance,
+                        "expected {:?} found {:?}",
+                        expected,
+                        found
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@ use crate::internal::*;
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{Fact, Graph, OutletId};
 
+pub mod memory;
+
 #[derive(Clone, Debug, Default)]
 pub struct SessionState {
     pub inputs: HashMap<usize, Arc<Tensor>>,
@@ -25,6 +27,7 @@ where
     pub outputs: Vec<OutletId>,
     pub order: Vec<usize>,
     pub flush_lists: Vec<TVec<usize>>,
+    pub more_dependencies: Vec<(usize, usize)>,
     _casper: PhantomData<(F, O)>,
 }
 
@@ -78,6 +81,7 @@ where
             order,
             flush_lists,
             outputs: outputs.to_vec(),
+            more_dependencies: deps.to_vec(),
             _casper: PhantomData,
         })
     }
@@ -92,6 +96,22 @@ where
     }
 }
 
+impl<F, O, M> SimplePlan<F, O, M>
+where
+    F: Fact + Hash + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static + Hash,
+    M: Borrow<Graph<F, O>> + Hash + Sync,
+{
+    /// Run the plan, evaluating independent nodes concurrently on the current
+    /// rayon thread pool.
+    ///
+    /// See `SimpleState::run_parallel`.
+    pub fn run_parallel(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut state = SimpleState::new(self)?;
+        state.run_parallel(inputs)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SimpleState<F, O, M, P>
 where
@@ -194,26 +214,7 @@ where
                 }
 
                 if cfg!(debug_assertions) {
-                    let facts = model.node_input_facts(node.id)?;
-                    if facts.len() != inputs.len() {
-                        bail!(
-                            "Evaluating {}: expected {} inputs, got {}",
-                            node,
-                            facts.len(),
-                            inputs.len()
-                        );
-                    }
-                    for (ix, (v, f)) in inputs.iter().zip(facts.iter()).enumerate() {
-                        if !f.matches(v)? {
-                            bail!(
-                                "Evaluating {}: input {:?}, expected {:?}, got {:?}",
-                                node,
-                                ix,
-                                f,
-                                v
-                            );
-                        }
-                    }
+                    check_inputs(model, node, &inputs)?;
                 }
 
                 let vs =
@@ -221,29 +222,7 @@ where
                         .map_err(|e| e.into())?;
 
                 if cfg!(debug_assertions) {
-                    let facts = model.node_output_facts(node.id)?;
-                    if facts.len() != vs.len() {
-                        bail!(
-                            "Evaluating {}: expected {} outputs, got {}",
-                            node,
-                            facts.len(),
-                            vs.len()
-                        );
-                    }
-                    for (ix, (v, f)) in vs.iter().zip(facts.iter()).enumerate() {
-                        if node.outputs[ix].successors.len() == 0 {
-                            continue;
-                        }
-                        if !f.matches(v)? {
-                            bail!(
-                                "Evaluating {}: output {:?}, expected {:?}, got {:?}",
-                                node,
-                                ix,
-                                f,
-                                v
-                            );
-                        }
-                    }
+                    check_outputs(model, node, &vs)?;
                 }
 
                 values[node.id] = Some(vs);
@@ -396,6 +375,241 @@ where
     }
 }
 
+impl<F, O, M, P> SimpleState<F, O, M, P>
+where
+    F: Fact + Hash + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static + Hash,
+    M: Borrow<Graph<F, O>> + Hash + Sync,
+    P: Borrow<SimplePlan<F, O, M>> + Clone,
+{
+    /// Run the plan, evaluating nodes as soon as their inputs are ready on the
+    /// current rayon thread pool.
+    ///
+    /// Nodes are dispatched following the data dependencies (and the extra
+    /// dependencies the plan was built with) instead of the plan order, so
+    /// independent branches of the graph run concurrently. A value is released
+    /// once its last consumer has picked it up, matching what `flush_lists`
+    /// does for sequential runs. Stateful ops have exclusive access to the
+    /// session state while they run, and each op evaluation is the same as in
+    /// `run`, so results are identical to the sequential execution.
+    ///
+    /// Use `rayon::ThreadPool::install` to pick the pool and its size.
+    pub fn run_parallel(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.set_inputs(inputs)?;
+        let mut result = tvec!();
+        let mut error = None;
+        {
+            let &mut SimpleState {
+                ref plan,
+                ref mut session_state,
+                ref mut states,
+                ref mut values,
+                ..
+            } = self;
+            let plan = plan.borrow();
+            let model = plan.model();
+            let mut successors: Vec<TVec<usize>> = vec![tvec!(); model.nodes().len()];
+            let mut missing_inputs = vec![0; model.nodes().len()];
+            let mut consumers = vec![0; model.nodes().len()];
+            for &n in &plan.order {
+                for i in &model.node(n).inputs {
+                    successors[i.node].push(n);
+                    missing_inputs[n] += 1;
+                    consumers[i.node] += 1;
+                }
+            }
+            for &(node, dep) in &plan.more_dependencies {
+                if !plan.order.contains(&node) {
+                    continue;
+                }
+                successors[dep].push(node);
+                missing_inputs[node] += 1;
+            }
+            for o in &plan.outputs {
+                consumers[o.node] += 1;
+            }
+            let ready: Vec<usize> =
+                plan.order.iter().cloned().filter(|&n| missing_inputs[n] == 0).collect();
+            let run = ParallelRun {
+                model,
+                successors,
+                session_state: std::sync::Mutex::new(session_state),
+                scheduling: std::sync::Mutex::new(ParallelScheduling {
+                    values,
+                    states,
+                    missing_inputs,
+                    consumers,
+                    error: None,
+                }),
+            };
+            rayon::scope(|scope| {
+                for n in ready {
+                    run.spawn(scope, n)
+                }
+            });
+            let scheduling = run.scheduling.into_inner().unwrap();
+            if let Some(e) = scheduling.error {
+                error = Some(e);
+            } else {
+                for output in &plan.outputs {
+                    trace!("Extracting value {:?} ({})", output, model.node(output.node));
+                    result
+                        .push(scheduling.values[output.node].as_ref().unwrap()[output.slot].clone())
+                }
+            }
+        }
+        self.reset_wires()?;
+        if let Some(e) = error {
+            return Err(e);
+        }
+        Ok(result)
+    }
+}
+
+struct ParallelScheduling<'a> {
+    values: &'a mut Vec<Option<TVec<Arc<Tensor>>>>,
+    states: &'a mut Vec<Option<Box<dyn OpState>>>,
+    missing_inputs: Vec<usize>,
+    consumers: Vec<usize>,
+    error: Option<TractError>,
+}
+
+struct ParallelRun<'a, F, O>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+{
+    model: &'a Graph<F, O>,
+    successors: Vec<TVec<usize>>,
+    session_state: std::sync::Mutex<&'a mut SessionState>,
+    scheduling: std::sync::Mutex<ParallelScheduling<'a>>,
+}
+
+impl<'a, F, O> ParallelRun<'a, F, O>
+where
+    F: Fact + Hash + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static + Hash,
+{
+    fn spawn<'s>(&'s self, scope: &rayon::Scope<'s>, node: usize) {
+        scope.spawn(move |scope| {
+            if let Err(e) = self.step(scope, node) {
+                let mut scheduling = self.scheduling.lock().unwrap();
+                if scheduling.error.is_none() {
+                    scheduling.error = Some(e);
+                }
+            }
+        })
+    }
+
+    fn step<'s>(&'s self, scope: &rayon::Scope<'s>, n: usize) -> TractResult<()> {
+        let node = self.model.node(n);
+        trace!("Running node {}", node);
+        let (inputs, mut state) = {
+            let mut scheduling = self.scheduling.lock().unwrap();
+            if scheduling.error.is_some() {
+                return Ok(());
+            }
+            let mut inputs: TVec<Arc<Tensor>> = tvec![];
+            for i in &node.inputs {
+                let prec = scheduling.values[i.node].as_ref().ok_or_else(|| {
+                    format_err!(
+                        "Computing {}, precursor {} not done:",
+                        node,
+                        self.model.node(i.node)
+                    )
+                })?;
+                inputs.push(prec[i.slot].clone())
+            }
+            for i in &node.inputs {
+                scheduling.consumers[i.node] -= 1;
+                if scheduling.consumers[i.node] == 0 {
+                    trace!("  Ran {} can now flush {}", node, self.model.node(i.node));
+                    scheduling.values[i.node] = None;
+                }
+            }
+            (inputs, scheduling.states[n].take())
+        };
+
+        if cfg!(debug_assertions) {
+            check_inputs(self.model, node, &inputs)?;
+        }
+
+        let vs = match state {
+            Some(ref mut state) => {
+                let mut session_state = self.session_state.lock().unwrap();
+                state.eval(&mut session_state, node.op(), inputs)
+            }
+            None => node.op().eval(inputs),
+        }
+        .with_context(|| format!("Evaluating {}", node));
+
+        let mut scheduling = self.scheduling.lock().unwrap();
+        scheduling.states[n] = state;
+        let vs = vs?;
+        if cfg!(debug_assertions) {
+            check_outputs(self.model, node, &vs)?;
+        }
+        scheduling.values[n] = Some(vs);
+        let mut ready = tvec!();
+        for &succ in &self.successors[n] {
+            scheduling.missing_inputs[succ] -= 1;
+            if scheduling.missing_inputs[succ] == 0 {
+                ready.push(succ);
+            }
+        }
+        drop(scheduling);
+        for succ in ready {
+            self.spawn(scope, succ);
+        }
+        Ok(())
+    }
+}
+
+fn check_inputs<F, O>(
+    model: &Graph<F, O>,
+    node: &Node<F, O>,
+    inputs: &[Arc<Tensor>],
+) -> TractResult<()>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+{
+    let facts = model.node_input_facts(node.id)?;
+    if facts.len() != inputs.len() {
+        bail!("Evaluating {}: expected {} inputs, got {}", node, facts.len(), inputs.len());
+    }
+    for (ix, (v, f)) in inputs.iter().zip(facts.iter()).enumerate() {
+        if !f.matches(v)? {
+            bail!("Evaluating {}: input {:?}, expected {:?}, got {:?}", node, ix, f, v);
+        }
+    }
+    Ok(())
+}
+
+fn check_outputs<F, O>(
+    model: &Graph<F, O>,
+    node: &Node<F, O>,
+    outputs: &[Arc<Tensor>],
+) -> TractResult<()>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+{
+    let facts = model.node_output_facts(node.id)?;
+    if facts.len() != outputs.len() {
+        bail!("Evaluating {}: expected {} outputs, got {}", node, facts.len(), outputs.len());
+    }
+    for (ix, (v, f)) in outputs.iter().zip(facts.iter()).enumerate() {
+        if node.outputs[ix].successors.len() == 0 {
+            continue;
+        }
+        if !f.matches(v)? {
+            bail!("Evaluating {}: output {:?}, expected {:?}, got {:?}", node, ix, f, v);
+        }
+    }
+    Ok(())
+}
+
 pub fn eval<F, O>(
     session_state: &mut SessionState,
     mut state: Option<&mut (dyn OpState + 'static)>,
@@ -413,3 +627,42 @@ where
     .with_context(|| format!("Evaluating {}", node));
     r
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn parallel_branches() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let mut branches = tvec!();
+        for i in 0..8 {
+            let k = model.add_const(format!("k-{}", i), tensor1(&[i as f32]))?;
+            let wire =
+                model.wire_node(format!("add-{}", i), math::add::bin_typed(), &[source, k])?;
+            branches.push(
+                model.wire_node(
+                    format!("mul-{}", i),
+                    math::mul::bin_typed(),
+                    &[wire[0], wire[0]],
+                )?[0],
+            );
+        }
+        let mut wire = branches[0];
+        for (i, b) in branches.iter().enumerate().skip(1) {
+            wire = model.wire_node(format!("sum-{}", i), math::add::bin_typed(), &[wire, *b])?[0];
+        }
+        model.set_output_outlets(&[wire, branches[3]])?;
+        let plan = SimplePlan::new(&model)?;
+        let input = tensor1(&[1f32, 2.5, -3.0]);
+        let sequential = plan.run(tvec!(input.clone()))?;
+        let mut state = SimpleState::new(&plan)?;
+        for _ in 0..4 {
+            let parallel = state.run_parallel(tvec!(input.clone()))?;
+            assert_eq!(parallel, sequential);
+        }
+        Ok(())
+    }
+}
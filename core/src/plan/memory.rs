@@ -0,0 +1,210 @@
+//! Static memory planning for typed plans.
+//!
+//! With all shapes known, the lifetime of each intermediate value is fixed by
+//! the plan order. Values that are never alive at the same time can share
+//! memory, so they can all be packed in a single arena, whose size is the peak
+//! memory of a run.
+//!
+//! This is a layout only: `SimpleState` does not run in the arena, as ops
+//! allocate their own outputs.
+
+use std::borrow::Borrow;
+use std::ops::Range;
+
+use crate::internal::*;
+
+/// Alignment of every region in the arena.
+pub const ARENA_ALIGNMENT: usize = 64;
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPlan {
+    /// Region of the arena assigned to each node output, if any.
+    pub regions: Vec<TVec<Option<Range<usize>>>>,
+    /// Total size of the arena, in bytes.
+    pub arena_size: usize,
+}
+
+#[derive(Debug)]
+struct Buffer {
+    size: usize,
+    start: usize,
+    end: usize,
+    offset: usize,
+}
+
+impl MemoryPlan {
+    /// Compute the arena layout for running `order` on `model`.
+    ///
+    /// Model inputs, constants and the plan outputs (which outlive the run) are
+    /// left out, as well as non-copy datum types. Every other value must have a
+    /// concrete shape.
+    pub fn new(
+        model: &TypedModel,
+        order: &[usize],
+        outputs: &[OutletId],
+    ) -> TractResult<MemoryPlan> {
+        let mut last_use: HashMap<OutletId, usize> = HashMap::default();
+        for (step, &n) in order.iter().enumerate() {
+            for i in &model.node(n).inputs {
+                last_use.insert(*i, step);
+            }
+        }
+
+        let mut buffers: Vec<Buffer> = vec![];
+        let mut buffer_of: HashMap<OutletId, usize> = HashMap::default();
+        for (step, &n) in order.iter().enumerate() {
+            let node = model.node(n);
+            if node.op_is::<crate::ops::source::TypedSource>()
+                || node.op_is::<crate::ops::konst::Const>()
+            {
+                continue;
+            }
+            let input_facts = model.node_input_facts(n)?;
+            for (slot, output) in node.outputs.iter().enumerate() {
+                let outlet = OutletId::new(n, slot);
+                let fact = &output.fact;
+                if outputs.contains(&outlet) || !fact.datum_type.is_copy() {
+                    continue;
+                }
+                let shape = fact.shape.as_concrete().with_context(|| {
+                    format!("Memory planning needs concrete shapes, got {:?} for {}", fact, node)
+                })?;
+                let size = shape.iter().product::<usize>() * fact.datum_type.size_of();
+                if size == 0 {
+                    continue;
+                }
+                let end = last_use.get(&outlet).cloned().unwrap_or(step);
+                let aliased = if node.outputs.len() == 1 {
+                    node.op.in_place_input(&*input_facts).and_then(|ix| {
+                        let input = node.inputs[ix];
+                        let buffer = *buffer_of.get(&input)?;
+                        let reusable = last_use[&input] == step
+                            && node.inputs.iter().filter(|i| **i == input).count() == 1
+                            && model.node(input.node).outputs.len() == 1
+                            && buffers[buffer].size == size;
+                        if reusable {
+                            Some(buffer)
+                        } else {
+                            None
+                        }
+                    })
+                } else {
+                    None
+                };
+                if let Some(buffer) = aliased {
+                    buffers[buffer].end = end;
+                    buffer_of.insert(outlet, buffer);
+                } else {
+                    buffer_of.insert(outlet, buffers.len());
+                    buffers.push(Buffer { size, start: step, end, offset: 0 });
+                }
+            }
+        }
+
+        let arena_size = pack(&mut buffers);
+        let mut regions: Vec<TVec<Option<Range<usize>>>> =
+            model.nodes().iter().map(|n| tvec!(None; n.outputs.len())).collect();
+        for (outlet, buffer) in buffer_of {
+            let buffer = &buffers[buffer];
+            regions[outlet.node][outlet.slot] = Some(buffer.offset..buffer.offset + buffer.size);
+        }
+        Ok(MemoryPlan { regions, arena_size })
+    }
+
+    /// Compute the arena layout of a typed plan.
+    pub fn for_plan<M>(plan: &SimplePlan<TypedFact, Box<dyn TypedOp>, M>) -> TractResult<MemoryPlan>
+    where
+        M: Borrow<TypedModel> + Hash,
+    {
+        MemoryPlan::new(plan.model(), &plan.order, &plan.outputs)
+    }
+
+    pub fn region(&self, outlet: OutletId) -> Option<Range<usize>> {
+        self.regions.get(outlet.node).and_then(|r| r.get(outlet.slot)).cloned().flatten()
+    }
+}
+
+/// Greedy first fit, biggest buffers first. Returns the arena size.
+fn pack(buffers: &mut [Buffer]) -> usize {
+    let mut by_size: Vec<usize> = (0..buffers.len()).collect();
+    by_size.sort_by_key(|&b| (std::cmp::Reverse(buffers[b].size), buffers[b].start));
+    let mut placed: Vec<usize> = vec![];
+    let mut arena_size = 0;
+    for b in by_size {
+        let mut taken: Vec<(usize, usize)> = placed
+            .iter()
+            .map(|&p| &buffers[p])
+            .filter(|p| p.start <= buffers[b].end && buffers[b].start <= p.end)
+            .map(|p| (p.offset, p.offset + p.size))
+            .collect();
+        taken.sort();
+        let mut offset = 0;
+        for (start, end) in taken {
+            if offset + buffers[b].size <= start {
+                break;
+            }
+            offset = offset.max(round_up(end));
+        }
+        buffers[b].offset = offset;
+        arena_size = arena_size.max(offset + buffers[b].size);
+        placed.push(b);
+    }
+    round_up(arena_size)
+}
+
+fn round_up(offset: usize) -> usize {
+    (offset + ARENA_ALIGNMENT - 1) / ARENA_ALIGNMENT * ARENA_ALIGNMENT
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+    use crate::ops::nn::sigmoid;
+
+    fn chain() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[16]))?;
+        let k = model.add_const("k", tensor1(&[2f32; 16]))?;
+        let a = model.wire_node("a", math::mul::bin_typed(), &[source, k])?;
+        let b = model.wire_node("b", sigmoid(), &a)?;
+        let c = model.wire_node("c", AxisOp::Add(0), &b)?;
+        let d = model.wire_node("d", math::add::bin_typed(), &[c[0], c[0]])?;
+        let e = model.wire_node("e", math::mul::bin_typed(), &[d[0], d[0]])?;
+        model.set_output_outlets(&e)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn in_place_and_reuse() -> TractResult<()> {
+        let model = chain()?;
+        let memory = MemoryPlan::for_plan(&SimplePlan::new(&model)?)?;
+        let region = |name: &str| -> TractResult<Option<Range<usize>>> {
+            Ok(memory.region(OutletId::new(model.node_id_by_name(name)?, 0)))
+        };
+        assert!(region("source")?.is_none());
+        assert!(region("k")?.is_none());
+        assert!(region("e")?.is_none());
+        // sigmoid and AxisOp work in place
+        assert_eq!(region("a")?, Some(0..64));
+        assert_eq!(region("b")?, region("a")?);
+        assert_eq!(region("c")?, region("a")?);
+        // c is still alive when d is computed
+        assert_eq!(region("d")?, Some(64..128));
+        assert_eq!(memory.arena_size, 128);
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_shapes_are_refused() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = Symbol::from('S');
+        let source = model
+            .add_source("source", TypedFact::dt_shape(f32::datum_type(), [s.to_dim()].as_ref()))?;
+        let a = model.wire_node("a", sigmoid(), &[source])?;
+        let b = model.wire_node("b", sigmoid(), &a)?;
+        model.set_output_outlets(&b)?;
+        assert!(MemoryPlan::for_plan(&SimplePlan::new(&model)?).is_err());
+        Ok(())
+    }
+}
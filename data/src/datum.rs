@@ -1,6 +1,6 @@
 //! `Tensor` is the main data container for tract
 use crate::dim::TDim;
-use crate::f16::f16;
+use crate::f16::{bf16, f16};
 use crate::tensor::litteral::*;
 use crate::tensor::Tensor;
 use crate::TVec;
@@ -46,6 +46,7 @@ pub enum DatumType {
     I32,
     I64,
     F16,
+    BF16,
     F32,
     F64,
     TDim,
@@ -58,6 +59,8 @@ impl DatumType {
         use DatumType::*;
         if *self == String || *self == TDim || *self == Blob || *self == Bool {
             tvec!(*self)
+        } else if *self == BF16 {
+            tvec!(BF16, F32, F64)
         } else if self.is_float() {
             [F16, F32, F64].iter().filter(|s| s.size_of() >= self.size_of()).copied().collect()
         } else if self.is_signed() {
@@ -115,7 +118,7 @@ impl DatumType {
 
     pub fn is_float(&self) -> bool {
         match self {
-            DatumType::F16 | DatumType::F32 | DatumType::F64 => true,
+            DatumType::F16 | DatumType::BF16 | DatumType::F32 | DatumType::F64 => true,
             _ => false,
         }
     }
@@ -170,6 +173,7 @@ impl std::str::FromStr for DatumType {
             "U32" | "u32" => Ok(DatumType::U32),
             "U64" | "u64" => Ok(DatumType::U64),
             "F16" | "f16" => Ok(DatumType::F16),
+            "BF16" | "bf16" => Ok(DatumType::BF16),
             "F32" | "f32" => Ok(DatumType::F32),
             "F64" | "f64" => Ok(DatumType::F64),
             "Bool" | "bool" => Ok(DatumType::Bool),
@@ -210,6 +214,7 @@ macro_rules! datum {
 
 datum!(bool, Bool);
 datum!(f16, F16);
+datum!(bf16, BF16);
 datum!(f32, F32);
 datum!(f64, F64);
 datum!(i8, I8);
@@ -251,6 +256,24 @@ mod tests {
         t_i32.cast_to::<TDim>().unwrap();
     }
 
+    #[test]
+    fn test_cast_f32_to_bf16() {
+        let t_f32: Tensor = tensor1(&[0.5f32, -3.0, 1024.0]);
+        let t_bf16 = t_f32.cast_to::<bf16>().unwrap();
+        assert_eq!(t_bf16.datum_type(), DatumType::BF16);
+        assert_eq!(t_bf16.cast_to::<f32>().unwrap().into_owned(), t_f32);
+        assert_eq!(
+            t_bf16.cast_to::<f16>().unwrap().into_owned(),
+            t_f32.cast_to::<f16>().unwrap().into_owned()
+        );
+    }
+
+    #[test]
+    fn test_half_floats_super_type() {
+        assert_eq!(DatumType::F16.common_super_type(DatumType::BF16), Some(DatumType::F32));
+        assert_eq!(DatumType::BF16.common_super_type(DatumType::F64), Some(DatumType::F64));
+    }
+
     #[test]
     fn test_cast_i64_to_bool() {
         let t_i64: Tensor = tensor1(&[0i64]);
@@ -3,9 +3,11 @@ use num_traits::Zero;
 use std::fmt;
 use std::ops;
 
+mod sym;
 mod tree;
 
-pub use self::tree::{Symbol, SymbolValues, TDim};
+pub use self::sym::{Assertion, Symbol, SymbolTable, SymbolValues};
+pub use self::tree::TDim;
 type TractError = anyhow::Error;
 type TractResult<T> = anyhow::Result<T>;
 
@@ -83,15 +85,21 @@ impl DimLike for TDim {
             }
             (_, Ok(q)) => Some((self.clone() / q, 1)),
             (_, _) => {
-                if self.symbols().len() == 1 && other.symbols().len() == 1 {
+                if let Some(quotient) = self.div_factors(other) {
+                    Some(quotient)
+                } else if self.symbols().len() == 1 && other.symbols().len() == 1 {
                     let sym = self.symbols().into_iter().nth(0).unwrap();
-                    let slope_p = self.slope(sym);
-                    let slope_q = other.slope(sym);
-                    let (p, q) = tree::reduce_ratio(
-                        slope_p.0 * slope_q.1 as i64,
-                        slope_q.0 * slope_p.1 as i64,
-                    );
-                    Some((p.into(), q))
+                    let slope_p = self.slope(&sym);
+                    let slope_q = other.slope(&sym);
+                    if slope_q.0 == 0 {
+                        None
+                    } else {
+                        let (p, q) = tree::reduce_ratio(
+                            slope_p.0 * slope_q.1 as i64,
+                            slope_q.0 * slope_p.1 as i64,
+                        );
+                        Some((p.into(), q))
+                    }
                 } else {
                     None
                 }
@@ -106,13 +114,7 @@ impl DimLike for TDim {
     }
 
     fn maybe_mul(&self, other: &Self) -> TractResult<Self> {
-        if let Ok(d) = other.to_i64() {
-            Ok(self.clone() * d)
-        } else if let Ok(a) = self.to_i64() {
-            Ok(other.clone() * a)
-        } else {
-            anyhow::bail!("product with too many symbols")
-        }
+        Ok(self.clone() * other)
     }
 
     fn to_i64(&self) -> TractResult<i64> {
@@ -199,11 +201,11 @@ mod tests {
     use super::*;
 
     lazy_static::lazy_static! {
-        static ref S: Symbol = crate::dim::Symbol::new('S');
+        static ref S: Symbol = crate::dim::Symbol::from('S');
     }
 
     pub fn s() -> TDim {
-        (*S).into()
+        S.clone().into()
     }
 
     #[test]
@@ -226,6 +228,14 @@ mod tests {
         assert_eq!((s() * 13).maybe_div(&(s() * 4)).unwrap(), (13.into(), 4));
     }
 
+    #[test]
+    fn div_products() {
+        let table = SymbolTable::default();
+        let b: TDim = table.sym("B").into();
+        let product = s().maybe_mul(&b).unwrap() * 6;
+        assert_eq!(product.maybe_div(&(b * 4)).unwrap(), (s() * 3, 2));
+    }
+
     #[test]
     fn div_sym_sym_rem() {
         assert!((s() + 1).maybe_div(&(s() * 4)).is_err());
@@ -0,0 +1,358 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+lazy_static::lazy_static! {
+    static ref DEFAULT_TABLE: SymbolTable = SymbolTable::with_id(0);
+}
+
+// tables are ordered by creation, after the default table (id 0)
+static TABLE_ID: AtomicUsize = AtomicUsize::new(1);
+
+struct SymbolTableData {
+    id: usize,
+    names: Vec<Arc<str>>,
+    assertions: Vec<Assertion>,
+}
+
+impl Default for SymbolTableData {
+    fn default() -> SymbolTableData {
+        SymbolTableData {
+            id: TABLE_ID.fetch_add(1, Ordering::Relaxed),
+            names: vec![],
+            assertions: vec![],
+        }
+    }
+}
+
+/// A scope for named symbols.
+///
+/// Models own their symbol table: symbols from different tables are never equal,
+/// even when they share a name. Cloning a table gives a handle to the same scope.
+#[derive(Clone, Default)]
+pub struct SymbolTable(Arc<Mutex<SymbolTableData>>);
+
+impl SymbolTable {
+    fn with_id(id: usize) -> SymbolTable {
+        let data = SymbolTableData { id, names: vec![], assertions: vec![] };
+        SymbolTable(Arc::new(Mutex::new(data)))
+    }
+
+    fn symbol(&self, table: &SymbolTableData, id: usize) -> Symbol {
+        Symbol {
+            table: Arc::downgrade(&self.0),
+            scope: table.id,
+            id,
+            name: table.names[id].clone(),
+        }
+    }
+
+    /// Get the symbol called `name`, creating it if needed.
+    pub fn sym(&self, name: &str) -> Symbol {
+        let mut table = self.0.lock().unwrap();
+        let id = if let Some(pos) = table.names.iter().position(|s| &**s == name) {
+            pos
+        } else {
+            table.names.push(name.into());
+            table.names.len() - 1
+        };
+        self.symbol(&table, id)
+    }
+
+    /// Get the symbol called `name` if it exists in this scope.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        let table = self.0.lock().unwrap();
+        table.names.iter().position(|s| &**s == name).map(|id| self.symbol(&table, id))
+    }
+
+    /// Create a fresh symbol, named after `prefix` and guaranteed not to clash with
+    /// the existing ones.
+    pub fn new_with_prefix(&self, prefix: &str) -> Symbol {
+        let mut table = self.0.lock().unwrap();
+        let taken = |n: &str| table.names.iter().any(|s| &**s == n);
+        let name = if !taken(prefix) {
+            prefix.to_string()
+        } else {
+            (1..).map(|i| format!("{}_{}", prefix, i)).find(|n| !taken(n)).unwrap()
+        };
+        table.names.push(name.into());
+        self.symbol(&table, table.names.len() - 1)
+    }
+
+    /// All the symbols of the scope, in creation order.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let table = self.0.lock().unwrap();
+        (0..table.names.len()).map(|id| self.symbol(&table, id)).collect()
+    }
+
+    /// Declare a constraint the symbol values will always satisfy.
+    ///
+    /// TDim simplification relies on them, so they must hold for every run of the model.
+    pub fn add_assertion(&self, assertion: Assertion) -> anyhow::Result<()> {
+        if assertion.symbol().scope != self.0.lock().unwrap().id {
+            anyhow::bail!("Assertion {} is about a symbol from another scope", assertion)
+        }
+        if let Assertion::MultipleOf(_, 0) = assertion {
+            anyhow::bail!("Invalid assertion {}", assertion)
+        }
+        let mut table = self.0.lock().unwrap();
+        if !table.assertions.contains(&assertion) {
+            table.assertions.push(assertion);
+        }
+        Ok(())
+    }
+
+    pub fn assertions(&self) -> Vec<Assertion> {
+        self.0.lock().unwrap().assertions.clone()
+    }
+
+    /// Check the assertions against the symbol values. Symbols without a value are ignored.
+    pub fn check(&self, values: &SymbolValues) -> anyhow::Result<()> {
+        for assertion in self.assertions() {
+            if let Some(value) = values[assertion.symbol()] {
+                if !assertion.check(value) {
+                    anyhow::bail!(
+                        "Symbol value {}={} violates assertion {}",
+                        assertion.symbol(),
+                        value,
+                        assertion
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SymbolTable {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let names = self.0.lock().unwrap().names.clone();
+        write!(fmt, "SymbolTable{:?}", names)?;
+        let assertions = self.assertions();
+        if !assertions.is_empty() {
+            write!(fmt, " assuming {}", assertions.iter().map(|a| a.to_string()).join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A constraint on the value of a symbol.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Assertion {
+    GreaterOrEqual(Symbol, i64),
+    LessOrEqual(Symbol, i64),
+    MultipleOf(Symbol, u64),
+}
+
+impl Assertion {
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            Assertion::GreaterOrEqual(s, _)
+            | Assertion::LessOrEqual(s, _)
+            | Assertion::MultipleOf(s, _) => s,
+        }
+    }
+
+    pub fn check(&self, value: i64) -> bool {
+        match self {
+            Assertion::GreaterOrEqual(_, min) => value >= *min,
+            Assertion::LessOrEqual(_, max) => value <= *max,
+            Assertion::MultipleOf(_, q) => value % *q as i64 == 0,
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Assertion::GreaterOrEqual(s, min) => write!(fmt, "{}>={}", s, min),
+            Assertion::LessOrEqual(s, max) => write!(fmt, "{}<={}", s, max),
+            Assertion::MultipleOf(s, q) => write!(fmt, "{}%{}==0", s, q),
+        }
+    }
+}
+
+/// A symbolic integer, living in a SymbolTable.
+///
+/// Symbols keep their name and the id of their table, so they still display and
+/// compare after the table is dropped (they lose its assertions).
+#[derive(Clone)]
+pub struct Symbol {
+    table: Weak<Mutex<SymbolTableData>>,
+    scope: usize,
+    id: usize,
+    name: Arc<str>,
+}
+
+impl Symbol {
+    /// Create a fresh symbol named after `c` (`K`, then `K_1`, `K_2`...) in the
+    /// process-wide default table.
+    ///
+    /// Prefer SymbolTable::new_with_prefix on the model symbol table.
+    pub fn new(c: char) -> Symbol {
+        DEFAULT_TABLE.new_with_prefix(&c.to_string())
+    }
+
+    pub fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    /// Inclusive bounds of the symbol value, from the assertions of its table.
+    pub fn bounds(&self) -> (Option<i64>, Option<i64>) {
+        let mut bounds = (None, None);
+        if let Some(table) = self.table.upgrade() {
+            for assertion in &table.lock().unwrap().assertions {
+                match assertion {
+                    Assertion::GreaterOrEqual(s, min) if s == self => {
+                        bounds.0 = Some(bounds.0.map_or(*min, |b: i64| b.max(*min)))
+                    }
+                    Assertion::LessOrEqual(s, max) if s == self => {
+                        bounds.1 = Some(bounds.1.map_or(*max, |b: i64| b.min(*max)))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Largest integer known to divide the symbol value.
+    pub fn multiple_of(&self) -> u64 {
+        use num_integer::Integer;
+        let mut multiple = 1;
+        if let Some(table) = self.table.upgrade() {
+            for assertion in &table.lock().unwrap().assertions {
+                if let Assertion::MultipleOf(s, q) = assertion {
+                    if s == self {
+                        multiple = multiple.lcm(q);
+                    }
+                }
+            }
+        }
+        multiple
+    }
+}
+
+/// Get or create a single-letter symbol in the process-wide default table.
+impl From<char> for Symbol {
+    fn from(c: char) -> Symbol {
+        DEFAULT_TABLE.sym(&c.to_string())
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        self.id == other.id && self.scope == other.scope
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Symbol) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Symbol) -> std::cmp::Ordering {
+        (self.scope, self.id).cmp(&(other.scope, other.id))
+    }
+}
+
+impl std::hash::Hash for Symbol {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.scope, self.id).hash(state)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SymbolValues(HashMap<Symbol, Option<i64>>);
+
+impl SymbolValues {
+    pub fn with(mut self, s: Symbol, v: i64) -> Self {
+        self[s] = Some(v);
+        self
+    }
+}
+
+impl std::ops::Index<Symbol> for SymbolValues {
+    type Output = Option<i64>;
+    fn index(&self, index: Symbol) -> &Self::Output {
+        self.0.get(&index).unwrap_or(&None)
+    }
+}
+
+impl<'a> std::ops::Index<&'a Symbol> for SymbolValues {
+    type Output = Option<i64>;
+    fn index(&self, index: &'a Symbol) -> &Self::Output {
+        self.0.get(index).unwrap_or(&None)
+    }
+}
+
+impl std::ops::IndexMut<Symbol> for SymbolValues {
+    fn index_mut(&mut self, index: Symbol) -> &mut Self::Output {
+        self.0.entry(index).or_insert(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_symbols() {
+        let table = SymbolTable::default();
+        let batch = table.sym("batch");
+        assert_eq!(batch, table.sym("batch"));
+        assert_eq!(batch.to_string(), "batch");
+        assert_ne!(batch, SymbolTable::default().sym("batch"));
+        assert_eq!(table.get("seq_len"), None);
+        let fresh = table.new_with_prefix("batch");
+        assert_ne!(fresh, batch);
+        assert_eq!(fresh.to_string(), "batch_1");
+    }
+
+    #[test]
+    fn assertions() {
+        let table = SymbolTable::default();
+        let n = table.sym("N");
+        table.add_assertion(Assertion::GreaterOrEqual(n.clone(), 1)).unwrap();
+        table.add_assertion(Assertion::MultipleOf(n.clone(), 8)).unwrap();
+        table.add_assertion(Assertion::MultipleOf(n.clone(), 6)).unwrap();
+        assert_eq!(n.bounds(), (Some(1), None));
+        assert_eq!(n.multiple_of(), 24);
+        assert!(table.check(&SymbolValues::default().with(n.clone(), 48)).is_ok());
+        let err = table.check(&SymbolValues::default().with(n.clone(), 12)).unwrap_err();
+        assert_eq!(err.to_string(), "Symbol value N=12 violates assertion N%8==0");
+        let other = SymbolTable::default().sym("N");
+        assert!(table.add_assertion(Assertion::LessOrEqual(other, 4)).is_err());
+    }
+
+    #[test]
+    fn symbols_outlive_their_table() {
+        let (a, b) = {
+            let table = SymbolTable::default();
+            (table.sym("a"), table.sym("b"))
+        };
+        let other = SymbolTable::default().sym("a");
+        assert_eq!(a.to_string(), "a");
+        assert!(a < b);
+        assert!(a < other);
+        assert_ne!(a, other);
+    }
+}
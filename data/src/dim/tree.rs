@@ -3,72 +3,20 @@ use num_traits::{AsPrimitive, Zero};
 use std::collections::HashMap;
 use std::{fmt, ops};
 
-macro_rules! b( ($e:expr) => { Box::new($e) } );
-
-lazy_static::lazy_static! {
-    static ref SYMBOL_TABLE: std::sync::Mutex<Vec<char>> = std::sync::Mutex::new(Vec::new());
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
-pub struct Symbol(char, usize);
-
-impl Symbol {
-    pub fn new(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        table.push(c);
-        Symbol(c, table.len() - 1)
-    }
-}
-
-impl From<char> for Symbol {
-    fn from(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        if let Some(pos) = table.iter().position(|s| *s == c) {
-            Symbol(c, pos)
-        } else {
-            table.push(c);
-            Symbol(c, table.len() - 1)
-        }
-    }
-}
-
-#[derive(Clone, Debug, Default)]
-pub struct SymbolValues(Vec<Option<i64>>);
-
-impl SymbolValues {
-    pub fn with(mut self, s: Symbol, v: i64) -> Self {
-        self[s] = Some(v);
-        self
-    }
-}
-
-impl std::ops::Index<Symbol> for SymbolValues {
-    type Output = Option<i64>;
-    fn index(&self, index: Symbol) -> &Self::Output {
-        if index.1 < self.0.len() {
-            &self.0[index.1]
-        } else {
-            &None
-        }
-    }
-}
+use super::sym::{Symbol, SymbolValues};
 
-impl std::ops::IndexMut<Symbol> for SymbolValues {
-    fn index_mut(&mut self, index: Symbol) -> &mut Self::Output {
-        if index.1 >= self.0.len() {
-            self.0.resize_with(index.1 + 1, Default::default)
-        }
-        &mut self.0[index.1]
-    }
-}
+macro_rules! b( ($e:expr) => { Box::new($e) } );
 
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub enum TDim {
     Sym(Symbol),
     Val(i64),
     Add(Vec<TDim>),
-    Mul(i64, Box<TDim>),
+    Mul(Vec<TDim>),
+    MulInt(i64, Box<TDim>),
     Div(Box<TDim>, u64),
+    Min(Vec<TDim>),
+    Max(Vec<TDim>),
 }
 
 use TDim::*;
@@ -76,11 +24,20 @@ use TDim::*;
 impl fmt::Display for TDim {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match &self {
-            Sym(sym) => write!(fmt, "{}", sym.0),
+            Sym(sym) => write!(fmt, "{}", sym),
             Val(it) => write!(fmt, "{}", it),
             Add(it) => write!(fmt, "{}", it.iter().map(|x| format!("{}", x)).join("+")),
-            Mul(a, b) => write!(fmt, "{}.{}", a, b),
+            Mul(it) => write!(
+                fmt,
+                "{}",
+                it.iter()
+                    .map(|x| if let Add(_) = x { format!("({})", x) } else { format!("{}", x) })
+                    .join("*")
+            ),
+            MulInt(a, b) => write!(fmt, "{}.{}", a, b),
             Div(a, b) => write!(fmt, "({})/{}", a, b),
+            Min(it) => write!(fmt, "min({})", it.iter().map(|x| format!("{}", x)).join(",")),
+            Max(it) => write!(fmt, "max({})", it.iter().map(|x| format!("{}", x)).join(",")),
         }
     }
 }
@@ -100,11 +57,14 @@ impl TDim {
 
     pub fn eval(&self, values: &SymbolValues) -> TDim {
         match self {
-            Sym(sym) => values[*sym].map(|s| Val(s)).unwrap_or(Sym(*sym)),
+            Sym(sym) => values[sym].map(Val).unwrap_or_else(|| Sym(sym.clone())),
             Val(v) => Val(*v),
             Add(terms) => terms.iter().fold(Val(0), |acc, it| -> TDim { acc + it.eval(values) }),
+            Mul(terms) => terms.iter().fold(Val(1), |acc, it| -> TDim { acc * it.eval(values) }),
             Div(a, q) => a.eval(values) / *q as i64,
-            Mul(p, a) => a.eval(values) * *p,
+            MulInt(p, a) => a.eval(values) * *p,
+            Min(terms) => Min(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
+            Max(terms) => Max(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
         }
     }
 
@@ -123,16 +83,20 @@ impl TDim {
         use self::TDim::*;
         match self {
             Sym(_) | Val(_) => 1,
-            Add(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
+            Add(terms) | Mul(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
             Div(a, _) => 3 * a.cost(),
-            Mul(_, a) => 2 * a.cost(),
+            MulInt(_, a) => 2 * a.cost(),
+            Min(terms) | Max(terms) => 4 * terms.iter().map(TDim::cost).sum::<usize>(),
         }
     }
 
     fn wiggle(&self) -> Vec<TDim> {
         use self::TDim::*;
         match self {
-            Sym(_) | Val(_) => vec![self.clone()],
+            Sym(_) | Val(_) | Min(_) | Max(_) => vec![self.clone()],
+            Mul(terms) => {
+                terms.iter().map(|e| e.wiggle()).multi_cartesian_product().map(Mul).collect()
+            }
             Add(terms) => {
                 let mut forms = vec![];
                 let sub_wiggle = terms.iter().map(|e| e.wiggle()).multi_cartesian_product();
@@ -150,7 +114,7 @@ impl TDim {
                             .enumerate()
                             .map(|(ix2, t)| {
                                 if ix2 != ix {
-                                    Mul(*q as i64, b!(t.clone()))
+                                    MulInt(*q as i64, b!(t.clone()))
                                 } else {
                                     (**num).clone()
                                 }
@@ -162,7 +126,7 @@ impl TDim {
                 }
                 forms
             }
-            Mul(p, a) => a.wiggle().into_iter().map(|a| Mul(*p, b!(a))).collect(),
+            MulInt(p, a) => a.wiggle().into_iter().map(|a| MulInt(*p, b!(a))).collect(),
             Div(a, q) => {
                 let mut forms = vec![];
                 for num in a.wiggle() {
@@ -198,7 +162,7 @@ impl TDim {
                         }
                         Val(0) => (),
                         Val(v) => *reduced.entry(Val(1)).or_insert(0) += v,
-                        Mul(v, f) => {
+                        MulInt(v, f) => {
                             *reduced.entry((*f).clone()).or_insert(0) += v;
                         }
                         n => *reduced.entry(n).or_insert(0) += 1,
@@ -214,7 +178,7 @@ impl TDim {
                         } else if v == 1 {
                             Some(k)
                         } else {
-                            Some(Mul(v, b![k]))
+                            Some(MulInt(v, b![k]))
                         }
                     })
                     .collect();
@@ -227,9 +191,9 @@ impl TDim {
                     members.remove(0)
                 }
             }
-            Mul(p, a) => {
-                if let Mul(p2, a) = *a {
-                    return Mul(p * p2, a).simplify();
+            MulInt(p, a) => {
+                if let MulInt(p2, a) = *a {
+                    return MulInt(p * p2, a).simplify();
                 } else if let Val(p2) = *a {
                     return Val(p * p2);
                 }
@@ -239,13 +203,20 @@ impl TDim {
                 } else if p == 1 {
                     a
                 } else if let Add(terms) = &a {
-                    Add(terms.clone().into_iter().map(|a| Mul(p, b!(a)).simplify()).collect())
+                    Add(terms.clone().into_iter().map(|a| MulInt(p, b!(a)).simplify()).collect())
                 } else if let Val(p2) = a {
                     Val(p * p2)
-                } else if let Mul(p2, a) = a {
-                    Mul(p * p2, a)
+                } else if let MulInt(p2, a) = a {
+                    MulInt(p * p2, a)
+                } else if let Div(num, q) = &a {
+                    // exact division, thanks to the assertions on the symbols
+                    if p % *q as i64 == 0 && num.gcd() % q == 0 {
+                        MulInt(p / *q as i64, num.clone()).simplify()
+                    } else {
+                        MulInt(p, b!(a))
+                    }
                 } else {
-                    Mul(p, b!(a))
+                    MulInt(p, b!(a))
                 }
             }
             Div(a, q) => {
@@ -255,13 +226,16 @@ impl TDim {
                     return Div(a, q * q2).simplify();
                 }
                 let a = a.simplify();
+                if !matches!(a, Sym(_)) && a.gcd() % q == 0 {
+                    return a.div(q).simplify();
+                }
                 if let Val(a) = a {
                     Val(a / q as i64)
-                } else if let Mul(-1, a) = a {
-                    Mul(-1, b!(Div(a, q)))
+                } else if let MulInt(-1, a) = a {
+                    MulInt(-1, b!(Div(a, q)))
                 } else if let Add(mut terms) = a {
                     if terms.iter().any(|t| {
-                        if let Mul(-1, s) = t {
+                        if let MulInt(-1, s) = t {
                             if let Sym(_) = &**s {
                                 true
                             } else {
@@ -271,10 +245,10 @@ impl TDim {
                             false
                         }
                     }) {
-                        Mul(
+                        MulInt(
                             -1,
                             b!(Div(
-                                b!(Add(terms.into_iter().map(|t| Mul(-1, b!(t))).collect())
+                                b!(Add(terms.into_iter().map(|t| MulInt(-1, b!(t))).collect())
                                     .simplify()),
                                 q
                             )),
@@ -300,7 +274,7 @@ impl TDim {
                     } else {
                         Div(b!(Add(terms)), q)
                     }
-                } else if let Mul(p, a) = a {
+                } else if let MulInt(p, a) = a {
                     if p == q as i64 {
                         a.simplify()
                     } else {
@@ -308,32 +282,124 @@ impl TDim {
                         if gcd == p {
                             Div(a, q / gcd as u64)
                         } else if gcd == q as i64 {
-                            Mul(p / gcd, a)
+                            MulInt(p / gcd, a)
                         } else if gcd > 1 {
-                            Div(b!(Mul(p / gcd, a)), q / gcd as u64).simplify()
+                            Div(b!(MulInt(p / gcd, a)), q / gcd as u64).simplify()
                         } else {
-                            Div(b!(Mul(p, a)), q)
+                            Div(b!(MulInt(p, a)), q)
                         }
                     }
                 } else {
                     Div(b!(a), q)
                 }
             }
+            Mul(terms) => {
+                let mut coef = 1;
+                let mut factors = vec![];
+                let mut pending = terms;
+                while let Some(term) = pending.pop() {
+                    match term.simplify() {
+                        Mul(items) => pending.extend(items),
+                        Val(v) => coef *= v,
+                        MulInt(v, f) => {
+                            coef *= v;
+                            pending.push(*f);
+                        }
+                        f => factors.push(f),
+                    }
+                }
+                if coef == 0 {
+                    return Val(0);
+                }
+                // distribute over sums so that equal products compare equal
+                if let Some(pos) = factors.iter().position(|f| matches!(f, Add(_))) {
+                    if let Add(sum) = factors.remove(pos) {
+                        return Add(sum
+                            .into_iter()
+                            .map(|t| {
+                                let mut product = factors.clone();
+                                product.push(t);
+                                MulInt(coef, b!(Mul(product)))
+                            })
+                            .collect())
+                        .simplify();
+                    }
+                }
+                factors.sort();
+                let product = match factors.len() {
+                    0 => return Val(coef),
+                    1 => factors.remove(0),
+                    _ => Mul(factors),
+                };
+                if coef == 1 {
+                    product
+                } else {
+                    MulInt(coef, b!(product))
+                }
+            }
+            Min(terms) => Self::simplify_extremum(terms, false),
+            Max(terms) => Self::simplify_extremum(terms, true),
             _ => self,
         }
     }
 
+    fn simplify_extremum(terms: Vec<TDim>, max: bool) -> TDim {
+        let mut flat = vec![];
+        let mut pending = terms;
+        while let Some(term) = pending.pop() {
+            match term.simplify() {
+                Max(items) if max => pending.extend(items),
+                Min(items) if !max => pending.extend(items),
+                t => flat.push(t),
+            }
+        }
+        let (values, mut others): (Vec<_>, Vec<_>) =
+            flat.into_iter().partition(|t| matches!(t, Val(_)));
+        let values = values.iter().map(|v| if let Val(v) = v { *v } else { unreachable!() });
+        if let Some(v) = if max { values.max() } else { values.min() } {
+            others.push(Val(v));
+        }
+        others.sort();
+        others.dedup();
+        // drop the terms that can be proven to never be the extremum
+        let mut kept: Vec<TDim> = vec![];
+        'terms: for term in others {
+            let mut ix = 0;
+            while ix < kept.len() {
+                let diff = term.clone() - &kept[ix];
+                let (lower, higher) =
+                    (diff.prove_negative_or_zero(), diff.prove_positive_or_zero());
+                if (max && lower) || (!max && higher) {
+                    continue 'terms;
+                } else if lower || higher {
+                    kept.remove(ix);
+                } else {
+                    ix += 1;
+                }
+            }
+            kept.push(term);
+        }
+        if kept.len() == 1 {
+            kept.remove(0)
+        } else if max {
+            Max(kept)
+        } else {
+            Min(kept)
+        }
+    }
+
     fn gcd(&self) -> u64 {
         use self::TDim::*;
         use num_integer::Integer;
         match self {
             Val(v) => v.abs() as u64,
-            Sym(_) => 1,
-            Add(terms) => {
+            Sym(s) => s.multiple_of(),
+            Add(terms) | Min(terms) | Max(terms) => {
                 let (head, tail) = terms.split_first().unwrap();
                 tail.iter().fold(head.gcd(), |a, b| a.gcd(&b.gcd()))
             }
-            Mul(p, a) => a.gcd() * p.abs() as u64,
+            Mul(terms) => terms.iter().map(|t| t.gcd()).product(),
+            MulInt(p, a) => a.gcd() * p.abs() as u64,
             Div(a, q) => {
                 if a.gcd() % *q == 0 {
                     a.gcd() / *q
@@ -352,34 +418,148 @@ impl TDim {
         }
         match self {
             Val(v) => Val(v / d as i64),
-            Sym(_) => panic!(),
+            Sym(_) => Div(b!(self.clone()), d),
             Add(terms) => Add(terms.iter().map(|t| t.div(d)).collect()),
-            Mul(p, a) => {
+            Min(terms) => Min(terms.iter().map(|t| t.div(d)).collect()),
+            Max(terms) => Max(terms.iter().map(|t| t.div(d)).collect()),
+            Mul(terms) => {
+                let mut d = d;
+                Mul(terms
+                    .iter()
+                    .map(|t| {
+                        let g = t.gcd().gcd(&d);
+                        d /= g;
+                        t.div(g)
+                    })
+                    .collect())
+            }
+            MulInt(p, a) => {
                 if *p == d as i64 {
                     (**a).clone()
                 } else {
                     let gcd = (p.abs() as u64).gcd(&d);
-                    Mul(p / gcd as i64, b!(a.div(d / gcd)))
+                    MulInt(p / gcd as i64, b!(a.div(d / gcd)))
                 }
             }
             Div(a, q) => Div(a.clone(), q * d),
         }
     }
 
+    /// Smallest of two dimensions.
+    pub fn mini(self, other: TDim) -> TDim {
+        Min(vec![self, other]).reduce()
+    }
+
+    /// Largest of two dimensions.
+    pub fn maxi(self, other: TDim) -> TDim {
+        Max(vec![self, other]).reduce()
+    }
+
+    /// Split a product into its integer coefficient and its other factors.
+    fn factors(&self) -> (i64, Vec<TDim>) {
+        match self {
+            Val(v) => (*v, vec![]),
+            MulInt(p, a) => {
+                let (q, factors) = a.factors();
+                (p * q, factors)
+            }
+            Mul(terms) => (1, terms.clone()),
+            _ => (1, vec![self.clone()]),
+        }
+    }
+
+    /// Quotient of two products, if all the factors of `other` appear in self.
+    pub(super) fn div_factors(&self, other: &TDim) -> Option<(TDim, u64)> {
+        let (p, mut num) = self.factors();
+        let (q, den) = other.factors();
+        if q == 0 {
+            return None;
+        }
+        for f in den {
+            let pos = num.iter().position(|n| n == &f)?;
+            num.remove(pos);
+        }
+        let (p, q) = reduce_ratio(p, q);
+        Some((Mul(num).reduce() * p, q))
+    }
+
     pub fn div_ceil(self, rhs: u64) -> TDim {
         TDim::Div(Box::new(Add(vec![self, Val(rhs as i64 - 1)])), rhs).reduce()
     }
 
-    pub fn slope(&self, sym: Symbol) -> (i64, u64) {
-        fn slope_rec(d: &TDim, sym: Symbol) -> (i64, i64) {
+    /// Inclusive bounds of the expression values, from the assertions on its symbols.
+    pub fn bounds(&self) -> (Option<i64>, Option<i64>) {
+        fn sum(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+            a.and_then(|a| b.map(|b| a + b))
+        }
+        match self {
+            Val(v) => (Some(*v), Some(*v)),
+            Sym(s) => s.bounds(),
+            Add(terms) => terms.iter().fold((Some(0), Some(0)), |acc, t| {
+                let (low, high) = t.bounds();
+                (sum(acc.0, low), sum(acc.1, high))
+            }),
+            MulInt(p, a) => {
+                let (low, high) = a.bounds();
+                let (low, high) = (low.map(|l| l * p), high.map(|h| h * p));
+                if *p >= 0 {
+                    (low, high)
+                } else {
+                    (high, low)
+                }
+            }
+            Mul(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect::<Vec<_>>();
+                if bounds.iter().all(|b| b.0.map(|l| l >= 0).unwrap_or(false)) {
+                    let low = bounds.iter().map(|b| b.0.unwrap()).product();
+                    let high = bounds.iter().try_fold(1, |acc, b| b.1.map(|h| acc * h));
+                    (Some(low), high)
+                } else {
+                    (None, None)
+                }
+            }
+            Div(a, q) => {
+                let (low, high) = a.bounds();
+                (low.map(|l| l / *q as i64), high.map(|h| h / *q as i64))
+            }
+            Min(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect::<Vec<_>>();
+                let low = bounds.iter().map(|b| b.0).collect::<Option<Vec<_>>>();
+                (low.and_then(|l| l.into_iter().min()), bounds.iter().filter_map(|b| b.1).min())
+            }
+            Max(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect::<Vec<_>>();
+                let high = bounds.iter().map(|b| b.1).collect::<Option<Vec<_>>>();
+                (bounds.iter().filter_map(|b| b.0).max(), high.and_then(|h| h.into_iter().max()))
+            }
+        }
+    }
+
+    pub fn prove_positive_or_zero(&self) -> bool {
+        self.bounds().0.map(|l| l >= 0).unwrap_or(false)
+    }
+
+    pub fn prove_strict_positive(&self) -> bool {
+        self.bounds().0.map(|l| l > 0).unwrap_or(false)
+    }
+
+    pub fn prove_negative_or_zero(&self) -> bool {
+        self.bounds().1.map(|h| h <= 0).unwrap_or(false)
+    }
+
+    /// Slope of a linear expression with regard to `sym`.
+    ///
+    /// Products of symbols, min and max are not linear and have no slope: they count as zero.
+    pub fn slope(&self, sym: &Symbol) -> (i64, u64) {
+        fn slope_rec(d: &TDim, sym: &Symbol) -> (i64, i64) {
             match d {
-                Val(_) => (0, 1),
-                Sym(s) => ((sym == *s) as i64, 1),
+                Val(_) | Mul(_) | Min(_) | Max(_) => (0, 1),
+                Sym(s) => ((sym == s) as i64, 1),
                 Add(terms) => terms
                     .iter()
                     .map(|d| slope_rec(d, sym))
                     .fold((1, 1), |a, b| ((a.0 * b.1 + a.1 * b.0), (b.1 * a.1))),
-                Mul(p, a) => {
+                MulInt(p, a) => {
                     let (n, d) = slope_rec(a, sym);
                     (p * n, d)
                 }
@@ -396,12 +576,14 @@ impl TDim {
     pub fn symbols(&self) -> std::collections::HashSet<Symbol> {
         match self {
             Val(_) => maplit::hashset!(),
-            Sym(s) => maplit::hashset!(*s),
-            Add(terms) => terms.iter().fold(maplit::hashset!(), |mut set, v| {
-                set.extend(v.symbols().into_iter());
-                set
-            }),
-            Mul(_, a) => a.symbols(),
+            Sym(s) => maplit::hashset!(s.clone()),
+            Add(terms) | Mul(terms) | Min(terms) | Max(terms) => {
+                terms.iter().fold(maplit::hashset!(), |mut set, v| {
+                    set.extend(v.symbols().into_iter());
+                    set
+                })
+            }
+            MulInt(_, a) => a.symbols(),
             Div(a, _) => a.symbols(),
         }
     }
@@ -476,14 +658,14 @@ impl From<Symbol> for TDim {
 
 impl<'a> From<&'a Symbol> for TDim {
     fn from(it: &'a Symbol) -> Self {
-        TDim::Sym(*it)
+        TDim::Sym(it.clone())
     }
 }
 
 impl ops::Neg for TDim {
     type Output = Self;
     fn neg(self) -> Self {
-        TDim::Mul(-1, Box::new(self)).reduce()
+        TDim::MulInt(-1, Box::new(self)).reduce()
     }
 }
 
@@ -560,7 +742,7 @@ impl<'a> ops::Sub<&'a TDim> for TDim {
 
 impl ops::MulAssign<i64> for TDim {
     fn mul_assign(&mut self, rhs: i64) {
-        *self = TDim::Mul(rhs, Box::new(std::mem::take(self))).reduce()
+        *self = TDim::MulInt(rhs, Box::new(std::mem::take(self))).reduce()
     }
 }
 
@@ -572,6 +754,34 @@ impl<I: AsPrimitive<i64>> ops::Mul<I> for TDim {
     }
 }
 
+impl<'a> ops::MulAssign<&'a TDim> for TDim {
+    fn mul_assign(&mut self, rhs: &'a TDim) {
+        *self = TDim::Mul(vec![std::mem::take(self), rhs.clone()]).reduce()
+    }
+}
+
+impl ops::MulAssign<TDim> for TDim {
+    fn mul_assign(&mut self, rhs: TDim) {
+        *self *= &rhs
+    }
+}
+
+impl<'a> ops::Mul<&'a TDim> for TDim {
+    type Output = Self;
+    fn mul(mut self, rhs: &'a TDim) -> Self {
+        self *= rhs;
+        self
+    }
+}
+
+impl ops::Mul<TDim> for TDim {
+    type Output = Self;
+    fn mul(mut self, rhs: TDim) -> Self {
+        self *= &rhs;
+        self
+    }
+}
+
 impl<I: AsPrimitive<u64>> ops::DivAssign<I> for TDim {
     fn div_assign(&mut self, rhs: I) {
         *self = TDim::Div(Box::new(std::mem::take(self)), rhs.as_()).reduce()
@@ -610,15 +820,16 @@ impl std::str::FromStr for TDim {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dim::{Assertion, SymbolTable};
 
     macro_rules! b( ($e:expr) => { Box::new($e) } );
 
     lazy_static::lazy_static! {
-        static ref S: Symbol = crate::dim::Symbol::new('S');
+        static ref S: Symbol = crate::dim::Symbol::from('S');
     }
 
     fn s() -> TDim {
-        (*S).into()
+        S.clone().into()
     }
 
     fn neg(a: &TDim) -> TDim {
@@ -630,7 +841,7 @@ mod tests {
     }
 
     fn mul(a: i64, b: &TDim) -> TDim {
-        TDim::Mul(a, b![b.clone()])
+        TDim::MulInt(a, b![b.clone()])
     }
 
     fn div(a: &TDim, b: u64) -> TDim {
@@ -658,7 +869,7 @@ mod tests {
 
     #[test]
     fn reduce_cplx_ex_3() {
-        assert_eq!(div(&Mul(1, b!(Mul(4, b!(s())))), 4).reduce(), s())
+        assert_eq!(div(&MulInt(1, b!(MulInt(4, b!(s())))), 4).reduce(), s())
     }
 
     #[test]
@@ -700,12 +911,54 @@ mod tests {
     #[test]
     fn substitution() {
         let x = Symbol::new('x');
-        let e: TDim = x.into();
-        assert_eq!(e.eval(&SymbolValues::default().with(x, 2)).to_i64().unwrap(), 2);
+        let e: TDim = x.clone().into();
+        assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), 2)).to_i64().unwrap(), 2);
         let e = e + 3;
         assert_eq!(e.eval(&SymbolValues::default().with(x, 2)).to_i64().unwrap(), 5);
     }
 
+    #[test]
+    fn products_of_symbols() {
+        let table = SymbolTable::default();
+        let b: TDim = table.sym("batch").into();
+        let t: TDim = table.sym("seq_len").into();
+        assert_eq!(b.clone() * &t, t.clone() * &b);
+        assert_eq!((b.clone() * &t * 2).to_string(), "2.batch*seq_len");
+        assert_eq!(b.clone() * &t + t.clone() * &b, b.clone() * &t * 2);
+        assert_eq!((b.clone() + 1) * &t, b.clone() * &t + &t);
+        let values =
+            SymbolValues::default().with(table.sym("batch"), 3).with(table.sym("seq_len"), 5);
+        assert_eq!((b * &t * 2).eval(&values), 30.into());
+    }
+
+    #[test]
+    fn min_max() {
+        assert_eq!(s().mini(s() + 2), s());
+        assert_eq!(s().maxi(s() + 2), s() + 2);
+        assert_eq!(TDim::from(3).mini(4.into()), 3.into());
+        let m = s().mini(12.into());
+        assert_eq!(m.to_string(), "min(S,12)");
+        assert_eq!(m.clone().mini(15.into()), m);
+        assert_eq!(m.eval(&SymbolValues::default().with(S.clone(), 20)), 12.into());
+    }
+
+    #[test]
+    fn assertions() {
+        let table = SymbolTable::default();
+        let sym = table.sym("N");
+        table.add_assertion(Assertion::GreaterOrEqual(sym.clone(), 1)).unwrap();
+        table.add_assertion(Assertion::MultipleOf(sym.clone(), 8)).unwrap();
+        let n: TDim = sym.into();
+        assert_eq!(n.clone() / 8 * 8, n);
+        assert_eq!(n.clone().div_ceil(8) * 8, n);
+        assert_eq!(n.clone() / 2 * 3 / 4, n.clone() / 8 * 3);
+        assert_eq!(n.clone().mini(1.into()), 1.into());
+        assert_eq!((n.clone() - 1).maxi(0.into()), n.clone() - 1);
+        assert_eq!(n.clone().maxi(s()).to_string(), "max(S,N)");
+        assert!(n.prove_strict_positive());
+        assert!(!s().prove_positive_or_zero());
+    }
+
     #[test]
     fn reduce_adds() {
         let e: TDim = TDim::from(2) + 1;
@@ -33,6 +33,7 @@ macro_rules! dispatch_datum {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::Blob => $($path)::*::<Blob>($($args),*),
@@ -57,6 +58,7 @@ macro_rules! dispatch_datum_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             DatumType::Blob => $($path)::*::<Blob>($($args),*),
@@ -81,6 +83,7 @@ macro_rules! dispatch_copy {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             _ => panic!("{:?} is not Copy", $dt)
@@ -103,6 +106,7 @@ macro_rules! dispatch_copy_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             _ => panic!("{:?} is not Copy", $dt)
@@ -124,6 +128,7 @@ macro_rules! dispatch_numbers {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             _ => $crate::anyhow::bail!("{:?} is not a number", $dt)
@@ -136,7 +141,8 @@ macro_rules! dispatch_floatlike {
     ($($path:ident)::* ($dt:expr) ($($args:expr),*)) => { {
         use $crate::prelude::DatumType;
         match $dt {
-            DatumType::F16  => $($path)::*::<f32>($($args),*), // FIXME !!!
+            DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             _ => $crate::anyhow::bail!("{:?} is not float-like", $dt)
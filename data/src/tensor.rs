@@ -1,7 +1,7 @@
 //! `Tensor`, tract main data object of interest.
 use crate::datum::{Blob, Datum, DatumType};
 use crate::dim::TDim;
-use crate::f16::f16;
+use crate::f16::{bf16, f16};
 use crate::TVec;
 use ndarray::prelude::*;
 #[cfg(feature = "serialize")]
@@ -46,7 +46,7 @@ impl Hash for Tensor {
                 U16 => self.as_slice_unchecked::<u16>().hash(state),
                 U32 => self.as_slice_unchecked::<u32>().hash(state),
                 U64 => self.as_slice_unchecked::<u64>().hash(state),
-                F16 => self.as_slice_unchecked::<i16>().hash(state),
+                F16 | BF16 => self.as_slice_unchecked::<i16>().hash(state),
                 F32 => self.as_slice_unchecked::<i32>().hash(state),
                 F64 => self.as_slice_unchecked::<i64>().hash(state),
                 TDim => self.as_slice_unchecked::<crate::dim::TDim>().hash(state),
@@ -166,7 +166,7 @@ impl Tensor {
         // map all copy types to the i* of the same size
         let mut tensor = unsafe {
             match dt {
-                DatumType::F16 => i16::stack_tensors(axis, &tensors),
+                DatumType::F16 | DatumType::BF16 => i16::stack_tensors(axis, &tensors),
                 DatumType::F32 => i32::stack_tensors(axis, &tensors),
                 DatumType::F64 => i64::stack_tensors(axis, &tensors),
                 DatumType::Bool => i8::stack_tensors(axis, &tensors),
@@ -796,6 +796,7 @@ impl Tensor {
                             DatumType::U32 => self.natural_cast::<$source, u32>(&mut result),
                             DatumType::U64 => self.natural_cast::<$source, u64>(&mut result),
                             DatumType::F16 => self.natural_cast::<$source, f16>(&mut result),
+                            DatumType::BF16 => self.natural_cast::<$source, bf16>(&mut result),
                             DatumType::F32 => self.natural_cast::<$source, f32>(&mut result),
                             DatumType::F64 => self.natural_cast::<$source, f64>(&mut result),
                             DatumType::TDim => {
@@ -822,6 +823,7 @@ impl Tensor {
             n!(i32);
             n!(i64);
             n!(f16);
+            n!(bf16);
             n!(f32);
             n!(f64);
             anyhow::bail!("Unsupported cast from {:?} to {:?}", self.dt, dt)
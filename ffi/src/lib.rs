@@ -0,0 +1,535 @@
+//! C API for tract.
+//!
+//! All functions return a `TRACT_RESULT`. On failure, the error message can be retrieved with
+//! `tract_get_last_error`. Objects created by a `tract_*` function must be released with the
+//! matching `tract_*_destroy`. Functions consuming an object (like
+//! `tract_inference_model_into_typed`) take a pointer to the caller's handle and set it to null.
+//!
+//! The matching C header is `tract.h`, generated by `cbindgen -o tract.h` from this directory.
+//! The `header` test fails when it is out of date.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::convert::{TryFrom, TryInto};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::sync::Arc;
+
+use tract_hir::internal::*;
+use tract_pulse::internal::{PulsedModel, PulsedModelExt};
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum TRACT_RESULT {
+    TRACT_RESULT_OK = 0,
+    TRACT_RESULT_KO = 1,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn wrap<F: FnOnce() -> TractResult<()>>(func: F) -> TRACT_RESULT {
+    let result = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(func)) {
+        Ok(Ok(())) => return TRACT_RESULT::TRACT_RESULT_OK,
+        Ok(Err(e)) => format!("{:#}", e),
+        Err(panic) => {
+            if let Some(s) = panic.downcast_ref::<&str>() {
+                format!("panic: {}", s)
+            } else if let Some(s) = panic.downcast_ref::<String>() {
+                format!("panic: {}", s)
+            } else {
+                "panic".to_string()
+            }
+        }
+    };
+    LAST_ERROR.with(|p| {
+        *p.borrow_mut() = Some(CString::new(result.replace('\0', "")).unwrap());
+    });
+    TRACT_RESULT::TRACT_RESULT_KO
+}
+
+/// Retrieve the last error that happened in this thread.
+///
+/// The returned pointer is owned by the library and stays valid until the next failing call
+/// in the same thread. Returns null if no error occurred.
+#[no_mangle]
+pub extern "C" fn tract_get_last_error() -> *const c_char {
+    LAST_ERROR.with(|msg| msg.borrow().as_ref().map(|s| s.as_ptr()).unwrap_or(std::ptr::null()))
+}
+
+/// Release a string allocated by the library.
+#[no_mangle]
+pub unsafe extern "C" fn tract_free_cstring(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s))
+    }
+}
+
+macro_rules! check_not_null {
+    ($($ptr:expr),*) => {
+        $(
+            if $ptr.is_null() {
+                bail!("Unexpected null pointer {}", stringify!($ptr));
+            }
+         )*
+    }
+}
+
+unsafe fn c_str<'a>(s: *const c_char) -> TractResult<&'a str> {
+    check_not_null!(s);
+    Ok(CStr::from_ptr(s).to_str()?)
+}
+
+unsafe fn take<T>(handle: *mut *mut T) -> TractResult<Box<T>> {
+    check_not_null!(handle, *handle);
+    let it = Box::from_raw(*handle);
+    *handle = std::ptr::null_mut();
+    Ok(it)
+}
+
+unsafe fn destroy<T>(handle: *mut *mut T) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(handle);
+        if !(*handle).is_null() {
+            drop(take(handle)?);
+        }
+        Ok(())
+    })
+}
+
+/// Element type of a TractTensor, one of the TRACT_DATUM_TYPE_* values.
+///
+/// It is a plain integer rather than a C enum, so that any value coming from C is
+/// defined behaviour: unknown values are reported as errors.
+#[repr(transparent)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct TractDatumType(pub u32);
+
+pub const TRACT_DATUM_TYPE_BOOL: TractDatumType = TractDatumType(0x01);
+pub const TRACT_DATUM_TYPE_U8: TractDatumType = TractDatumType(0x11);
+pub const TRACT_DATUM_TYPE_U16: TractDatumType = TractDatumType(0x12);
+pub const TRACT_DATUM_TYPE_U32: TractDatumType = TractDatumType(0x14);
+pub const TRACT_DATUM_TYPE_U64: TractDatumType = TractDatumType(0x18);
+pub const TRACT_DATUM_TYPE_I8: TractDatumType = TractDatumType(0x21);
+pub const TRACT_DATUM_TYPE_I16: TractDatumType = TractDatumType(0x22);
+pub const TRACT_DATUM_TYPE_I32: TractDatumType = TractDatumType(0x24);
+pub const TRACT_DATUM_TYPE_I64: TractDatumType = TractDatumType(0x28);
+pub const TRACT_DATUM_TYPE_F16: TractDatumType = TractDatumType(0x32);
+pub const TRACT_DATUM_TYPE_F32: TractDatumType = TractDatumType(0x34);
+pub const TRACT_DATUM_TYPE_F64: TractDatumType = TractDatumType(0x38);
+pub const TRACT_DATUM_TYPE_BF16: TractDatumType = TractDatumType(0x42);
+
+impl TryFrom<TractDatumType> for DatumType {
+    type Error = TractError;
+    fn try_from(dt: TractDatumType) -> TractResult<DatumType> {
+        Ok(match dt {
+            TRACT_DATUM_TYPE_BOOL => DatumType::Bool,
+            TRACT_DATUM_TYPE_U8 => DatumType::U8,
+            TRACT_DATUM_TYPE_U16 => DatumType::U16,
+            TRACT_DATUM_TYPE_U32 => DatumType::U32,
+            TRACT_DATUM_TYPE_U64 => DatumType::U64,
+            TRACT_DATUM_TYPE_I8 => DatumType::I8,
+            TRACT_DATUM_TYPE_I16 => DatumType::I16,
+            TRACT_DATUM_TYPE_I32 => DatumType::I32,
+            TRACT_DATUM_TYPE_I64 => DatumType::I64,
+            TRACT_DATUM_TYPE_F16 => DatumType::F16,
+            TRACT_DATUM_TYPE_F32 => DatumType::F32,
+            TRACT_DATUM_TYPE_F64 => DatumType::F64,
+            TRACT_DATUM_TYPE_BF16 => DatumType::BF16,
+            TractDatumType(other) => bail!("Invalid datum type {:#x}", other),
+        })
+    }
+}
+
+/// A tensor view over memory owned by the caller.
+///
+/// `shape` points to `rank` dimensions, `data` to the densely packed, row-major, elements.
+#[repr(C)]
+pub struct TractTensor {
+    pub datum_type: TractDatumType,
+    pub rank: usize,
+    pub shape: *const usize,
+    pub data: *mut c_void,
+}
+
+impl TractTensor {
+    unsafe fn shape(&self) -> TractResult<&[usize]> {
+        if self.rank == 0 {
+            return Ok(&[]);
+        }
+        check_not_null!(self.shape);
+        Ok(std::slice::from_raw_parts(self.shape, self.rank))
+    }
+
+    unsafe fn len(&self) -> TractResult<usize> {
+        let dt: DatumType = self.datum_type.try_into()?;
+        Ok(self.shape()?.iter().product::<usize>() * dt.size_of())
+    }
+
+    unsafe fn to_tensor(&self) -> TractResult<Tensor> {
+        check_not_null!(self.data);
+        let content = std::slice::from_raw_parts(self.data as *const u8, self.len()?);
+        Tensor::from_raw_dt(self.datum_type.try_into()?, self.shape()?, content)
+    }
+
+    unsafe fn copy_from(&mut self, tensor: &Tensor) -> TractResult<()> {
+        check_not_null!(self.data);
+        let dt: DatumType = self.datum_type.try_into()?;
+        if tensor.datum_type() != dt || tensor.shape() != self.shape()? {
+            bail!(
+                "Output buffer is {:?},{:?}, tract produced {:?},{:?}",
+                self.shape()?,
+                dt,
+                tensor.shape(),
+                tensor.datum_type()
+            )
+        }
+        let dst = std::slice::from_raw_parts_mut(self.data as *mut u8, self.len()?);
+        dst.copy_from_slice(tensor.as_bytes());
+        Ok(())
+    }
+}
+
+unsafe fn run_with<F>(
+    nbio: (usize, usize),
+    inputs: *const TractTensor,
+    outputs: *mut TractTensor,
+    run: F,
+) -> TractResult<()>
+where
+    F: FnOnce(TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>>,
+{
+    if nbio.0 > 0 {
+        check_not_null!(inputs);
+    }
+    if nbio.1 > 0 {
+        check_not_null!(outputs);
+    }
+    let values = (0..nbio.0).map(|ix| (*inputs.add(ix)).to_tensor()).collect::<TractResult<_>>()?;
+    let results = run(values)?;
+    for (ix, result) in results.iter().enumerate() {
+        (*outputs.add(ix)).copy_from(result)?;
+    }
+    Ok(())
+}
+
+unsafe fn string_out(value: String, out: *mut *mut c_char) -> TractResult<()> {
+    check_not_null!(out);
+    *out = CString::new(value)?.into_raw();
+    Ok(())
+}
+
+// INFERENCE MODEL
+
+pub struct TractInferenceModel(InferenceModel);
+
+unsafe fn load_inference_model<P: std::fmt::Debug, F: Framework<P, InferenceModel>>(
+    framework: F,
+    path: *const c_char,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        *model = std::ptr::null_mut();
+        let path = c_str(path)?;
+        let m = framework.model_for_path(path).with_context(|| format!("Loading {}", path))?;
+        *model = Box::into_raw(Box::new(TractInferenceModel(m)));
+        Ok(())
+    })
+}
+
+/// Load an ONNX model from a file.
+#[no_mangle]
+pub unsafe extern "C" fn tract_onnx_model_for_path(
+    path: *const c_char,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    load_inference_model(tract_onnx::onnx(), path, model)
+}
+
+/// Load a TensorFlow frozen graph from a file.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tensorflow_model_for_path(
+    path: *const c_char,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    load_inference_model(tract_tensorflow::tensorflow(), path, model)
+}
+
+/// Set the fact of an input of the model.
+///
+/// `spec` uses the command line format: comma-separated dimensions, optionally followed by the
+/// datum type, like `1,S,40,f32`. Names like `S` or `batch` are symbolic dimensions, and `S`
+/// is the streaming one.
+#[no_mangle]
+pub unsafe extern "C" fn tract_inference_model_set_input_fact(
+    model: *mut TractInferenceModel,
+    input: usize,
+    spec: *const c_char,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        let spec = c_str(spec)?;
+        let fact = InferenceFact::parse(&(*model).0.symbol_table, spec)?;
+        (*model).0.set_input_fact(input, fact)
+    })
+}
+
+/// Convert the inference model to a typed model and declutter it.
+///
+/// The inference model is consumed, `*model` is set to null.
+#[no_mangle]
+pub unsafe extern "C" fn tract_inference_model_into_typed(
+    model: *mut *mut TractInferenceModel,
+    typed: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(typed);
+        let m = take(model)?.0.into_typed()?.declutter()?;
+        *typed = Box::into_raw(Box::new(TractModel(m)));
+        Ok(())
+    })
+}
+
+/// Convert the inference model to an optimized typed model, ready to run.
+///
+/// The inference model is consumed, `*model` is set to null.
+#[no_mangle]
+pub unsafe extern "C" fn tract_inference_model_into_optimized(
+    model: *mut *mut TractInferenceModel,
+    optimized: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(optimized);
+        let m = take(model)?.0.into_optimized()?;
+        *optimized = Box::into_raw(Box::new(TractModel(m)));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_inference_model_destroy(
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    destroy(model)
+}
+
+// TYPED MODEL
+
+pub struct TractModel(TypedModel);
+
+/// Load a NNEF model from a directory or a tar archive.
+///
+/// tract-core, pulse and onnx extensions are enabled.
+#[no_mangle]
+pub unsafe extern "C" fn tract_nnef_model_for_path(
+    path: *const c_char,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    use tract_onnx::WithOnnx;
+    use tract_pulse::WithPulse;
+    wrap(|| {
+        check_not_null!(model);
+        *model = std::ptr::null_mut();
+        let path = c_str(path)?;
+        let m = tract_nnef::nnef()
+            .with_tract_core()
+            .with_onnx()
+            .with_pulse()
+            .model_for_path(path)
+            .with_context(|| format!("Loading {}", path))?;
+        *model = Box::into_raw(Box::new(TractModel(m)));
+        Ok(())
+    })
+}
+
+/// Number of inputs and outputs of the model.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_nbio(
+    model: *const TractModel,
+    inputs: *mut usize,
+    outputs: *mut usize,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        if !inputs.is_null() {
+            *inputs = (*model).0.inputs.len();
+        }
+        if !outputs.is_null() {
+            *outputs = (*model).0.outputs.len();
+        }
+        Ok(())
+    })
+}
+
+/// Describe the fact of an input, like `1,S,40,F32`.
+///
+/// The string must be released with `tract_free_cstring`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_input_fact(
+    model: *const TractModel,
+    input: usize,
+    fact: *mut *mut c_char,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        string_out(format!("{:?}", (*model).0.input_fact(input)?), fact)
+    })
+}
+
+/// Describe the fact of an output, like `1,S,40,F32`.
+///
+/// The string must be released with `tract_free_cstring`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_output_fact(
+    model: *const TractModel,
+    output: usize,
+    fact: *mut *mut c_char,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        string_out(format!("{:?}", (*model).0.output_fact(output)?), fact)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_declutter(model: *mut TractModel) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        (*model).0 = (*model).0.declutter()?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_optimize(model: *mut TractModel) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        (*model).0 = (*model).0.clone().optimize()?;
+        Ok(())
+    })
+}
+
+/// Pulsify the model: its streaming input is consumed `pulse` frames at a time.
+///
+/// The model is replaced by its pulsed (and decluttered) version. Use
+/// `tract_model_output_delay` to find how many frames the outputs lag behind.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_pulse(model: *mut TractModel, pulse: usize) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model);
+        let pulsed = PulsedModel::new(&(*model).0, pulse)?;
+        (*model).0 = pulsed.into_typed()?.declutter()?;
+        Ok(())
+    })
+}
+
+/// Delay, in frames, of an output of a pulsed model.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_output_delay(
+    model: *const TractModel,
+    output: usize,
+    delay: *mut usize,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(model, delay);
+        let delays =
+            (*model).0.properties.get("pulse.delay").context("Model has not been pulsified")?;
+        let delays = delays.as_slice::<i64>()?;
+        *delay = *delays.get(output).with_context(|| format!("No output #{}", output))? as usize;
+        Ok(())
+    })
+}
+
+/// Make the model runnable.
+///
+/// The model is consumed, `*model` is set to null.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_into_runnable(
+    model: *mut *mut TractModel,
+    runnable: *mut *mut TractRunnable,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(runnable);
+        let plan = take(model)?.0.into_runnable()?;
+        *runnable = Box::into_raw(Box::new(TractRunnable(Arc::new(plan))));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_destroy(model: *mut *mut TractModel) -> TRACT_RESULT {
+    destroy(model)
+}
+
+// RUNNABLE MODEL
+
+pub struct TractRunnable(Arc<TypedRunnableModel<TypedModel>>);
+
+/// Run the model once.
+///
+/// `inputs` and `outputs` point to as many tensors as the model has inputs and outputs. Outputs
+/// buffers are allocated by the caller, and their datum type and shape must match the model
+/// results.
+#[no_mangle]
+pub unsafe extern "C" fn tract_runnable_run(
+    runnable: *const TractRunnable,
+    inputs: *const TractTensor,
+    outputs: *mut TractTensor,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(runnable);
+        let plan = &(*runnable).0;
+        let nbio = (plan.model().inputs.len(), plan.model().outputs.len());
+        run_with(nbio, inputs, outputs, |values| plan.run(values))
+    })
+}
+
+/// Create a state to run the model several times, keeping internal state between calls, like
+/// a pulsed model does.
+#[no_mangle]
+pub unsafe extern "C" fn tract_runnable_spawn_state(
+    runnable: *const TractRunnable,
+    state: *mut *mut TractState,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(runnable, state);
+        *state = std::ptr::null_mut();
+        let s = TypedSimpleState::new((*runnable).0.clone())?;
+        *state = Box::into_raw(Box::new(TractState(s)));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_runnable_destroy(runnable: *mut *mut TractRunnable) -> TRACT_RESULT {
+    destroy(runnable)
+}
+
+// STATE
+
+pub struct TractState(TypedSimpleState<TypedModel, Arc<TypedRunnableModel<TypedModel>>>);
+
+/// Run one step of a state. Same conventions as `tract_runnable_run`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_run(
+    state: *mut TractState,
+    inputs: *const TractTensor,
+    outputs: *mut TractTensor,
+) -> TRACT_RESULT {
+    wrap(|| {
+        check_not_null!(state);
+        let state = &mut (*state).0;
+        let model = state.model();
+        let nbio = (model.inputs.len(), model.outputs.len());
+        run_with(nbio, inputs, outputs, |values| state.run(values))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_destroy(state: *mut *mut TractState) -> TRACT_RESULT {
+    destroy(state)
+}
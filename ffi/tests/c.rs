@@ -0,0 +1,39 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn lib_dir() -> PathBuf {
+    // tests run from target/<profile>/deps, next to the freshly built cdylib
+    let exe = std::env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn c_test() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = lib_dir();
+    let exe = lib_dir.join("tract-ffi-c-test");
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .arg(manifest.join("tests/c/test.c"))
+        .arg("-I")
+        .arg(manifest)
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-ltract_ffi")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("Failed to run the C compiler");
+    assert!(status.success());
+    // cargo puts target/<profile> in the library path, where the cdylib may be stale
+    let status = Command::new(&exe)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .arg(manifest.join("tests/relu.nnef"))
+        .arg(manifest.join("../onnx/test_cases/qtdnn_10x5_101_i32_biases/model.onnx"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,130 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "tract.h"
+
+#define check(call)                                                            \
+    {                                                                          \
+        TRACT_RESULT result = call;                                            \
+        if (result == TRACT_RESULT_KO) {                                       \
+            fprintf(stderr, "Error calling tract: %s\n",                       \
+                    tract_get_last_error());                                   \
+            return 1;                                                          \
+        }                                                                      \
+    }
+
+static int errors() {
+    TractInferenceModel *model = NULL;
+    assert(tract_onnx_model_for_path("does-not-exist.onnx", &model) ==
+           TRACT_RESULT_KO);
+    assert(model == NULL);
+    const char *error = tract_get_last_error();
+    assert(error != NULL);
+    assert(strstr(error, "does-not-exist.onnx") != NULL);
+    return 0;
+}
+
+static int nnef_relu(const char *path) {
+    TractModel *model = NULL;
+    check(tract_nnef_model_for_path(path, &model));
+    check(tract_model_optimize(model));
+    TractRunnable *runnable = NULL;
+    check(tract_model_into_runnable(&model, &runnable));
+    assert(model == NULL);
+
+    size_t shape[] = {1, 3};
+    float input_data[] = {-1.0, 0.0, 2.0};
+    float output_data[3];
+    TractTensor input = {TRACT_DATUM_TYPE_F32, 2, shape, input_data};
+    TractTensor output = {TRACT_DATUM_TYPE_F32, 2, shape, output_data};
+    check(tract_runnable_run(runnable, &input, &output));
+    assert(output_data[0] == 0.0);
+    assert(output_data[1] == 0.0);
+    assert(output_data[2] == 2.0);
+
+    size_t wrong_shape[] = {3};
+    output.rank = 1;
+    output.shape = wrong_shape;
+    assert(tract_runnable_run(runnable, &input, &output) == TRACT_RESULT_KO);
+
+    input.datum_type = 0xff;
+    assert(tract_runnable_run(runnable, &input, &output) == TRACT_RESULT_KO);
+    assert(strstr(tract_get_last_error(), "Invalid datum type") != NULL);
+
+    check(tract_runnable_destroy(&runnable));
+    assert(runnable == NULL);
+    return 0;
+}
+
+#define FRAMES 12
+#define PULSE 4
+
+/* a tdnn with one frame of left and right context: 10 features in, 5 out */
+static int onnx_pulse(const char *path) {
+    float input_data[FRAMES * 10];
+    for (int i = 0; i < FRAMES * 10; i++) {
+        input_data[i] = (float)((i * 7) % 13) / 13.0 - 0.5;
+    }
+
+    TractInferenceModel *inference = NULL;
+    check(tract_onnx_model_for_path(path, &inference));
+    check(tract_inference_model_set_input_fact(inference, 0, "12,10,f32"));
+    TractModel *model = NULL;
+    check(tract_inference_model_into_optimized(&inference, &model));
+    assert(inference == NULL);
+    char *fact = NULL;
+    check(tract_model_output_fact(model, 0, &fact));
+    assert(strcmp(fact, "10,5,F32") == 0);
+    tract_free_cstring(fact);
+    TractRunnable *runnable = NULL;
+    check(tract_model_into_runnable(&model, &runnable));
+    float expected[(FRAMES - 2) * 5];
+    size_t input_shape[] = {FRAMES, 10};
+    size_t expected_shape[] = {FRAMES - 2, 5};
+    TractTensor input = {TRACT_DATUM_TYPE_F32, 2, input_shape, input_data};
+    TractTensor output = {TRACT_DATUM_TYPE_F32, 2, expected_shape, expected};
+    check(tract_runnable_run(runnable, &input, &output));
+    check(tract_runnable_destroy(&runnable));
+
+    check(tract_onnx_model_for_path(path, &inference));
+    check(tract_inference_model_set_input_fact(inference, 0, "S,10,f32"));
+    check(tract_inference_model_into_typed(&inference, &model));
+    check(tract_model_pulse(model, PULSE));
+    size_t delay;
+    check(tract_model_output_delay(model, 0, &delay));
+    assert(delay == 2);
+    check(tract_model_optimize(model));
+    size_t inputs, outputs;
+    check(tract_model_nbio(model, &inputs, &outputs));
+    assert(inputs == 1 && outputs == 1);
+    check(tract_model_into_runnable(&model, &runnable));
+    TractState *state = NULL;
+    check(tract_runnable_spawn_state(runnable, &state));
+
+    float pulsed[FRAMES * 5];
+    size_t chunk_input_shape[] = {PULSE, 10};
+    size_t chunk_output_shape[] = {PULSE, 5};
+    for (int i = 0; i < FRAMES / PULSE; i++) {
+        TractTensor input = {TRACT_DATUM_TYPE_F32, 2, chunk_input_shape,
+                             input_data + i * PULSE * 10};
+        TractTensor output = {TRACT_DATUM_TYPE_F32, 2, chunk_output_shape,
+                              pulsed + i * PULSE * 5};
+        check(tract_state_run(state, &input, &output));
+    }
+    for (int i = 0; i < (FRAMES - 2) * 5; i++) {
+        assert(pulsed[i + delay * 5] == expected[i]);
+    }
+
+    check(tract_state_destroy(&state));
+    check(tract_runnable_destroy(&runnable));
+    return 0;
+}
+
+int main(int argc, char **argv) {
+    assert(argc == 3);
+    if (errors() || nnef_relu(argv[1]) || onnx_pulse(argv[2])) {
+        return 1;
+    }
+    return 0;
+}
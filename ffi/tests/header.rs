@@ -0,0 +1,18 @@
+use std::fs;
+use std::path::Path;
+
+// tract.h is checked in for C users: regenerate it with `cbindgen -o tract.h` from ffi/
+#[test]
+fn header_is_up_to_date() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(manifest.join("cbindgen.toml")).unwrap();
+    let mut generated = vec![];
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest.join("src/lib.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+    let current = fs::read(manifest.join("tract.h")).unwrap();
+    assert!(generated == current, "ffi/tract.h is out of date, regenerate it with cbindgen");
+}
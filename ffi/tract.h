@@ -0,0 +1,218 @@
+#ifndef TRACT_H
+#define TRACT_H
+
+/* Generated by cbindgen from ffi/src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum TRACT_RESULT {
+  TRACT_RESULT_OK = 0,
+  TRACT_RESULT_KO = 1,
+} TRACT_RESULT;
+
+typedef struct TractInferenceModel TractInferenceModel;
+
+typedef struct TractModel TractModel;
+
+typedef struct TractRunnable TractRunnable;
+
+typedef struct TractState TractState;
+
+/**
+ * Element type of a TractTensor, one of the TRACT_DATUM_TYPE_* values.
+ *
+ * It is a plain integer rather than a C enum, so that any value coming from C is
+ * defined behaviour: unknown values are reported as errors.
+ */
+typedef uint32_t TractDatumType;
+
+/**
+ * A tensor view over memory owned by the caller.
+ *
+ * `shape` points to `rank` dimensions, `data` to the densely packed, row-major, elements.
+ */
+typedef struct TractTensor {
+  TractDatumType datum_type;
+  size_t rank;
+  const size_t *shape;
+  void *data;
+} TractTensor;
+
+#define TRACT_DATUM_TYPE_BOOL 1
+
+#define TRACT_DATUM_TYPE_U8 17
+
+#define TRACT_DATUM_TYPE_U16 18
+
+#define TRACT_DATUM_TYPE_U32 20
+
+#define TRACT_DATUM_TYPE_U64 24
+
+#define TRACT_DATUM_TYPE_I8 33
+
+#define TRACT_DATUM_TYPE_I16 34
+
+#define TRACT_DATUM_TYPE_I32 36
+
+#define TRACT_DATUM_TYPE_I64 40
+
+#define TRACT_DATUM_TYPE_F16 50
+
+#define TRACT_DATUM_TYPE_F32 52
+
+#define TRACT_DATUM_TYPE_F64 56
+
+#define TRACT_DATUM_TYPE_BF16 66
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Retrieve the last error that happened in this thread.
+ *
+ * The returned pointer is owned by the library and stays valid until the next failing call
+ * in the same thread. Returns null if no error occurred.
+ */
+const char *tract_get_last_error(void);
+
+/**
+ * Release a string allocated by the library.
+ */
+void tract_free_cstring(char *s);
+
+/**
+ * Load an ONNX model from a file.
+ */
+enum TRACT_RESULT tract_onnx_model_for_path(const char *path, struct TractInferenceModel **model);
+
+/**
+ * Load a TensorFlow frozen graph from a file.
+ */
+enum TRACT_RESULT tract_tensorflow_model_for_path(const char *path,
+                                                  struct TractInferenceModel **model);
+
+/**
+ * Set the fact of an input of the model.
+ *
+ * `spec` uses the command line format: comma-separated dimensions, optionally followed by the
+ * datum type, like `1,S,40,f32`. Names like `S` or `batch` are symbolic dimensions, and `S`
+ * is the streaming one.
+ */
+enum TRACT_RESULT tract_inference_model_set_input_fact(struct TractInferenceModel *model,
+                                                       size_t input,
+                                                       const char *spec);
+
+/**
+ * Convert the inference model to a typed model and declutter it.
+ *
+ * The inference model is consumed, `*model` is set to null.
+ */
+enum TRACT_RESULT tract_inference_model_into_typed(struct TractInferenceModel **model,
+                                                   struct TractModel **typed);
+
+/**
+ * Convert the inference model to an optimized typed model, ready to run.
+ *
+ * The inference model is consumed, `*model` is set to null.
+ */
+enum TRACT_RESULT tract_inference_model_into_optimized(struct TractInferenceModel **model,
+                                                       struct TractModel **optimized);
+
+enum TRACT_RESULT tract_inference_model_destroy(struct TractInferenceModel **model);
+
+/**
+ * Load a NNEF model from a directory or a tar archive.
+ *
+ * tract-core, pulse and onnx extensions are enabled.
+ */
+enum TRACT_RESULT tract_nnef_model_for_path(const char *path, struct TractModel **model);
+
+/**
+ * Number of inputs and outputs of the model.
+ */
+enum TRACT_RESULT tract_model_nbio(const struct TractModel *model, size_t *inputs, size_t *outputs);
+
+/**
+ * Describe the fact of an input, like `1,S,40,F32`.
+ *
+ * The string must be released with `tract_free_cstring`.
+ */
+enum TRACT_RESULT tract_model_input_fact(const struct TractModel *model, size_t input, char **fact);
+
+/**
+ * Describe the fact of an output, like `1,S,40,F32`.
+ *
+ * The string must be released with `tract_free_cstring`.
+ */
+enum TRACT_RESULT tract_model_output_fact(const struct TractModel *model,
+                                          size_t output,
+                                          char **fact);
+
+enum TRACT_RESULT tract_model_declutter(struct TractModel *model);
+
+enum TRACT_RESULT tract_model_optimize(struct TractModel *model);
+
+/**
+ * Pulsify the model: its streaming input is consumed `pulse` frames at a time.
+ *
+ * The model is replaced by its pulsed (and decluttered) version. Use
+ * `tract_model_output_delay` to find how many frames the outputs lag behind.
+ */
+enum TRACT_RESULT tract_model_pulse(struct TractModel *model, size_t pulse);
+
+/**
+ * Delay, in frames, of an output of a pulsed model.
+ */
+enum TRACT_RESULT tract_model_output_delay(const struct TractModel *model,
+                                           size_t output,
+                                           size_t *delay);
+
+/**
+ * Make the model runnable.
+ *
+ * The model is consumed, `*model` is set to null.
+ */
+enum TRACT_RESULT tract_model_into_runnable(struct TractModel **model,
+                                            struct TractRunnable **runnable);
+
+enum TRACT_RESULT tract_model_destroy(struct TractModel **model);
+
+/**
+ * Run the model once.
+ *
+ * `inputs` and `outputs` point to as many tensors as the model has inputs and outputs. Outputs
+ * buffers are allocated by the caller, and their datum type and shape must match the model
+ * results.
+ */
+enum TRACT_RESULT tract_runnable_run(const struct TractRunnable *runnable,
+                                     const struct TractTensor *inputs,
+                                     struct TractTensor *outputs);
+
+/**
+ * Create a state to run the model several times, keeping internal state between calls, like
+ * a pulsed model does.
+ */
+enum TRACT_RESULT tract_runnable_spawn_state(const struct TractRunnable *runnable,
+                                             struct TractState **state);
+
+enum TRACT_RESULT tract_runnable_destroy(struct TractRunnable **runnable);
+
+/**
+ * Run one step of a state. Same conventions as `tract_runnable_run`.
+ */
+enum TRACT_RESULT tract_state_run(struct TractState *state,
+                                  const struct TractTensor *inputs,
+                                  struct TractTensor *outputs);
+
+enum TRACT_RESULT tract_state_destroy(struct TractState **state);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* TRACT_H */
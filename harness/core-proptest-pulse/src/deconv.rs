@@ -0,0 +1,109 @@
+use proptest::proptest;
+use proptest::test_runner::TestCaseResult;
+use tract_hir::internal::*;
+use tract_hir::ops::cnn;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+struct DeconvProblem {
+    input: Array3<f32>,
+    pulse: usize,
+    stride: usize,
+    dilation: usize,
+    ker: Array3<f32>,
+    padding: cnn::PaddingSpec,
+    adjustment: usize,
+}
+
+impl Arbitrary for DeconvProblem {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> BoxedStrategy<Self> {
+        (
+            1usize..4,
+            1usize..3,
+            vec(1usize..4),
+            prop_oneof![
+                Just(cnn::PaddingSpec::Valid),
+                Just(cnn::PaddingSpec::SameUpper),
+                Just(cnn::PaddingSpec::Explicit(tvec!(1), tvec!(0), false))
+            ],
+            1usize..3,
+            vec(1usize..12),
+        )
+            .prop_flat_map(|(stride, dilation, ker, padding, pulse, input)| {
+                (Just((stride, dilation, ker, padding, pulse, input)), 0..stride)
+            })
+            .prop_map(|((stride, dilation, ker, padding, pulse, input), adjustment)| {
+                let input = Array3::from_shape_vec((1, 1, input.len()), input).unwrap(); // NCHW
+                let ker = Array3::from_shape_vec((1, 1, ker.len()), ker).unwrap();
+                DeconvProblem { input, pulse, stride, dilation, ker, padding, adjustment }
+            })
+            .prop_filter("padding larger than output", |pb| {
+                pb.padding != cnn::PaddingSpec::Explicit(tvec!(1), tvec!(0), false)
+                    || pb.ker.len() > 1
+                    || pb.stride > 1
+                    || pb.input.len() > 1
+            })
+            .boxed()
+    }
+}
+
+impl DeconvProblem {
+    pub fn run(&self) -> TestCaseResult {
+        let mut model = InferenceModel::default();
+        let s = stream_dim(&model.symbol_table);
+        let input = model
+            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(1, 1, s)))
+            .unwrap();
+        let kernel = model.add_const("kernel", self.ker.clone()).unwrap();
+        let deconv = cnn::Deconv {
+            padding: self.padding.clone(),
+            strides: Some(tvec!(self.stride)),
+            dilations: Some(tvec!(self.dilation)),
+            adjustments: Some(tvec!(self.adjustment)),
+            group: 1,
+            ..cnn::Deconv::default()
+        };
+        model.wire_node("deconv", expand(deconv), &[input, kernel]).unwrap();
+        model.auto_outputs().unwrap();
+        proptest_regular_against_pulse(model, self.pulse as _, self.input.clone().into_dyn(), 2)
+    }
+}
+
+proptest! {
+    #[test]
+    fn proptest(pb in DeconvProblem::arbitrary()) { pb.run().unwrap() }
+}
+
+#[test]
+fn overlapping_kernel() {
+    DeconvProblem {
+        input: arr3(&[[[1f32, 2.0, 3.0, 4.0, 5.0]]]),
+        pulse: 2,
+        stride: 2,
+        dilation: 1,
+        ker: arr3(&[[[1f32, 2.0, 3.0]]]),
+        padding: cnn::PaddingSpec::Explicit(tvec!(1), tvec!(0), false),
+        adjustment: 0,
+    }
+    .run()
+    .unwrap()
+}
+
+#[test]
+fn stride_larger_than_kernel() {
+    DeconvProblem {
+        input: arr3(&[[[1f32, 2.0, 3.0]]]),
+        pulse: 1,
+        stride: 3,
+        dilation: 1,
+        ker: arr3(&[[[1f32, 2.0]]]),
+        padding: cnn::PaddingSpec::Valid,
+        adjustment: 1,
+    }
+    .run()
+    .unwrap()
+}
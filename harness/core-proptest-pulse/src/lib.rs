@@ -12,6 +12,7 @@ use tract_ndarray::*;
 use tract_pulse::internal::*;
 
 mod conv_plus_conv;
+mod deconv;
 mod delay_plus_pool;
 mod pad_plus_conv;
 
@@ -28,7 +29,7 @@ fn proptest_regular_against_pulse(
 ) -> TestCaseResult {
     setup_test_logger();
     let mut ref_model = model.clone();
-    let s = stream_symbol();
+    let s = stream_symbol(&model.symbol_table);
     debug!("Run reference");
     ref_model
         .set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), input_array.shape()))
@@ -71,7 +72,7 @@ fn proptest_regular_against_pulse(
                 &[chunk.view(), ArrayD::from_elem(filler_shape, std::f32::NAN).view()],
             )
             .unwrap();
-            state.session_state.resolved_symbols[s] = Some(written as i64);
+            state.session_state.resolved_symbols[s.clone()] = Some(written as i64);
             output_len = output_fact
                 .dim
                 .eval(&state.session_state.resolved_symbols)
@@ -115,8 +116,9 @@ proptest! {
         use tract_hir::ops::array::Slice;
         let full_len = input_len + begin + end;
         let mut model = InferenceModel::default();
+        let s = stream_dim(&model.symbol_table);
         let a = model
-            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
             .unwrap();
         let slice = model.wire_node("slice", Slice::new(0, begin as usize, (input_len + begin) as usize), &[a]).unwrap();
         model.set_output_outlets(&slice).unwrap();
@@ -129,8 +131,9 @@ proptest! {
     fn proptest_pad(pulse in 1i32..3, input_len in 0i32..10, begin in 0i32..3, end in 0i32..3) {
         use tract_hir::ops::array::{ Pad, PadMode };
         let mut model = InferenceModel::default();
+        let s = stream_dim(&model.symbol_table);
         let a = model
-            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
             .unwrap();
         let pad = model.wire_node("pad",Pad::new(vec![(begin as _, end as _)],
             PadMode::Constant(Arc::new(Tensor::from(-1f32)))), &[a]).unwrap();
@@ -151,9 +154,10 @@ fn test_simple_conv() {
     use tract_hir::ops::cnn::*;
 
     let mut model = InferenceModel::default();
+    let s = stream_dim(&model.symbol_table);
     let ker = model.add_const("kernel", tensor3(&[[[0.5f32, 1.0, -0.1]]])).unwrap();
     let a = model
-        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(1, 1, S))) // NCT
+        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(1, 1, s))) // NCT
         .unwrap();
 
     model.wire_node("conv", expand(Conv::default()), &[a, ker]).unwrap();
@@ -167,8 +171,9 @@ fn test_simple_conv() {
 fn test_crop_after_1() {
     use tract_hir::ops::array::Slice;
     let mut model = InferenceModel::default();
+    let s = stream_dim(&model.symbol_table);
     let a = model
-        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
         .unwrap();
     model.wire_node("slice", Slice::new(0, 0, 0), &[a]).unwrap();
     model.auto_outputs().unwrap();
@@ -181,8 +186,9 @@ fn test_crop_after_1() {
 fn test_pad_after_1() {
     use tract_hir::ops::array::{Pad, PadMode};
     let mut model = InferenceModel::default();
+    let s = stream_dim(&model.symbol_table);
     let a = model
-        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
         .unwrap();
     model
         .wire_node(
@@ -201,8 +207,9 @@ fn test_pad_after_1() {
 fn test_pad_before_1() {
     use tract_hir::ops::array::{Pad, PadMode};
     let mut model = InferenceModel::default();
+    let s = stream_dim(&model.symbol_table);
     let a = model
-        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
         .unwrap();
     model
         .wire_node(
@@ -221,8 +228,9 @@ fn test_pad_before_1() {
 fn test_pad_before_2() {
     use tract_hir::ops::array::{Pad, PadMode};
     let mut model = InferenceModel::default();
+    let s = stream_dim(&model.symbol_table);
     let a = model
-        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S)))
+        .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s)))
         .unwrap();
     model
         .wire_node(
@@ -137,6 +137,71 @@ impl Fact for InferenceFact {
     }
 }
 
+impl InferenceFact {
+    /// Parses a comma-separated list of dimensions, optionally followed by a
+    /// datum type, like `1,3,224,224,f32` or `S,40,f32`.
+    ///
+    /// `_` stands for an unknown dimension, and a dimension can be a named
+    /// symbol with an integer factor, like `2S` or `batch`. Symbols are looked up
+    /// (or created) in `symbol_table`, which should be the model one.
+    pub fn parse(symbol_table: &SymbolTable, spec: &str) -> TractResult<InferenceFact> {
+        if spec.len() == 0 {
+            return Ok(InferenceFact::default());
+        }
+        let splits = spec.split(",").collect::<Vec<_>>();
+        let last = splits.last().unwrap();
+        let (datum_type, shape) = if let Ok(dt) = parse_datum_type(last) {
+            (Some(dt), &splits[0..splits.len() - 1])
+        } else {
+            (None, &*splits)
+        };
+        let shape = ShapeFactoid::closed(
+            shape
+                .iter()
+                .map(|&s| {
+                    Ok(if s == "_" {
+                        GenericFactoid::Any
+                    } else {
+                        GenericFactoid::Only(parse_dim(symbol_table, s)?)
+                    })
+                })
+                .collect::<TractResult<TVec<DimFact>>>()?,
+        );
+        if let Some(dt) = datum_type {
+            Ok(InferenceFact::dt_shape(dt, shape))
+        } else {
+            Ok(InferenceFact::shape(shape))
+        }
+    }
+}
+
+/// Parses a datum type name (`f32`, `I64`, `bool`...), ignoring case.
+pub fn parse_datum_type(dt: &str) -> TractResult<DatumType> {
+    dt.to_lowercase().parse::<DatumType>()
+}
+
+/// Parses a dimension: an integer, a symbol name, or an integer factor
+/// followed by a symbol name (`12`, `S`, `2batch_size`).
+pub fn parse_dim(symbol_table: &SymbolTable, i: &str) -> TractResult<TDim> {
+    if i.len() == 0 {
+        bail!("Can not parse empty string as Dim")
+    }
+    let number_len = i.chars().take_while(|c| c.is_ascii_digit()).count();
+    let number: i64 = if number_len > 0 { i[..number_len].parse()? } else { 1 };
+    let name = &i[number_len..];
+    if name.len() == 0 {
+        return Ok(number.to_dim());
+    }
+    let mut chars = name.chars();
+    let first = chars.next().unwrap();
+    if !(first.is_ascii_alphabetic() || first == '_')
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        bail!("Can not parse {} as Dim", i)
+    }
+    Ok(symbol_table.sym(name).to_dim() * number)
+}
+
 impl<'a> TryFrom<&'a InferenceFact> for TypedFact {
     type Error = TractError;
     fn try_from(fact: &InferenceFact) -> TractResult<TypedFact> {
@@ -192,3 +257,62 @@ impl<'a> From<&'a Tensor> for InferenceFact {
         InferenceFact::from(t.clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_spec() {
+        let table = SymbolTable::default();
+        let s = table.sym("S");
+        assert_eq!(
+            InferenceFact::parse(&table, "1,S,2S,f32").unwrap(),
+            InferenceFact::dt_shape(
+                f32::datum_type(),
+                shapefactoid!(1, (s.to_dim()), (s.to_dim() * 2))
+            )
+        );
+        assert_eq!(
+            InferenceFact::parse(&table, "_,3").unwrap(),
+            InferenceFact::shape(shapefactoid!(_, 3))
+        );
+        assert_eq!(InferenceFact::parse(&table, "").unwrap(), InferenceFact::default());
+        assert!(InferenceFact::parse(&table, "1,S-1").is_err());
+        assert!(InferenceFact::parse(&table, "1,S.x").is_err());
+    }
+
+    #[test]
+    fn parse_datum_type_ignores_case() {
+        for name in &["f32", "F32", "bf16", "Bf16", "i64", "I64", "bool", "BOOL"] {
+            assert_eq!(
+                InferenceFact::parse(&SymbolTable::default(), &format!("2,{}", name))
+                    .unwrap()
+                    .datum_type
+                    .concretize(),
+                Some(parse_datum_type(name).unwrap())
+            );
+        }
+        assert_eq!(parse_datum_type("Tdim").unwrap(), DatumType::TDim);
+        assert!(parse_datum_type("f31").is_err());
+    }
+
+    #[test]
+    fn parse_named_symbols() {
+        let table = SymbolTable::default();
+        let batch = table.sym("batch_size");
+        let fact = InferenceFact::parse(&table, "batch_size,2seq_len,f32").unwrap();
+        let seq = table.get("seq_len").unwrap();
+        assert_eq!(
+            fact,
+            InferenceFact::dt_shape(
+                f32::datum_type(),
+                shapefactoid!((batch.to_dim()), (seq.to_dim() * 2))
+            )
+        );
+        assert_ne!(
+            InferenceFact::parse(&SymbolTable::default(), "batch_size").unwrap(),
+            InferenceFact::shape(shapefactoid!((batch.to_dim())))
+        );
+    }
+}
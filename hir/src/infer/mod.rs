@@ -12,7 +12,7 @@ mod model;
 mod ops;
 mod optim;
 
-pub use self::fact::InferenceFact;
+pub use self::fact::{parse_datum_type, parse_dim, InferenceFact};
 pub use self::factoid::*;
 pub use self::model::InferenceModelExt;
 pub use self::ops::InferenceOp;
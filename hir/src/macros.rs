@@ -54,9 +54,6 @@ macro_rules! dimfact {
     (_) => {
         $crate::infer::DimFact::default()
     };
-    (S) => {
-        $crate::infer::GenericFactoid::Only(tract_pulse::internal::stream_dim())
-    };
     ($arg:expr) => {
         $crate::infer::GenericFactoid::Only($arg.to_dim())
     };
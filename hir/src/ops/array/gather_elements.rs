@@ -0,0 +1,47 @@
+use crate::infer::*;
+use crate::internal::*;
+
+/// GatherElements with a possibly negative axis.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct GatherElements {
+    pub axis: i64,
+}
+
+impl_dyn_hash!(GatherElements);
+
+impl Expansion for GatherElements {
+    fn name(&self) -> Cow<str> {
+        "GatherElements".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&outputs[0].shape, &inputs[1].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(inputs[0])?.rank() as i64;
+        let axis = if self.axis < 0 { self.axis + rank } else { self.axis } as usize;
+        target.wire_node(
+            prefix,
+            tract_core::ops::array::GatherElements::new(axis),
+            &[inputs[0], inputs[1]],
+        )
+    }
+}
@@ -0,0 +1,26 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::array::GatherNd;
+
+impl InferenceRulesOp for GatherNd {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, data_shape, indices_shape| {
+            if indices_shape.last().map(|d| d.to_usize().is_ok()).unwrap_or(false) {
+                s.equals(&outputs[0].shape, self.compute_shape(&*data_shape, &*indices_shape)?)?;
+            }
+            Ok(())
+        })
+    }
+
+    as_op!();
+    to_typed!();
+}
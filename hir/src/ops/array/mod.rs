@@ -6,10 +6,14 @@ mod constant_of_shape;
 mod crop;
 mod flatten;
 mod gather;
+mod gather_elements;
+mod gather_nd;
 mod pad;
 pub mod permute_axes;
 mod reshape;
 mod rm_dims;
+mod scatter_elements;
+mod scatter_nd;
 mod shape;
 mod size;
 mod slice;
@@ -17,6 +21,7 @@ mod split;
 mod squeeze;
 mod strided_slice;
 mod tile;
+mod topk;
 
 pub use add_dims::AddDims;
 pub use broadcast::MultiBroadcastTo;
@@ -26,10 +31,14 @@ pub use constant_of_shape::ConstantOfShape;
 pub use crop::Crop;
 pub use flatten::Flatten;
 pub use gather::Gather;
+pub use gather_elements::GatherElements;
+pub use gather_nd::GatherNd;
 pub use pad::{Pad, PadMode};
 pub use permute_axes::PermuteAxes;
 pub use reshape::Reshape;
 pub use rm_dims::RmDims;
+pub use scatter_elements::ScatterElements;
+pub use scatter_nd::ScatterNd;
 pub use shape::Shape;
 pub use size::Size;
 pub use slice::Slice;
@@ -37,3 +46,5 @@ pub use split::Split;
 pub use squeeze::Squeeze;
 pub use strided_slice::StridedSlice;
 pub use tile::Tile;
+pub use topk::TopK;
+pub use tract_core::ops::array::ScatterReduction;
@@ -0,0 +1,40 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::array::TopK;
+
+impl InferenceRulesOp for TopK {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[0].rank, &outputs[1].rank)?;
+        s.equals(&outputs[0].shape, &outputs[1].shape)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            for ix in 0..rank as usize {
+                if ix != self.axis {
+                    s.equals(&inputs[0].shape[ix], &outputs[0].shape[ix])?;
+                }
+            }
+            Ok(())
+        })?;
+        s.given(&inputs[1].value, move |s, k| {
+            let k = k.cast_to_scalar::<i64>()?;
+            s.equals(&outputs[0].shape[self.axis], k.to_dim())
+        })
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    as_op!();
+    to_typed!();
+}
@@ -0,0 +1,207 @@
+use crate::infer::*;
+use crate::internal::*;
+
+use tract_core::ops::cnn::deconv::DeconvUnary;
+use tract_core::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+use tract_core::ops::nn::DataFormat;
+
+/// Transposed convolution, with a constant kernel and optional constant bias
+/// inputs.
+///
+/// If `output_shape` is set, it overrides the padding and adjustments
+/// computation, as in ONNX ConvTranspose.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Deconv {
+    pub data_format: DataFormat,
+    pub kernel_format: KernelFormat,
+    pub padding: PaddingSpec,
+    pub strides: Option<TVec<usize>>,
+    pub dilations: Option<TVec<usize>>,
+    pub adjustments: Option<TVec<usize>>,
+    pub output_shape: Option<TVec<usize>>,
+    pub group: usize,
+    pub bias_input: Option<usize>,
+}
+
+impl_dyn_hash!(Deconv);
+
+impl Deconv {
+    fn output_channels(&self, kernel_shape: &[usize]) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => kernel_shape[1] * self.group,
+            KernelFormat::HWIO => kernel_shape[kernel_shape.len() - 2] * self.group,
+        }
+    }
+
+    /// Core pool spec and adjustments, with output_shape converted to explicit
+    /// padding or extra adjustments.
+    pub fn pool_spec_and_adjustments<D: DimLike>(
+        &self,
+        input_shape: &[D],
+        kernel_shape: &[usize],
+    ) -> TractResult<(PoolSpec, TVec<usize>)> {
+        let input_shape = self.data_format.shape(input_shape)?;
+        let rank = input_shape.hw_rank();
+        let kernel_spatial_shape: TVec<usize> =
+            kernel_shape[self.kernel_format.h_axis()..][..rank].into();
+        let strides = self.strides.clone().unwrap_or_else(|| tvec!(1; rank));
+        let dilations = self.dilations.clone().unwrap_or_else(|| tvec!(1; rank));
+        let mut adjustments = self.adjustments.clone().unwrap_or_else(|| tvec!(0; rank));
+        let padding = if let Some(output_shape) = &self.output_shape {
+            let mut before = tvec!();
+            let mut after = tvec!();
+            for ax in 0..rank {
+                let input = input_shape.hw_dims()[ax].to_usize()?;
+                let full = PaddingSpec::Valid
+                    .compute_one_for_deconv(
+                        ax,
+                        &input,
+                        kernel_spatial_shape[ax],
+                        dilations[ax],
+                        strides[ax],
+                        adjustments[ax],
+                    )
+                    .output;
+                let total = full.saturating_sub(output_shape[ax]);
+                adjustments[ax] += output_shape[ax].saturating_sub(full);
+                if self.padding == PaddingSpec::SameUpper {
+                    before.push(total / 2);
+                    after.push(total - total / 2);
+                } else {
+                    before.push(total - total / 2);
+                    after.push(total / 2);
+                }
+            }
+            PaddingSpec::Explicit(before, after, false)
+        } else {
+            self.padding.clone()
+        };
+        let pool_spec = PoolSpec::new(
+            self.data_format,
+            kernel_spatial_shape,
+            padding,
+            Some(dilations),
+            Some(strides),
+            Some(self.output_channels(kernel_shape)),
+        );
+        Ok((pool_spec, adjustments))
+    }
+}
+
+impl Expansion for Deconv {
+    fn name(&self) -> Cow<str> {
+        "Deconv".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2 + self.bias_input.is_some() as usize)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &inputs[1].datum_type)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(
+            &inputs[0].rank,
+            inputs[1].rank.bex() + (self.data_format.has_n() as usize as i64 - 1),
+        )?;
+        s.equals(&outputs[0].rank, &inputs[0].rank)?;
+        if let Some(bias) = self.bias_input {
+            s.equals(&inputs[bias].rank, 1)?;
+        }
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, ishape, kshape| {
+            if let Some(kshape) =
+                kshape.iter().map(|d| d.to_usize().ok()).collect::<Option<TVec<_>>>()
+            {
+                let oshape = if let Some(spatial) = &self.output_shape {
+                    let x_shape = self.data_format.shape(&*ishape)?;
+                    self.data_format
+                        .from_n_c_hw(
+                            x_shape.n().cloned().unwrap_or_else(|| 1.to_dim()),
+                            self.output_channels(&*kshape).to_dim(),
+                            spatial.iter().map(|d| d.to_dim()).collect::<TVec<_>>(),
+                        )?
+                        .shape
+                } else {
+                    let (pool_spec, adjustments) =
+                        self.pool_spec_and_adjustments(&*ishape, &*kshape)?;
+                    tract_core::ops::cnn::deconv::output_shape(&pool_spec, &*ishape, &*adjustments)?
+                };
+                s.equals(&outputs[0].shape, oshape)?;
+                if let Some(bias) = self.bias_input {
+                    s.equals(&inputs[bias].shape[0], self.output_channels(&*kshape).to_dim())?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let kernel = if let Some(k) = &target.outlet_fact(inputs[1])?.konst {
+            k.clone()
+        } else {
+            bail!("Deconvolution kernel must be a constant")
+        };
+        let bias = if let Some(slot) = self.bias_input {
+            if let Some(b) = &target.outlet_fact(inputs[slot])?.konst {
+                Some(b.clone())
+            } else {
+                bail!("Deconvolution bias must be a constant")
+            }
+        } else {
+            None
+        };
+        let input_shape = target.outlet_fact(inputs[0])?.shape.to_tvec();
+        let (pool_spec, adjustments) =
+            self.pool_spec_and_adjustments(&*input_shape, kernel.shape())?;
+        let op =
+            DeconvUnary::new(pool_spec, self.kernel_format, kernel, bias, adjustments, self.group);
+        target.wire_node(prefix, op, &[inputs[0]])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn infer_output_shape() {
+        let mut op = expand(Deconv {
+            strides: Some(tvec!(3, 2)),
+            adjustments: Some(tvec!(1, 1)),
+            group: 1,
+            ..Deconv::default()
+        });
+        let ifact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 3));
+        let kfact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 3, 3));
+        let ofact = InferenceFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!()).unwrap();
+        assert_eq!(
+            facts.1,
+            tvec!(InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 10, 8)))
+        );
+    }
+
+    #[test]
+    fn output_shape_as_padding() {
+        let op = Deconv {
+            strides: Some(tvec!(3, 2)),
+            output_shape: Some(tvec!(10, 8)),
+            group: 1,
+            ..Deconv::default()
+        };
+        let (pool_spec, adjustments) =
+            op.pool_spec_and_adjustments(&[1usize, 1, 3, 3], &[1, 2, 3, 3]).unwrap();
+        assert_eq!(pool_spec.padding, PaddingSpec::Explicit(tvec!(0, 0), tvec!(0, 0), false));
+        assert_eq!(adjustments, tvec!(1, 1));
+    }
+}
@@ -131,32 +131,14 @@ impl Expansion for LayerSoftmax {
         target: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        use tract_core::ops::{math, nn};
         let input = inputs[0];
         let rank = target.outlet_fact(input)?.rank();
         let axis = if self.axis < 0 { rank as isize + self.axis } else { self.axis } as usize;
-        let reducing_axes = (axis..rank).collect::<TVec<usize>>();
-        let maxes = target.wire_node(
-            format!("{}.max", name),
-            nn::Reduce::new(reducing_axes.clone(), nn::Reducer::Max),
-            &[input],
-        )?[0];
-        let normed = target.wire_node(
-            format!("{}.normed", name),
-            math::sub::bin_typed(),
-            &[input, maxes],
-        )?[0];
-        let exp =
-            target.wire_node(format!("{}.exp", name), tract_core::ops::math::exp(), &[normed])?[0];
-        let sum = target.wire_node(
-            format!("{}.sum", name),
-            nn::Reduce::new(reducing_axes, nn::Reducer::Sum),
-            &[exp],
-        )?[0];
+        let axes = (axis..rank).collect::<TVec<usize>>();
         target.wire_node(
             format!("{}.softmax", name),
-            tract_core::ops::math::div::bin_typed(),
-            &[exp, sum],
+            tract_core::ops::nn::Softmax::new(axes),
+            &[input],
         )
     }
 }
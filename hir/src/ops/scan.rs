@@ -1,8 +1,11 @@
 use crate::infer::*;
 use crate::internal::*;
 
-pub use tract_core::ops::scan::Scan;
+mod loops;
+
+pub use self::loops::InferenceLoop;
 pub use tract_core::ops::scan::{InputMapping, OutputMapping, StateInitializer};
+pub use tract_core::ops::scan::{Loop, Scan};
 
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct InferenceScan {
@@ -95,7 +98,7 @@ impl InferenceScan {
         )?))
     }
 
-    fn unify_scanning_tensor_fact(
+    pub fn unify_scanning_tensor_fact(
         outer: &mut InferenceFact,
         inner: &mut InferenceFact,
         outer_scan_axis: usize,
@@ -0,0 +1,186 @@
+use crate::infer::*;
+use crate::internal::*;
+
+use super::{InferenceScan, InputMapping, OutputMapping, StateInitializer};
+
+/// Loop with an inference body, laid out as expected by
+/// `tract_core::ops::scan::Loop`.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct InferenceLoop {
+    pub body: InferenceModel,
+    pub trip_count_slot: Option<usize>,
+    pub cond_slot: Option<usize>,
+    pub input_mapping: Vec<InputMapping>,
+    pub output_mapping: Vec<OutputMapping<TDim>>,
+}
+
+impl_dyn_hash!(InferenceLoop);
+
+impl Op for InferenceLoop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut lines = vec![];
+        for (ix, im) in self.input_mapping.iter().enumerate() {
+            lines.push(format!("Model input  #{}: {:?}", ix + 2, im));
+        }
+        for (ix, om) in self.output_mapping.iter().enumerate() {
+            lines.push(format!("Model output #{}: {:?}", ix + 1, om));
+        }
+        Ok(lines)
+    }
+
+    op_hir!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for InferenceLoop {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.to_loop(None)?.eval(inputs)
+    }
+}
+
+impl InferenceLoop {
+    pub fn to_loop(&self, full_dim_hint: Option<TDim>) -> TractResult<tract_core::ops::scan::Loop> {
+        let output_mapping = self
+            .output_mapping
+            .iter()
+            .map(|om| OutputMapping {
+                full_dim_hint: om.full_slot.and(full_dim_hint.clone()),
+                ..om.clone()
+            })
+            .collect();
+        tract_core::ops::scan::Loop::new(
+            self.body.clone().into_typed()?,
+            self.trip_count_slot,
+            self.cond_slot,
+            self.input_mapping.clone(),
+            output_mapping,
+        )
+    }
+
+    fn unify_facts(
+        &mut self,
+        inputs: &mut [InferenceFact],
+        outputs: &mut [InferenceFact],
+    ) -> TractResult<bool> {
+        let mut changed = false;
+        let i64_fact = InferenceFact::dt(i64::datum_type());
+        let bool_fact = InferenceFact::dt(bool::datum_type());
+        // the loop feeds the body with scalar iteration number and condition
+        let iter_fact = InferenceFact::dt_shape(i64::datum_type(), &[0usize; 0]);
+        let cond_fact = InferenceFact::dt_shape(bool::datum_type(), &[0usize; 0]);
+        changed |= self.body.input_fact_mut(0)?.unify_with(&iter_fact)?;
+        changed |= self.body.input_fact_mut(1)?.unify_with(&cond_fact)?;
+        changed |= self.body.output_fact_mut(0)?.unify_with(&bool_fact)?;
+        if let Some(slot) = self.trip_count_slot {
+            changed |= inputs[slot].unify_with(&i64_fact)?;
+        }
+        if let Some(slot) = self.cond_slot {
+            changed |= inputs[slot].unify_with(&bool_fact)?;
+        }
+        let state_outputs: Vec<usize> = self
+            .output_mapping
+            .iter()
+            .enumerate()
+            .filter(|(_, om)| om.state)
+            .map(|(ix, _)| ix + 1)
+            .collect();
+        let mut state_ix = 0;
+        for (ix, im) in self.input_mapping.iter().enumerate() {
+            match im {
+                InputMapping::State { initializer: StateInitializer::FromInput(slot) } => {
+                    let mut facts = self.body.outlets_fact_mut(&[
+                        self.body.input_outlets()?[ix + 2],
+                        self.body.output_outlets()?[state_outputs[state_ix]],
+                    ])?;
+                    facts.push(&mut inputs[*slot]);
+                    changed |= Factoid::unify_all(
+                        &mut *facts.iter_mut().map(|f| &mut f.datum_type).collect::<TVec<_>>(),
+                    )?;
+                    changed |= Factoid::unify_all(
+                        &mut *facts.iter_mut().map(|f| &mut f.shape).collect::<TVec<_>>(),
+                    )
+                    .with_context(|| format!("Loop state #{} changes shape", state_ix))?;
+                    state_ix += 1;
+                }
+                InputMapping::Full { slot } => {
+                    // values stay out of the body: it would fold them into
+                    // constants clashing with its own sources
+                    let inner = self.body.input_fact_mut(ix + 2)?;
+                    let outer = &mut inputs[*slot];
+                    changed |= outer.datum_type.unify_with_mut(&mut inner.datum_type)?;
+                    changed |= outer.shape.unify_with_mut(&mut inner.shape)?;
+                }
+                _ => bail!("Unexpected loop input mapping {:?}", im),
+            }
+        }
+        for (ix, om) in self.output_mapping.iter().enumerate() {
+            let inner = self.body.output_fact_mut(ix + 1)?;
+            if let Some(slot) = om.last_value_slot {
+                changed |= outputs[slot].unify_with_mut(inner)?;
+            }
+            if let Some(slot) = om.full_slot {
+                changed |=
+                    InferenceScan::unify_scanning_tensor_fact(&mut outputs[slot], inner, om.axis)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+impl InferenceOp for InferenceLoop {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        loop {
+            let mut changed = self.unify_facts(&mut inputs, &mut outputs)?;
+            if self.body.analyse(false).context("analysing loop body")? {
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let static_trip_count = if let Some(slot) = self.trip_count_slot {
+            target.outlet_fact(inputs[slot])?.konst.is_some()
+        } else {
+            false
+        };
+        // the number of iterations is only known when running the loop
+        let full_dim_hint = if self.cond_slot.is_some() || !static_trip_count {
+            Some(target.symbol_table.new_with_prefix("L").into())
+        } else {
+            None
+        };
+        target.wire_node(&*node.name, self.to_loop(full_dim_hint)?, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.output_mapping.iter().filter(|om| !om.invisible()).count())
+    }
+
+    as_op!();
+}
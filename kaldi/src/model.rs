@@ -167,6 +167,21 @@ pub struct ParsingContext<'a> {
     pub proto_model: &'a KaldiProtoModel,
 }
 
+impl<'a> ParsingContext<'a> {
+    /// Component used by a component node.
+    pub fn component(&self, node: &str) -> TractResult<&'a Component> {
+        let line = self.proto_model.config_lines.nodes.iter().find(|l| l.0 == node);
+        if let Some((_, NodeLine::Component(line))) = line {
+            self.proto_model
+                .components
+                .get(&line.component)
+                .with_context(|| format!("Could not find component {}", line.component))
+        } else {
+            bail!("Could not find component node {}", node)
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct KaldiOpRegister(
     pub HashMap<String, fn(&ParsingContext, node: &str) -> TractResult<Box<dyn InferenceOp>>>,
@@ -198,7 +213,7 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
     fn model_for_proto_model(&self, proto_model: &KaldiProtoModel) -> TractResult<InferenceModel> {
         let ctx = ParsingContext { proto_model };
         let mut model = InferenceModel::default();
-        let s = tract_pulse::internal::stream_dim();
+        let s = tract_pulse::internal::stream_dim(&model.symbol_table);
         model.add_source(
             proto_model.config_lines.input_name.clone(),
             InferenceFact::dt_shape(
@@ -11,9 +11,11 @@ macro_rules! op_kaldi {
 }
 
 pub(crate) mod affine;
+mod elementwise_product;
 pub(crate) mod lstm_nonlin;
 pub(crate) mod memory;
 mod renorm;
+mod scale_and_offset;
 
 pub const AFFINE: &'static [&'static str] =
     &["FixedAffineComponent", "NaturalGradientAffineComponent"];
@@ -30,4 +32,19 @@ pub fn register_all_ops(reg: &mut KaldiOpRegister) {
     reg.insert("RectifiedLinearComponent", |_, _| {
         Ok(expand(tract_hir::ops::activations::Clip::new(Some(0.0), None)))
     });
+    reg.insert("TdnnComponent", affine::tdnn_component);
+    reg.insert("LinearComponent", affine::linear_component);
+    reg.insert("BatchNormComponent", scale_and_offset::batch_norm);
+    reg.insert("ScaleAndOffsetComponent", scale_and_offset::scale_and_offset);
+    reg.insert("ElementwiseProductComponent", elementwise_product::elementwise_product);
+    reg.insert("SigmoidComponent", |_, _| Ok(Box::new(tract_hir::ops::nn::sigmoid())));
+    reg.insert("TanhComponent", |_, _| Ok(Box::new(tract_hir::ops::math::tanh())));
+    reg.insert("SoftmaxComponent", |_, _| Ok(expand(tract_hir::ops::nn::LayerSoftmax::new(1))));
+    reg.insert("LogSoftmaxComponent", |_, _| {
+        Ok(expand(tract_hir::ops::nn::LayerLogSoftmax::new(1)))
+    });
+    // dropout is a no-op at inference time
+    for identity in &["GeneralDropoutComponent", "NoOpComponent"] {
+        reg.insert(identity, |_, _| Ok(Box::new(tract_hir::ops::identity::Identity::default())));
+    }
 }
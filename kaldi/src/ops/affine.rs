@@ -15,17 +15,32 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
     let kernel: &Tensor =
         component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
     let bias = component.attributes.get("BiasParams").context("missing attribute BiasParams")?;
-    // O•TI -> t -> TI•O -> T•I•O = HWIO
-    let o_ti = kernel.to_array_view::<f32>()?;
-    let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / bias.len(), bias.len());
-    let t_i_o =
-        tract_ndarray::Array::from_shape_vec(t_i_o_shape, o_ti.t().iter().cloned().collect())?;
-    Ok(expand(Affine {
-        kernel_len,
-        dilation,
-        linear_params: t_i_o.into_arc_tensor(),
-        bias_params: Arc::clone(bias),
-    }))
+    let offsets: Vec<isize> = (0..kernel_len).map(|t| (t * dilation) as isize).collect();
+    Ok(expand(Affine::from_params(&offsets, kernel, Arc::clone(bias))?))
+}
+
+pub fn tdnn_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let offsets =
+        component.attributes.get("TimeOffsets").context("missing attribute TimeOffsets")?;
+    let offsets: Vec<isize> =
+        offsets.cast_to::<i32>()?.as_slice::<i32>()?.iter().map(|&o| o as isize).collect();
+    let kernel: &Tensor =
+        component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
+    let output_dim = kernel.shape()[0];
+    // bias-less tdnn layers store an empty vector
+    let bias = match component.attributes.get("BiasParams") {
+        Some(bias) if bias.len() == output_dim => Arc::clone(bias),
+        _ => Tensor::zero::<f32>(&[output_dim])?.into_arc_tensor(),
+    };
+    Ok(expand(Affine::from_params(&offsets, kernel, bias)?))
+}
+
+pub fn linear_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let kernel: &Tensor = component.attributes.get("Params").context("missing attribute Params")?;
+    let bias = Tensor::zero::<f32>(&[kernel.shape()[0]])?.into_arc_tensor();
+    Ok(expand(Affine::from_params(&[0], kernel, bias)?))
 }
 
 #[derive(Clone, Debug, new, Hash)]
@@ -39,6 +54,46 @@ struct Affine {
 impl_dyn_hash!(Affine);
 
 impl Affine {
+    /// Build from a kaldi O•TI parameter matrix and the sorted time offsets
+    /// of its T input blocks. Offsets missing from the dilated kernel get
+    /// null weights.
+    fn from_params(offsets: &[isize], kernel: &Tensor, bias: Arc<Tensor>) -> TractResult<Affine> {
+        let mut dilation = 0;
+        for pair in offsets.windows(2) {
+            let mut a = (pair[1] - pair[0]) as usize;
+            let mut b = dilation;
+            while b != 0 {
+                let r = a % b;
+                a = b;
+                b = r;
+            }
+            dilation = a;
+        }
+        let dilation = dilation.max(1);
+        let kernel_len = (offsets[offsets.len() - 1] - offsets[0]) as usize / dilation + 1;
+        // O•TI -> t -> TI•O -> T•I•O = HWIO
+        let o_ti = kernel.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+        let input_dim = o_ti.shape()[1] / offsets.len();
+        let mut t_i_o = tract_ndarray::Array3::<f32>::zeros((kernel_len, input_dim, bias.len()));
+        for (ix, offset) in offsets.iter().enumerate() {
+            let t = (offset - offsets[0]) as usize / dilation;
+            t_i_o.index_axis_mut(tract_ndarray::Axis(0), t).assign(
+                &o_ti
+                    .slice_axis(
+                        tract_ndarray::Axis(1),
+                        (ix * input_dim..(ix + 1) * input_dim).into(),
+                    )
+                    .t(),
+            );
+        }
+        Ok(Affine {
+            kernel_len,
+            dilation,
+            linear_params: t_i_o.into_arc_tensor(),
+            bias_params: bias,
+        })
+    }
+
     fn as_conv(&self) -> tract_hir::ops::cnn::Conv {
         use tract_hir::ops::cnn::*;
         Conv::default()
@@ -108,3 +163,35 @@ impl Expansion for Affine {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tract_hir::prelude::*;
+
+    #[test]
+    fn tdnn_with_sparse_offsets() {
+        let slice = r#"<Nnet3>
+
+input-node name=input dim=1
+component-node name=tdnn input=input component=tdnn
+output-node name=output input=tdnn
+
+<NumComponents> 1
+<ComponentName> tdnn <TdnnComponent> <MaxChange> 0.75 <LearningRate> 0.001 <TimeOffsets> [ -2 0 1 ]
+<LinearParams>  [
+  1 10 100 ]
+<BiasParams>  [ 0.5 ]
+<OrthonormalConstraint> 0 <UseNaturalGradient> T <NumSamplesHistory> 2000 <AlphaInOut> 4 4 <RankInOut> 20 80 </TdnnComponent>
+</Nnet3>"#;
+        let model = crate::kaldi()
+            .model_for_read(&mut slice.as_bytes())
+            .unwrap()
+            .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(6, 1)))
+            .unwrap()
+            .into_optimized()
+            .unwrap();
+        let input = tensor2(&[[1.0f32], [2.0], [3.0], [4.0], [5.0], [6.0]]);
+        let output = model.into_runnable().unwrap().run(tvec!(input)).unwrap();
+        assert_eq!(*output[0], tensor2(&[[431.5f32], [542.5], [653.5]]));
+    }
+}
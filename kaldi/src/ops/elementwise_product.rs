@@ -0,0 +1,91 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+
+pub fn elementwise_product(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let input_dim = component
+        .attributes
+        .get("InputDim")
+        .context("missing attribute InputDim")?
+        .cast_to_scalar::<i64>()? as usize;
+    let output_dim = component
+        .attributes
+        .get("OutputDim")
+        .context("missing attribute OutputDim")?
+        .cast_to_scalar::<i64>()? as usize;
+    if output_dim == 0 || input_dim < output_dim || input_dim % output_dim != 0 {
+        bail!("ElementwiseProduct input dim {} is not a multiple of {}", input_dim, output_dim)
+    }
+    Ok(expand(ElementwiseProduct::new(input_dim, output_dim)))
+}
+
+/// Splits the features in `input_dim / output_dim` consecutive blocks and
+/// multiplies them together.
+#[derive(Clone, Debug, new, Hash)]
+struct ElementwiseProduct {
+    input_dim: usize,
+    output_dim: usize,
+}
+
+impl_dyn_hash!(ElementwiseProduct);
+
+impl Expansion for ElementwiseProduct {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ElementwiseProduct".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.input_dim.to_dim())?;
+        s.equals(&outputs[0].shape[1], self.output_dim.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let blocks = self.input_dim / self.output_dim;
+        let mut product = model.wire_node(
+            if blocks == 1 { prefix.to_string() } else { format!("{}.block-0", prefix) },
+            tract_hir::ops::array::Slice::new(1, 0, self.output_dim),
+            inputs,
+        )?;
+        for ix in 1..blocks {
+            let block = model.wire_node(
+                format!("{}.block-{}", prefix, ix),
+                tract_hir::ops::array::Slice::new(
+                    1,
+                    ix * self.output_dim,
+                    (ix + 1) * self.output_dim,
+                ),
+                inputs,
+            )?;
+            product = model.wire_node(
+                if ix + 1 == blocks {
+                    prefix.to_string()
+                } else {
+                    format!("{}.mul-{}", prefix, ix)
+                },
+                tract_hir::ops::math::mul::bin_typed(),
+                &[product[0], block[0]],
+            )?;
+        }
+        Ok(product)
+    }
+}
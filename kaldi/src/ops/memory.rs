@@ -31,7 +31,7 @@ impl EvalOp for Memory {
         _session: &mut SessionState,
         _id: usize,
     ) -> TractResult<Option<Box<dyn OpState>>> {
-        unimplemented!()
+        bail!("Memory {} must be incorporated (as a Scan or a shift) before running", self.name)
     }
 }
 
@@ -65,6 +65,17 @@ impl InferenceOp for Memory {
     as_op!();
 }
 
+/// A dimension the scan body needs to be a known integer.
+fn concrete_dim(fact: &InferenceFact, axis: usize, node: &str) -> TractResult<usize> {
+    let dim = fact
+        .shape
+        .dim(axis)
+        .and_then(|d| d.concretize())
+        .ok_or_else(|| format_err!("Axis {} of {} is unknown ({:?})", axis, node, fact))?;
+    dim.to_usize()
+        .with_context(|| format!("Axis {} of {} must be an integer, got {}", axis, node, dim))
+}
+
 fn incorporate_memory_ops_as_scans(
     model: &InferenceModel,
     _: &InferenceNode,
@@ -82,6 +93,18 @@ fn incorporate_memory_ops_as_scans(
     trace!("Loops: {:?}", loops);
 
     let mut patch = InferenceModelPatch::default();
+
+    // memories not reachable from their observed node are mere time shifts.
+    // lower them first, the recurrent ones will be dealt with on next pass.
+    let shifts: Vec<usize> =
+        loops.iter().filter(|(_, time_loop)| time_loop.is_empty()).map(|(mem, _)| *mem).collect();
+    if !shifts.is_empty() {
+        for mem in shifts {
+            incorporate_memory_op_as_shift(model, &mut patch, mem)?;
+        }
+        return Ok(patch);
+    }
+
     while loops.len() > 0 {
         let (mem, time_loop) = loops.iter().next().unwrap();
 
@@ -126,8 +149,7 @@ fn incorporate_memory_ops_as_scans(
         for &mem in &coupled_mem_ops {
             let mem_node = model.node(mem);
             let op = mem_node.op_as::<Memory>().unwrap();
-            let channel =
-                mem_node.outputs[0].fact.shape.dim(1).unwrap().concretize().unwrap().to_usize()?;
+            let channel = concrete_dim(&mem_node.outputs[0].fact, 1, &mem_node.name)?;
             let chunk = op.offset.abs();
             let id = inner_model.add_source(
                 &*mem_node.name,
@@ -154,8 +176,7 @@ fn incorporate_memory_ops_as_scans(
         }
         for scan_input in &scan_inputs {
             let old_node = model.node(scan_input.node);
-            let channel =
-                old_node.outputs[0].fact.shape.dim(1).unwrap().concretize().unwrap().to_usize()?;
+            let channel = concrete_dim(&old_node.outputs[0].fact, 1, &old_node.name)?;
             let new_id = inner_model.add_source(
                 format!("{}-scan", old_node.name),
                 InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(_, channel)),
@@ -201,22 +222,29 @@ fn incorporate_memory_ops_as_scans(
 
         inner_model.analyse(false)?;
 
-        for (ix, scan_input) in scan_inputs.iter().enumerate() {
-            let old_node = model.node(scan_input.node);
+        for ix in 0..scan_inputs.len() {
             let fact = inner_model.input_fact(coupled_mem_ops.len() + ix)?;
-            let chunk = fact.shape.dim(0).unwrap().concretize().unwrap().to_isize()?;
+            let chunk = concrete_dim(fact, 0, "scan input")? as isize;
             mapped_inputs.push(tract_hir::ops::scan::InputMapping::Scan {
                 axis: 0,
                 chunk,
                 slot: ix,
             });
+        }
+        for (ix, scan_output) in scan_outputs.iter().enumerate() {
+            let fact = inner_model.output_fact(coupled_mem_ops.len() + ix)?;
+            let chunk = concrete_dim(fact, 0, "scan output")? as isize;
             mapped_outputs.push(tract_hir::ops::scan::OutputMapping {
                 state: false,
                 axis: 0,
                 chunk,
                 full_slot: Some(ix),
                 last_value_slot: None,
-                full_dim_hint: old_node.outputs[0].fact.shape.dim(0).unwrap().concretize(),
+                full_dim_hint: model
+                    .outlet_fact(*scan_output)?
+                    .shape
+                    .dim(0)
+                    .and_then(|d| d.concretize()),
             });
         }
 
@@ -256,6 +284,37 @@ fn incorporate_memory_ops_as_scans(
     Ok(patch)
 }
 
+/// Replace a non recurrent memory by its observed input, delayed (or advanced)
+/// by the memory offset, with zeros where it is not defined.
+fn incorporate_memory_op_as_shift(
+    model: &InferenceModel,
+    patch: &mut InferenceModelPatch,
+    mem: usize,
+) -> TractResult<()> {
+    use tract_hir::ops::array::{Crop, Pad, PadMode};
+    let mem_node = model.node(mem);
+    let op = mem_node.op_as::<Memory>().unwrap();
+    let observed = patch.tap_model(model, OutletId::new(model.node_by_name(&op.name)?.id, 0))?;
+    let shift = op.offset.abs() as usize;
+    let (pads, crop) = if op.offset < 0 {
+        ((shift, 0), Crop::new(0, 0, shift))
+    } else {
+        ((0, shift), Crop::new(0, shift, 0))
+    };
+    let pad = Pad::new(vec![pads, (0, 0)], PadMode::Constant(tensor0(0f32).into_arc_tensor()));
+    let padded = patch.wire_node(format!("{}.pad", mem_node.name), pad, &[observed])?;
+    // keep the memory fact: scans built on the same pass rely on it
+    let shifted = patch.add_node(
+        format!("{}.crop", mem_node.name),
+        expand(crop),
+        tvec!(mem_node.outputs[0].fact.clone()),
+    )?;
+    patch.add_edge(padded[0], InletId::new(shifted, 0))?;
+    patch.shunt_outside(model, OutletId::new(mem, 0), OutletId::new(shifted, 0))?;
+    patch.obliterate(mem)?;
+    Ok(())
+}
+
 pub fn time_loop_nodes_for_memory(
     model: &InferenceModel,
     memory_node_id: usize,
@@ -305,3 +364,61 @@ pub fn all_precursors(model: &InferenceModel, id: usize) -> TractResult<BitSet>
     }
     Ok(visited)
 }
+
+#[cfg(test)]
+mod test {
+    use tract_hir::internal::*;
+
+    #[test]
+    fn recurrent_and_shifted_offsets() {
+        // a(t) = input(t) + 0.5 * a(t-2) + 10 * input(t-1) + 100 * input(t+2)
+        let slice = r#"<Nnet3>
+
+input-node name=input dim=1
+component-node name=a input=Append(input, IfDefined(Offset(a, -2)), IfDefined(Offset(input, -1)), IfDefined(Offset(input, 2))) component=a
+output-node name=output input=a
+
+<NumComponents> 1
+<ComponentName> a <FixedAffineComponent> <LinearParams> [
+  1 0.5 10 100 ]
+<BiasParams> [ 0 ]
+</FixedAffineComponent>
+</Nnet3>"#;
+        let x = [1.0f32, 2., 3., 4., 5.];
+        let mut expected = vec![];
+        for t in 0..x.len() {
+            let mut y = x[t];
+            if t >= 2 {
+                y += 0.5 * expected[t - 2];
+            }
+            if t >= 1 {
+                y += 10.0 * x[t - 1];
+            }
+            if t + 2 < x.len() {
+                y += 100.0 * x[t + 2];
+            }
+            expected.push(y);
+        }
+        let model = crate::kaldi()
+            .model_for_read(&mut slice.as_bytes())
+            .unwrap()
+            .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(5, 1)))
+            .unwrap()
+            .into_optimized()
+            .unwrap();
+        let input = tract_ndarray::Array2::from_shape_vec((5, 1), x.to_vec()).unwrap();
+        let output = model.into_runnable().unwrap().run(tvec!(input.into_tensor())).unwrap();
+        assert_eq!(output[0].as_slice::<f32>().unwrap(), &*expected);
+    }
+
+    #[test]
+    fn symbolic_dims_are_errors() {
+        let s = SymbolTable::default().sym("S");
+        let fact = InferenceFact::dt_shape(f32::datum_type(), shapefactoid!((s.to_dim()), 3));
+        assert_eq!(super::concrete_dim(&fact, 1, "node").unwrap(), 3);
+        assert!(super::concrete_dim(&fact, 0, "node").is_err());
+        let fact = InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(_, 3));
+        assert!(super::concrete_dim(&fact, 0, "node").is_err());
+        assert!(super::concrete_dim(&InferenceFact::default(), 1, "node").is_err());
+    }
+}
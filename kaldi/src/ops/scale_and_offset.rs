@@ -0,0 +1,83 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+
+fn attr<'a>(component: &'a crate::model::Component, name: &str) -> TractResult<&'a Arc<Tensor>> {
+    component.attributes.get(name).with_context(|| format!("missing attribute {}", name))
+}
+
+/// Repeat a per-block vector over the whole feature dimension.
+fn tile(block: &[f32], dim: usize) -> TractResult<Arc<Tensor>> {
+    if block.is_empty() || dim % block.len() != 0 {
+        bail!("Can not tile a vector of {} elements over dimension {}", block.len(), dim)
+    }
+    let data: Vec<f32> = block.iter().cycle().take(dim).cloned().collect();
+    Ok(tract_ndarray::Array2::from_shape_vec((1, dim), data)?.into_arc_tensor())
+}
+
+pub fn batch_norm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attr(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let epsilon = attr(component, "Epsilon")?.cast_to_scalar::<f32>()?;
+    let target_rms = attr(component, "TargetRms")?.cast_to_scalar::<f32>()?;
+    let mean = attr(component, "StatsMean")?.as_slice::<f32>()?;
+    let var = attr(component, "StatsVar")?.as_slice::<f32>()?;
+    let scales: Vec<f32> = var.iter().map(|v| target_rms / (v + epsilon).sqrt()).collect();
+    let offsets: Vec<f32> = mean.iter().zip(scales.iter()).map(|(m, s)| -m * s).collect();
+    Ok(expand(ScaleAndOffset::new(tile(&scales, dim)?, tile(&offsets, dim)?)))
+}
+
+pub fn scale_and_offset(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attr(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let scales = tile(attr(component, "Scales")?.as_slice::<f32>()?, dim)?;
+    let offsets = tile(attr(component, "Offsets")?.as_slice::<f32>()?, dim)?;
+    Ok(expand(ScaleAndOffset::new(scales, offsets)))
+}
+
+/// Per-feature affine transform: `x * scales + offsets`.
+#[derive(Clone, Debug, new, Hash)]
+struct ScaleAndOffset {
+    scales: Arc<Tensor>,
+    offsets: Arc<Tensor>,
+}
+
+impl_dyn_hash!(ScaleAndOffset);
+
+impl Expansion for ScaleAndOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ScaleAndOffset".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.scales.shape()[1].to_dim())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let scaled = model.wire_node(
+            prefix.to_string() + ".scale",
+            tract_hir::ops::math::mul::unary(self.scales.clone()),
+            inputs,
+        )?;
+        model.wire_node(prefix, tract_hir::ops::math::add::unary(self.offsets.clone()), &scaled)
+    }
+}
@@ -10,7 +10,7 @@ use nom::{
     bytes::complete::*,
     combinator::*,
     multi::many_m_n,
-    number::complete::{le_f32, le_f64},
+    number::complete::{le_f32, le_f64, le_i32},
     sequence::*,
     IResult,
 };
@@ -18,9 +18,14 @@ use nom::{
 pub enum KaldiAttributeKind {
     Bool,
     Int,
+    IntPair,
+    IntVector,
     Float,
+    FloatPair,
     FloatVector,
     FloatMatrix,
+    /// Token without a value, present only when set (`<TestMode>`).
+    Flag,
 }
 
 impl KaldiAttributeKind {
@@ -31,9 +36,28 @@ impl KaldiAttributeKind {
                 map(tag("T"), |_| Tensor::from(true)),
             ))(i),
             Int => map(super::integer(true), Tensor::from)(i),
+            IntPair => {
+                map(pair(super::integer(true), super::integer(true)), |(a, b)| tensor1(&[a, b]))(i)
+            }
+            IntVector => Self::parse_int_vector(i),
             Float => map(Self::parse_float_value, Tensor::from)(i),
+            FloatPair => map(pair(Self::parse_float_value, Self::parse_float_value), |(a, b)| {
+                tensor1(&[a, b])
+            })(i),
             FloatVector => preceded(multispaced(tag("FV")), Self::parse_float_vector)(i),
             FloatMatrix => preceded(multispaced(tag("FM")), Self::parse_float_matrix)(i),
+            Flag => Ok((i, Tensor::from(true))),
+        }
+    }
+
+    fn parse_int_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
+        // element size, then length, then raw elements
+        let (i, _) = tag([4])(i)?;
+        let (i, len) = le_i32(i)?;
+        if len == 0 {
+            Ok((i, tensor1(&[0i32; 0])))
+        } else {
+            map(many_m_n(len as usize, len as usize, le_i32), |data| tensor1(&*data))(i)
         }
     }
 
@@ -71,6 +95,24 @@ impl KaldiAttributeKind {
 
 use KaldiAttributeKind::*;
 
+/// Attributes shared by all components deriving from nnet3 NonlinearComponent.
+fn nonlinear() -> HashMap<&'static str, KaldiAttributeKind> {
+    hashmap! {
+        "Dim" => Int,
+        "BlockDim" => Int,
+        "ValueAvg" => FloatVector,
+        "DerivAvg" => FloatVector,
+        "Count" => Float,
+        "OderivRms" => FloatVector,
+        "OderivCount" => Float,
+        "NumDimsSelfRepaired" => Float,
+        "NumDimsProcessed" => Float,
+        "SelfRepairLowerThreshold" => Float,
+        "SelfRepairUpperThreshold" => Float,
+        "SelfRepairScale" => Float,
+    }
+}
+
 lazy_static::lazy_static! {
     pub static ref COMPONENTS: HashMap<&'static str, HashMap<&'static str, KaldiAttributeKind>> = hashmap! {
         "FixedAffineComponent" => hashmap! {
@@ -123,22 +165,77 @@ lazy_static::lazy_static! {
             "NumElementsProcessed" => Float,
             "NumZeroingBoundaries" => Float,
         },
-        "LogSoftmaxComponent" => hashmap!{
-            "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
-            "Count" => Int,
-            "NumDimsSelfRepaired" => Int,
-            "NumDimsProcessed" => Int,
+        "LogSoftmaxComponent" => nonlinear(),
+        "RectifiedLinearComponent" => nonlinear(),
+        "SigmoidComponent" => nonlinear(),
+        "SoftmaxComponent" => nonlinear(),
+        "TanhComponent" => nonlinear(),
+        "TdnnComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "TimeOffsets" => IntVector,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumSamplesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        },
+        "LinearComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "Params" => FloatMatrix,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "RankInOut" => IntPair,
+            "Alpha" => Float,
+            "NumSamplesHistory" => Float,
+            "UpdatePeriod" => Int,
         },
-        "RectifiedLinearComponent" => hashmap!{
+        "BatchNormComponent" => hashmap!{
             "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
+            "BlockDim" => Int,
+            "Epsilon" => Float,
+            "TargetRms" => Float,
+            "TestMode" => Bool,
             "Count" => Float,
-            "NumDimsSelfRepaired" => Float,
-            "NumDimsProcessed" => Float,
-            "SelfRepairScale" => Float,
+            "StatsMean" => FloatVector,
+            "StatsVar" => FloatVector,
+        },
+        "ScaleAndOffsetComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "Dim" => Int,
+            "Scales" => FloatVector,
+            "Offsets" => FloatVector,
+            "UseNaturalGradient" => Bool,
+            "Rank" => Int,
+        },
+        "ElementwiseProductComponent" => hashmap!{
+            "InputDim" => Int,
+            "OutputDim" => Int,
+        },
+        "GeneralDropoutComponent" => hashmap!{
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "TimePeriod" => Int,
+            "DropoutProportion" => Float,
+            "Continuous" => Flag,
+            "TestMode" => Flag,
+        },
+        "NoOpComponent" => hashmap!{
+            "Dim" => Int,
+            "BackpropScale" => Float,
         }
     };
 }
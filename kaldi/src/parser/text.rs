@@ -2,21 +2,34 @@ use tract_hir::internal::*;
 
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list,
-    number::complete::float, sequence::*,
+    bytes::complete::*,
+    character::complete::*,
+    combinator::*,
+    multi::{many1, separated_list},
+    number::complete::float,
+    sequence::*,
 };
 
 use super::{integer, multispaced, open_any, spaced};
 
 pub fn attributes(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
-    let (i, attributes) = nom::multi::many0(map(pair(open_any, tensor), |(k, v)| {
-        (k.to_string(), v.into_arc_tensor())
+    // valueless tokens are flags, set by their mere presence
+    let (i, attributes) = nom::multi::many0(map(pair(open_any, opt(tensor)), |(k, v)| {
+        (k.to_string(), v.unwrap_or_else(|| Tensor::from(true)).into_arc_tensor())
     }))(i)?;
     Ok((i, attributes.into_iter().collect()))
 }
 
 pub fn tensor(i: &[u8]) -> IResult<&[u8], Tensor> {
-    nom::branch::alt((scalar, vector, matrix))(i)
+    nom::branch::alt((numbers, scalar, vector, matrix))(i)
+}
+
+/// Several numbers after a single token, like `<RankInOut> 20 80`.
+pub fn numbers(i: &[u8]) -> IResult<&[u8], Tensor> {
+    map(pair(float, many1(preceded(space1, float))), |(first, mut others)| {
+        others.insert(0, first);
+        tensor1(&*others)
+    })(i)
 }
 
 pub fn scalar(i: &[u8]) -> IResult<&[u8], Tensor> {
@@ -72,6 +85,16 @@ output-node name=output input=fixed1
         nnet3(slice.as_bytes()).unwrap();
     }
 
+    #[test]
+    fn test_multiple_values_and_flags() {
+        let slice = r#"<Dim> 4 <RankInOut> 20 80 <TestMode> <Scale> [ 1.0 ]"#;
+        let attributes = attributes(slice.as_bytes()).unwrap().1;
+        assert_eq!(*attributes["Dim"], tensor0(4.0f32));
+        assert_eq!(*attributes["RankInOut"], tensor1(&[20.0f32, 80.0]));
+        assert_eq!(*attributes["TestMode"], tensor0(true));
+        assert_eq!(*attributes["Scale"], tensor1(&[1.0f32]));
+    }
+
     #[test]
     fn test_vector() {
         let slice = r#"[ 7.0 8.0 ]"#;
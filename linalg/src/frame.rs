@@ -1,4 +1,6 @@
 #[macro_use]
+pub mod layer_norm;
+#[macro_use]
 pub mod lut;
 #[macro_use]
 pub mod mmm;
@@ -6,11 +8,15 @@ pub mod pack;
 #[macro_use]
 pub mod sigmoid;
 #[macro_use]
+pub mod softmax;
+#[macro_use]
 pub mod tanh;
 
 pub use pack::Packer;
 
 pub use self::mmm::{MatMatMul, MatMatMulImpl};
 
+pub use self::layer_norm::LayerNormImpl;
 pub use self::sigmoid::SigmoidImpl;
+pub use self::softmax::SoftmaxImpl;
 pub use self::tanh::TanhImpl;
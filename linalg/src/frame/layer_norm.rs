@@ -0,0 +1,87 @@
+use num_traits::Float;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// In place normalization of a contiguous slice to zero mean and unit
+/// variance.
+pub trait LayerNorm<T>: Send + Sync + Debug + dyn_clone::DynClone
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+{
+    fn run(&self, vec: &mut [T], epsilon: T);
+}
+
+dyn_clone::clone_trait_object!(<T> LayerNorm<T> where T: Copy);
+
+#[derive(Debug, Clone, new)]
+pub struct LayerNormImpl<K, T>
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+    K: LayerNormKer<T> + Clone,
+{
+    phantom: PhantomData<(K, T)>,
+}
+
+impl<K, T> LayerNorm<T> for LayerNormImpl<K, T>
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+    K: LayerNormKer<T> + Clone,
+{
+    fn run(&self, vec: &mut [T], epsilon: T) {
+        if vec.len() == 0 {
+            return;
+        }
+        let (mean, var) = K::mean_var(vec);
+        K::normalize(vec, mean, (var + epsilon).sqrt().recip());
+    }
+}
+
+pub trait LayerNormKer<T>: Send + Sync + Debug + dyn_clone::DynClone + Clone
+where
+    T: Copy + Debug + PartialEq + Send + Sync,
+{
+    fn name() -> &'static str;
+    /// Mean and (biased) variance of the slice.
+    fn mean_var(vec: &[T]) -> (T, T);
+    /// Replaces each x by (x - mean) * factor.
+    fn normalize(vec: &mut [T], mean: T, factor: T);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    use super::LayerNormKer;
+    use proptest::test_runner::TestCaseResult;
+
+    #[macro_export]
+    macro_rules! layer_norm_frame_tests {
+        ($cond:expr, $ker:ty) => {
+            proptest::proptest! {
+                #[test]
+                fn layer_norm(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+                    if $cond {
+                        crate::frame::layer_norm::test::test_layer_norm::<$ker>(&*xs).unwrap()
+                    }
+                }
+            }
+
+            #[test]
+            fn layer_norm_constant() {
+                if $cond {
+                    crate::frame::layer_norm::test::test_layer_norm::<$ker>(&[3.0; 7]).unwrap()
+                }
+            }
+        };
+    }
+
+    pub fn test_layer_norm<K: LayerNormKer<f32>>(values: &[f32]) -> TestCaseResult {
+        use crate::frame::layer_norm::LayerNorm;
+        let op = crate::frame::layer_norm::LayerNormImpl::<K, f32>::new();
+        let mut found = values.to_vec();
+        op.run(&mut found, 1e-5);
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let var = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / values.len() as f32;
+        let expected = values.iter().map(|x| (x - mean) / (var + 1e-5).sqrt()).collect::<Vec<_>>();
+        crate::test::check_close(&*found, &*expected)
+    }
+}
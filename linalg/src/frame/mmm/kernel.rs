@@ -56,6 +56,58 @@ macro_rules! test_mmm_kernel_f32 {
     };
 }
 
+#[macro_export]
+macro_rules! test_mmm_kernel_f16 {
+    ($k: ty, $id: ident, $cond: expr) => {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod $id {
+            mmm_kernel_tests!(
+                $cond,
+                $k,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                f32
+            );
+            mmm_frame_tests!(
+                $cond,
+                $k,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                f32
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! test_mmm_kernel_bf16 {
+    ($k: ty, $id: ident, $cond: expr) => {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod $id {
+            mmm_kernel_tests!(
+                $cond,
+                $k,
+                tract_data::prelude::bf16,
+                tract_data::prelude::bf16,
+                tract_data::prelude::bf16,
+                f32
+            );
+            mmm_frame_tests!(
+                $cond,
+                $k,
+                tract_data::prelude::bf16,
+                tract_data::prelude::bf16,
+                tract_data::prelude::bf16,
+                f32
+            );
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! test_mmm_kernel_i8 {
     ($k: ty, $id: ident, $cond: expr) => {
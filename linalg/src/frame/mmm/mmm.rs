@@ -37,6 +37,13 @@ pub trait MatMatMul:
     unsafe fn c_vec_from_data_and_stride(&mut self, stride: isize);
     unsafe fn c_vec_from_data(&mut self);
 
+    /// Number of workers the tile loop is split across.
+    ///
+    /// 1 (the default) runs sequentially on the calling thread, 0 uses as
+    /// many workers as the current rayon thread pool.
+    fn threads(&self) -> usize;
+    fn set_threads(&mut self, threads: usize);
+
     unsafe fn run(
         &self,
         a: &TensorView,
@@ -71,6 +78,8 @@ where
     pub zero_point_c: Option<Tensor>,
     pub scale_factor: Option<(TI, usize)>,
 
+    pub threads: usize,
+
     phantom: PhantomData<(K, TA, TB, TC, TI)>,
 }
 
@@ -119,6 +128,7 @@ where
             zero_point_b: None,
             zero_point_c: None,
             scale_factor: None,
+            threads: 1,
             phantom: PhantomData,
         }
     }
@@ -214,6 +224,14 @@ where
         self.c_vec_from_data_and_stride(1)
     }
 
+    fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn set_threads(&mut self, threads: usize) {
+        self.threads = threads
+    }
+
     unsafe fn run(
         &self,
         a: &TensorView,
@@ -226,24 +244,11 @@ where
         debug_assert_eq!(a.datum_type(), TA::datum_type());
         debug_assert_eq!(b.datum_type(), TB::datum_type());
         debug_assert_eq!(c.datum_type(), TC::datum_type());
-        let prefetch = crate::ops().prefetch.as_ref();
         let m = self.m;
         let n = self.n;
-        let mut scratch = ScratchSpaceFusedNonLinear::default();
-        let mut tmpc = Vec::with_capacity(mr * nr);
-        tmpc.set_len(mr * nr);
-        let tmp_c_storage = MatrixStoreSpec::Strides {
-            row_byte_stride: (std::mem::size_of::<TC>() * nr) as isize,
-            col_byte_stride: std::mem::size_of::<TC>() as isize,
-            mr,
-            nr,
-        };
-        let ref mut tmp_tile = tmp_c_storage.wrap(tmpc.as_ptr());
-
         let a = a.as_ptr_unchecked::<TA>();
         let b = b.as_ptr_unchecked::<TB>();
         let c = c.as_ptr_mut_unchecked::<TC>();
-        let ref linear = LinearSpec::k(self.k);
         let mut non_linear = non_linear.to_vec();
         if let Some(ref a0) = self.zero_point_a {
             let mut sum_b_over_k = self.sum_b_over_k(b);
@@ -306,101 +311,27 @@ where
             non_linear.push(FusedSpec::Min(tensor0(TC::max_value().as_())));
             non_linear.push(FusedSpec::Max(tensor0(TC::min_value().as_())));
         }
-        let a = self.a_storage.wrap(a);
-        let b = self.b_storage.wrap(b);
-        //        eprintln!("{:?} {:?}", a, b);
-        let mut c = self.c_storage.wrap(c);
-        for ia in 0..m / mr {
-            let ref a = a.panel_a(ia);
-            for ib in 0..n / nr {
-                if let PanelStore::Packed { ptr } = a {
-                    prefetch(*ptr as *const u8, 512);
-                }
-                let ref b = b.panel_b(nr, ib, nr);
-                match b {
-                    PanelStore::Packed { ptr } => prefetch(*ptr as *const u8, 512),
-                    PanelStore::VecStride { ptr, .. } => prefetch(*ptr as *const u8, 128),
-                    _ => (),
-                }
-                let ref direct_c = c.tile_c(ia, ib);
-                let non_linear = scratch.for_tile::<TA, TB, TC, K>(&non_linear, ia, ib);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: a as _,
-                    b: b as _,
-                    c: direct_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-            }
-            if n % nr != 0 {
-                if let PanelStore::Packed { ptr } = a {
-                    prefetch(*ptr as *const u8, 512);
-                }
-                let ref b = b.panel_b(nr, n / nr, n % nr);
-                match b {
-                    PanelStore::Packed { ptr } => prefetch(*ptr as *const u8, 512),
-                    PanelStore::VecStride { ptr, .. } => prefetch(*ptr as *const u8, 128),
-                    _ => (),
-                }
-                let ref tmp_tile_c = tmp_tile.tile_c(0, 0);
-                let non_linear = scratch.for_tile::<TA, TB, TC, K>(&non_linear, ia, n / nr);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_tile(ia, n / nr, mr, n % nr, &*tmpc);
-            }
+        let tiles = Tiles {
+            a: self.a_storage.wrap(a),
+            b: self.b_storage.wrap(b),
+            c: self.c_storage.wrap(c),
+            non_linear: &non_linear,
+        };
+        let rows = (m + mr - 1) / mr;
+        let cols = (n + nr - 1) / nr;
+        let workers = match self.threads {
+            0 => rayon::current_num_threads(),
+            t => t,
         }
-        if m % mr != 0 {
-            let ref panel_a = a.panel_a(m / mr);
-            let ref tmp_tile_c = tmp_tile.tile_c(0, 0);
-            for ib in 0..n / nr {
-                if let PanelStore::Packed { ptr } = panel_a {
-                    prefetch(*ptr as *const u8, 512);
-                }
-                let ref b = b.panel_b(nr, ib, nr);
-                match b {
-                    PanelStore::Packed { ptr } => prefetch(*ptr as *const u8, 512),
-                    PanelStore::VecStride { ptr, .. } => prefetch(*ptr as *const u8, 128),
-                    _ => (),
-                }
-                let non_linear = scratch.for_tile::<TA, TB, TC, K>(&non_linear, m / mr, ib);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: panel_a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_tile(m / mr, ib, m % mr, nr, &*tmpc);
-            }
-            if n % nr != 0 {
-                if let PanelStore::Packed { ptr } = panel_a {
-                    prefetch(*ptr as *const u8, 512);
-                }
-                let ref b = b.panel_b(nr, n / nr, n % nr);
-                match b {
-                    PanelStore::Packed { ptr } => prefetch(*ptr as *const u8, 512),
-                    PanelStore::VecStride { ptr, .. } => prefetch(*ptr as *const u8, 128),
-                    _ => (),
-                }
-                let non_linear = scratch.for_tile::<TA, TB, TC, K>(&non_linear, m / mr, n / nr);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: panel_a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_tile(m / mr, n / nr, m % mr, n % nr, &*tmpc);
-            }
+        .min(rows * cols);
+        if workers > 1 {
+            use rayon::prelude::*;
+            let chunk = (rows * cols + workers - 1) / workers;
+            (0..workers).into_par_iter().for_each(|w| {
+                self.run_tiles(&tiles, w * chunk..((w + 1) * chunk).min(rows * cols), cols)
+            });
+        } else {
+            self.run_tiles(&tiles, 0..rows * cols, cols);
         }
         Ok(())
     }
@@ -437,6 +368,76 @@ where
     K: MatMatMulKer<TI> + 'static,
     i32: AsPrimitive<TI>,
 {
+    /// Compute a range of tiles (row-major indexes in the tile grid).
+    unsafe fn run_tiles(
+        &self,
+        tiles: &Tiles<TA, TB, TC>,
+        range: std::ops::Range<usize>,
+        cols: usize,
+    ) where
+        TA: Datum,
+        TB: Datum,
+        TC: Datum,
+        TI: Datum,
+    {
+        let mr = K::mr();
+        let nr = K::nr();
+        let m = self.m;
+        let n = self.n;
+        let prefetch = crate::ops().prefetch.as_ref();
+        let mut scratch = ScratchSpaceFusedNonLinear::default();
+        let mut tmpc = Vec::with_capacity(mr * nr);
+        tmpc.set_len(mr * nr);
+        let tmp_c_storage = MatrixStoreSpec::Strides {
+            row_byte_stride: (std::mem::size_of::<TC>() * nr) as isize,
+            col_byte_stride: std::mem::size_of::<TC>() as isize,
+            mr,
+            nr,
+        };
+        let ref tmp_tile = tmp_c_storage.wrap(tmpc.as_ptr());
+        let ref linear = LinearSpec::k(self.k);
+        let mut c = tiles.c.clone();
+        for tile in range {
+            let ia = tile / cols;
+            let ib = tile % cols;
+            let height = if ia < m / mr { mr } else { m % mr };
+            let width = if ib < n / nr { nr } else { n % nr };
+            let ref a = tiles.a.panel_a(ia);
+            if let PanelStore::Packed { ptr } = a {
+                prefetch(*ptr as *const u8, 512);
+            }
+            let ref b = tiles.b.panel_b(nr, ib, width);
+            match b {
+                PanelStore::Packed { ptr } => prefetch(*ptr as *const u8, 512),
+                PanelStore::VecStride { ptr, .. } => prefetch(*ptr as *const u8, 128),
+                _ => (),
+            }
+            let non_linear = scratch.for_tile::<TA, TB, TC, K>(tiles.non_linear, ia, ib);
+            if height == mr && width == nr {
+                let ref direct_c = c.tile_c(ia, ib);
+                let err = K::kernel(&MatMatMulKerSpec {
+                    a: a as _,
+                    b: b as _,
+                    c: direct_c as _,
+                    linear,
+                    non_linear,
+                });
+                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+            } else {
+                let ref tmp_tile_c = tmp_tile.tile_c(0, 0);
+                let err = K::kernel(&MatMatMulKerSpec {
+                    a: a as _,
+                    b: b as _,
+                    c: tmp_tile_c as _,
+                    linear,
+                    non_linear,
+                });
+                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                c.set_from_tile(ia, ib, height, width, &*tmpc);
+            }
+        }
+    }
+
     fn sum_a_over_k(&self, mut a: *const TA) -> Vec<TI> {
         match &self.a_storage {
             MatrixStoreSpec::Packed { .. } => {
@@ -530,6 +531,23 @@ where
             K::name(),
             K::mr(),
             K::nr()
-        )
+        )?;
+        if self.threads != 1 {
+            write!(fmt, " threads:{}", self.threads)?;
+        }
+        Ok(())
     }
 }
+
+/// Matrices and fused ops shared by the workers computing the tiles of a
+/// product.
+struct Tiles<'a, TA: Copy, TB: Copy, TC: Copy> {
+    a: MatrixStore<'a, TA>,
+    b: MatrixStore<'a, TB>,
+    c: MatrixStore<'a, TC>,
+    non_linear: &'a [FusedSpec],
+}
+
+// Workers only read A and B, and write disjoint tiles of C.
+unsafe impl<'a, TA: Copy, TB: Copy, TC: Copy> Send for Tiles<'a, TA, TB, TC> {}
+unsafe impl<'a, TA: Copy, TB: Copy, TC: Copy> Sync for Tiles<'a, TA, TB, TC> {}
@@ -25,6 +25,16 @@ macro_rules! mmm_frame_tests {
                     }
                 }
 
+                #[test]
+                fn mat_mul_prepacked_threads_prop(
+                    (m, k, n, ref a, ref b) in strat_mat_mat_mul_sized::<$ta, $tb>(40, 5, 40),
+                    threads in 2usize..5
+                ) {
+                    if $cond {
+                        test_mat_mat_mul_prep_threads::<$ker, $ta, $tb, $tc, $ti>(m, k, n, &a, &b, threads)?
+                    }
+                }
+
                 #[test]
                 fn mat_vec_prepacked_prop((m, k, ref a, ref b) in strat_mat_vec_mul::<$ta, $tb>()) {
                     if $cond {
@@ -123,8 +133,8 @@ macro_rules! mmm_frame_tests {
                         kt: 1,
                         stride: 1,
                         dilation: 1,
-                        filters: tensor2(&[[2 as $ta]]),
-                        data: tensor2(&[[-65i32 as $tb]]),
+                        filters: tensor2(&[[2i32]]).cast_to::<$ta>().unwrap().into_owned(),
+                        data: tensor2(&[[-65i32]]).cast_to::<$tb>().unwrap().into_owned(),
                         phantom: std::marker::PhantomData,
                     };
                     let expected = pb.expected::<$tc, $ti>();
@@ -195,7 +205,15 @@ macro_rules! mmm_s_frame_tests {
 
 pub fn strat_mat_mat_mul<TA: LADatum, TB: LADatum>(
 ) -> BoxedStrategy<(usize, usize, usize, Tensor, Tensor)> {
-    (1usize..5, 1usize..5, 1usize..5)
+    strat_mat_mat_mul_sized::<TA, TB>(5, 5, 5)
+}
+
+pub fn strat_mat_mat_mul_sized<TA: LADatum, TB: LADatum>(
+    max_m: usize,
+    max_k: usize,
+    max_n: usize,
+) -> BoxedStrategy<(usize, usize, usize, Tensor, Tensor)> {
+    (1usize..max_m, 1usize..max_k, 1usize..max_n)
         .prop_flat_map(move |(m, k, n)| {
             (
                 Just(m),
@@ -239,6 +257,25 @@ pub fn test_mat_mat_mul_prep<K: MatMatMulKer<TI> + 'static, TA, TB, TC, TI>(
     a: &Tensor,
     b: &Tensor,
 ) -> Result<(), proptest::test_runner::TestCaseError>
+where
+    TA: LADatum + AsPrimitive<TI> + 'static,
+    TB: LADatum + AsPrimitive<TI> + 'static,
+    TC: LADatum + AsPrimitive<TI> + 'static,
+    TI: LADatum + AsPrimitive<TC> + 'static + Neg<Output = TI>,
+    i32: AsPrimitive<TI>,
+    usize: AsPrimitive<TI>,
+{
+    test_mat_mat_mul_prep_threads::<K, TA, TB, TC, TI>(m, k, n, a, b, 1)
+}
+
+pub fn test_mat_mat_mul_prep_threads<K: MatMatMulKer<TI> + 'static, TA, TB, TC, TI>(
+    m: usize,
+    k: usize,
+    n: usize,
+    a: &Tensor,
+    b: &Tensor,
+    threads: usize,
+) -> Result<(), proptest::test_runner::TestCaseError>
 where
     TA: LADatum + AsPrimitive<TI> + 'static,
     TB: LADatum + AsPrimitive<TI> + 'static,
@@ -248,7 +285,8 @@ where
     usize: AsPrimitive<TI>,
 {
     assert_eq!(a.datum_type(), TA::datum_type());
-    let op = MatMatMulImpl::<K, TA, TB, TC, TI>::new(m, k, n);
+    let mut op = MatMatMulImpl::<K, TA, TB, TC, TI>::new(m, k, n);
+    op.set_threads(threads);
     unsafe {
         let mut packed_a =
             Tensor::uninitialized_aligned::<TA>(&[op.a_pack().len(m)], op.a_pack().alignment())
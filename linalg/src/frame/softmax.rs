@@ -0,0 +1,88 @@
+use num_traits::Float;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// In place softmax of a contiguous slice.
+pub trait Softmax<T>: Send + Sync + Debug + dyn_clone::DynClone
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+{
+    fn run(&self, vec: &mut [T]);
+}
+
+dyn_clone::clone_trait_object!(<T> Softmax<T> where T: Copy);
+
+#[derive(Debug, Clone, new)]
+pub struct SoftmaxImpl<K, T>
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+    K: SoftmaxKer<T> + Clone,
+{
+    phantom: PhantomData<(K, T)>,
+}
+
+impl<K, T> Softmax<T> for SoftmaxImpl<K, T>
+where
+    T: Copy + Debug + PartialEq + Send + Sync + Float,
+    K: SoftmaxKer<T> + Clone,
+{
+    fn run(&self, vec: &mut [T]) {
+        if vec.len() == 0 {
+            return;
+        }
+        let max = K::max(vec);
+        let sum = K::exp_sum(vec, max);
+        K::scale(vec, sum.recip());
+    }
+}
+
+pub trait SoftmaxKer<T>: Send + Sync + Debug + dyn_clone::DynClone + Clone
+where
+    T: Copy + Debug + PartialEq + Send + Sync,
+{
+    fn name() -> &'static str;
+    fn max(vec: &[T]) -> T;
+    /// Replaces each x by exp(x - max) and returns the sum.
+    fn exp_sum(vec: &mut [T], max: T) -> T;
+    fn scale(vec: &mut [T], factor: T);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    use super::SoftmaxKer;
+    use proptest::test_runner::TestCaseResult;
+
+    #[macro_export]
+    macro_rules! softmax_frame_tests {
+        ($cond:expr, $ker:ty) => {
+            proptest::proptest! {
+                #[test]
+                fn softmax(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+                    if $cond {
+                        crate::frame::softmax::test::test_softmax::<$ker>(&*xs).unwrap()
+                    }
+                }
+            }
+
+            #[test]
+            fn softmax_large_values() {
+                if $cond {
+                    crate::frame::softmax::test::test_softmax::<$ker>(&[1000f32, 1001.0, 999.0])
+                        .unwrap()
+                }
+            }
+        };
+    }
+
+    pub fn test_softmax<K: SoftmaxKer<f32>>(values: &[f32]) -> TestCaseResult {
+        use crate::frame::softmax::Softmax;
+        let op = crate::frame::softmax::SoftmaxImpl::<K, f32>::new();
+        let mut found = values.to_vec();
+        op.run(&mut found);
+        let max = values.iter().fold(std::f32::MIN, |a, &b| a.max(b));
+        let sum: f32 = values.iter().map(|x| (x - max).exp()).sum();
+        let expected = values.iter().map(|x| (x - max).exp() / sum).collect::<Vec<_>>();
+        crate::test::check_close(&*found, &*expected)
+    }
+}
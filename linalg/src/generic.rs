@@ -1,9 +1,13 @@
+pub mod layer_norm;
 pub mod lut;
 pub mod mmm;
 pub mod sigmoid;
+pub mod softmax;
 pub mod tanh;
 
+pub use self::layer_norm::SLayerNorm;
 pub use self::lut::GenericLut8;
 pub use self::mmm::GenericMmm4x4;
 pub use self::sigmoid::SSigmoid4;
+pub use self::softmax::SSoftmax;
 pub use self::tanh::STanh4;
@@ -0,0 +1,32 @@
+use crate::frame::layer_norm::LayerNormKer;
+
+#[derive(Clone, Debug)]
+pub struct SLayerNorm;
+
+impl LayerNormKer<f32> for SLayerNorm {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn mean_var(x: &[f32]) -> (f32, f32) {
+        // shifting by the first value keeps the one-pass variance accurate
+        let shift = x[0];
+        let (sum, sum_sq) = x.iter().fold((0.0f32, 0.0f32), |(s, s2), &x| {
+            let d = x - shift;
+            (s + d, s2 + d * d)
+        });
+        let len = x.len() as f32;
+        let mean = sum / len;
+        (mean + shift, (sum_sq / len - mean * mean).max(0.0))
+    }
+
+    fn normalize(x: &mut [f32], mean: f32, factor: f32) {
+        x.iter_mut().for_each(|px| *px = (*px - mean) * factor)
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    layer_norm_frame_tests!(true, crate::generic::layer_norm::SLayerNorm);
+}
@@ -654,6 +654,8 @@ where
 }
 
 test_mmm_kernel_f32!(crate::generic::mmm::GenericMmm4x4<f32, f32, f32, f32>, test_GenericMmm4x4_f32, true);
+test_mmm_kernel_f16!(crate::generic::mmm::GenericMmm4x4<tract_data::prelude::f16, tract_data::prelude::f16, tract_data::prelude::f16, f32>, test_GenericMmm4x4_f16, true);
+test_mmm_kernel_bf16!(crate::generic::mmm::GenericMmm4x4<tract_data::prelude::bf16, tract_data::prelude::bf16, tract_data::prelude::bf16, f32>, test_GenericMmm4x4_bf16, true);
 test_mmm_kernel_i8!(crate::generic::mmm::GenericMmm4x4<i8, i8, i8, i32>, test_GenericMmm4x4_i8, true);
 test_mmm_kernel_u8!(crate::generic::mmm::GenericMmm4x4<u8, u8, u8, i32>, test_GenericMmm4x4_u8, true);
 test_mmm_kernel_i8_i32!(crate::generic::mmm::GenericMmm4x4<i8, i8, i32, i32>, test_GenericMmm4x4_i8_i32, true);
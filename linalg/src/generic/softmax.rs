@@ -0,0 +1,33 @@
+use crate::frame::softmax::SoftmaxKer;
+
+#[derive(Clone, Debug)]
+pub struct SSoftmax;
+
+impl SoftmaxKer<f32> for SSoftmax {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn max(x: &[f32]) -> f32 {
+        x.iter().fold(std::f32::NEG_INFINITY, |acc, &x| acc.max(x))
+    }
+
+    fn exp_sum(x: &mut [f32], max: f32) -> f32 {
+        let mut sum = 0.0;
+        for px in x.iter_mut() {
+            *px = (*px - max).exp();
+            sum += *px;
+        }
+        sum
+    }
+
+    fn scale(x: &mut [f32], factor: f32) {
+        x.iter_mut().for_each(|px| *px *= factor)
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    softmax_frame_tests!(true, crate::generic::softmax::SSoftmax);
+}
@@ -22,15 +22,20 @@ pub mod arm64;
 #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
 pub mod arm32;
 
+pub use self::frame::layer_norm;
 pub use self::frame::lut;
 pub use self::frame::mmm;
 pub use self::frame::sigmoid;
+pub use self::frame::softmax;
 pub use self::frame::tanh;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tract_data::prelude::*;
 
 pub struct Ops {
     pub mmm_f32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
+    pub mmm_f16: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
+    pub mmm_bf16: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     pub qmmm_i8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     pub qmmm_u8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     pub qmmm_u8_u8: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
@@ -38,8 +43,11 @@ pub struct Ops {
     pub qmmm_i8_u8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     pub sigmoid_f32: Box<dyn Fn() -> Box<dyn sigmoid::Sigmoid<f32>> + Send + Sync>,
     pub tanh_f32: Box<dyn Fn() -> Box<dyn tanh::Tanh<f32>> + Send + Sync>,
+    pub softmax_f32: Box<dyn Fn() -> Box<dyn softmax::Softmax<f32>> + Send + Sync>,
+    pub layer_norm_f32: Box<dyn Fn() -> Box<dyn layer_norm::LayerNorm<f32>> + Send + Sync>,
     pub lut_u8: Box<dyn Fn(&[u8]) -> Box<dyn lut::Lut> + Send + Sync>,
     pub(crate) prefetch: Box<dyn Fn(*const u8, usize) + Send + Sync>,
+    pub(crate) mmm_threads: AtomicUsize,
 }
 
 impl Ops {
@@ -55,6 +63,8 @@ impl Ops {
         use DatumType::*;
         match (a, b, c) {
             (F32, F32, F32) => Some((self.mmm_f32)(m, k, n)),
+            (F16, F16, F16) => Some((self.mmm_f16)(m, k, n)),
+            (BF16, BF16, BF16) => Some((self.mmm_bf16)(m, k, n)),
             (I8, I8, I32) => Some((self.qmmm_i8_i32)(m, k, n)),
             (U8, U8, I32) => Some((self.qmmm_u8_i32)(m, k, n)),
             (I8, I8, I8) => Some((self.qmmm_i8_i8)(m, k, n)),
@@ -63,8 +73,34 @@ impl Ops {
             _ => None,
         }
     }
+
+    /// Number of workers matrix multipliers opting into multithreading will
+    /// use (see `MatMatMul::set_threads`). Defaults to 1.
+    pub fn mmm_threads(&self) -> usize {
+        self.mmm_threads.load(Ordering::Relaxed)
+    }
+
+    /// Set the number of workers for multithreaded matrix multipliers. 0 means
+    /// as many as the current rayon thread pool.
+    pub fn set_mmm_threads(&self, threads: usize) {
+        self.mmm_threads.store(threads, Ordering::Relaxed)
+    }
+
+    /// Number of workers to use for a m×k×n product: the configured value if
+    /// the product is big enough to be worth splitting, 1 otherwise.
+    pub fn mmm_threads_for(&self, m: usize, k: usize, n: usize) -> usize {
+        if m * k * n < MMM_MIN_PARALLEL_WORK {
+            1
+        } else {
+            self.mmm_threads()
+        }
+    }
 }
 
+/// Products with fewer multiply-adds than this are not worth dispatching to
+/// several workers.
+const MMM_MIN_PARALLEL_WORK: usize = 1 << 18;
+
 pub fn generic() -> Ops {
     Ops {
         mmm_f32: Box::new(|m, k, n| {
@@ -76,6 +112,24 @@ pub fn generic() -> Ops {
                 f32,
             >::new(m, k, n))
         }),
+        mmm_f16: Box::new(|m, k, n| {
+            Box::new(mmm::MatMatMulImpl::<
+                generic::GenericMmm4x4<f16, f16, f16, f32>,
+                f16,
+                f16,
+                f16,
+                f32,
+            >::new(m, k, n))
+        }),
+        mmm_bf16: Box::new(|m, k, n| {
+            Box::new(mmm::MatMatMulImpl::<
+                generic::GenericMmm4x4<bf16, bf16, bf16, f32>,
+                bf16,
+                bf16,
+                bf16,
+                f32,
+            >::new(m, k, n))
+        }),
         qmmm_i8_i32: Box::new(|m, k, n| {
             Box::new(mmm::MatMatMulImpl::<
                      generic::GenericMmm4x4<i8, i8, i32, i32>,
@@ -119,8 +173,13 @@ pub fn generic() -> Ops {
         }),
         sigmoid_f32: Box::new(|| Box::new(sigmoid::SigmoidImpl::<generic::SSigmoid4, f32>::new())),
         tanh_f32: Box::new(|| Box::new(tanh::TanhImpl::<generic::STanh4, f32>::new())),
+        softmax_f32: Box::new(|| Box::new(softmax::SoftmaxImpl::<generic::SSoftmax, f32>::new())),
+        layer_norm_f32: Box::new(|| {
+            Box::new(layer_norm::LayerNormImpl::<generic::SLayerNorm, f32>::new())
+        }),
         lut_u8: Box::new(|table: &[u8]| Box::new(lut::LutImpl::<generic::GenericLut8>::new(table))),
         prefetch: Box::new(|_,_| {}),
+        mmm_threads: AtomicUsize::new(1),
     }
 }
 
@@ -213,6 +272,26 @@ mod test {
         }
     }
 
+    impl LADatum for tract_data::prelude::f16 {
+        fn strat() -> BoxedStrategy<Self> {
+            // multiples of 1/8 keep f32 accumulation exact, whatever the order
+            (-16isize..16).prop_map(|i| (i as f32 / 8.0).into()).boxed()
+        }
+        fn close(&self, other: &Self) -> bool {
+            (self.0.to_f32() - other.0.to_f32()).abs() < 0.01
+        }
+    }
+
+    impl LADatum for tract_data::prelude::bf16 {
+        fn strat() -> BoxedStrategy<Self> {
+            // multiples of 1/8 keep f32 accumulation exact, whatever the order
+            (-16isize..16).prop_map(|i| (i as f32 / 8.0).into()).boxed()
+        }
+        fn close(&self, other: &Self) -> bool {
+            (self.0.to_f32() - other.0.to_f32()).abs() < 0.05
+        }
+    }
+
     impl LADatum for u8 {
         fn strat() -> BoxedStrategy<Self> {
             any::<u8>().boxed()
@@ -2,11 +2,51 @@ use crate::internal::*;
 
 pub mod dump;
 pub mod parse;
+pub mod quant;
 
 #[derive(Clone, Debug)]
 pub struct ProtoModel {
     pub doc: Document,
     pub tensors: Vec<(String, Arc<Tensor>)>,
+    pub quantization: Option<HashMap<String, QuantFormat>>,
+}
+
+/// Linear quantization of a tensor, as declared in graph.quant.
+///
+/// A real value x is represented by the integer q with x = (q - zero_point) * scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantFormat {
+    pub bits: usize,
+    pub signed: bool,
+    pub zero_point: i32,
+    pub scale: f32,
+}
+
+impl QuantFormat {
+    pub fn new(dt: DatumType, zero_point: i32, scale: f32) -> TractResult<QuantFormat> {
+        if !dt.is_integer() {
+            bail!("Can not quantize to {:?}", dt)
+        }
+        Ok(QuantFormat { bits: dt.size_of() * 8, signed: dt.is_signed(), zero_point, scale })
+    }
+
+    pub fn datum_type(&self) -> TractResult<DatumType> {
+        use DatumType::*;
+        let dt = match (self.bits, self.signed) {
+            (8, true) => I8,
+            (8, false) => U8,
+            (16, true) => I16,
+            (16, false) => U16,
+            (32, true) => I32,
+            (32, false) => U32,
+            _ => bail!("No integer type for {}-bits quantization", self.bits),
+        };
+        Ok(dt)
+    }
+
+    pub fn zero_point_tensor(&self) -> TractResult<Arc<Tensor>> {
+        Ok(tensor0(self.zero_point).cast_to_dt(self.datum_type()?)?.into_owned().into_arc_tensor())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -31,6 +31,13 @@ pub fn parse_parameters(doc: &str) -> TractResult<Vec<Parameter>> {
     all_consuming(parameter_list)(doc).map(|pair| pair.1).map_err(translate_error)
 }
 
+#[inline(never)]
+pub fn parse_quantization(doc: &str) -> TractResult<Vec<(String, Invocation)>> {
+    all_consuming(terminated(many0(quantization), space_and_comments))(doc)
+        .map(|pair| pair.1)
+        .map_err(translate_error)
+}
+
 // <document> ::= <version> <extension>* <fragmentdefinition>* <graph-definition>
 fn document(i: &str) -> IResult<&str, Document> {
     map(
@@ -344,6 +351,13 @@ fn loop_iters(i: &str) -> IResult<&str, Vec<(String, RValue)>> {
     separated_list(stag(","), separated_pair(identifier, stag("in"), rvalue))(i)
 }
 
+// QUANTIZATION
+
+// <quantization> ::= <string-literal> ":" <invocation> ";"
+fn quantization(i: &str) -> IResult<&str, (String, Invocation)> {
+    pair(terminated(spaced(string_literal), stag(":")), terminated(invocation, stag(";")))(i)
+}
+
 // TERMINALS
 
 // identifier: identifiers must consist of the following ASCII characters: _, [a-z], [A-Z], [0-9].
@@ -812,4 +826,23 @@ graph y( x, s, bias ) -> ( y ) {
             "#,
         );
     }
+
+    #[test]
+    fn test_quantization() {
+        let parsed = parse_quantization(
+            r#"
+            "input": zero_point_linear_quantize(zero_point = 128, scale = 0.5, bits = 8, signed = false, symmetric = false);
+            "filter": linear_quantize(min = -1.0, max = 1.0, bits = 8);
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].0, "input");
+        assert_eq!(parsed[0].1.id, "zero_point_linear_quantize");
+        assert_eq!(parsed[1].1.arguments[0].id.as_deref(), Some("min"));
+        assert_eq!(
+            parsed[1].1.arguments[0].rvalue,
+            RValue::Literal(Literal::Numeric("-1.0".to_string()))
+        );
+    }
 }
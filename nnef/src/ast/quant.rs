@@ -0,0 +1,131 @@
+use crate::ast::*;
+use tract_itertools::Itertools;
+
+/// Parse a graph.quant document, resolving each entry against the quantization fragments
+/// of the standard library.
+pub fn parse_quantization(
+    stdlib: &[FragmentDef],
+    doc: &str,
+) -> TractResult<HashMap<String, QuantFormat>> {
+    crate::ast::parse::parse_quantization(doc)?
+        .into_iter()
+        .map(|(id, invocation)| {
+            let format = quant_format(stdlib, &invocation)
+                .with_context(|| format!("Quantization for tensor {:?}", id))?;
+            Ok((id, format))
+        })
+        .collect()
+}
+
+pub fn write_quantization(
+    w: &mut dyn std::io::Write,
+    quantization: &HashMap<String, QuantFormat>,
+) -> TractResult<()> {
+    for (id, q) in quantization.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        writeln!(
+            w,
+            "\"{}\": zero_point_linear_quantize(zero_point = {}, scale = {:?}, bits = {}, signed = {}, symmetric = false);",
+            id, q.zero_point, q.scale, q.bits, q.signed
+        )?;
+    }
+    Ok(())
+}
+
+fn quant_format(stdlib: &[FragmentDef], invocation: &Invocation) -> TractResult<QuantFormat> {
+    let decl = &stdlib
+        .iter()
+        .find(|f| f.decl.id == invocation.id)
+        .ok_or_else(|| format_err!("Unknown quantization fragment {}", invocation.id))?
+        .decl;
+    // the quantized tensor (x) is implicit in graph.quant
+    let params = &decl.parameters[1..];
+    let arg = |name: &str| -> TractResult<Literal> {
+        let (ix, param) = params
+            .iter()
+            .enumerate()
+            .find(|(_, p)| p.id == name)
+            .ok_or_else(|| format_err!("{} expects no parameter {}", invocation.id, name))?;
+        let rv = if let Some(arg) =
+            invocation.arguments.iter().find(|arg| arg.id.as_deref() == Some(name))
+        {
+            Some(&arg.rvalue)
+        } else if invocation.arguments.iter().take(ix + 1).all(|arg| arg.id.is_none()) {
+            invocation.arguments.get(ix).map(|arg| &arg.rvalue)
+        } else {
+            None
+        };
+        match rv {
+            Some(RValue::Literal(lit)) => Ok(lit.clone()),
+            Some(RValue::Unary(op, rv)) if op == "-" => match &**rv {
+                RValue::Literal(Literal::Numeric(n)) => Ok(Literal::Numeric(format!("-{}", n))),
+                _ => bail!("Expected a literal for {}, got {:?}", name, rv),
+            },
+            Some(rv) => bail!("Expected a literal for {}, got {:?}", name, rv),
+            None => param.lit.clone().ok_or_else(|| format_err!("Missing argument {}", name)),
+        }
+    };
+    let number = |name: &str| -> TractResult<f32> {
+        match arg(name)? {
+            Literal::Numeric(n) => Ok(n.parse::<f32>()?),
+            lit => bail!("Expected a number for {}, got {:?}", name, lit),
+        }
+    };
+    let logical = |name: &str| -> TractResult<bool> {
+        match arg(name)? {
+            Literal::Logical(b) => Ok(b),
+            lit => bail!("Expected a logical for {}, got {:?}", name, lit),
+        }
+    };
+    let bits = number("bits")? as usize;
+    match &*invocation.id {
+        "zero_point_linear_quantize" => Ok(QuantFormat {
+            bits,
+            signed: logical("signed")?,
+            zero_point: number("zero_point")? as i32,
+            scale: number("scale")?,
+        }),
+        "linear_quantize" | "min_max_linear_quantize" => {
+            let (signed, symmetric) = if invocation.id == "linear_quantize" {
+                (false, false)
+            } else {
+                (logical("signed")?, logical("symmetric")?)
+            };
+            let (min, max) = (number("min")?, number("max")?);
+            let r = 2f32.powi(bits as i32) - 1.0 - (signed && symmetric) as usize as f32;
+            let p =
+                if signed { 2f32.powi(bits as i32 - 1) - symmetric as usize as f32 } else { 0.0 };
+            let scale = (max - min) / r;
+            Ok(QuantFormat { bits, signed, zero_point: ((-min / scale).round() - p) as i32, scale })
+        }
+        _ => bail!("Unsupported quantization fragment {}", invocation.id),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn min_max() {
+        let q = parse_quantization(
+            &crate::framework::stdlib(),
+            "\"a\": linear_quantize(min = -1.28, max = 1.27, bits = 8);",
+        )
+        .unwrap();
+        assert_eq!((q["a"].bits, q["a"].signed, q["a"].zero_point), (8, false, 128));
+        assert!((q["a"].scale - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn write_and_parse() {
+        let mut formats = HashMap::new();
+        formats.insert("a".to_string(), QuantFormat::new(DatumType::I8, -3, 0.25).unwrap());
+        formats.insert("b".to_string(), QuantFormat::new(DatumType::U8, 128, 0.1).unwrap());
+        let mut doc = vec![];
+        write_quantization(&mut doc, &formats).unwrap();
+        let parsed =
+            parse_quantization(&crate::framework::stdlib(), std::str::from_utf8(&doc).unwrap())
+                .unwrap();
+        assert_eq!(parsed, formats);
+    }
+}
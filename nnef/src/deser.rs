@@ -75,10 +75,22 @@ impl<'mb> ModelBuilder<'mb> {
         for assignment in body {
             let identifiers = assignment.left.to_identifiers()?;
             self.naming_scopes.push(identifiers[0].to_string());
-            let values: TVec<OutletId> =
-                assignment.right.resolve(self).and_then(|v| v.to(self)).with_context(|| {
-                    format!("Plugging in assignement for {:?}", identifiers.join(", "))
-                })?;
+            let value = assignment.right.resolve(self).with_context(|| {
+                format!("Plugging in assignement for {:?}", identifiers.join(", "))
+            })?;
+            // sizes and other parameters computed in fragment bodies stay out of the graph
+            if identifiers.len() == 1 {
+                if let Value::Dim(_) | Value::Bool(_) | Value::Array(_) = value {
+                    if value.is_static() {
+                        self.scopes.last_mut().unwrap().insert(identifiers[0].to_string(), value);
+                        self.naming_scopes.pop();
+                        continue;
+                    }
+                }
+            }
+            let values: TVec<OutletId> = value.to(self).with_context(|| {
+                format!("Plugging in assignement for {:?}", identifiers.join(", "))
+            })?;
             if values.len() != identifiers.len() {
                 bail!(
                     "Assignement for {} received {} value(s).",
@@ -175,6 +187,22 @@ impl<'mb> ModelBuilder<'mb> {
         }
         self.model.wire_node(name, op, inputs).with_context(|| format!("inputs are {:?}", inputs))
     }
+
+    /// Quantization declared in graph.quant for a graph-level identifier.
+    pub fn quant_format(&self, id: &str) -> Option<QuantFormat> {
+        if self.scopes.len() != 1 {
+            return None;
+        }
+        self.proto_model.quantization.as_ref()?.get(id).cloned()
+    }
+
+    /// Quantization declared for the graph-level identifier currently being assigned.
+    pub fn current_quant_format(&self) -> Option<QuantFormat> {
+        if self.naming_scopes.len() != 1 {
+            return None;
+        }
+        self.quant_format(&self.naming_scopes[0])
+    }
 }
 
 #[derive(Clone)]
@@ -195,6 +223,17 @@ impl<'a> ResolvedInvocation<'a> {
         v.to::<T>(builder).with_context(|| format!("Converting argument `{}' from {:?}", name, v))
     }
 
+    pub fn named_arg_quant_format(
+        &self,
+        builder: &ModelBuilder,
+        name: &str,
+    ) -> Option<QuantFormat> {
+        match self.get_named_arg(name)?.as_ref() {
+            RValue::Identifier(id) => builder.quant_format(id),
+            _ => None,
+        }
+    }
+
     pub fn named_arg(&self, name: &str) -> TractResult<Cow<RValue>> {
         self.get_named_arg(name).ok_or_else(|| format_err!("expected argument {}", name))
     }
@@ -260,7 +299,96 @@ impl RValue {
                     .ok_or_else(|| format_err!("No value for name {}", id))?;
                 Ok(outlet)
             }
+            RValue::Invocation(inv) if inv.id == "length_of" || inv.id == "range_of" => {
+                let array = inv.arguments[0].rvalue.resolve(builder)?;
+                let len = match array {
+                    Value::Array(items) => items.len(),
+                    _ => bail!("{} expects an array, got {:?}", inv.id, array),
+                };
+                if inv.id == "length_of" {
+                    Ok(Value::Dim(len.to_dim()))
+                } else {
+                    Ok(Value::Array((0..len).map(|i| Value::Dim(i.to_dim())).collect()))
+                }
+            }
             RValue::Invocation(inv) => builder.wire_invocation(inv),
+            RValue::Binary(left, op, right)
+                if left.is_static(builder) && right.is_static(builder) =>
+            {
+                let left = left.resolve(builder)?;
+                let right = right.resolve(builder)?;
+                static_binary(&left, op, &right)
+            }
+            RValue::Unary(op, rv) if rv.is_static(builder) => match (&**op, rv.resolve(builder)?) {
+                ("-", Value::Dim(d)) => Ok(Value::Dim(-d)),
+                ("-", Value::Scalar(f)) => Ok(Value::Scalar(-f)),
+                ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+                (op, v) => bail!("Can not apply {} to {:?}", op, v),
+            },
+            RValue::Subscript(array, subscript) => {
+                let items = match array.resolve(builder)? {
+                    Value::Array(items) => items,
+                    v => bail!("Subscript only apply to arrays, got {:?}", v),
+                };
+                match &**subscript {
+                    Subscript::Single(ix) => {
+                        let ix: usize = ix.resolve(builder)?.to(builder)?;
+                        items.get(ix).cloned().context("Subscript out of range")
+                    }
+                    Subscript::Range(start, end) => {
+                        let start: usize = start
+                            .as_ref()
+                            .map(|s| s.resolve(builder)?.to(builder))
+                            .transpose()?
+                            .unwrap_or(0);
+                        let end: usize = end
+                            .as_ref()
+                            .map(|e| e.resolve(builder)?.to(builder))
+                            .transpose()?
+                            .unwrap_or(items.len());
+                        Ok(Value::Array(items[start..end].to_vec()))
+                    }
+                }
+            }
+            RValue::Comprehension(comp) => {
+                let mut iters = vec![];
+                for (name, rv) in &comp.loop_iters {
+                    match rv.resolve(builder)? {
+                        Value::Array(items) => iters.push((name, items)),
+                        v => bail!("Comprehension iterates over arrays, got {:?}", v),
+                    }
+                }
+                let len = iters.iter().map(|(_, items)| items.len()).min().unwrap_or(0);
+                let mut result = vec![];
+                for i in 0..len {
+                    for (name, items) in &iters {
+                        builder
+                            .scopes
+                            .last_mut()
+                            .unwrap()
+                            .insert(name.to_string(), items[i].clone());
+                    }
+                    let keep = if let Some(filter) = &comp.filter {
+                        filter.resolve(builder)?.to(builder)?
+                    } else {
+                        true
+                    };
+                    if keep {
+                        result.push(comp.yields.resolve(builder)?);
+                    }
+                }
+                for (name, _) in &iters {
+                    builder.scopes.last_mut().unwrap().remove(*name);
+                }
+                Ok(Value::Array(result))
+            }
+            RValue::IfThenElse(ite) => {
+                if ite.cond.resolve(builder)?.to(builder)? {
+                    ite.then.resolve(builder)
+                } else {
+                    ite.otherwise.resolve(builder)
+                }
+            }
             RValue::Binary(left, op, right) => {
                 let op = match &**op {
                     "+" => "add",
@@ -311,11 +439,84 @@ impl RValue {
                     .map(|i| RValue::Literal(i.clone()).resolve(builder))
                     .collect::<TractResult<_>>()?,
             )),
-            _ => panic!("{:?}", self),
+            _ => bail!("Unsupported expression {:?}", self),
+        }
+    }
+
+    /// True if the expression can be computed without wiring anything in the model (shapes,
+    /// sizes, and other parameters).
+    fn is_static(&self, builder: &ModelBuilder) -> bool {
+        match self {
+            RValue::Literal(_) | RValue::Comprehension(_) => true,
+            RValue::Identifier(id) => builder
+                .scopes
+                .last()
+                .and_then(|scope| scope.get(id))
+                .map(|v| v.is_static())
+                .unwrap_or(false),
+            RValue::Array(items) | RValue::Tuple(items) => {
+                items.iter().all(|i| i.is_static(builder))
+            }
+            RValue::Binary(left, _, right) => left.is_static(builder) && right.is_static(builder),
+            RValue::Unary(_, rv) => rv.is_static(builder),
+            RValue::Subscript(array, _) => array.is_static(builder),
+            RValue::IfThenElse(ite) => {
+                ite.cond.is_static(builder)
+                    && ite.then.is_static(builder)
+                    && ite.otherwise.is_static(builder)
+            }
+            RValue::Invocation(inv) => inv.id == "length_of" || inv.id == "range_of",
         }
     }
 }
 
+fn static_binary(left: &Value, op: &str, right: &Value) -> TractResult<Value> {
+    use Value::*;
+    let v = match (left, op, right) {
+        (Dim(a), "+", Dim(b)) => Dim(a.clone() + b),
+        (Dim(a), "-", Dim(b)) => Dim(a.clone() - b),
+        (Dim(a), "*", Dim(b)) => Dim(a.clone() * b.to_i64()?),
+        (Dim(a), "/", Dim(b)) => Dim((a.to_i64()? / b.to_i64()?).to_dim()),
+        (Dim(a), "^", Dim(b)) => Dim(a.to_i64()?.pow(b.to_i64()? as u32).to_dim()),
+        (Dim(a), op, Dim(b)) => {
+            let (a, b) = (a.to_i64()?, b.to_i64()?);
+            match op {
+                "<" => Bool(a < b),
+                ">" => Bool(a > b),
+                "<=" => Bool(a <= b),
+                ">=" => Bool(a >= b),
+                "==" => Bool(a == b),
+                "!=" => Bool(a != b),
+                _ => bail!("Unknown binary operator: {}", op),
+            }
+        }
+        (Dim(a), _, Scalar(_)) => return static_binary(&Scalar(a.to_i64()? as f32), op, right),
+        (Scalar(_), _, Dim(b)) => return static_binary(left, op, &Scalar(b.to_i64()? as f32)),
+        (Scalar(a), op, Scalar(b)) => match op {
+            "+" => Scalar(a + b),
+            "-" => Scalar(a - b),
+            "*" => Scalar(a * b),
+            "/" => Scalar(a / b),
+            "^" => Scalar(a.powf(*b)),
+            "<" => Bool(a < b),
+            ">" => Bool(a > b),
+            "<=" => Bool(a <= b),
+            ">=" => Bool(a >= b),
+            "==" => Bool(a == b),
+            "!=" => Bool(a != b),
+            _ => bail!("Unknown binary operator: {}", op),
+        },
+        (Bool(a), "&&", Bool(b)) => Bool(*a && *b),
+        (Bool(a), "||", Bool(b)) => Bool(*a || *b),
+        (Array(a), "+", Array(b)) => Array(a.iter().chain(b.iter()).cloned().collect()),
+        (Array(a), "*", Dim(n)) => {
+            Array((0..n.to_usize()?).flat_map(|_| a.iter().cloned()).collect())
+        }
+        _ => bail!("Can not compute {:?} {} {:?}", left, op, right),
+    };
+    Ok(v)
+}
+
 #[derive(Clone, Debug)]
 pub enum Value {
     Tensor(Arc<Tensor>),
@@ -329,6 +530,14 @@ pub enum Value {
 }
 
 impl Value {
+    fn is_static(&self) -> bool {
+        match self {
+            Value::Tensor(_) | Value::Wire(_) => false,
+            Value::Array(items) | Value::Tuple(items) => items.iter().all(|i| i.is_static()),
+            _ => true,
+        }
+    }
+
     pub fn to<T>(&self, builder: &mut ModelBuilder) -> TractResult<T>
     where
         T: CoerceFrom<Value>,
@@ -54,6 +54,18 @@ impl Nnef {
         header.set_cksum();
         ar.append(&header, &mut &*graph_data)?;
 
+        if let Some(quantization) = &proto_model.quantization {
+            let mut quant_data = vec![];
+            crate::ast::quant::write_quantization(&mut quant_data, quantization)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_path("graph.quant")?;
+            header.set_size(quant_data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(now.as_secs());
+            header.set_cksum();
+            ar.append(&header, &mut &*quant_data)?;
+        }
+
         for (label, t) in &proto_model.tensors {
             let label = label.to_string() + ".dat";
             let filename = std::path::Path::new(&label);
@@ -84,6 +96,10 @@ impl Nnef {
         std::fs::create_dir_all(path)?;
         let mut graph_nnef = std::fs::File::create(path.join("graph.nnef"))?;
         crate::ast::dump::Dumper::new(&mut graph_nnef).document(&proto_model.doc)?;
+        if let Some(quantization) = &proto_model.quantization {
+            let mut graph_quant = std::fs::File::create(path.join("graph.quant"))?;
+            crate::ast::quant::write_quantization(&mut graph_quant, quantization)?;
+        }
         for (label, t) in &proto_model.tensors {
             let label = label.to_string() + ".dat";
             std::fs::create_dir_all(path.join(&label).parent().unwrap())?;
@@ -108,6 +124,7 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
             return self.proto_model_for_read(&mut f);
         }
         let mut text: Option<String> = None;
+        let mut quant: Option<String> = None;
         let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
         for entry in walkdir::WalkDir::new(path) {
             let entry =
@@ -118,15 +135,18 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
                 .skip(path.components().count())
                 .collect::<std::path::PathBuf>();
             let mut stream = std::fs::File::open(entry.path())?;
-            read_stream(&subpath, &mut stream, &mut text, &mut tensors)?;
+            read_stream(&subpath, &mut stream, &mut text, &mut quant, &mut tensors)?;
         }
         let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
         let doc = crate::ast::parse::parse_document(&text)?;
-        Ok(ProtoModel { doc, tensors })
+        let quantization =
+            quant.map(|q| crate::ast::quant::parse_quantization(&self.stdlib, &q)).transpose()?;
+        Ok(ProtoModel { doc, tensors, quantization })
     }
 
     fn proto_model_for_read(&self, reader: &mut dyn std::io::Read) -> TractResult<ProtoModel> {
         let mut text: Option<String> = None;
+        let mut quant: Option<String> = None;
         let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
         let mut buffer = vec![0u8; 2];
         reader.read_exact(&mut buffer)?;
@@ -146,11 +166,13 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
         for entry in tar.entries()? {
             let mut entry = entry?;
             let path = entry.path()?.to_path_buf();
-            read_stream(&path, &mut entry, &mut text, &mut tensors)?;
+            read_stream(&path, &mut entry, &mut text, &mut quant, &mut tensors)?;
         }
         let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
         let doc = crate::ast::parse::parse_document(&text)?;
-        Ok(ProtoModel { doc, tensors })
+        let quantization =
+            quant.map(|q| crate::ast::quant::parse_quantization(&self.stdlib, &q)).transpose()?;
+        Ok(ProtoModel { doc, tensors, quantization })
     }
 
     fn model_for_proto_model(&self, proto: &ProtoModel) -> TractResult<TypedModel> {
@@ -162,12 +184,17 @@ fn read_stream<R: std::io::Read>(
     path: &std::path::Path,
     reader: &mut R,
     text: &mut Option<String>,
+    quant: &mut Option<String>,
     tensors: &mut Vec<(String, Arc<Tensor>)>,
 ) -> TractResult<()> {
     if path.file_name().map(|n| n == "graph.nnef").unwrap_or(false) {
         let mut t = String::new();
         reader.read_to_string(&mut t)?;
         *text = Some(t);
+    } else if path.file_name().map(|n| n == "graph.quant").unwrap_or(false) {
+        let mut q = String::new();
+        reader.read_to_string(&mut q)?;
+        *quant = Some(q);
     } else if path.extension().map(|e| e == "dat").unwrap_or(false) {
         let mut path = path.to_path_buf();
         path.set_extension("");
@@ -179,3 +206,53 @@ fn read_stream<R: std::io::Read>(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+    use tract_core::ops::nn::DataFormat;
+    use tract_core::ops::quant::QParams;
+
+    #[test]
+    fn quantized_conv_round_trip() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("input", TypedFact::dt_shape(i8::datum_type(), [1, 2, 3, 3]))?;
+        let kernel = tensor4(&[[[[1i8]], [[-2]]], [[[3]], [[4]]]]);
+        let q_params = QParams::new(i8::datum_type())
+            .with_zero_point_b(&rctensor0(2i8))
+            .with_zero_point_c(&rctensor0(-1i8))
+            .with_scale_factor(0.25);
+        let pool_spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(1, 1), PaddingSpec::Valid, None, None, Some(2));
+        let conv = ConvUnary::new(
+            pool_spec,
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            1,
+            Some(rctensor1(&[5i32, -5])),
+            Some(q_params),
+        );
+        let conv = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&conv)?;
+
+        let nnef = crate::nnef();
+        let mut buffer = vec![];
+        nnef.write_to_tar(&model, &mut buffer)?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let conv = reloaded.node(reloaded.output_outlets()?[0].node);
+        let q_params = conv.op_as::<ConvUnary>().unwrap().q_params.as_ref().unwrap();
+        assert_eq!(q_params.c_datum_type, i8::datum_type());
+        assert_eq!(q_params.scale_factor, Some(0.25));
+
+        let input = tract_ndarray::Array4::from_shape_fn((1, 2, 3, 3), |(_, c, h, w)| {
+            (c * 9 + h * 3 + w) as i8 - 8
+        })
+        .into_tensor();
+        let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+        let found = reloaded.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(expected, found);
+        Ok(())
+    }
+}
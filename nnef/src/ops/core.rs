@@ -5,10 +5,13 @@ mod broadcast;
 mod cast;
 mod downsample;
 mod gather;
+mod layer_norm;
 mod one_hot;
 mod reduce;
 mod scan;
+mod scatter;
 mod source;
+mod topk;
 
 
 pub fn register(registry: &mut Registry) {
@@ -30,8 +33,11 @@ pub fn register(registry: &mut Registry) {
     cast::register(registry);
     downsample::register(registry);
     gather::register(registry);
+    layer_norm::register(registry);
     one_hot::register(registry);
     reduce::register(registry);
     scan::register(registry);
+    scatter::register(registry);
     source::register(registry);
+    topk::register(registry);
 }
@@ -0,0 +1,36 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::LayerNorm;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<LayerNorm>(), ser_layer_norm);
+    registry.register_primitive(
+        "tract_core_layer_norm",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.array().named("axes"),
+            TypeName::Scalar.named("epsilon").default(1e-5),
+        ],
+        de_layer_norm,
+    );
+}
+
+fn ser_layer_norm(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<LayerNorm>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_layer_norm",
+        &[input],
+        &[("axes", ints(&op.axes)), ("epsilon", numeric(op.epsilon))],
+    )))
+}
+
+fn de_layer_norm(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axes = invocation.named_arg_as(builder, "axes")?;
+    let epsilon = invocation.named_arg_as(builder, "epsilon")?;
+    builder.wire(LayerNorm { axes, epsilon }, &[input])
+}
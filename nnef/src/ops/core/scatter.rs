@@ -0,0 +1,74 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::array::{ScatterElements, ScatterNd, ScatterReduction};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<ScatterElements>(), ser_scatter_elements);
+    registry.register_primitive(
+        "tract_core_scatter_elements",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("indices"),
+            TypeName::Scalar.tensor().named("updates"),
+            TypeName::Integer.named("axis"),
+            TypeName::String.named("reduction").default("none"),
+        ],
+        de_scatter_elements,
+    );
+    registry.register_dumper(TypeId::of::<ScatterNd>(), ser_scatter_nd);
+    registry.register_primitive(
+        "tract_core_scatter_nd",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("indices"),
+            TypeName::Scalar.tensor().named("updates"),
+            TypeName::String.named("reduction").default("none"),
+        ],
+        de_scatter_nd,
+    );
+}
+
+fn ser_scatter_elements(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ScatterElements>().unwrap();
+    let inputs = ast.shaped_inputs(node)?;
+    Ok(Some(invocation(
+        "tract_core_scatter_elements",
+        &inputs,
+        &[("axis", numeric(op.axis)), ("reduction", string(op.reduction.as_str()))],
+    )))
+}
+
+fn de_scatter_elements(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let indices = invocation.named_arg_as(builder, "indices")?;
+    let updates = invocation.named_arg_as(builder, "updates")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let reduction =
+        ScatterReduction::parse(&invocation.named_arg_as::<String>(builder, "reduction")?)?;
+    builder.wire(ScatterElements { axis, reduction }, &[input, indices, updates])
+}
+
+fn ser_scatter_nd(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ScatterNd>().unwrap();
+    let inputs = ast.shaped_inputs(node)?;
+    Ok(Some(invocation(
+        "tract_core_scatter_nd",
+        &inputs,
+        &[("reduction", string(op.reduction.as_str()))],
+    )))
+}
+
+fn de_scatter_nd(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let indices = invocation.named_arg_as(builder, "indices")?;
+    let updates = invocation.named_arg_as(builder, "updates")?;
+    let reduction =
+        ScatterReduction::parse(&invocation.named_arg_as::<String>(builder, "reduction")?)?;
+    builder.wire(ScatterNd { reduction }, &[input, indices, updates])
+}
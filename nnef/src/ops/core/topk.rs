@@ -0,0 +1,50 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::array::TopK;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<TopK>(), ser_topk);
+    registry.register_primitive(
+        "tract_core_topk",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.tensor().named("k"),
+            TypeName::Integer.named("axis"),
+            TypeName::Logical.named("largest").default(true),
+            TypeName::Logical.named("sorted").default(true),
+        ],
+        de_topk,
+    );
+}
+
+fn ser_topk(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<TopK>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let k = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_core_topk",
+        &[input, k],
+        &[
+            ("axis", numeric(op.axis)),
+            ("largest", logical(op.largest)),
+            ("sorted", logical(op.sorted)),
+        ],
+    )))
+}
+
+fn de_topk(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let k = invocation.named_arg_as(builder, "k")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let largest = invocation.named_arg_as(builder, "largest")?;
+    let sorted = invocation.named_arg_as(builder, "sorted")?;
+    let k_dim_hint = if builder.model.outlet_fact(k)?.konst.is_none() {
+        Some(builder.model.symbol_table.new_with_prefix("K").into())
+    } else {
+        None
+    };
+    builder.wire(TopK { axis, largest, sorted, k_dim_hint }, &[input, k])
+}
@@ -13,7 +13,9 @@ pub fn external(
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
     let type_name = invocation.invocation.generic_type_name.unwrap_or(TypeName::Scalar);
-    let dt = if type_name == TypeName::Scalar {
+    let dt = if let Some(q) = builder.current_quant_format() {
+        q.datum_type()?
+    } else if type_name == TypeName::Scalar {
         f32::datum_type()
     } else if type_name == TypeName::Logical {
         bool::datum_type()
@@ -47,10 +49,12 @@ pub fn variable(
             shape
         );
     }
-    let tensor = if tensor.datum_type() == f32::datum_type() {
+    let dt = builder.current_quant_format().map(|q| q.datum_type()).transpose()?;
+    let dt = dt.unwrap_or(DatumType::F32);
+    let tensor = if tensor.datum_type() == dt {
         tensor.clone()
     } else {
-        tensor.cast_to::<f32>()?.into_owned().into_arc_tensor()
+        tensor.cast_to_dt(dt)?.into_owned().into_arc_tensor()
     };
     builder.wire(tract_core::ops::konst::Const::new(tensor), &[])
 }
@@ -135,6 +139,61 @@ pub fn slice(
     })
 }
 
+// fragment split<?>( value: tensor<?>, axis: integer, ratios: integer[] ) -> ( values: tensor<?>[] );
+pub fn split(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let value = invocation.named_arg_as(builder, "value")?;
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let ratios: TVec<usize> = invocation.named_arg_as(builder, "ratios")?;
+    let dim = builder.model.outlet_fact(value)?.shape[axis].to_usize()?;
+    let total = ratios.iter().sum::<usize>();
+    if total == 0 || dim % total != 0 {
+        bail!("Can not split axis of size {} with ratios {:?}", dim, ratios);
+    }
+    let mut start = 0;
+    let mut outputs = tvec!();
+    for r in ratios {
+        let end = start + r * dim / total;
+        let op = ops::array::Slice { axis, start: start.to_dim(), end: end.to_dim() };
+        outputs.push(builder.wire(op, &[value])?[0]);
+        start = end;
+    }
+    Ok(outputs)
+}
+
+// fragment stack<?>( values: tensor<?>[], axis: integer ) -> ( value: tensor<?> );
+pub fn stack(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let values: TVec<OutletId> = invocation.named_arg_as(builder, "values")?;
+    let values = values
+        .iter()
+        .map(|v| Ok(builder.wire(AxisOp::Add(axis), &[*v])?[0]))
+        .collect::<TractResult<TVec<OutletId>>>()?;
+    builder.wire(ops::array::TypedConcat::concat_vars(axis, values.len()), &values)
+}
+
+// fragment unstack<?>( value: tensor<?>, axis: integer ) -> ( values: tensor<?>[] );
+pub fn unstack(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let value = invocation.named_arg_as(builder, "value")?;
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let dim = builder.model.outlet_fact(value)?.shape[axis].to_usize()?;
+    (0..dim)
+        .map(|ix| {
+            let op = ops::array::Slice { axis, start: ix.to_dim(), end: (ix + 1).to_dim() };
+            let wire = builder.wire(op, &[value])?;
+            Ok(builder.wire(AxisOp::Rm(axis), &wire)?[0])
+        })
+        .collect()
+}
+
 // fragment squeeze<?>( input: tensor<?>, axes: integer[] ) -> ( output: tensor<?> );
 pub fn squeeze(
     builder: &mut ModelBuilder,
@@ -216,7 +275,7 @@ pub fn conv(
     }
     let mut group = invocation.named_arg_as(builder, "groups")?;
     if group == 0 {
-        group = kernel.shape()[0]
+        group = input_fact.shape[1].to_usize()?
     }
     if input_fact.shape[1] != kernel.shape()[1].to_dim() * group {
         bail!("Convolution input and kernel channels (second axis in both) must match. Got {:?} and {:?}.", input_fact, kernel);
@@ -255,33 +314,177 @@ pub fn conv(
 
     let border: String = invocation.named_arg_as(builder, "border")?;
     assert_eq!(border, "constant");
-    let op = ConvUnary::new(pool_spec, KernelFormat::OIHW, kernel.clone(), group, bias, None);
+    let q_params = qparams(
+        builder,
+        invocation,
+        ("filter", kernel.datum_type()),
+        ("input", input_fact.datum_type),
+    )?;
+    let op = ConvUnary::new(pool_spec, KernelFormat::OIHW, kernel.clone(), group, bias, q_params);
     builder.wire(op, &[input])
 }
 
+/*
+fragment deconv( input: tensor<scalar>, filter: tensor<scalar>,
+bias: tensor<scalar> = 0.0, border: string = 'constant',
+padding: (integer,integer)[] = [], stride: integer[] = [],
+dilation: integer[] = [], output_shape: integer[] = [], groups: integer = 1 )
+-> ( output: tensor<scalar> );
+*/
+
+pub fn deconv(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::deconv::DeconvUnary;
+    use ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+    use ops::nn::DataFormat;
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let kernel: Arc<Tensor> = invocation.named_arg_as(builder, "filter")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != kernel.rank() {
+        bail!(
+            "Deconvolution input expected as NCHW, filter as IOHW. Got {:?} and {:?}.",
+            input_fact,
+            kernel
+        );
+    }
+    let mut group = invocation.named_arg_as(builder, "groups")?;
+    if group == 0 {
+        group = kernel.shape()[0]
+    }
+    if input_fact.shape[1] != kernel.shape()[0].to_dim() {
+        bail!("Deconvolution input channels (second axis) and kernel first axis must match. Got {:?} and {:?}.", input_fact, kernel);
+    }
+    let geo_rank = kernel.rank() - 2;
+    let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
+    if dilation.len() != 0 && dilation.len() != geo_rank {
+        bail!("Deconvolution dilation only apply to spatial dimensions, so it should be of rank {}. Got {:?}", geo_rank, dilation)
+    }
+    let dilation = if dilation.len() > 0 { dilation } else { tvec!(1; geo_rank) };
+    let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
+    if stride.len() != 0 && stride.len() != geo_rank {
+        bail!("Deconvolution stride only apply to spatial dimensions, so it should be of rank {}. Got {:?}", geo_rank, stride)
+    }
+    let stride = if stride.len() > 0 { stride } else { tvec!(1; geo_rank) };
+    let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
+    let padding = if padding.len() == 0 {
+        PaddingSpec::SameUpper
+    } else {
+        let mut before = tvec!();
+        let mut after = tvec!();
+        for p in padding {
+            before.push(p[0]);
+            after.push(p[1]);
+        }
+        PaddingSpec::Explicit(before, after, false)
+    };
+    let output_channels = kernel.shape()[1] * group;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        kernel.shape()[2..].into(),
+        padding,
+        Some(dilation),
+        Some(stride),
+        Some(output_channels),
+    );
+    let output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    let adjustments = deconv_adjustments(&pool_spec, &input_fact.shape[2..], &output_shape)?;
+    let bias: Arc<Tensor> = invocation.named_arg_as(builder, "bias")?;
+    let bias: Option<Arc<Tensor>> = if bias.is_uniform()? && bias.cast_to_scalar::<f32>()? == 0.0 {
+        None
+    } else if bias.len() == 1 {
+        let scalar = bias.into_tensor().into_shape(&[])?;
+        Some(scalar.broadcast_scalar_to_shape(&[output_channels])?.into_arc_tensor())
+    } else {
+        Some(bias.into_tensor().into_shape(&[output_channels])?.into_arc_tensor())
+    };
+
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    assert_eq!(border, "constant");
+    let op = DeconvUnary::new(pool_spec, KernelFormat::OIHW, kernel, bias, adjustments, group);
+    builder.wire(op, &[input])
+}
+
+/// Extra output pixels a deconvolution must produce to reach the requested output_shape (if
+/// any).
+fn deconv_adjustments(
+    pool_spec: &ops::cnn::PoolSpec,
+    input_geo_shape: &[TDim],
+    output_shape: &[usize],
+) -> TractResult<TVec<usize>> {
+    let geo_rank = pool_spec.kernel_shape.len();
+    let mut adjustments = tvec!(0; geo_rank);
+    if !output_shape.is_empty() {
+        for ax in 0..geo_rank {
+            let computed = pool_spec.padding.compute_one_for_deconv(
+                ax,
+                &input_geo_shape[ax],
+                pool_spec.kernel_shape[ax],
+                pool_spec.dilation(ax),
+                pool_spec.stride(ax),
+                0,
+            );
+            adjustments[ax] = output_shape[output_shape.len() - geo_rank + ax]
+                .saturating_sub(computed.output.to_usize()?);
+        }
+    }
+    Ok(adjustments)
+}
+
+/// Pooling parameters may omit the batch and channel axes: prepend them.
+fn with_unit_leading_axes(values: TVec<usize>, rank: usize) -> TVec<usize> {
+    if values.len() + 2 == rank {
+        let mut full = tvec!(1, 1);
+        full.extend(values);
+        full
+    } else {
+        values
+    }
+}
+
+/// Pool spec from the NNEF pooling parameters, which cover all the input axes. Axes before
+/// first_geo_axis must not be pooled. The caller is responsible for giving the input an extra
+/// unit axis when first_geo_axis is 1 (pooling over channels).
 fn pool_spec_for_pools(
     builder: &mut ModelBuilder,
     invocation: &ResolvedInvocation,
     shape: &[usize],
+    first_geo_axis: usize,
 ) -> TractResult<ops::cnn::PoolSpec> {
     use ops::cnn::{PaddingSpec, PoolSpec};
     use ops::nn::DataFormat;
-    let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
-    if dilation.len() > 0 && (dilation.len() != shape.len() || dilation[0] != 1 || dilation[1] != 1)
+    let rank = shape.len();
+    if shape[..first_geo_axis].iter().any(|&s| s != 1) {
+        bail!("Pooling over the batch axis is not supported. Got size {:?}.", shape);
+    }
+    let dilation = with_unit_leading_axes(invocation.named_arg_as(builder, "dilation")?, rank);
+    if dilation.len() > 0
+        && (dilation.len() != rank || dilation[..first_geo_axis].iter().any(|&d| d != 1))
     {
         bail!("dilation should be like [1, 1, ... ]. Got dilation {:?}.", dilation);
     }
-    let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
-    if stride.len() > 0 && (stride.len() != shape.len() || stride[0] != 1 || stride[1] != 1) {
+    let stride = with_unit_leading_axes(invocation.named_arg_as(builder, "stride")?, rank);
+    if stride.len() > 0
+        && (stride.len() != rank || stride[..first_geo_axis].iter().any(|&s| s != 1))
+    {
         bail!("stride should be like [1, 1, ... ]. Got stride {:?}.", stride);
     }
     let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
     let padding = if padding.len() == 0 {
         PaddingSpec::SameUpper
     } else {
+        // padding covers all axes, or only the spatial ones
+        let skip = if padding.len() == rank {
+            first_geo_axis
+        } else if padding.len() == rank - 2 && first_geo_axis == 2 {
+            0
+        } else {
+            bail!("padding should cover all {} axes. Got {:?}.", rank, padding)
+        };
         let mut before = tvec!();
         let mut after = tvec!();
-        for p in padding {
+        for p in padding.iter().skip(skip) {
             before.push(p[0]);
             after.push(p[1]);
         }
@@ -289,10 +492,10 @@ fn pool_spec_for_pools(
     };
     Ok(PoolSpec::new(
         DataFormat::NCHW,
-        shape[2..].into(),
+        shape[first_geo_axis..].into(),
         padding,
-        if dilation.len() > 2 { Some(dilation[2..].into()) } else { None },
-        if stride.len() > 2 { Some(stride[2..].into()) } else { None },
+        if dilation.len() > 0 { Some(dilation[first_geo_axis..].into()) } else { None },
+        if stride.len() > 0 { Some(stride[first_geo_axis..].into()) } else { None },
         None,
     ))
 }
@@ -308,8 +511,9 @@ pub fn max_pool_with_index(
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
     let input = invocation.named_arg_as(builder, "input")?;
-    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let size = invocation.named_arg_as(builder, "size")?;
     let input_fact = builder.model.outlet_fact(input)?;
+    let size = with_unit_leading_axes(size, input_fact.rank());
     if input_fact.rank() != size.len() {
         bail!(
             "Max pool input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
@@ -317,9 +521,23 @@ pub fn max_pool_with_index(
             size
             );
     }
+    let input_shape = input_fact.shape.to_tvec();
     let border: String = invocation.named_arg_as(builder, "border")?;
-    assert_eq!(border, "ignore");
-    let pool_spec = pool_spec_for_pools(builder, invocation, &size)?;
+    let pool_spec = pool_spec_for_pools(builder, invocation, &size, 2)?;
+    // border only matters if the pooling window can get out of the input
+    let unpadded = pool_spec
+        .padding
+        .compute(
+            &input_shape[2..],
+            &pool_spec.kernel_shape,
+            &pool_spec.dilations(),
+            &pool_spec.strides(),
+        )
+        .iter()
+        .all(|d| d.pad_before == 0.to_dim() && d.pad_after == 0.to_dim());
+    if border != "ignore" && !unpadded {
+        bail!("Unsupported border mode for max pool: {}", border);
+    }
     let op = ops::cnn::MaxPool { pool_spec, with_index_outputs: Some(i64::datum_type()) };
     builder.wire(op, &[input])
 }
@@ -335,26 +553,313 @@ pub fn sum_pool(
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
     let input = invocation.named_arg_as(builder, "input")?;
-    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let size = invocation.named_arg_as(builder, "size")?;
     let input_fact = builder.model.outlet_fact(input)?;
+    let size = with_unit_leading_axes(size, input_fact.rank());
     if input_fact.rank() != size.len() {
         bail!(
-            "Max pool input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            "Box input expected as NCHW, and \"size\" paramater must be [ 1, c, x, y ]. Got {:?}, and {:?}",
             input_fact,
             size
             );
     }
     let border: String = invocation.named_arg_as(builder, "border")?;
-    assert_eq!(border, "ignore");
-    let pool_spec = pool_spec_for_pools(builder, invocation, &size)?;
+    let count_include_pad = match &*border {
+        "ignore" => false,
+        "constant" => true,
+        _ => bail!("Unsupported border mode for box: {}", border),
+    };
+    // a window across channels (local response normalization) pools a unit channel input
+    let across_channels = size[1] != 1;
+    let pool_spec = pool_spec_for_pools(builder, invocation, &size, 2 - across_channels as usize)?;
     let op = ops::cnn::SumPool {
         pool_spec,
-        count_include_pad: false,
+        count_include_pad,
         normalize: invocation.named_arg_as(builder, "normalize")?,
     };
+    if across_channels {
+        let wire = builder.wire(AxisOp::Add(1), &[input])?;
+        let wire = builder.wire(op, &wire)?;
+        builder.wire(AxisOp::Rm(1), &wire)
+    } else {
+        builder.wire(op, &[input])
+    }
+}
+
+/*
+ * fragment debox( input: tensor<scalar>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [],
+ *   output_shape: integer[] = [], normalize: logical = false ) -> ( output: tensor<scalar> );
+ *
+ * Wired as a depthwise deconvolution with a constant kernel.
+ */
+pub fn debox(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::deconv::DeconvUnary;
+    use ops::cnn::KernelFormat;
+    let input = invocation.named_arg_as(builder, "input")?;
+    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != size.len() {
+        bail!(
+            "Debox input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            size
+        );
+    }
+    let normalize: bool = invocation.named_arg_as(builder, "normalize")?;
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    if border != "constant" && (border != "ignore" || normalize) {
+        bail!("Unsupported border mode for debox: {}", border);
+    }
+    let channels = input_fact.shape[1].to_usize()?;
+    let mut pool_spec = pool_spec_for_pools(builder, invocation, &size, 2)?;
+    pool_spec.output_channel_override = Some(channels);
+    let mut kernel_shape = tvec!(channels, 1);
+    kernel_shape.extend(size[2..].iter().copied());
+    let value = if normalize { 1.0 / size.iter().product::<usize>() as f32 } else { 1.0 };
+    let kernel = tensor0(value)
+        .cast_to_dt(input_fact.datum_type)?
+        .broadcast_scalar_to_shape(&kernel_shape)?;
+    let output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    let adjustments = deconv_adjustments(&pool_spec, &input_fact.shape[2..], &output_shape)?;
+    let op = DeconvUnary::new(
+        pool_spec,
+        KernelFormat::OIHW,
+        kernel.into_arc_tensor(),
+        None,
+        adjustments,
+        channels,
+    );
     builder.wire(op, &[input])
 }
 
+/*
+ * fragment argmax_pool( input: tensor<scalar>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [] )
+ * -> ( index: tensor<integer> );
+ *
+ * As for max_pool_with_index, indices are flat positions in the spatial plane of the input.
+ */
+pub fn argmax_pool(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let size = invocation.named_arg_as(builder, "size")?;
+    let input_fact = builder.model.outlet_fact(input)?;
+    let size = with_unit_leading_axes(size, input_fact.rank());
+    if input_fact.rank() != size.len() {
+        bail!(
+            "Argmax pool input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            size
+        );
+    }
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    if border != "ignore" {
+        bail!("Unsupported border mode for argmax_pool: {}", border);
+    }
+    let pool_spec = pool_spec_for_pools(builder, invocation, &size, 2)?;
+    let op = ops::cnn::MaxPool { pool_spec, with_index_outputs: Some(i64::datum_type()) };
+    Ok(tvec!(builder.wire(op, &[input])?[1]))
+}
+
+/// Reshape the spatial axes of a NCHW wire into a single one.
+fn flatten_spatial(builder: &mut ModelBuilder, wire: OutletId) -> TractResult<OutletId> {
+    let shape = builder.model.outlet_fact(wire)?.shape.to_tvec();
+    let flat: TDim = shape[2..].iter().maybe_product()?;
+    Ok(builder.wire(AxisOp::Reshape(2, shape[2..].into(), tvec!(flat)), &[wire])?[0])
+}
+
+/*
+ * fragment sample( input: tensor<scalar>, index: tensor<integer>, size: integer[],
+ *   border: string = 'constant', padding: (integer,integer)[] = [], stride: integer[] = [],
+ *   dilation: integer[] = [] ) -> ( output: tensor<scalar> );
+ */
+pub fn sample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let index = invocation.named_arg_as(builder, "index")?;
+    let index_shape = builder.model.outlet_fact(index)?.shape.to_tvec();
+    let flat_input = flatten_spatial(builder, input)?;
+    let flat_index = flatten_spatial(builder, index)?;
+    let wire = builder.wire(ops::array::GatherElements { axis: 2 }, &[flat_input, flat_index])?;
+    let flat = builder.model.outlet_fact(wire[0])?.shape[2].clone();
+    builder.wire(AxisOp::Reshape(2, tvec!(flat), index_shape[2..].into()), &wire)
+}
+
+/*
+ * fragment desample( input: tensor<scalar>, index: tensor<integer>, size: integer[],
+ *   border: string = 'constant', padding: (integer,integer)[] = [], stride: integer[] = [],
+ *   dilation: integer[] = [], output_shape: integer[] = [] ) -> ( output: tensor<scalar> );
+ */
+pub fn desample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::array::{ScatterElements, ScatterReduction};
+    let input = invocation.named_arg_as(builder, "input")?;
+    let index = invocation.named_arg_as(builder, "index")?;
+    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != size.len() {
+        bail!(
+            "Desample input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            size
+        );
+    }
+    let mut output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    if output_shape.is_empty() {
+        let pool_spec = pool_spec_for_pools(builder, invocation, &size, 2)?;
+        output_shape =
+            input_fact.shape.as_concrete().context("Desample expects a concrete input")?[..2]
+                .into();
+        for ax in 0..size.len() - 2 {
+            let computed = pool_spec.padding.compute_one_for_deconv(
+                ax,
+                &input_fact.shape[2 + ax],
+                pool_spec.kernel_shape[ax],
+                pool_spec.dilation(ax),
+                pool_spec.stride(ax),
+                0,
+            );
+            output_shape.push(computed.output.to_usize()?);
+        }
+    }
+    let flat_len = output_shape[2..].iter().product::<usize>();
+    let zeros =
+        Tensor::zero_dt(input_fact.datum_type, &[output_shape[0], output_shape[1], flat_len])?;
+    let zeros = builder.wire(ops::konst::Const::new(zeros.into_arc_tensor()), &[])?[0];
+    let flat_input = flatten_spatial(builder, input)?;
+    let flat_index = flatten_spatial(builder, index)?;
+    let wire = builder.wire(
+        ScatterElements { axis: 2, reduction: ScatterReduction::Add },
+        &[zeros, flat_index, flat_input],
+    )?;
+    let spatial = output_shape[2..].iter().map(|d| d.to_dim()).collect();
+    builder.wire(AxisOp::Reshape(2, tvec!(flat_len.to_dim()), spatial), &wire)
+}
+
+/*
+ * fragment multilinear_upsample( input: tensor<scalar>, factor: integer[],
+ *   method: string = 'symmetric', border: string = 'replicate' ) -> ( output: tensor<scalar> );
+ *
+ * Wired as a depthwise deconvolution with a (separable) linear interpolation kernel. For each
+ * axis, input j contributes to output i with weight max(0, 1 - |x(i) - j|), x(i) being the
+ * position of output i in input coordinates.
+ */
+pub fn multilinear_upsample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::array::{Pad, PadMode};
+    use ops::cnn::deconv::DeconvUnary;
+    use ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+    use ops::nn::DataFormat;
+    let mut wire = invocation.named_arg_as(builder, "input")?;
+    let factor: TVec<usize> = invocation.named_arg_as(builder, "factor")?;
+    let method: String = invocation.named_arg_as(builder, "method")?;
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    let input_fact = builder.model.outlet_fact(wire)?.clone();
+    if input_fact.rank() != factor.len() + 2 {
+        bail!("Upsample input expected as NCHW, got {:?} for factor {:?}", input_fact, factor);
+    }
+    let channels = input_fact.shape[1].to_usize()?;
+    let replicate = match &*border {
+        "replicate" => true,
+        "constant" => false,
+        _ => bail!("Unsupported border mode for multilinear_upsample: {}", border),
+    };
+    if replicate {
+        let mut pads = vec![(0, 0), (0, 0)];
+        pads.extend(factor.iter().map(|_| (1, 1)));
+        wire = builder.wire(Pad::new(pads, PadMode::Edge), &[wire])?[0];
+    }
+    let mut weights = tvec!();
+    let mut before = tvec!();
+    let mut after = tvec!();
+    for &f in &factor {
+        // kernel center, and output pixels to crop before
+        let (pad, center) = match &*method {
+            "asymmetric" => (f - 1, (f - 1) as f32),
+            "symmetric" => ((f + 1) / 2, ((f + 1) / 2) as f32 + (f as f32 - 1.0) / 2.0),
+            _ => bail!("Unsupported method for multilinear_upsample: {}", method),
+        };
+        let len = (center + f as f32).ceil() as usize;
+        weights.push(
+            (0..len)
+                .map(|k| (1.0 - (k as f32 - center).abs() / f as f32).max(0.0))
+                .collect::<Vec<f32>>(),
+        );
+        // the natural output is (n - 1) * f + len, we keep n * f of it
+        let shift = if replicate { f } else { 0 };
+        before.push(pad + shift);
+        after.push(len + shift - pad - f);
+    }
+    let mut kernel_shape = vec![channels, 1];
+    kernel_shape.extend(weights.iter().map(|w| w.len()));
+    let kernel = tract_ndarray::ArrayD::from_shape_fn(&*kernel_shape, |coords| {
+        weights.iter().enumerate().map(|(ax, w)| w[coords[2 + ax]]).product::<f32>()
+    })
+    .into_tensor()
+    .cast_to_dt(input_fact.datum_type)?
+    .into_owned();
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        weights.iter().map(|w| w.len()).collect(),
+        PaddingSpec::Explicit(before, after, false),
+        None,
+        Some(factor.clone()),
+        Some(channels),
+    );
+    let op = DeconvUnary::new(
+        pool_spec,
+        KernelFormat::OIHW,
+        kernel.into_arc_tensor(),
+        None,
+        tvec!(0; factor.len()),
+        channels,
+    );
+    builder.wire(op, &[wire])
+}
+
+/*
+ * fragment avg_roi_pool( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[] ) -> ( output: tensor<scalar> );
+ * fragment max_roi_pool(...), same parameters
+ * fragment roi_resample(...), same parameters plus method: string = 'symmetric'
+ */
+pub fn roi(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::{ResampleMethod, RoiMode, RoiPool};
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let batch_index = invocation.named_arg_as(builder, "batch_index")?;
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let mode = match &*invocation.invocation.id {
+        "avg_roi_pool" => RoiMode::AvgPool,
+        "max_roi_pool" => RoiMode::MaxPool,
+        _ => {
+            let method: String = invocation.named_arg_as(builder, "method")?;
+            RoiMode::Resample(match &*method {
+                "symmetric" => ResampleMethod::Symmetric,
+                "asymmetric" => ResampleMethod::Asymmetric,
+                "aligned" => ResampleMethod::Aligned,
+                _ => bail!("Unsupported method for roi_resample: {}", method),
+            })
+        }
+    };
+    builder.wire(RoiPool::new(output_size, mode), &[input, rois, batch_index])
+}
+
 /*
  *   fragment sum_reduce( input: tensor<scalar>, axes: integer[], normalize: logical = false ) -> ( output: tensor<scalar> );
  *   fragment max_reduce( input: tensor<scalar>, axes: integer[] ) -> ( output: tensor<scalar> );
@@ -395,6 +900,16 @@ pub fn reduce(
     bail!("Normalization only works with float items and known dimensions");
 }
 
+// fragment softmax( x: tensor<scalar>, axes: integer[] = [1] ) -> ( y: tensor<scalar> )
+pub fn softmax(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let x = invocation.named_arg_as(builder, "x")?;
+    let axes: TVec<usize> = invocation.named_arg_as(builder, "axes")?;
+    builder.wire(ops::nn::Softmax::new(axes), &[x])
+}
+
 /*
  * fragment matmul( A: tensor<scalar>, B: tensor<scalar>, transposeA: logical = false, transposeB: logical = false ) -> ( C: tensor<scalar> );
  */
@@ -402,11 +917,47 @@ pub fn matmul(
     builder: &mut ModelBuilder,
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
-    let a = invocation.named_arg_as(builder, "A")?;
-    let b = invocation.named_arg_as(builder, "B")?;
+    let a: OutletId = invocation.named_arg_as(builder, "A")?;
+    let b: OutletId = invocation.named_arg_as(builder, "B")?;
     let a_trans = invocation.named_arg_as(builder, "transposeA")?;
     let b_trans = invocation.named_arg_as(builder, "transposeB")?;
-    builder.wire(ops::matmul::MatMul { a_trans, b_trans, c_trans: false, q_params: None }, &[a, b])
+    let a_dt = builder.model.outlet_fact(a)?.datum_type;
+    let b_dt = builder.model.outlet_fact(b)?.datum_type;
+    let q_params = qparams(builder, invocation, ("A", a_dt), ("B", b_dt))?;
+    builder.wire(ops::matmul::MatMul { a_trans, b_trans, c_trans: false, q_params }, &[a, b])
+}
+
+/// Quantization parameters for a conv-like or matmul operator, from the graph.quant entries
+/// of its operands (a, b) and of the identifier being assigned (c).
+fn qparams(
+    builder: &ModelBuilder,
+    invocation: &ResolvedInvocation,
+    (a, a_dt): (&str, DatumType),
+    (b, b_dt): (&str, DatumType),
+) -> TractResult<Option<ops::quant::QParams>> {
+    let qa = invocation.named_arg_quant_format(builder, a);
+    let qb = invocation.named_arg_quant_format(builder, b);
+    let qc = builder.current_quant_format();
+    if a_dt.is_float() && b_dt.is_float() && qc.is_none() {
+        return Ok(None);
+    }
+    let c_dt = qc.map(|q| q.datum_type()).transpose()?.unwrap_or(DatumType::I32);
+    let mut q_params = ops::quant::QParams::new(c_dt);
+    if let Some(q) = qa {
+        q_params.set_zero_point_a(&q.zero_point_tensor()?);
+    }
+    if let Some(q) = qb {
+        q_params.set_zero_point_b(&q.zero_point_tensor()?);
+    }
+    if let Some(q) = qc {
+        q_params.set_zero_point_c(&q.zero_point_tensor()?);
+    }
+    let scale = |q: Option<QuantFormat>| q.map(|q| q.scale).unwrap_or(1.0);
+    let scale_factor = scale(qa) * scale(qb) / scale(qc);
+    if scale_factor != 1.0 {
+        q_params.set_scale_factor(scale_factor);
+    }
+    Ok(Some(q_params))
 }
 
 /*
@@ -427,3 +978,204 @@ pub fn select(
     let inputs = crate::registry::multicast(builder, &[cond, true_value, false_value])?;
     builder.wire(ops::logic::Iff {}, &inputs)
 }
+
+#[cfg(test)]
+mod test {
+    use crate::internal::*;
+    use crate::ProtoModel;
+
+    fn run(
+        graph: &str,
+        tensors: Vec<(String, Arc<Tensor>)>,
+        input: Tensor,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let nnef = crate::nnef().with_tract_core();
+        let doc = crate::ast::parse::parse_document(graph)?;
+        let model = nnef.model_for_proto_model(&ProtoModel { doc, tensors, quantization: None })?;
+        let mut buffer = vec![];
+        nnef.write_to_tar(&model, &mut buffer)?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let outputs = model.into_runnable()?.run(tvec!(input.clone()))?;
+        assert_eq!(reloaded.into_runnable()?.run(tvec!(input))?, outputs);
+        Ok(outputs)
+    }
+
+    fn graph(shape: &str, outputs: &str, body: &str) -> String {
+        format!(
+            "version 1.0;\ngraph g(input) -> ({}) {{\n input = external(shape = {});\n{}\n}}",
+            outputs, shape, body
+        )
+    }
+
+    #[test]
+    fn multilinear_upsample() -> TractResult<()> {
+        let input = tensor3(&[[[1f32, 2.]]]);
+        for (method, border, expected) in &[
+            ("symmetric", "constant", [0.75f32, 1.25, 1.75, 1.5]),
+            ("symmetric", "replicate", [1., 1.25, 1.75, 2.]),
+            ("asymmetric", "constant", [1., 1.5, 2., 1.]),
+            ("asymmetric", "replicate", [1., 1.5, 2., 2.]),
+        ] {
+            let body = format!(
+                "output = multilinear_upsample(input, factor = [2], method = '{}', border = '{}');",
+                method, border
+            );
+            let output = run(&graph("[1, 1, 2]", "output", &body), vec![], input.clone())?;
+            assert_eq!(*output[0], tensor3(&[[*expected]]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn up_and_down_samples() -> TractResult<()> {
+        let body = "up = nearest_upsample(input, factor = [2, 2]);
+            down = area_downsample(up, factor = [2, 2]);
+            nearest = nearest_downsample(up, factor = [2, 2]);";
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let output = run(&graph("[1, 1, 2, 2]", "up, down, nearest", body), vec![], input.clone())?;
+        let up = tensor4(&[[[
+            [1f32, 1., 2., 2.],
+            [1., 1., 2., 2.],
+            [3., 3., 4., 4.],
+            [3., 3., 4., 4.],
+        ]]]);
+        assert_eq!(*output[0], up);
+        assert_eq!(*output[1], input);
+        assert_eq!(*output[2], input);
+        Ok(())
+    }
+
+    #[test]
+    fn argmax_sample_desample() -> TractResult<()> {
+        let pool = "size = [1, 1, 2, 2], stride = [1, 1, 2, 2], border = 'ignore',
+            padding = [(0, 0), (0, 0), (0, 0), (0, 0)]";
+        let body = format!(
+            "index = argmax_pool(input, {0});
+            max = sample(input, index, {0});
+            unpooled = desample(max, index, {0});",
+            pool
+        );
+        let input = tensor4(&[[[[1f32, 5., 2., 0.], [3., 4., 7., 6.]]]]);
+        let output = run(&graph("[1, 1, 2, 4]", "max, unpooled", &body), vec![], input)?;
+        assert_eq!(*output[0], tensor4(&[[[[5f32, 7.]]]]));
+        assert_eq!(*output[1], tensor4(&[[[[0f32, 5., 0., 0.], [0., 0., 7., 0.]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn desample_with_output_shape() -> TractResult<()> {
+        let pool = "size = [1, 1, 2, 2], stride = [1, 1, 2, 2], border = 'ignore',
+            padding = [(0, 0), (0, 0), (0, 0), (0, 0)]";
+        let body = format!(
+            "index = argmax_pool(input, {0});
+            max = sample(input, index, {0});
+            unpooled = desample(max, index, {0}, output_shape = [1, 1, 2, 3]);",
+            pool
+        );
+        let input = tensor4(&[[[[1f32, 5., 9.], [3., 4., 2.]]]]);
+        let output = run(&graph("[1, 1, 2, 3]", "max, unpooled", &body), vec![], input)?;
+        assert_eq!(*output[0], tensor4(&[[[[5f32]]]]));
+        assert_eq!(*output[1], tensor4(&[[[[0f32, 5., 0.], [0., 0., 0.]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn multilinear_upsample_2d() -> TractResult<()> {
+        let body = "output = multilinear_upsample(input, factor = [2, 2], method = 'asymmetric',
+            border = 'constant');";
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let output = run(&graph("[1, 1, 2, 2]", "output", body), vec![], input)?;
+        let expected = tensor4(&[[[
+            [1f32, 1.5, 2., 1.],
+            [2., 2.5, 3., 1.5],
+            [3., 3.5, 4., 2.],
+            [1.5, 1.75, 2., 1.],
+        ]]]);
+        output[0].close_enough(&expected, false)?;
+        Ok(())
+    }
+
+    #[test]
+    fn split_stack_unstack() -> TractResult<()> {
+        let body = "[a, b] = split(input, axis = 1, ratios = [1, 3]);
+            stacked = stack([a, a], axis = 0);
+            [c, d] = unstack(input, axis = 0);";
+        let input = tensor2(&[[1f32, 2., 3., 4.], [5., 6., 7., 8.]]);
+        let output = run(&graph("[2, 4]", "b, stacked, d", body), vec![], input)?;
+        assert_eq!(*output[0], tensor2(&[[2f32, 3., 4.], [6., 7., 8.]]));
+        assert_eq!(*output[1], tensor3(&[[[1f32], [5.]], [[1.], [5.]]]));
+        assert_eq!(*output[2], tensor1(&[5f32, 6., 7., 8.]));
+        Ok(())
+    }
+
+    #[test]
+    fn normalizations() -> TractResult<()> {
+        let body = "[mean, variance] = moments(input, axes = [1]);
+            lrn = local_response_normalization(input, size = [1, 3, 1, 1]);";
+        let input = tensor4(&[[[[1f32]], [[2.]], [[3.]]]]);
+        let output = run(&graph("[1, 3, 1, 1]", "mean, variance, lrn", body), vec![], input)?;
+        assert_eq!(*output[0], tensor4(&[[[[2f32]]]]));
+        output[1].close_enough(&tensor4(&[[[[2f32 / 3.]]]]), true)?;
+        let lrn = [1f32, 2., 3.]
+            .iter()
+            .zip([5f32, 14., 13.].iter())
+            .map(|(x, s)| x / (1. + s / 3.).sqrt())
+            .collect::<Vec<_>>();
+        output[2].close_enough(&tensor1(&lrn).into_shape(&[1, 3, 1, 1])?, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn local_normalizations() -> TractResult<()> {
+        let body = "mean = local_mean_normalization(input, size = [1, 3, 1, 1]);
+            variance = local_variance_normalization(input, size = [1, 3, 1, 1]);
+            contrast = local_contrast_normalization(input, size = [1, 3, 1, 1], epsilon = 0.5);";
+        let input = tensor4(&[[[[1f32]], [[2.]], [[3.]]]]);
+        let output = run(&graph("[1, 3, 1, 1]", "mean, variance, contrast", body), vec![], input)?;
+        // windows of 3 channels, zero padded: means are 1, 2 and 5/3
+        let mean = tensor1(&[0f32, 0., 4. / 3.]).into_shape(&[1, 3, 1, 1])?;
+        output[0].close_enough(&mean, true)?;
+        let variance = [1f32, 2., 3.]
+            .iter()
+            .zip([5f32, 14., 13.].iter())
+            .map(|(x, s)| x / (s / 3.).sqrt())
+            .collect::<Vec<_>>();
+        output[1].close_enough(&tensor1(&variance).into_shape(&[1, 3, 1, 1])?, true)?;
+        // centered values are 0, 0 and 4/3: the last one is divided by sqrt(16/27)
+        let contrast = tensor1(&[0f32, 0., 3f32.sqrt()]).into_shape(&[1, 3, 1, 1])?;
+        output[2].close_enough(&contrast, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn separable_conv() -> TractResult<()> {
+        let body = "plane = variable(shape = [2, 1, 1, 2], label = 'plane');
+            point = variable(shape = [1, 2, 1, 1], label = 'point');
+            output = separable_conv(input, plane, point, padding = [(0, 0), (0, 0)]);";
+        let tensors = vec![
+            ("plane".to_string(), rctensor4(&[[[[1f32, 1.]]], [[[1., -1.]]]])),
+            ("point".to_string(), rctensor4(&[[[[1f32]], [[10.]]]])),
+        ];
+        let input = tensor4(&[[[[1f32, 2., 4.]], [[8., 16., 32.]]]]);
+        let output = run(&graph("[1, 2, 1, 3]", "output", body), tensors, input)?;
+        assert_eq!(*output[0], tensor4(&[[[[-77f32, -154.]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn roi_align() -> TractResult<()> {
+        let body = "rois = variable(shape = [1, 4], label = 'rois');
+            batch = variable<integer>(shape = [1], label = 'batch');
+            output = max_roi_align(input, rois, batch, output_size = [1, 1], sampling_rate = [2, 2]);";
+        let tensors = vec![
+            ("rois".to_string(), rctensor2(&[[0f32, 0., 4., 4.]])),
+            ("batch".to_string(), rctensor1(&[0i64])),
+        ];
+        let input =
+            tract_ndarray::Array4::from_shape_fn((1, 1, 4, 4), |(_, _, y, x)| (y * 4 + x) as f32)
+                .into_tensor();
+        let output = run(&graph("[1, 1, 4, 4]", "output", body), tensors, input)?;
+        assert_eq!(*output[0], tensor4(&[[[[12.5f32]]]]));
+        Ok(())
+    }
+}
@@ -33,6 +33,9 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::array::TypedConcat, ser::concat);
     primitive(&mut registry, "slice", deser::slice);
     dumper!(ops::array::Slice, ser::slice);
+    primitive(&mut registry, "split", deser::split);
+    primitive(&mut registry, "stack", deser::stack);
+    primitive(&mut registry, "unstack", deser::unstack);
 
     primitive(&mut registry, "squeeze", deser::squeeze);
     primitive(&mut registry, "unsqueeze", deser::unsqueeze);
@@ -98,6 +101,9 @@ pub fn tract_nnef() -> Registry {
     primitive(&mut registry, "conv", deser::conv);
     dumper!(ops::cnn::ConvUnary, ser::conv);
 
+    primitive(&mut registry, "deconv", deser::deconv);
+    dumper!(ops::cnn::DeconvUnary, ser::deconv);
+
     primitive(&mut registry, "sum_reduce", deser::reduce);
     primitive(&mut registry, "max_reduce", deser::reduce);
     primitive(&mut registry, "min_reduce", deser::reduce);
@@ -105,10 +111,23 @@ pub fn tract_nnef() -> Registry {
     primitive(&mut registry, "argmin_reduce", deser::reduce);
     dumper!(ops::nn::Reduce, ser::reduce);
 
+    primitive(&mut registry, "softmax", deser::softmax);
+    dumper!(ops::nn::Softmax, ser::softmax);
+
     primitive(&mut registry, "max_pool_with_index", deser::max_pool_with_index);
     dumper!(ops::cnn::MaxPool, ser::max_pool);
     primitive(&mut registry, "box", deser::sum_pool);
     dumper!(ops::cnn::SumPool, ser::sum_pool);
+    primitive(&mut registry, "debox", deser::debox);
+    primitive(&mut registry, "argmax_pool", deser::argmax_pool);
+    primitive(&mut registry, "sample", deser::sample);
+    primitive(&mut registry, "desample", deser::desample);
+    primitive(&mut registry, "multilinear_upsample", deser::multilinear_upsample);
+
+    primitive(&mut registry, "avg_roi_pool", deser::roi);
+    primitive(&mut registry, "max_roi_pool", deser::roi);
+    primitive(&mut registry, "roi_resample", deser::roi);
+    dumper!(ops::cnn::RoiPool, ser::roi);
 
     for frag in stdlib {
         if frag.body.is_some() {
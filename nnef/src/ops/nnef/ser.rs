@@ -8,7 +8,7 @@ pub fn source(
     _node: &TypedNode,
     op: &ops::source::TypedSource,
 ) -> TractResult<Option<Arc<RValue>>> {
-    if op.fact.datum_type == DatumType::F32 {
+    if op.fact.datum_type == DatumType::F32 || op.fact.datum_type.is_integer() {
         Ok(Some(invocation(
             "external",
             &[],
@@ -126,17 +126,22 @@ fn data_from_ncwh(data_format: DataFormat, geo_rank: usize, mut wire: Arc<RValue
     wire
 }
 
-fn conv_fragment<'a>(ast: &'a mut IntoAst, data_format: DataFormat, geo_rank: usize) -> String {
+fn conv_fragment<'a>(
+    ast: &'a mut IntoAst,
+    op_name: &str,
+    data_format: DataFormat,
+    geo_rank: usize,
+) -> String {
     if data_format == DataFormat::NCHW {
-        return "conv".into();
+        return op_name.into();
     }
-    let fragment_name = format!("tract_conv_{:?}_{}D", data_format, geo_rank).to_lowercase();
+    let fragment_name = format!("tract_{}_{:?}_{}D", op_name, data_format, geo_rank).to_lowercase();
     if ast.fragments.contains_key(&fragment_name) {
         return fragment_name;
     }
 
     let mut body = vec![];
-    let mut fragment = ast.framework.stdlib.iter().find(|f| f.decl.id == "conv").unwrap().clone();
+    let mut fragment = ast.framework.stdlib.iter().find(|f| f.decl.id == op_name).unwrap().clone();
     fragment.decl.id = fragment_name.clone();
 
     let mut wire = ident("input").into();
@@ -144,7 +149,7 @@ fn conv_fragment<'a>(ast: &'a mut IntoAst, data_format: DataFormat, geo_rank: us
 
     body.push(assignment("nchw", wire));
     wire = invocation(
-        "conv",
+        op_name,
         &[ident("nchw").into(), ident("filter").into(), ident("bias").into()],
         &*fragment
             .decl
@@ -185,7 +190,20 @@ pub fn conv(
     weights.set_shape(&*kernel_shape)?;
     let weigths = ast.konst_variable(format!("{}_weigths", node.name), &weights.into_arc_tensor());
     wire = ast.force_assign(format!("{}_input", node.name), &wire);
-    let conv_fragment = conv_fragment(ast, op.pool_spec.data_format, op.pool_spec.rank());
+    if let Some(q_params) = &op.q_params {
+        if op.pool_spec.data_format != DataFormat::NCHW {
+            bail!("Quantized convolution can only be serialized in NCHW")
+        }
+        let input_dt = ast.model.outlet_fact(node.inputs[0])?.datum_type;
+        quantize_operands(
+            ast,
+            q_params,
+            (&weigths, op.kernel.datum_type()),
+            (&wire, input_dt),
+            &node.name,
+        )?;
+    }
+    let conv_fragment = conv_fragment(ast, "conv", op.pool_spec.data_format, op.pool_spec.rank());
     let padding = match &op.pool_spec.padding {
         PaddingSpec::Explicit(bef, after, _) => array(
             &bef.iter()
@@ -201,7 +219,13 @@ pub fn conv(
     };
     let mut inputs = tvec![wire, weigths];
     if let Some(bias) = op.bias.as_ref() {
-        let bias = ast.konst(format!("{}_bias", node.name), bias);
+        let bias = if op.q_params.is_some() {
+            let bias = ast.konst_variable(format!("{}_bias", node.name), bias);
+            ast.quant_format(identifier(&bias)?, bias_dt(op)?, 0, None)?;
+            bias
+        } else {
+            ast.konst(format!("{}_bias", node.name), bias)
+        };
         inputs.push(bias)
     }
     wire = invocation(
@@ -219,6 +243,69 @@ pub fn conv(
     Ok(Some(wire))
 }
 
+pub fn deconv(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::deconv::DeconvUnary,
+) -> TractResult<Option<Arc<RValue>>> {
+    let mut wire = ast.mapping[&node.inputs[0]].clone();
+    let weigths = ast.konst_variable(format!("{}_weigths", node.name), &op.kernel_as_oihw()?);
+    wire = ast.force_assign(format!("{}_input", node.name), &wire);
+    let deconv_fragment =
+        conv_fragment(ast, "deconv", op.pool_spec.data_format, op.pool_spec.rank());
+    // cropping does not depend on the input size, so it is always serialized explicitly
+    let padding = (0..op.pool_spec.rank())
+        .map(|ax| {
+            if let ops::cnn::PaddingSpec::Explicit(before, after, _) = &op.pool_spec.padding {
+                return tuple_2(numeric(before[ax]), numeric(after[ax]));
+            }
+            let computed = op.pool_spec.padding.compute_one_for_deconv(
+                ax,
+                &1usize,
+                op.pool_spec.kernel_shape[ax],
+                op.pool_spec.dilation(ax),
+                op.pool_spec.stride(ax),
+                op.adjustments[ax],
+            );
+            tuple_2(numeric(computed.pad_before), numeric(computed.pad_after))
+        })
+        .collect::<Vec<_>>();
+    let mut inputs = tvec![wire, weigths];
+    if let Some(bias) = op.bias.as_ref() {
+        let bias = ast.konst(format!("{}_bias", node.name), bias);
+        inputs.push(bias)
+    }
+    let mut params = vec![
+        ("dilation", ints(&op.pool_spec.dilations())),
+        ("stride", ints(&op.pool_spec.strides())),
+        ("border", string("constant")),
+        ("groups", numeric(op.group)),
+        ("padding", array(&padding)),
+    ];
+    // SAME padding with a kernel field narrower than the stride pads the output
+    // beyond the full deconvolution: NNEF needs the output shape to do the same
+    let same = op.pool_spec.padding == ops::cnn::PaddingSpec::SameUpper
+        || op.pool_spec.padding == ops::cnn::PaddingSpec::SameLower;
+    let same_overflows = same
+        && (0..op.pool_spec.rank()).any(|ax| {
+            (op.pool_spec.kernel_shape[ax] - 1) * op.pool_spec.dilation(ax) + 1 + op.adjustments[ax]
+                < op.pool_spec.stride(ax)
+        });
+    if same_overflows || op.adjustments.iter().any(|a| *a != 0) {
+        let output_shape = op.pool_spec.data_format.shape(
+            node.outputs[0].fact.shape.as_concrete().ok_or_else(|| {
+                format_err!("Deconvolution adjustments require a known output shape")
+            })?,
+        )?;
+        let mut nchw = tvec!(*output_shape.n().unwrap_or(&1), *output_shape.c());
+        nchw.extend(output_shape.hw_dims().iter().cloned());
+        params.push(("output_shape", ints(&nchw)));
+    }
+    wire = invocation(&deconv_fragment, &inputs, &params);
+    wire = ast.force_assign(&node.name, &wire);
+    Ok(Some(wire))
+}
+
 fn cnn_pool_fragment<'a>(
     ast: &'a mut IntoAst,
     data_format: DataFormat,
@@ -267,6 +354,7 @@ fn cnn_pool(
     node: &TypedNode,
     op_name: &str,
     pool_spec: &tract_core::ops::cnn::PoolSpec,
+    border: &str,
     normalize_arg: Option<(&'static str, RValue)>,
 ) -> TractResult<Option<Arc<RValue>>> {
     use tract_core::ops::cnn::PaddingSpec;
@@ -296,14 +384,16 @@ fn cnn_pool(
         ("size", ints(&size)),
         ("dilation", ints(&dilations)),
         ("stride", ints(&strides)),
-        ("border", string("ignore")),
+        ("border", string(border)),
         ("padding", padding),
     );
     if let Some(normalize_arg) = normalize_arg {
         params.push(normalize_arg);
     };
     wire = invocation(&conv_fragment, &[wire], &params);
-    wire = ast.force_assign(&node.name, &wire);
+    if node.outputs.len() == 1 {
+        wire = ast.force_assign(&node.name, &wire);
+    }
     Ok(Some(wire))
 }
 
@@ -312,7 +402,8 @@ pub fn max_pool(
     node: &TypedNode,
     op: &ops::cnn::MaxPool,
 ) -> TractResult<Option<Arc<RValue>>> {
-    cnn_pool(ast, node, "max_pool", &op.pool_spec, None)
+    let op_name = if op.with_index_outputs.is_some() { "max_pool_with_index" } else { "max_pool" };
+    cnn_pool(ast, node, op_name, &op.pool_spec, "ignore", None)
 }
 
 pub fn sum_pool(
@@ -320,7 +411,32 @@ pub fn sum_pool(
     node: &TypedNode,
     op: &ops::cnn::SumPool,
 ) -> TractResult<Option<Arc<RValue>>> {
-    cnn_pool(ast, node, "box", &op.pool_spec, Some(("normalize", logical(op.normalize))))
+    let border = if op.count_include_pad { "constant" } else { "ignore" };
+    cnn_pool(ast, node, "box", &op.pool_spec, border, Some(("normalize", logical(op.normalize))))
+}
+
+pub fn roi(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiPool,
+) -> TractResult<Option<Arc<RValue>>> {
+    use ops::cnn::{ResampleMethod, RoiMode};
+    let inputs = ast.shaped_inputs(node)?;
+    let mut params = vec![("output_size", ints(&op.output_size))];
+    let id = match op.mode {
+        RoiMode::AvgPool => "avg_roi_pool",
+        RoiMode::MaxPool => "max_roi_pool",
+        RoiMode::Resample(method) => {
+            let method = match method {
+                ResampleMethod::Symmetric => "symmetric",
+                ResampleMethod::Asymmetric => "asymmetric",
+                ResampleMethod::Aligned => "aligned",
+            };
+            params.push(("method", string(method)));
+            "roi_resample"
+        }
+    };
+    Ok(Some(invocation(id, &inputs, &params)))
 }
 
 pub fn axis_op(
@@ -372,6 +488,15 @@ pub fn reduce(
     Ok(Some(invocation(oper, &[wire], &[("axes", ints(&*op.axes))])))
 }
 
+pub fn softmax(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::nn::Softmax,
+) -> TractResult<Option<Arc<RValue>>> {
+    let wire = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation("softmax", &[wire], &[("axes", ints(&*op.axes))])))
+}
+
 pub fn matmul(
     ast: &mut IntoAst,
     node: &TypedNode,
@@ -379,6 +504,11 @@ pub fn matmul(
 ) -> TractResult<Option<Arc<RValue>>> {
     let a = ast.force_assign(format!("{}_a", node.name), &ast.mapping[&node.inputs[0]].clone());
     let b = ast.force_assign(format!("{}_b", node.name), &ast.mapping[&node.inputs[1]].clone());
+    if let Some(q_params) = &op.q_params {
+        let a_dt = ast.model.outlet_fact(node.inputs[0])?.datum_type;
+        let b_dt = ast.model.outlet_fact(node.inputs[1])?.datum_type;
+        quantize_operands(ast, q_params, (&a, a_dt), (&b, b_dt), &node.name)?;
+    }
     let c = if op.c_trans {
         invocation(
             "matmul",
@@ -400,8 +530,15 @@ pub fn matmul_unary(
     node: &TypedNode,
     op: &ops::matmul::MatMulUnary,
 ) -> TractResult<Option<Arc<RValue>>> {
-    let a = ast.konst(format!("{}_a", node.name), &op.a);
     let b = ast.force_assign(format!("{}_b", node.name), &ast.mapping[&node.inputs[0]].clone());
+    let a = if let Some(q_params) = &op.q_params {
+        let a = ast.konst_variable(format!("{}_a", node.name), &op.a);
+        let b_dt = ast.model.outlet_fact(node.inputs[0])?.datum_type;
+        quantize_operands(ast, q_params, (&a, op.a.datum_type()), (&b, b_dt), &node.name)?;
+        a
+    } else {
+        ast.konst(format!("{}_a", node.name), &op.a)
+    };
     let c = if op.c_trans {
         invocation(
             "matmul",
@@ -418,6 +555,62 @@ pub fn matmul_unary(
     Ok(Some(ast.force_assign(&node.name, &c)))
 }
 
+fn identifier(rv: &RValue) -> TractResult<&str> {
+    match rv {
+        RValue::Identifier(id) => Ok(id),
+        _ => bail!("Expected an identifier, got {:?}", rv),
+    }
+}
+
+fn zero_point(zp: &Option<Arc<Tensor>>) -> TractResult<i32> {
+    if let Some(zp) = zp {
+        if !zp.is_uniform()? {
+            bail!("Per-channel zero points can not be expressed in graph.quant")
+        }
+        Ok(zp.cast_to::<i32>()?.as_slice::<i32>()?[0])
+    } else {
+        Ok(0)
+    }
+}
+
+fn bias_dt(op: &ops::cnn::conv::ConvUnary) -> TractResult<DatumType> {
+    let dt = op.bias.as_ref().map(|b| b.datum_type()).unwrap_or(DatumType::I32);
+    if !dt.is_integer() {
+        bail!("Quantized convolution expects an integer bias, got {:?}", dt)
+    }
+    Ok(dt)
+}
+
+/// Declares graph.quant entries for the operands (a, b) and the output of a quantized
+/// operator. Only the product of the scales is known: it goes to a, or to the output when a
+/// has already been declared by another operator.
+fn quantize_operands(
+    ast: &mut IntoAst,
+    q_params: &ops::quant::QParams,
+    (a, a_dt): (&RValue, DatumType),
+    (b, b_dt): (&RValue, DatumType),
+    c: &str,
+) -> TractResult<()> {
+    if q_params.inputs_kind.is_some() {
+        bail!("Dynamic quantization parameters can not be expressed in graph.quant")
+    }
+    let factor = q_params.scale_factor.unwrap_or(1.0);
+    let a = identifier(a)?;
+    let c = ast.scoped_id(c);
+    let sb =
+        ast.quant_format(identifier(b)?, b_dt, zero_point(&q_params.zero_point_b)?, None)?.scale;
+    let zp_a = zero_point(&q_params.zero_point_a)?;
+    let zp_c = zero_point(&q_params.zero_point_c)?;
+    if ast.quantization.contains_key(a) {
+        let sa = ast.quant_format(a, a_dt, zp_a, None)?.scale;
+        ast.quant_format(&c, q_params.c_datum_type, zp_c, Some(sa * sb / factor))?;
+    } else {
+        let sc = ast.quant_format(&c, q_params.c_datum_type, zp_c, None)?.scale;
+        ast.quant_format(a, a_dt, zp_a, Some(factor * sc / sb))?;
+    }
+    Ok(())
+}
+
 pub fn select(
     ast: &mut IntoAst,
     node: &TypedNode,
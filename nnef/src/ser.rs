@@ -28,6 +28,7 @@ pub struct IntoAst<'a> {
     pub results: Vec<String>,
     pub mapping: HashMap<OutletId, Arc<RValue>>,
     pub tensors: Vec<(String, Arc<Tensor>)>,
+    pub quantization: HashMap<String, QuantFormat>,
     pub fragments: HashMap<String, FragmentDef>,
     pub body: Vec<Assignment>,
 }
@@ -50,6 +51,7 @@ impl<'a> IntoAst<'a> {
             results: vec![],
             mapping: Default::default(),
             tensors: Default::default(),
+            quantization: Default::default(),
             fragments: Default::default(),
             body: vec![],
             parent: None,
@@ -90,6 +92,14 @@ impl<'a> IntoAst<'a> {
             }
             self.node(self.model.node(node))?;
         }
+        // integer inputs are only representable as quantized scalars
+        for input in self.model.input_outlets()? {
+            let dt = self.model.outlet_fact(*input)?.datum_type;
+            let id = self.scoped_id(&self.model.node(input.node).name);
+            if dt.is_integer() && !self.quantization.contains_key(&id) {
+                self.quant_format(&id, dt, 0, None)?;
+            }
+        }
         let outlets: Vec<OutletId> = self.model.output_outlets()?.to_vec();
         for (ix, o) in outlets.into_iter().enumerate() {
             let rv = self.force_assign(format!("output_{}", ix), &self.mapping[&o].clone());
@@ -160,7 +170,9 @@ impl<'a> IntoAst<'a> {
                 .clone(),
         ));
         let properties: Assignment = assignment("properties", Arc::new(array(properties)));
-        let IntoAst { prefix, mut fragments, body, tensors, parameters, results, .. } = self;
+        let IntoAst {
+            prefix, mut fragments, body, tensors, quantization, parameters, results, ..
+        } = self;
         let mut id = prefix
             .map(|p| p.trim_end_matches(&['-', '/', '.'][..]).replace(&['-', '/', '.'][..], "_"))
             .unwrap_or("network".into());
@@ -193,7 +205,8 @@ impl<'a> IntoAst<'a> {
             fragments: fragments.into_iter().map(|(_, v)| v).collect(),
             graph_def: GraphDef { id, parameters, results, body },
         };
-        Ok(ProtoModel { doc, tensors })
+        let quantization = if !quantization.is_empty() { Some(quantization) } else { None };
+        Ok(ProtoModel { doc, tensors, quantization })
     }
 
     fn node(&mut self, node: &TypedNode) -> TractResult<TVec<Arc<RValue>>> {
@@ -243,7 +256,30 @@ impl<'a> IntoAst<'a> {
         if name.len() > 0 && char::is_digit(name.chars().next().unwrap(), 10) {
             name = "_".to_string() + &name;
         }
-        name.replace("/", "_").replace(".", "_").replace("-", "_").into()
+        name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+    }
+
+    /// Declares the quantization of an identifier for graph.quant, or checks it against a
+    /// previous declaration. New entries get the given scale, or 1.
+    pub fn quant_format(
+        &mut self,
+        id: &str,
+        dt: DatumType,
+        zero_point: i32,
+        scale: Option<f32>,
+    ) -> TractResult<QuantFormat> {
+        if let Some(q) = self.quantization.get(id) {
+            if q.datum_type()? != dt
+                || q.zero_point != zero_point
+                || scale.map(|s| s != q.scale).unwrap_or(false)
+            {
+                bail!("Conflicting quantization for {}: {:?} and {:?} {}", id, q, dt, zero_point)
+            }
+            return Ok(*q);
+        }
+        let q = QuantFormat::new(dt, zero_point, scale.unwrap_or(1.0))?;
+        self.quantization.insert(id.to_string(), q);
+        Ok(q)
     }
 
     pub fn force_assign(&mut self, name: impl Into<String>, exp: &Arc<RValue>) -> Arc<RValue> {
@@ -256,6 +292,23 @@ impl<'a> IntoAst<'a> {
         }
     }
 
+    /// Node inputs, for an operator that needs all of them with their actual
+    /// shape: uniform constants, inlined as scalar literals by `konst`, are
+    /// dumped again as variables.
+    pub fn shaped_inputs(&mut self, node: &TypedNode) -> TractResult<TVec<Arc<RValue>>> {
+        let mut inputs = tvec!();
+        for (ix, input) in node.inputs.iter().enumerate() {
+            match &self.model.outlet_fact(*input)?.konst {
+                Some(konst) if konst.rank() > 0 && konst.is_uniform()? => {
+                    let name = format!("{}_input_{}", node.name, ix);
+                    inputs.push(self.konst_variable(name, konst))
+                }
+                _ => inputs.push(self.mapping[input].clone()),
+            }
+        }
+        Ok(inputs)
+    }
+
     pub fn konst(&mut self, name: impl Into<String>, tensor: &Arc<Tensor>) -> Arc<RValue> {
         self.do_konst(name, tensor, false)
     }
@@ -16,6 +16,10 @@ struct Header {
     padding: [u32; 11],
 }
 
+/// NNEF has no bfloat16: tract stores them under its own vendor code.
+const TRACT_ITEM_TYPE_VENDOR: u16 = ((b'T' as u16) << 8) | b'R' as u16;
+const TRACT_ITEM_TYPE_BF16: u16 = 0;
+
 pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
     unsafe {
         let mut header: Header = std::mem::zeroed();
@@ -41,21 +45,22 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
                 header.data_size_bytes
             );
         }
-        if header.item_type_vendor != 0 {
+        if header.item_type_vendor != 0 && header.item_type_vendor != TRACT_ITEM_TYPE_VENDOR {
             bail!("Unknownn item type vendor {}", header.item_type_vendor);
         }
-        let dt = match (header.item_type, header.bits_per_item) {
-            (0, 16) => DatumType::F16,
-            (0, 32) => DatumType::F32,
-            (0, 64) => DatumType::F64,
-            (1, 8) => DatumType::U8,
-            (1, 16) => DatumType::U16,
-            (1, 32) => DatumType::U32,
-            (1, 64) => DatumType::U64,
-            (0x0100, 8) => DatumType::I8,
-            (0x0100, 16) => DatumType::I16,
-            (0x0100, 32) => DatumType::I32,
-            (0x0100, 64) => DatumType::I64,
+        let dt = match (header.item_type_vendor, header.item_type, header.bits_per_item) {
+            (TRACT_ITEM_TYPE_VENDOR, TRACT_ITEM_TYPE_BF16, 16) => DatumType::BF16,
+            (0, 0, 16) => DatumType::F16,
+            (0, 0, 32) => DatumType::F32,
+            (0, 0, 64) => DatumType::F64,
+            (0, 1, 8) => DatumType::U8,
+            (0, 1, 16) => DatumType::U16,
+            (0, 1, 32) => DatumType::U32,
+            (0, 1, 64) => DatumType::U64,
+            (0, 0x0100, 8) => DatumType::I8,
+            (0, 0x0100, 16) => DatumType::I16,
+            (0, 0x0100, 32) => DatumType::I32,
+            (0, 0x0100, 64) => DatumType::I64,
             _ => bail!(
                 "Unsupported type in tensor type:{} bits_per_item:{}",
                 header.item_type,
@@ -83,7 +88,10 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
         }
         header.data_size_bytes = (tensor.len() * tensor.datum_type().size_of()) as u32;
         header.bits_per_item = (tensor.datum_type().size_of() * 8) as u32;
-        header.item_type = if tensor.datum_type().is_float() {
+        header.item_type = if tensor.datum_type() == DatumType::BF16 {
+            header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
+            TRACT_ITEM_TYPE_BF16
+        } else if tensor.datum_type().is_float() {
             0
         } else if tensor.datum_type().is_signed() {
             0x100
@@ -107,4 +115,17 @@ mod test {
     fn header_is_128_bytes() {
         assert_eq!(std::mem::size_of::<Header>(), 128);
     }
+
+    #[test]
+    fn half_floats_round_trip() -> TractResult<()> {
+        for dt in &[DatumType::F16, DatumType::BF16] {
+            let tensor = tensor1(&[0.5f32, -2.0, 3.25]).cast_to_dt(*dt)?.into_owned();
+            let mut buffer = vec![];
+            write_tensor(&mut buffer, &tensor)?;
+            let read = read_tensor(&*buffer)?;
+            assert_eq!(read.datum_type(), *dt);
+            assert_eq!(read, tensor);
+        }
+        Ok(())
+    }
 }
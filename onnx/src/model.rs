@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use tract_hir::internal::*;
 
 use crate::pb;
+use crate::tensor::translate_inference_fact;
 use prost::Message;
 
 pub fn optional_inputs(pb: &pb::NodeProto) -> impl Iterator<Item = Option<usize>> + '_ {
@@ -38,6 +39,7 @@ pub struct ParsingContext<'a> {
     pub framework: &'a Onnx,
     pub model: &'a pb::ModelProto,
     pub parent_graphs: Vec<&'a pb::GraphProto>,
+    pub symbol_table: SymbolTable,
 }
 
 #[derive(Clone, Debug)]
@@ -52,6 +54,7 @@ impl<'a> ParsingContext<'a> {
         let mut ctx = self.clone();
         ctx.parent_graphs.push(graph);
         let mut model = InferenceModel::default();
+        model.symbol_table = self.symbol_table.clone();
         let mut unresolved_inputs = vec![];
         let mut closures_to_wire = vec![];
         let mut initializers: HashMap<&str, Tensor> = graph
@@ -72,7 +75,7 @@ impl<'a> ParsingContext<'a> {
                 let fact = input.r#type.as_ref().unwrap().value.as_ref().unwrap();
                 #[allow(irrefutable_let_patterns)]
                 let fact: InferenceFact = if let pb::type_proto::Value::TensorType(fact) = fact {
-                    fact.try_into()?
+                    translate_inference_fact(&self.symbol_table, fact)?
                 } else {
                     bail!("Can not parse tensor type");
                 };
@@ -155,7 +158,7 @@ impl<'a> ParsingContext<'a> {
             let outlet = outlets_by_name[&*output.name];
             outputs.push(outlet);
             model.set_outlet_label(outlet, output.name.clone())?;
-            model.set_outlet_fact(outlet, fact.try_into()?)?;
+            model.set_outlet_fact(outlet, translate_inference_fact(&self.symbol_table, fact)?)?;
         }
         model.set_output_outlets(&outputs)?;
         let result = ParseResult { model, unresolved_inputs, outlets_by_name };
@@ -212,9 +215,19 @@ impl Onnx {
             model: proto,
             parent_graphs: vec![],
             onnx_operator_set_version,
+            symbol_table: SymbolTable::default(),
         };
         ctx.parse_graph(graph.as_ref().unwrap())
     }
+
+    /// Serialize a decluttered typed model as an ONNX protobuf.
+    pub fn write(&self, model: &TypedModel, mut w: impl std::io::Write) -> TractResult<()> {
+        let proto = crate::ser::to_proto_model(model)?;
+        let mut buf = vec![];
+        proto.encode(&mut buf)?;
+        w.write_all(&buf)?;
+        Ok(())
+    }
 }
 
 impl Framework<pb::ModelProto, InferenceModel> for Onnx {
@@ -3,6 +3,7 @@ mod nonzero;
 mod one_hot;
 mod pad;
 mod slice;
+mod topk;
 
 use tract_hir::internal::*;
 use tract_hir::ops::array;
@@ -19,14 +20,20 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("EyeLike", eye_like);
     reg.insert("Flatten", flatten);
     reg.insert("Gather", gather);
+    reg.insert("GatherElements", gather_elements);
+    reg.insert("GatherND", gather_nd);
     reg.insert("NonZero", |_, _| Ok((Box::new(nonzero::NonZero), vec![])));
     reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pad", pad::pad);
     reg.insert("Reshape", |_, _| Ok((expand(array::Reshape::default()), vec![])));
+    reg.insert("Scatter", scatter_elements);
+    reg.insert("ScatterElements", scatter_elements);
+    reg.insert("ScatterND", scatter_nd);
     reg.insert("Shape", |_, _| Ok((expand(array::Shape::new(DatumType::I64)), vec![])));
     reg.insert("Size", |_, _| Ok((expand(array::Size::new(DatumType::I64)), vec![])));
     reg.insert("Transpose", transpose);
     reg.insert("Tile", |_, _| Ok((expand(array::Tile::default()), vec![])));
+    reg.insert("TopK", topk::topk);
     reg.insert("Slice", slice::slice);
     reg.insert("Split", split);
     reg.insert("Squeeze", squeeze);
@@ -99,6 +106,43 @@ pub fn gather(
     Ok((Box::new(array::Gather::new(axis)), vec![]))
 }
 
+pub fn gather_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    Ok((expand(array::GatherElements::new(axis)), vec![]))
+}
+
+pub fn gather_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let batch_dims = node.get_attr_opt("batch_dims")?.unwrap_or(0);
+    Ok((Box::new(array::GatherNd::new(batch_dims)), vec![]))
+}
+
+fn scatter_reduction(node: &NodeProto) -> TractResult<array::ScatterReduction> {
+    array::ScatterReduction::parse(node.get_attr_opt("reduction")?.unwrap_or("none"))
+}
+
+pub fn scatter_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    let reduction = scatter_reduction(node)?;
+    Ok((expand(array::ScatterElements::new(axis, reduction)), vec![]))
+}
+
+pub fn scatter_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let reduction = scatter_reduction(node)?;
+    Ok((Box::new(array::ScatterNd::new(reduction)), vec![]))
+}
+
 pub fn split(
     _ctx: &ParsingContext,
     node: &NodeProto,
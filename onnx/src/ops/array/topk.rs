@@ -0,0 +1,104 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn topk(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let largest = node.get_attr_opt::<i64>("largest")?.unwrap_or(1) == 1;
+    let sorted = node.get_attr_opt::<i64>("sorted")?.unwrap_or(1) == 1;
+    let k = if ctx.onnx_operator_set_version < 10 { Some(node.get_attr("k")?) } else { None };
+    Ok((expand(TopK { axis, k, largest, sorted }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct TopK {
+    axis: i64,
+    k: Option<i64>,
+    largest: bool,
+    sorted: bool,
+}
+
+impl_dyn_hash!(TopK);
+
+impl TopK {
+    fn axis(&self, rank: usize) -> usize {
+        if self.axis < 0 {
+            (self.axis + rank as i64) as usize
+        } else {
+            self.axis as usize
+        }
+    }
+}
+
+impl Expansion for TopK {
+    fn name(&self) -> Cow<str> {
+        "TopK".into()
+    }
+
+    op_onnx!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2 - self.k.is_some() as usize)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[0].rank, &outputs[1].rank)?;
+        s.equals(&outputs[0].shape, &outputs[1].shape)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = self.axis(rank as usize);
+            for ix in 0..rank as usize {
+                if ix != axis {
+                    s.equals(&inputs[0].shape[ix], &outputs[0].shape[ix])?;
+                }
+            }
+            if let Some(k) = self.k {
+                s.equals(&outputs[0].shape[axis], k.to_dim())?;
+            } else {
+                s.given(&inputs[1].value, move |s, k| {
+                    let k = k.cast_to_scalar::<i64>()?;
+                    s.equals(&outputs[0].shape[axis], k.to_dim())
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(inputs[0])?.rank();
+        let k = if let Some(k) = self.k {
+            target.add_const(format!("{}.k", prefix), tensor0(k))?
+        } else {
+            inputs[1]
+        };
+        let k_dim_hint = if target.outlet_fact(k)?.konst.is_none() {
+            Some(target.symbol_table.new_with_prefix("K").into())
+        } else {
+            None
+        };
+        let op = tract_hir::ops::array::TopK::new(
+            self.axis(rank),
+            self.largest,
+            self.sorted,
+            k_dim_hint,
+        );
+        target.wire_node(prefix, op, &[inputs[0], k])
+    }
+}
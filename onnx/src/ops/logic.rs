@@ -1,4 +1,5 @@
-use crate::model::OnnxOpRegister;
+use crate::model::{OnnxOpRegister, ParseResult, ParsingContext};
+use crate::pb::*;
 use tract_hir::internal::*;
 use tract_hir::ops;
 
@@ -15,4 +16,232 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("GreaterOrEqual", |_, _| Ok((ops::logic::GreaterEqual.into_hir(), vec![])));
 
     reg.insert("Where", |_, _| Ok((Box::new(ops::logic::Iff::default()), vec![])));
+
+    reg.insert("If", if_then_else);
+}
+
+pub fn if_then_else(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("then_branch")?;
+    let ParseResult { model: then_body, unresolved_inputs: then_inputs, .. } =
+        ctx.parse_graph(graph)?;
+    let graph: &GraphProto = node.get_attr("else_branch")?;
+    let ParseResult { model: else_body, unresolved_inputs: else_inputs, .. } =
+        ctx.parse_graph(graph)?;
+    let mut closures = then_inputs.clone();
+    for input in &else_inputs {
+        if !closures.contains(input) {
+            closures.push(input.clone());
+        }
+    }
+    // op input 0 is the condition, closures come after it
+    let mapping = |inputs: &[String]| -> Vec<usize> {
+        inputs.iter().map(|i| 1 + closures.iter().position(|c| c == i).unwrap()).collect()
+    };
+    let then_input_mapping = mapping(&then_inputs);
+    let else_input_mapping = mapping(&else_inputs);
+    Ok((
+        Box::new(InferenceIf { then_body, then_input_mapping, else_body, else_input_mapping }),
+        closures,
+    ))
+}
+
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct InferenceIf {
+    pub then_body: InferenceModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: InferenceModel,
+    pub else_input_mapping: Vec<usize>,
+}
+
+impl_dyn_hash!(InferenceIf);
+
+impl Op for InferenceIf {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    op_onnx!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for InferenceIf {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let cond = inputs[0].cast_to_scalar::<bool>()?;
+        let (body, input_mapping) = if cond {
+            (&self.then_body, &self.then_input_mapping)
+        } else {
+            (&self.else_body, &self.else_input_mapping)
+        };
+        let body_inputs =
+            input_mapping.iter().map(|ix| inputs[*ix].clone().into_tensor()).collect();
+        SimplePlan::new(body)?.run(body_inputs)
+    }
+}
+
+/// Only the datum type and rank of a fact.
+fn dt_and_rank(fact: &InferenceFact) -> InferenceFact {
+    let mut result = InferenceFact::new();
+    result.datum_type = fact.datum_type;
+    if let Some(rank) = fact.shape.rank().concretize() {
+        result.shape = ShapeFactoid::closed(tvec!(GenericFactoid::Any; rank as usize));
+    }
+    result
+}
+
+impl InferenceIf {
+    /// Unify a branch with the op inputs and outputs. Unless the branch is
+    /// known to be taken, the outputs only share datum type and rank with it.
+    fn unify_body(
+        body: &mut InferenceModel,
+        input_mapping: &[usize],
+        inputs: &mut [InferenceFact],
+        outputs: &mut [InferenceFact],
+        taken: bool,
+    ) -> TractResult<bool> {
+        let mut changed = false;
+        for (ix, slot) in input_mapping.iter().enumerate() {
+            changed |= inputs[*slot].unify_with_mut(body.input_fact_mut(ix)?)?;
+        }
+        for (ix, output) in outputs.iter_mut().enumerate() {
+            let body_output = body.output_fact_mut(ix)?;
+            if taken {
+                changed |= output.unify_with_mut(body_output)?;
+            } else {
+                changed |= output.unify_with(&dt_and_rank(body_output))?;
+                changed |= body_output.unify_with(&dt_and_rank(output))?;
+            }
+        }
+        changed |= body.analyse(false)?;
+        Ok(changed)
+    }
+}
+
+impl InferenceOp for InferenceIf {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        inputs[0].unify_with(&InferenceFact::dt(bool::datum_type()))?;
+        // with a known condition, only the selected branch constrains the outputs
+        let cond = if let Some(cond) = inputs[0].value.concretize() {
+            Some(cond.cast_to_scalar::<bool>()?)
+        } else {
+            None
+        };
+        loop {
+            let mut changed = false;
+            if cond != Some(false) {
+                changed |= Self::unify_body(
+                    &mut self.then_body,
+                    &self.then_input_mapping,
+                    &mut inputs,
+                    &mut outputs,
+                    cond.is_some(),
+                )
+                .context("analysing then branch")?;
+            }
+            if cond != Some(true) {
+                changed |= Self::unify_body(
+                    &mut self.else_body,
+                    &self.else_input_mapping,
+                    &mut inputs,
+                    &mut outputs,
+                    cond.is_some(),
+                )
+                .context("analysing else branch")?;
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        if let Some(cond) = &target.outlet_fact(inputs[0])?.konst {
+            let (body, input_mapping) = if cond.cast_to_scalar::<bool>()? {
+                (&self.then_body, &self.then_input_mapping)
+            } else {
+                (&self.else_body, &self.else_input_mapping)
+            };
+            let body = body.clone().into_typed()?;
+            return ops::logic::If::wire_body(&node.name, &body, input_mapping, target, &inputs);
+        }
+        let op = ops::logic::If::new(
+            self.then_body.clone().into_typed()?,
+            self.then_input_mapping.clone(),
+            self.else_body.clone().into_typed()?,
+            self.else_input_mapping.clone(),
+            &target.symbol_table,
+        )?;
+        target.wire_node(&*node.name, op, &inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // then: x, else: [4., 5.]
+    fn if_model() -> TractResult<InferenceModel> {
+        let mut then_body = InferenceModel::default();
+        let x = then_body.add_source("x", InferenceFact::dt_shape(f32::datum_type(), tvec!(3)))?;
+        then_body.set_output_outlets(&[x])?;
+        let mut else_body = InferenceModel::default();
+        else_body.add_source("x", InferenceFact::dt_shape(f32::datum_type(), tvec!(3)))?;
+        let y = else_body.add_const("y", tensor1(&[4f32, 5.]))?;
+        else_body.set_output_outlets(&[y])?;
+        let mut model = InferenceModel::default();
+        let cond =
+            model.add_source("cond", InferenceFact::dt_shape(bool::datum_type(), &[0usize; 0]))?;
+        let x = model.add_source("x", InferenceFact::dt_shape(f32::datum_type(), tvec!(3)))?;
+        let op = InferenceIf::new(then_body, vec![1], else_body, vec![1]);
+        let output = model.wire_node("if", op, &[cond, x])?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn if_branches_only_share_type_and_rank() -> TractResult<()> {
+        let mut model = if_model()?;
+        model.analyse(false)?;
+        let fact = model.output_fact(0)?;
+        assert_eq!(fact.datum_type.concretize(), Some(f32::datum_type()));
+        assert_eq!(fact.shape.rank().concretize(), Some(1));
+        assert!(fact.shape.dim(0).unwrap().concretize().is_none());
+        let model = model.into_typed()?;
+        let i = model.symbol_table.get("I").unwrap();
+        assert_eq!(model.output_fact(0)?.shape.to_tvec(), tvec!(TDim::from(i)));
+        let plan = SimplePlan::new(model)?;
+        let x = tensor1(&[1f32, 2., 3.]);
+        let then = plan.run(tvec!(tensor0(true), x.clone()))?;
+        assert_eq!(*then[0], tensor1(&[1f32, 2., 3.]));
+        let other = plan.run(tvec!(tensor0(false), x))?;
+        assert_eq!(*other[0], tensor1(&[4f32, 5.]));
+        Ok(())
+    }
 }
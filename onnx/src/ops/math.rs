@@ -6,6 +6,7 @@ use tract_hir::ops;
 use tract_hir::ops::binary::Nary;
 
 mod clip;
+mod einsum;
 mod gemm;
 mod mat_mul_integer;
 mod pow;
@@ -61,6 +62,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("MatMulInteger", mat_mul_integer::mat_mul_integer);
     reg.insert("QLinearMatMul", mat_mul_integer::q_linear_mat_mul);
     reg.insert("Gemm", gemm::gemm);
+    reg.insert("Einsum", einsum::einsum);
 }
 
 fn isinf(
@@ -0,0 +1,64 @@
+use crate::model::ParsingContext;
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::einsum::Expr;
+
+pub fn einsum(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let equation = node.get_attr::<String>("equation")?;
+    Ok((expand(Einsum { equation }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Einsum {
+    equation: String,
+}
+
+impl_dyn_hash!(Einsum);
+
+impl Expansion for Einsum {
+    fn name(&self) -> Cow<str> {
+        "Einsum".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(&outputs, 1)?;
+        for input in inputs {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+        }
+        s.given_all(inputs.iter().map(|i| &i.rank), move |s, ranks| {
+            let ranks: TVec<usize> = ranks.iter().map(|r| *r as usize).collect();
+            let expr = Expr::parse(&self.equation, &ranks)?;
+            s.equals(&outputs[0].rank, expr.output.len() as i64)
+        })?;
+        s.given_all(inputs.iter().map(|i| &i.shape), move |s, shapes: Vec<TVec<TDim>>| {
+            let ranks: TVec<usize> = shapes.iter().map(|s| s.len()).collect();
+            let expr = Expr::parse(&self.equation, &ranks)?;
+            let shapes: TVec<&[TDim]> = shapes.iter().map(|s| &**s).collect();
+            s.equals(&outputs[0].shape, expr.output_shape(&shapes)?)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let ranks = inputs
+            .iter()
+            .map(|i| Ok(target.outlet_fact(*i)?.rank()))
+            .collect::<TractResult<TVec<usize>>>()?;
+        let expr = Expr::parse(&self.equation, &ranks)?;
+        target.wire_node(prefix, tract_hir::tract_core::ops::einsum::Einsum::new(expr), inputs)
+    }
+}
@@ -26,6 +26,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Constant", konst);
     reg.insert("Identity", |_, _| Ok((Box::new(ops::identity::Identity::default()), vec![])));
     reg.insert("Resize", resize::resize);
+    reg.insert("Upsample", resize::upsample);
     array::register_all_ops(reg);
     category_mapper::register_all_ops(reg);
     logic::register_all_ops(reg);
@@ -42,6 +42,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("BatchNormalization", batch_normalization);
     reg.insert("Conv", conv);
     reg.insert("ConvInteger", conv_integer);
+    reg.insert("ConvTranspose", conv_transpose);
     reg.insert("Dropout", dropout::dropout);
     reg.insert("Elu", elu);
     reg.insert("GlobalAveragePool", |_, _| Ok((expand(ops::nn::GlobalAvgPool), vec![])));
@@ -175,6 +176,23 @@ pub fn conv_integer(
     Ok((expand(op), vec![]))
 }
 
+pub fn conv_transpose(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let op = ops::cnn::Deconv {
+        padding: pad(node)?,
+        strides: strides(node)?,
+        dilations: dilations(node)?,
+        adjustments: node.get_attr_opt_tvec("output_padding")?,
+        output_shape: node.get_attr_opt_tvec("output_shape")?,
+        group: node.get_attr_opt("group")?.unwrap_or(1),
+        bias_input: if node.input.len() == 3 { Some(2) } else { None },
+        ..ops::cnn::Deconv::default()
+    };
+    Ok((expand(op), vec![]))
+}
+
 pub fn conv_qlinear(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -0,0 +1,70 @@
+use crate::model::{ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+
+use tract_hir::ops;
+use tract_hir::ops::scan::{InferenceLoop, InputMapping, OutputMapping, StateInitializer};
+
+pub fn loop_(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { mut model, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let present = |ix: usize| node.input.get(ix).map(|s| !s.is_empty()).unwrap_or(false);
+    let trip_count_slot = if present(0) { Some(0) } else { None };
+    let cond_slot = if present(1) { Some(trip_count_slot.is_some() as usize) } else { None };
+    let first_state_slot = trip_count_slot.is_some() as usize + cond_slot.is_some() as usize;
+    let closure_inputs = unresolved_inputs.len();
+    let num_states = model.input_outlets()?.len() - 2 - closure_inputs;
+    let num_scan_outputs = model.output_outlets()?.len() - 1 - num_states;
+
+    let mut input_mapping = vec![];
+    let mut output_mapping = vec![];
+    for ix in 0..num_states {
+        input_mapping.push(InputMapping::State {
+            initializer: StateInitializer::FromInput(first_state_slot + ix),
+        });
+        output_mapping.push(OutputMapping {
+            state: true,
+            last_value_slot: Some(ix),
+            full_slot: None,
+            axis: 0,
+            chunk: 1,
+            full_dim_hint: None,
+        });
+    }
+    for ix in 0..closure_inputs {
+        input_mapping.push(InputMapping::Full { slot: first_state_slot + num_states + ix });
+    }
+    for ix in 0..num_scan_outputs {
+        let outlet = model.output_outlets()?[1 + num_states + ix];
+        InferenceModelPatch::intercept(
+            &model,
+            outlet,
+            format!("{}.output-{}-adjust-dim", node.name, ix),
+            expand(ops::array::AddDims::new(vec![0])),
+            InferenceFact::default(),
+        )?
+        .apply(&mut model)?;
+        output_mapping.push(OutputMapping {
+            state: false,
+            last_value_slot: None,
+            full_slot: Some(num_states + ix),
+            axis: 0,
+            chunk: 1,
+            full_dim_hint: None,
+        });
+    }
+
+    Ok((
+        Box::new(InferenceLoop::new(
+            model,
+            trip_count_slot,
+            cond_slot,
+            input_mapping,
+            output_mapping,
+        )),
+        unresolved_inputs,
+    ))
+}
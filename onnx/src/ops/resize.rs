@@ -2,84 +2,207 @@ use crate::model::ParsingContext;
 use crate::pb::*;
 use std::hash::Hash;
 use tract_hir::internal::*;
+use tract_ndarray::{ArrayD, Axis};
 
 pub fn resize(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let interpolator = match node.get_attr_opt("mode")?.unwrap_or("nearest") {
+        "nearest" => Interpolator::Nearest,
+        "linear" => Interpolator::Linear,
+        "cubic" => Interpolator::Cubic,
+        s => bail!("Unsupported Resize mode: {}", s),
+    };
+    if ctx.onnx_operator_set_version < 11 {
+        // opset 10: X, scales. No roi, no sizes, asymmetric coordinates.
+        return Ok((
+            Box::new(Resize { optional_scales_input: Some(1), interpolator, ..Resize::default() }),
+            vec![],
+        ));
+    }
     let coord_transformer =
         match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or("half_pixel") {
-            "align_corners" => CoordTransformer::AlignCorners,
             "half_pixel" => CoordTransformer::HalfPixel,
-            s => todo!("coordinate_transformation_mode: {}", s),
+            "pytorch_half_pixel" => CoordTransformer::PytorchHalfPixel,
+            "align_corners" => CoordTransformer::AlignCorners,
+            "asymmetric" => CoordTransformer::Asymmetric,
+            "tf_half_pixel_for_nn" => CoordTransformer::TfHalfPixelForNn,
+            "tf_crop_and_resize" => CoordTransformer::TfCropAndResize,
+            s => bail!("Unsupported Resize coordinate_transformation_mode: {}", s),
         };
-    let interpolator = match node.get_attr("mode")? {
-        "linear" => Interpolator::Linear,
-        s => todo!("mode: {}", s),
-    };
     let nearest = match node.get_attr_opt("nearest_mode")?.unwrap_or("round_prefer_floor") {
         "floor" => Nearest::Floor,
+        "ceil" => Nearest::Ceil,
         "round_prefer_floor" => Nearest::RoundPreferFloor,
-        s => todo!("nearest_mode: {}", s),
+        "round_prefer_ceil" => Nearest::RoundPreferCeil,
+        s => bail!("Unsupported Resize nearest_mode: {}", s),
     };
-    let mut options = crate::model::optional_inputs(node).skip(2);
+    let mut options = crate::model::optional_inputs(node).skip(1);
+    let optional_roi_input = options.next().unwrap();
+    if coord_transformer == CoordTransformer::TfCropAndResize && optional_roi_input.is_none() {
+        bail!("Resize with tf_crop_and_resize requires a roi input");
+    }
     Ok((
         Box::new(Resize {
-            optional_scales_input: options.next().unwrap(),
-            optional_sizes_input: options.next().unwrap(),
             coord_transformer,
             interpolator,
             nearest,
+            cubic_coeff_a: node.get_attr_opt("cubic_coeff_a")?.unwrap_or(-0.75),
+            exclude_outside: node.get_attr_opt("exclude_outside")?.unwrap_or(false),
+            extrapolation_value: node.get_attr_opt("extrapolation_value")?.unwrap_or(0.0),
+            static_scales: None,
+            optional_roi_input,
+            optional_scales_input: options.next().unwrap(),
+            optional_sizes_input: options.next().unwrap(),
         }),
         vec![],
     ))
 }
 
-#[derive(Clone, Debug, Hash)]
+pub fn upsample(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let interpolator = match node.get_attr_opt("mode")?.unwrap_or("nearest") {
+        "nearest" => Interpolator::Nearest,
+        "linear" | "bilinear" => Interpolator::Linear,
+        s => bail!("Unsupported Upsample mode: {}", s),
+    };
+    let op = if ctx.onnx_operator_set_version < 9 {
+        let scales: TVec<f32> = node.get_attr_tvec("scales")?;
+        Resize { interpolator, static_scales: Some(rctensor1(&*scales)), ..Resize::default() }
+    } else {
+        Resize { interpolator, optional_scales_input: Some(1), ..Resize::default() }
+    };
+    Ok((Box::new(op), vec![]))
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
 enum CoordTransformer {
     HalfPixel,
+    PytorchHalfPixel,
     AlignCorners,
+    Asymmetric,
+    TfHalfPixelForNn,
+    TfCropAndResize,
 }
 
 impl CoordTransformer {
-    fn transform(&self, x_out: usize, scale: f32, len_in: usize, len_out: usize) -> f32 {
+    fn transform(
+        &self,
+        x_out: usize,
+        scale: f32,
+        len_in: usize,
+        len_out: usize,
+        roi: (f32, f32),
+    ) -> f32 {
+        let x_out = x_out as f32;
         match self {
-            CoordTransformer::HalfPixel => (x_out as f32 + 0.5) * scale - 0.5,
+            CoordTransformer::HalfPixel => (x_out + 0.5) / scale - 0.5,
+            CoordTransformer::PytorchHalfPixel => {
+                if len_out > 1 {
+                    (x_out + 0.5) / scale - 0.5
+                } else {
+                    0.0
+                }
+            }
             CoordTransformer::AlignCorners => {
-                (x_out as f32 * (len_in as f32 - 1.0)) / (len_out as f32 - 1.0)
+                if len_out > 1 {
+                    x_out * (len_in as f32 - 1.0) / (len_out as f32 - 1.0)
+                } else {
+                    0.0
+                }
+            }
+            CoordTransformer::Asymmetric => x_out / scale,
+            CoordTransformer::TfHalfPixelForNn => (x_out + 0.5) / scale,
+            CoordTransformer::TfCropAndResize => {
+                let (start, end) = roi;
+                if len_out > 1 {
+                    start * (len_in as f32 - 1.0)
+                        + x_out * (end - start) * (len_in as f32 - 1.0) / (len_out as f32 - 1.0)
+                } else {
+                    0.5 * (start + end) * (len_in as f32 - 1.0)
+                }
             }
         }
     }
 }
 
-#[derive(Clone, Debug, Hash)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
 enum Interpolator {
+    Nearest,
     Linear,
+    Cubic,
 }
 
-impl Interpolator {
-    fn interpolate(&self, y_left: f32, y_right: f32, x_ratio: f32) -> f32 {
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
+enum Nearest {
+    Floor,
+    Ceil,
+    RoundPreferFloor,
+    RoundPreferCeil,
+}
+
+impl Nearest {
+    fn round(&self, x: f32) -> f32 {
+        let half = x == x.floor() + 0.5;
         match self {
-            Interpolator::Linear => y_left * (1.0 - x_ratio) + y_right * x_ratio,
+            Nearest::Floor => x.floor(),
+            Nearest::Ceil => x.ceil(),
+            Nearest::RoundPreferFloor if half => x.floor(),
+            Nearest::RoundPreferCeil if half => x.ceil(),
+            _ => x.round(),
         }
     }
 }
 
-#[derive(Clone, Debug, Hash)]
-enum Nearest {
-    Floor,
-    RoundPreferFloor,
+fn cubic_coeff(a: f32, d: f32) -> f32 {
+    let d = d.abs();
+    if d <= 1.0 {
+        ((a + 2.0) * d - (a + 3.0)) * d * d + 1.0
+    } else if d < 2.0 {
+        ((a * d - 5.0 * a) * d + 8.0 * a) * d - 4.0 * a
+    } else {
+        0.0
+    }
 }
 
-#[derive(Clone, new, Debug, Hash)]
+#[derive(Clone, Debug, Educe)]
+#[educe(Hash)]
 struct Resize {
     coord_transformer: CoordTransformer,
     interpolator: Interpolator,
     nearest: Nearest,
+    #[educe(Hash(method = "hash_f32"))]
+    cubic_coeff_a: f32,
+    exclude_outside: bool,
+    #[educe(Hash(method = "hash_f32"))]
+    extrapolation_value: f32,
+    static_scales: Option<Arc<Tensor>>,
+    optional_roi_input: Option<usize>,
     optional_scales_input: Option<usize>,
     optional_sizes_input: Option<usize>,
 }
 
+impl Default for Resize {
+    // Upsample and opset 10 Resize behaviour
+    fn default() -> Resize {
+        Resize {
+            coord_transformer: CoordTransformer::Asymmetric,
+            interpolator: Interpolator::Nearest,
+            nearest: Nearest::Floor,
+            cubic_coeff_a: -0.75,
+            exclude_outside: false,
+            extrapolation_value: 0.0,
+            static_scales: None,
+            optional_roi_input: None,
+            optional_scales_input: None,
+            optional_sizes_input: None,
+        }
+    }
+}
+
 impl_dyn_hash!(Resize);
 
 impl Op for Resize {
@@ -98,6 +221,7 @@ impl Resize {
         input_scale: Option<&Tensor>,
         input_sizes: Option<&Tensor>,
     ) -> TractResult<TVec<usize>> {
+        let input_scale = self.static_scales.as_deref().or(input_scale);
         if let Some(scale) = input_scale {
             if scale.len() == input_shape.len() {
                 let scales = scale.cast_to::<f32>()?;
@@ -114,7 +238,45 @@ impl Resize {
                 return Ok(size.as_slice::<i64>()?.iter().map(|i| *i as usize).collect());
             }
         }
-        bail!("Neither shape not scale makes sense: input_shape: {:?}, scale: {:?}, sizes: {:?}")
+        bail!(
+            "Neither shape not scale makes sense: input_shape: {:?}, scale: {:?}, sizes: {:?}",
+            input_shape,
+            input_scale,
+            input_sizes
+        )
+    }
+
+    /// Input positions and weights contributing to an output point which
+    /// maps to `x_in` in the input.
+    fn weights(&self, x_in: f32, len_in: usize) -> TVec<(usize, f32)> {
+        let clamp = |x: isize| x.max(0).min(len_in as isize - 1) as usize;
+        match self.interpolator {
+            Interpolator::Nearest => tvec!((clamp(self.nearest.round(x_in) as isize), 1.0)),
+            Interpolator::Linear => {
+                let x_left = x_in.floor();
+                let ratio = x_in - x_left;
+                let x_left = x_left as isize;
+                tvec!((clamp(x_left), 1.0 - ratio), (clamp(x_left + 1), ratio))
+            }
+            Interpolator::Cubic => {
+                let x_floor = x_in.floor();
+                let mut weights: TVec<(usize, f32)> = tvec!();
+                for offset in -1..=2 {
+                    let x = x_floor as isize + offset;
+                    let w = cubic_coeff(self.cubic_coeff_a, x as f32 - x_in);
+                    if x >= 0 && x < len_in as isize {
+                        weights.push((x as usize, w));
+                    } else if !self.exclude_outside {
+                        weights.push((clamp(x), w));
+                    }
+                }
+                if self.exclude_outside {
+                    let sum: f32 = weights.iter().map(|p| p.1).sum();
+                    weights.iter_mut().for_each(|p| p.1 /= sum);
+                }
+                weights
+            }
+        }
     }
 }
 
@@ -123,43 +285,66 @@ impl EvalOp for Resize {
         true
     }
 
-    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
-        let scales = self.optional_scales_input.and_then(|ix| inputs.get(ix));
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let roi = self.optional_roi_input.and_then(|ix| inputs.get(ix));
+        let scales = self.static_scales.as_ref().or_else(|| {
+            self.optional_scales_input.and_then(|ix| inputs.get(ix)).filter(|s| s.len() > 0)
+        });
         let sizes = self.optional_sizes_input.and_then(|ix| inputs.get(ix));
         let output_shape = self.compute_output_shape(
             inputs[0].shape(),
             scales.map(|t| &**t),
             sizes.map(|t| &**t),
         )?;
-        let mut data = inputs.remove(0).into_tensor().into_array::<f32>()?;
-        for axis in 0..data.ndim() {
-            if output_shape[axis] == data.shape()[axis] {
+        let rank = inputs[0].rank();
+        let scales = scales.map(|s| s.cast_to::<f32>()).transpose()?;
+        let roi = if self.coord_transformer == CoordTransformer::TfCropAndResize {
+            let roi = roi.unwrap().cast_to::<f32>()?.into_owned();
+            if roi.len() != 2 * rank {
+                bail!("Resize: expected a roi of length {}, got {:?}", 2 * rank, roi);
+            }
+            Some(roi)
+        } else {
+            None
+        };
+        let mut data = inputs[0].cast_to::<f32>()?.into_owned().into_array::<f32>()?;
+        for axis in 0..rank {
+            let len_in = data.shape()[axis];
+            let len_out = output_shape[axis];
+            let scale = if let Some(scales) = &scales {
+                scales.as_slice::<f32>()?[axis]
+            } else {
+                len_out as f32 / len_in as f32
+            };
+            let roi = if let Some(roi) = &roi {
+                let roi = roi.as_slice::<f32>()?;
+                (roi[axis], roi[rank + axis])
+            } else {
+                (0.0, 1.0)
+            };
+            if len_in == len_out && scale == 1.0 && roi == (0.0, 1.0) {
                 continue;
-            } else if output_shape[axis] > data.shape()[axis] {
-                let scale = output_shape[axis] as f32 / data.shape()[axis] as f32;
-                let mut new_shape: TVec<usize> = data.shape().into();
-                new_shape[axis] = output_shape[axis];
-                data = tract_ndarray::ArrayD::from_shape_fn(&*new_shape, |co_o| -> f32 {
-                    let x_out = co_o[axis];
-                    let x_in = self.coord_transformer.transform(
-                        x_out,
-                        scale,
-                        data.shape()[axis],
-                        new_shape[axis],
-                    );
-                    let mut co_i = co_o.clone();
-                    let x_left = (x_in as usize).min(data.shape()[axis] - 1).max(0);
-                    co_i[axis] = x_left;
-                    let y_left = data[&co_i];
-                    let x_right = (x_left + 1).min(data.shape()[axis] - 1);
-                    co_i[axis] = x_right;
-                    let y_right = data[&co_i];
-                    let x_frac = x_in - x_left as f32;
-                    self.interpolator.interpolate(y_left, y_right, x_frac)
-                })
             }
+            let mut new_shape: TVec<usize> = data.shape().into();
+            new_shape[axis] = len_out;
+            let mut output = ArrayD::<f32>::zeros(&*new_shape);
+            for x_out in 0..len_out {
+                let x_in = self.coord_transformer.transform(x_out, scale, len_in, len_out, roi);
+                let mut slice = output.index_axis_mut(Axis(axis), x_out);
+                if self.coord_transformer == CoordTransformer::TfCropAndResize
+                    && (x_in < 0.0 || x_in > len_in as f32 - 1.0)
+                {
+                    slice.fill(self.extrapolation_value);
+                    continue;
+                }
+                for (x, w) in self.weights(x_in, len_in) {
+                    slice.scaled_add(w, &data.index_axis(Axis(axis), x));
+                }
+            }
+            data = output;
         }
-        Ok(tvec!(data.into_arc_tensor()))
+        let output = data.into_tensor().cast_to_dt(inputs[0].datum_type())?.into_owned();
+        Ok(tvec!(output.into_arc_tensor()))
     }
 }
 
@@ -173,12 +358,24 @@ impl InferenceRulesOp for Resize {
         check_output_arity(&outputs, 1)?;
         s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
         s.equals(&inputs[0].rank, &outputs[0].rank)?;
-        if inputs.len() == 3 && self.optional_scales_input == Some(2) {
+        if let Some(scales) = &self.static_scales {
+            s.given(&inputs[0].shape, move |s, input_shape| {
+                let input_shape = input_shape
+                    .iter()
+                    .map(|d| d.to_usize())
+                    .collect::<TractResult<TVec<usize>>>()?;
+                let output_size = self.compute_output_shape(&input_shape, Some(scales), None)?;
+                for (i, d) in output_size.iter().enumerate() {
+                    s.equals(&outputs[0].shape[i], d.to_dim())?;
+                }
+                Ok(())
+            })
+        } else if self.optional_sizes_input.is_none() {
             rules_with_scales(self, s, inputs, outputs)
-        } else if inputs.len() == 3 && self.optional_sizes_input == Some(2) {
+        } else if self.optional_scales_input.is_none() {
             rules_with_sizes(self, s, inputs, outputs)
         } else {
-            // bogus 4 inputs case
+            // both scales and sizes are present, one of them should be empty
             s.given_2(
                 &inputs[0].rank,
                 &inputs[self.optional_scales_input.unwrap()].shape,
@@ -258,12 +455,4 @@ impl TypedOp for Resize {
         )?;
         Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &output_shape)))
     }
-
-    fn declutter(
-        &self,
-        _model: &TypedModel,
-        _node: &TypedNode,
-    ) -> TractResult<Option<TypedModelPatch>> {
-        Ok(None)
-    }
 }
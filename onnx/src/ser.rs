@@ -0,0 +1,806 @@
+//! Serialization of decluttered typed models to ONNX protobuf.
+
+use std::convert::TryFrom;
+
+use crate::pb::attribute_proto::AttributeType;
+use crate::pb::tensor_proto::DataType;
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops;
+use tract_hir::tract_core::ops::cnn::{KernelFormat, PaddingSpec};
+use tract_hir::tract_core::ops::nn::{DataFormat, Reducer};
+use tract_hir::tract_core::ops::scan::{InputMapping, StateInitializer};
+
+/// Operator set the exported models are written against.
+pub const OPSET_VERSION: i64 = 12;
+const IR_VERSION: i64 = 7;
+
+pub fn to_proto_model(model: &TypedModel) -> TractResult<ModelProto> {
+    let mut into_onnx = IntoOnnx::new(model, String::new());
+    for &input in model.input_outlets()? {
+        let name = into_onnx.outlet_name(input);
+        into_onnx.graph.input.push(value_info(&name, model.outlet_fact(input)?)?);
+        into_onnx.names.insert(input, name);
+    }
+    into_onnx.translate()?;
+    for &output in model.output_outlets()? {
+        let name = into_onnx.names[&output].clone();
+        into_onnx.graph.output.push(value_info(&name, model.outlet_fact(output)?)?);
+    }
+    let mut graph = into_onnx.graph;
+    graph.name = "tract".to_string();
+    Ok(ModelProto {
+        ir_version: IR_VERSION,
+        opset_import: vec![OperatorSetIdProto { domain: String::new(), version: OPSET_VERSION }],
+        producer_name: "tract".to_string(),
+        producer_version: env!("CARGO_PKG_VERSION").to_string(),
+        graph: Some(graph),
+        ..ModelProto::default()
+    })
+}
+
+struct IntoOnnx<'a> {
+    model: &'a TypedModel,
+    prefix: String,
+    names: HashMap<OutletId, String>,
+    graph: GraphProto,
+}
+
+impl<'a> IntoOnnx<'a> {
+    fn new(model: &'a TypedModel, prefix: String) -> IntoOnnx<'a> {
+        IntoOnnx { model, prefix, names: HashMap::default(), graph: GraphProto::default() }
+    }
+
+    fn outlet_name(&self, outlet: OutletId) -> String {
+        let node = &self.model.nodes()[outlet.node];
+        if outlet.slot == 0 {
+            format!("{}{}", self.prefix, node.name)
+        } else {
+            format!("{}{}.{}", self.prefix, node.name, outlet.slot)
+        }
+    }
+
+    /// Translates every node not named yet. Sources must have been named by
+    /// the caller.
+    fn translate(&mut self) -> TractResult<()> {
+        for id in self.model.eval_order()? {
+            let node = &self.model.nodes()[id];
+            if self.names.contains_key(&OutletId::new(id, 0)) {
+                continue;
+            }
+            if let Some(k) = node.op_as::<ops::konst::Const>() {
+                let name = self.outlet_name(id.into());
+                self.initializer(name.clone(), &k.0)?;
+                self.names.insert(id.into(), name);
+                continue;
+            }
+            if node.op_is::<ops::source::TypedSource>() {
+                bail!("Source {} has not been mapped", node);
+            }
+            let inputs: Vec<String> = node.inputs.iter().map(|i| self.names[i].clone()).collect();
+            let outputs: Vec<String> = (0..node.outputs.len())
+                .map(|slot| self.outlet_name(OutletId::new(id, slot)))
+                .collect();
+            self.translate_node(node, inputs, outputs.clone())
+                .with_context(|| format!("Translating {} to ONNX", node))?;
+            for (slot, name) in outputs.into_iter().enumerate() {
+                self.names.insert(OutletId::new(id, slot), name);
+            }
+        }
+        Ok(())
+    }
+
+    fn translate_node(
+        &mut self,
+        node: &TypedNode,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        let name = format!("{}{}", self.prefix, node.name);
+        if let Some(op) = node.op_as::<ops::cnn::ConvUnary>() {
+            self.conv(&name, op, inputs, outputs)
+        } else if let Some(op) = node.op_as::<ops::matmul::MatMulUnary>() {
+            if op.q_params.is_some() {
+                bail!("Quantized matmul can not be exported");
+            }
+            let rank = op.a.rank();
+            let mut a = op.a.clone().into_tensor();
+            if op.a_trans {
+                let perm: Vec<usize> =
+                    transposed_last(rank).into_iter().map(|a| a as usize).collect();
+                a = a.permute_axes(&perm)?;
+            }
+            let a = self.initializer(format!("{}.a", name), &a)?;
+            let b = inputs[0].clone();
+            let b_rank = self.model.outlet_fact(node.inputs[0])?.rank();
+            self.matmul(&name, (a, false, rank), (b, op.b_trans, b_rank), op.c_trans, outputs)
+        } else if let Some(op) = node.op_as::<ops::matmul::MatMul>() {
+            if op.q_params.is_some() {
+                bail!("Quantized matmul can not be exported");
+            }
+            let a_rank = self.model.outlet_fact(node.inputs[0])?.rank();
+            let b_rank = self.model.outlet_fact(node.inputs[1])?.rank();
+            self.matmul(
+                &name,
+                (inputs[0].clone(), op.a_trans, a_rank),
+                (inputs[1].clone(), op.b_trans, b_rank),
+                op.c_trans,
+                outputs,
+            )
+        } else if let Some(op) = node.op_as::<ops::element_wise::ElementWiseOp>() {
+            let input = inputs[0].clone();
+            if let Some(cast) = op.0.downcast_ref::<ops::cast::Cast>() {
+                let to = DataType::try_from(cast.to)? as i64;
+                self.node(&name, "Cast", inputs, outputs, vec![attr_int("to", to)]);
+                return Ok(());
+            }
+            match &*op.0.name() {
+                "Square" => self.node(&name, "Mul", vec![input.clone(), input], outputs, vec![]),
+                "Rsqrt" => {
+                    let sqrt = format!("{}.sqrt", name);
+                    self.node(&sqrt, "Sqrt", vec![input], vec![sqrt.clone()], vec![]);
+                    self.node(&name, "Reciprocal", vec![sqrt], outputs, vec![]);
+                }
+                other => {
+                    let op_type = element_wise_op_type(other)
+                        .with_context(|| format!("No ONNX operator for {}", other))?;
+                    self.node(&name, op_type, inputs, outputs, vec![]);
+                }
+            }
+            Ok(())
+        } else if let Some(op) = node.op_as::<ops::binary::TypedBinOp>() {
+            let (op_type, flipped) = bin_op_type(op.0.name())?;
+            let mut inputs = inputs;
+            if flipped {
+                inputs.reverse();
+            }
+            self.node(&name, op_type, inputs, outputs, vec![]);
+            Ok(())
+        } else if let Some(op) = node.op_as::<ops::binary::UnaryOp>() {
+            let (op_type, flipped) = bin_op_type(op.mini_op.name())?;
+            let a = self.initializer(format!("{}.a", name), &op.a)?;
+            let mut inputs = vec![a, inputs[0].clone()];
+            if flipped {
+                inputs.reverse();
+            }
+            self.node(&name, op_type, inputs, outputs, vec![]);
+            Ok(())
+        } else if let Some(op) = node.op_as::<AxisOp>() {
+            self.axis_op(&name, node, op, inputs, outputs)
+        } else if let Some(op) = node.op_as::<ops::nn::Reduce>() {
+            let axes = op.axes.iter().map(|&a| a as i64);
+            let op_type = match op.reducer {
+                Reducer::ArgMax(_) => "ArgMax",
+                Reducer::ArgMin(_) => "ArgMin",
+                Reducer::Max => "ReduceMax",
+                Reducer::Min => "ReduceMin",
+                Reducer::Prod => "ReduceProd",
+                Reducer::Sum => "ReduceSum",
+            };
+            let attributes = if let Reducer::ArgMax(last) | Reducer::ArgMin(last) = op.reducer {
+                if op.axes.len() != 1 {
+                    bail!("ONNX ArgMax and ArgMin work on a single axis, got {:?}", op.axes);
+                }
+                vec![
+                    attr_int("axis", op.axes[0] as i64),
+                    attr_int("keepdims", 1),
+                    attr_int("select_last_index", last as i64),
+                ]
+            } else {
+                vec![attr_ints("axes", axes), attr_int("keepdims", 1)]
+            };
+            self.node(&name, op_type, inputs, outputs, attributes);
+            Ok(())
+        } else if let Some(op) = node.op_as::<ops::array::Slice>() {
+            let start = op.start.to_i64().context("Slice start must be known")?;
+            let end = op.end.to_i64().context("Slice end must be known")?;
+            let mut inputs = inputs;
+            inputs.push(self.initializer(format!("{}.starts", name), &tensor1(&[start]))?);
+            inputs.push(self.initializer(format!("{}.ends", name), &tensor1(&[end]))?);
+            inputs.push(self.initializer(format!("{}.axes", name), &tensor1(&[op.axis as i64]))?);
+            self.node(&name, "Slice", inputs, outputs, vec![]);
+            Ok(())
+        } else if let Some(op) = node.op_as::<ops::array::TypedConcat>() {
+            let mut vars = inputs.into_iter();
+            let mut inputs = vec![];
+            for (ix, slice) in op.slices.iter().enumerate() {
+                match slice {
+                    ops::array::ConcatSlice::Const(t) => {
+                        inputs.push(self.initializer(format!("{}.slice-{}", name, ix), t)?)
+                    }
+                    ops::array::ConcatSlice::Var => inputs.push(vars.next().unwrap()),
+                }
+            }
+            self.node(&name, "Concat", inputs, outputs, vec![attr_int("axis", op.axis as i64)]);
+            Ok(())
+        } else if let Some(op) = node.op_as::<ops::scan::Scan>() {
+            self.scan(&name, op, inputs, outputs)
+        } else {
+            bail!("No ONNX translation for {} operator", node.op.name())
+        }
+    }
+
+    fn conv(
+        &mut self,
+        name: &str,
+        op: &ops::cnn::ConvUnary,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        if op.q_params.is_some() {
+            bail!("Quantized convolution can not be exported");
+        }
+        if op.pool_spec.data_format != DataFormat::NCHW {
+            bail!("Only NCHW convolutions can be exported, got {:?}", op.pool_spec.data_format);
+        }
+        let kernel = match op.kernel_fmt {
+            KernelFormat::OIHW => op.kernel.clone().into_tensor(),
+            KernelFormat::HWIO => {
+                // group-major output channels, as ONNX expects them
+                let o = op.kernel.shape()[op.kernel.rank() - 1] * op.group;
+                let spatial = op.pool_spec.kernel_shape.iter().product::<usize>();
+                let mut shape = tvec!(o, op.kernel.len() / o / spatial);
+                shape.extend(op.pool_spec.kernel_shape.iter().cloned());
+                op.kernel_as_group_o_ihw()?.into_tensor().into_shape(&shape)?
+            }
+        };
+        let mut inputs = inputs;
+        inputs.push(self.initializer(format!("{}.kernel", name), &kernel)?);
+        if let Some(bias) = &op.bias {
+            inputs.push(self.initializer(format!("{}.bias", name), bias)?);
+        }
+        let to_i64 = |v: &[usize]| v.iter().map(|&x| x as i64).collect::<Vec<_>>();
+        let mut attributes = vec![
+            attr_ints("kernel_shape", to_i64(&op.pool_spec.kernel_shape)),
+            attr_int("group", op.group as i64),
+        ];
+        if let Some(strides) = &op.pool_spec.strides {
+            attributes.push(attr_ints("strides", to_i64(strides)));
+        }
+        if let Some(dilations) = &op.pool_spec.dilations {
+            attributes.push(attr_ints("dilations", to_i64(dilations)));
+        }
+        match &op.pool_spec.padding {
+            PaddingSpec::Explicit(before, after, _) => {
+                attributes.push(attr_ints("pads", to_i64(before).into_iter().chain(to_i64(after))))
+            }
+            PaddingSpec::Valid => attributes.push(attr_string("auto_pad", "VALID")),
+            PaddingSpec::SameUpper => attributes.push(attr_string("auto_pad", "SAME_UPPER")),
+            PaddingSpec::SameLower => attributes.push(attr_string("auto_pad", "SAME_LOWER")),
+        }
+        self.node(name, "Conv", inputs, outputs, attributes);
+        Ok(())
+    }
+
+    fn matmul(
+        &mut self,
+        name: &str,
+        (a, a_trans, a_rank): (String, bool, usize),
+        (b, b_trans, b_rank): (String, bool, usize),
+        c_trans: bool,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        let a =
+            if a_trans { self.transpose_last(&format!("{}.a_trans", name), a, a_rank) } else { a };
+        let b =
+            if b_trans { self.transpose_last(&format!("{}.b_trans", name), b, b_rank) } else { b };
+        if c_trans {
+            let c = format!("{}.c", name);
+            self.node(&c, "MatMul", vec![a, b], vec![c.clone()], vec![]);
+            let rank = a_rank.max(b_rank);
+            let perm = transposed_last(rank);
+            self.node(name, "Transpose", vec![c], outputs, vec![attr_ints("perm", perm)]);
+        } else {
+            self.node(name, "MatMul", vec![a, b], outputs, vec![]);
+        }
+        Ok(())
+    }
+
+    fn transpose_last(&mut self, name: &str, input: String, rank: usize) -> String {
+        let perm = transposed_last(rank);
+        self.node(
+            name,
+            "Transpose",
+            vec![input],
+            vec![name.to_string()],
+            vec![attr_ints("perm", perm)],
+        );
+        name.to_string()
+    }
+
+    fn axis_op(
+        &mut self,
+        name: &str,
+        node: &TypedNode,
+        op: &AxisOp,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        match op {
+            AxisOp::Add(axis) => {
+                let attributes = vec![attr_ints("axes", Some(*axis as i64))];
+                self.node(name, "Unsqueeze", inputs, outputs, attributes);
+            }
+            AxisOp::Rm(axis) => {
+                let attributes = vec![attr_ints("axes", Some(*axis as i64))];
+                self.node(name, "Squeeze", inputs, outputs, attributes);
+            }
+            AxisOp::Move(from, to) => {
+                let rank = self.model.outlet_fact(node.inputs[0])?.rank();
+                let mut perm: Vec<i64> = (0..rank as i64).collect();
+                let axis = perm.remove(*from);
+                perm.insert(*to, axis);
+                self.node(name, "Transpose", inputs, outputs, vec![attr_ints("perm", perm)]);
+            }
+            AxisOp::Reshape(at, _, _) => {
+                let output_shape = &node.outputs[0].fact.shape;
+                let symbolic = output_shape.iter().filter(|d| d.to_i64().is_err()).count();
+                let shape = output_shape
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, d)| {
+                        if let Ok(d) = d.to_i64() {
+                            Ok(d)
+                        } else if ix < *at {
+                            Ok(0)
+                        } else if symbolic == 1 {
+                            Ok(-1)
+                        } else {
+                            bail!("Can not express reshape to {:?} in ONNX", output_shape)
+                        }
+                    })
+                    .collect::<TractResult<Vec<i64>>>()?;
+                let shape = self.initializer(format!("{}.shape", name), &tensor1(&shape))?;
+                let mut inputs = inputs;
+                inputs.push(shape);
+                self.node(name, "Reshape", inputs, outputs, vec![]);
+            }
+        }
+        Ok(())
+    }
+
+    fn scan(
+        &mut self,
+        name: &str,
+        op: &ops::scan::Scan,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        if op.skip != 0 || op.seq_length_input_slot.is_some() {
+            bail!("Scan with skip or sequence length input can not be exported");
+        }
+        let mut body = IntoOnnx::new(&op.body, format!("{}.", name));
+        let mut state_inputs = vec![];
+        let mut scan_inputs = vec![];
+        let mut body_state_inputs = vec![];
+        let mut body_scan_inputs = vec![];
+        let mut scan_input_axes = vec![];
+        for (ix, mapping) in op.input_mapping.iter().enumerate() {
+            let outlet = op.body.input_outlets()?[ix];
+            let fact = op.body.outlet_fact(outlet)?;
+            let body_name = body.outlet_name(outlet);
+            match mapping {
+                InputMapping::Full { slot } => {
+                    // ONNX bodies see the outer scope: refer to the outer value directly
+                    body.names.insert(outlet, inputs[*slot].clone());
+                }
+                InputMapping::State { initializer } => {
+                    state_inputs.push(match initializer {
+                        StateInitializer::FromInput(slot) => inputs[*slot].clone(),
+                        StateInitializer::Value(v) => {
+                            self.initializer(format!("{}.init", body_name), v)?
+                        }
+                    });
+                    body_state_inputs.push(value_info(&body_name, fact)?);
+                    body.names.insert(outlet, body_name);
+                }
+                InputMapping::Scan { slot, axis, chunk } => {
+                    if *chunk != 1 {
+                        bail!("Only forward scans by steps of 1 can be exported, got {}", chunk);
+                    }
+                    let slice = format!("{}.slice", body_name);
+                    let mut slice_fact = fact.clone();
+                    slice_fact.shape.remove_axis(*axis)?;
+                    body_scan_inputs.push(value_info(&slice, &slice_fact)?);
+                    body.node(
+                        &body_name,
+                        "Unsqueeze",
+                        vec![slice],
+                        vec![body_name.clone()],
+                        vec![attr_ints("axes", Some(*axis as i64))],
+                    );
+                    body.names.insert(outlet, body_name);
+                    scan_inputs.push(inputs[*slot].clone());
+                    scan_input_axes.push(*axis as i64);
+                }
+            }
+        }
+        body.translate()?;
+
+        let mut state_outputs = vec![];
+        let mut scan_outputs = vec![];
+        let mut body_state_outputs = vec![];
+        let mut body_scan_outputs = vec![];
+        let mut scan_output_axes = vec![];
+        for (ix, mapping) in op.output_mapping.iter().enumerate() {
+            let outlet = op.body.output_outlets()?[ix];
+            let fact = op.body.outlet_fact(outlet)?;
+            let body_name = body.names[&outlet].clone();
+            if mapping.state {
+                if mapping.full_slot.is_some() {
+                    bail!("Full output of a state can not be exported");
+                }
+                body_state_outputs.push(value_info(&body_name, fact)?);
+                state_outputs.push(match mapping.last_value_slot {
+                    Some(slot) => outputs[slot].clone(),
+                    None => format!("{}.state-{}", name, ix),
+                });
+            } else {
+                if mapping.last_value_slot.is_some() || mapping.chunk != 1 {
+                    bail!("Only full scan outputs by steps of 1 can be exported");
+                }
+                let slice = format!("{}.output-{}", body.prefix, ix);
+                let mut slice_fact = fact.clone();
+                slice_fact.shape.remove_axis(mapping.axis)?;
+                body.node(
+                    &slice,
+                    "Squeeze",
+                    vec![body_name],
+                    vec![slice.clone()],
+                    vec![attr_ints("axes", Some(mapping.axis as i64))],
+                );
+                body_scan_outputs.push(value_info(&slice, &slice_fact)?);
+                scan_outputs.push(match mapping.full_slot {
+                    Some(slot) => outputs[slot].clone(),
+                    None => format!("{}.output-{}", name, ix),
+                });
+                scan_output_axes.push(mapping.axis as i64);
+            }
+        }
+
+        let mut graph = body.graph;
+        graph.name = name.to_string();
+        graph.input = body_state_inputs.into_iter().chain(body_scan_inputs).collect();
+        graph.output = body_state_outputs.into_iter().chain(body_scan_outputs).collect();
+        let attributes = vec![
+            attr_graph("body", graph),
+            attr_int("num_scan_inputs", scan_inputs.len() as i64),
+            attr_ints("scan_input_axes", scan_input_axes),
+            attr_ints("scan_output_axes", scan_output_axes),
+        ];
+        let inputs = state_inputs.into_iter().chain(scan_inputs).collect();
+        let outputs = state_outputs.into_iter().chain(scan_outputs).collect();
+        self.node(name, "Scan", inputs, outputs, attributes);
+        Ok(())
+    }
+
+    fn node(
+        &mut self,
+        name: &str,
+        op_type: &str,
+        input: Vec<String>,
+        output: Vec<String>,
+        attribute: Vec<AttributeProto>,
+    ) {
+        self.graph.node.push(NodeProto {
+            name: name.to_string(),
+            op_type: op_type.to_string(),
+            input,
+            output,
+            attribute,
+            ..NodeProto::default()
+        })
+    }
+
+    fn initializer(&mut self, name: String, tensor: &Tensor) -> TractResult<String> {
+        let mut proto = TensorProto::try_from(tensor)?;
+        proto.name = name.clone();
+        self.graph.initializer.push(proto);
+        Ok(name)
+    }
+}
+
+fn element_wise_op_type(name: &str) -> Option<&'static str> {
+    let op_type = match name {
+        "Abs" => "Abs",
+        "Exp" => "Exp",
+        "Ln" => "Log",
+        "Sqrt" => "Sqrt",
+        "Recip" => "Reciprocal",
+        "Neg" => "Neg",
+        "Sign" => "Sign",
+        "Ceil" => "Ceil",
+        "Floor" => "Floor",
+        "RoundHalfToEven" => "Round",
+        "Sigmoid" => "Sigmoid",
+        "Cos" => "Cos",
+        "Sin" => "Sin",
+        "Tan" => "Tan",
+        "Acos" => "Acos",
+        "Asin" => "Asin",
+        "Atan" => "Atan",
+        "Cosh" => "Cosh",
+        "Sinh" => "Sinh",
+        "Tanh" => "Tanh",
+        "Acosh" => "Acosh",
+        "Asinh" => "Asinh",
+        "Atanh" => "Atanh",
+        "Erf" => "Erf",
+        "Not" => "Not",
+        _ => return None,
+    };
+    Some(op_type)
+}
+
+/// ONNX operator for a binary mini op, and whether its operands are swapped.
+fn bin_op_type(name: &str) -> TractResult<(&'static str, bool)> {
+    let op_type = match name {
+        "Add" => ("Add", false),
+        "Sub" => ("Sub", false),
+        "Mul" => ("Mul", false),
+        "Div" => ("Div", false),
+        "Pow" => ("Pow", false),
+        "FlippedPow" => ("Pow", true),
+        "Max" => ("Max", false),
+        "Min" => ("Min", false),
+        "And" => ("And", false),
+        "Or" => ("Or", false),
+        "Xor" => ("Xor", false),
+        "Equals" => ("Equal", false),
+        "Lesser" => ("Less", false),
+        "LesserEqual" => ("LessOrEqual", false),
+        "Greater" => ("Greater", false),
+        "GreaterEqual" => ("GreaterOrEqual", false),
+        _ => bail!("No ONNX operator for {}", name),
+    };
+    Ok(op_type)
+}
+
+fn transposed_last(rank: usize) -> Vec<i64> {
+    let mut perm: Vec<i64> = (0..rank as i64).collect();
+    perm.swap(rank - 2, rank - 1);
+    perm
+}
+
+fn value_info(name: &str, fact: &TypedFact) -> TractResult<ValueInfoProto> {
+    let dim = fact
+        .shape
+        .iter()
+        .map(|d| {
+            use tensor_shape_proto::dimension::Value;
+            // only plain symbols can be named, expressions are left for inference to recover
+            let value = if let Ok(d) = d.to_i64() {
+                Some(Value::DimValue(d))
+            } else if let TDim::Sym(sym) = d {
+                Some(Value::DimParam(sym.to_string()))
+            } else {
+                None
+            };
+            tensor_shape_proto::Dimension { value, ..Default::default() }
+        })
+        .collect();
+    let tensor = type_proto::Tensor {
+        elem_type: DataType::try_from(fact.datum_type)? as i32,
+        shape: Some(TensorShapeProto { dim }),
+    };
+    Ok(ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(tensor)),
+            ..TypeProto::default()
+        }),
+        ..ValueInfoProto::default()
+    })
+}
+
+fn attr_int(name: &str, i: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Int as i32,
+        i,
+        ..AttributeProto::default()
+    }
+}
+
+fn attr_ints(name: &str, ints: impl IntoIterator<Item = i64>) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Ints as i32,
+        ints: ints.into_iter().collect(),
+        ..AttributeProto::default()
+    }
+}
+
+fn attr_string(name: &str, s: &str) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::String as i32,
+        s: s.as_bytes().to_vec(),
+        ..AttributeProto::default()
+    }
+}
+
+fn attr_graph(name: &str, g: GraphProto) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Graph as i32,
+        g: Some(g),
+        ..AttributeProto::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::onnx;
+    use tract_hir::tract_core::ops::cnn::{ConvUnary, PoolSpec};
+    use tract_hir::tract_core::ops::matmul::MatMulUnary;
+    use tract_hir::tract_core::ops::scan::{OutputMapping, Scan};
+
+    fn round_trip(model: &TypedModel, input: Tensor) -> TractResult<()> {
+        let mut buf = vec![];
+        onnx().write(model, &mut buf)?;
+        let reloaded = onnx().model_for_read(&mut &*buf)?.into_optimized()?;
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let found = reloaded.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(expected.len(), found.len());
+        for (e, f) in expected.iter().zip(found.iter()) {
+            e.close_enough(f, true)?;
+        }
+        Ok(())
+    }
+
+    fn seq(len: usize, shape: &[usize]) -> Tensor {
+        let data: Vec<f32> = (0..len).map(|i| ((i * 7) % 11) as f32 / 11. - 0.5).collect();
+        tract_ndarray::ArrayD::from_shape_vec(shape, data).unwrap().into()
+    }
+
+    #[test]
+    fn conv_matmul_and_friends() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [1, 2, 5, 5]))?;
+        let conv = ConvUnary {
+            pool_spec: PoolSpec {
+                data_format: DataFormat::NCHW,
+                kernel_shape: tvec!(3, 3),
+                padding: PaddingSpec::Explicit(tvec!(1, 1), tvec!(1, 0), false),
+                dilations: None,
+                strides: Some(tvec!(1, 2)),
+                output_channel_override: Some(4),
+            },
+            kernel_fmt: KernelFormat::OIHW,
+            kernel: seq(72, &[4, 2, 3, 3]).into_arc_tensor(),
+            group: 1,
+            bias: Some(rctensor1(&[0.1f32, 0.2, 0.3, 0.4])),
+            q_params: None,
+        };
+        let wire = model.wire_node("conv", conv, &[x])?;
+        let grouped = ConvUnary {
+            pool_spec: PoolSpec {
+                data_format: DataFormat::NCHW,
+                kernel_shape: tvec!(1, 1),
+                padding: PaddingSpec::Valid,
+                dilations: None,
+                strides: None,
+                output_channel_override: Some(2),
+            },
+            kernel_fmt: KernelFormat::HWIO,
+            kernel: seq(4, &[1, 1, 4, 1]).into_arc_tensor(),
+            group: 2,
+            bias: None,
+            q_params: None,
+        };
+        let wire = model.wire_node("grouped", grouped, &wire)?;
+        let wire = model.wire_node("move", AxisOp::Move(1, 3), &wire)?;
+        let wire = model.wire_node(
+            "reshape",
+            AxisOp::Reshape(2, tvec!(2.to_dim(), 2.to_dim()), tvec!(4.to_dim())),
+            &wire,
+        )?;
+        let matmul = MatMulUnary {
+            a: seq(20, &[1, 5, 4]).into_arc_tensor(),
+            a_trans: true,
+            b_trans: false,
+            c_trans: true,
+            q_params: None,
+        };
+        let wire = model.wire_node("matmul", matmul, &wire)?;
+        let wire = model.wire_node(
+            "sub",
+            ops::binary::UnaryOp::new(Box::new(ops::math::Sub), rctensor3(&[[[1f32, 2., 3., 4.]]])),
+            &wire,
+        )?;
+        let wire = model.wire_node("sigmoid", ops::nn::sigmoid(), &wire)?;
+        let wire =
+            model.wire_node("reduce", ops::nn::Reduce::new(tvec!(2), Reducer::Sum), &wire)?;
+        let wire = model.wire_node("rm", AxisOp::Rm(2), &wire)?;
+        model.set_output_outlets(&wire)?;
+        round_trip(&model, seq(50, &[1, 2, 5, 5]))
+    }
+
+    #[test]
+    fn symbolic_batch_and_sequence() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let b = model.symbol_table.sym("batch").to_dim();
+        let t = model.symbol_table.sym("seq_len").to_dim();
+        let shape = [b.clone(), t.clone(), 4.to_dim()];
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), shape.as_ref()))?;
+        let reshape = AxisOp::Reshape(
+            0,
+            tvec!(b, t),
+            tvec!(model.outlet_fact(x)?.shape[0..2].iter().maybe_product()?),
+        );
+        let wire = model.wire_node("flatten", reshape, &[x])?;
+        model.set_output_outlets(&wire)?;
+        let mut buf = vec![];
+        onnx().write(&model, &mut buf)?;
+        let reloaded = onnx().model_for_read(&mut &*buf)?.into_typed()?;
+        let b = reloaded.symbol_table.get("batch").unwrap().to_dim();
+        let t = reloaded.symbol_table.get("seq_len").unwrap().to_dim();
+        assert_eq!(reloaded.output_fact(0)?.shape.to_tvec(), tvec!(b * t, 4.to_dim()));
+        Ok(())
+    }
+
+    #[test]
+    fn pulsify_symbolic_time() -> TractResult<()> {
+        use tract_pulse::internal::{stream_dim, PulsedModel, PulsedModelExt};
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S").to_dim();
+        let shape = [1.to_dim(), s, 3.to_dim()];
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), shape.as_ref()))?;
+        let wire = model.wire_node("abs", tract_hir::ops::math::abs(), &[x])?;
+        model.set_output_outlets(&wire)?;
+        let mut buf = vec![];
+        onnx().write(&model, &mut buf)?;
+        let reloaded = onnx().model_for_read(&mut &*buf)?.into_typed()?;
+        let pulsed = PulsedModel::new(&reloaded, 2)?;
+        let input = pulsed.input_fact(0)?;
+        assert_eq!((input.axis, &input.dim), (1, &stream_dim(&reloaded.symbol_table)));
+        let output = pulsed.output_fact(0)?;
+        assert_eq!(output.shape, tvec!(1.to_dim(), 2.to_dim(), 3.to_dim()));
+        Ok(())
+    }
+
+    #[test]
+    fn scan_cumulative_sum() -> TractResult<()> {
+        let mut body = TypedModel::default();
+        let acc = body.add_source("acc", TypedFact::dt_shape(f32::datum_type(), [1, 3]))?;
+        let x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), [1, 3]))?;
+        let sum = body.wire_node("sum", ops::math::add::bin_typed(), &[acc, x])?;
+        body.set_output_outlets(&[sum[0], sum[0]])?;
+        let scan = Scan::new(
+            body,
+            vec![
+                InputMapping::State {
+                    initializer: StateInitializer::Value(rctensor2(&[[0f32; 3]])),
+                },
+                InputMapping::Scan { slot: 0, axis: 0, chunk: 1 },
+            ],
+            vec![
+                OutputMapping {
+                    state: true,
+                    last_value_slot: Some(1),
+                    full_slot: None,
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                },
+                OutputMapping {
+                    state: false,
+                    last_value_slot: None,
+                    full_slot: Some(0),
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                },
+            ],
+            None,
+            0,
+        )?;
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [4, 3]))?;
+        let wires = model.wire_node("loop", scan, &[x])?;
+        model.set_output_outlets(&wires)?;
+        round_trip(&model, seq(12, &[4, 3]))
+    }
+}
@@ -18,6 +18,7 @@ impl TryFrom<DataType> for DatumType {
             DataType::Int32 => Ok(DatumType::I32),
             DataType::Int64 => Ok(DatumType::I64),
             DataType::Float16 => Ok(DatumType::F16),
+            DataType::Bfloat16 => Ok(DatumType::BF16),
             DataType::Float => Ok(DatumType::F32),
             DataType::Double => Ok(DatumType::F64),
             DataType::String => Ok(DatumType::String),
@@ -26,28 +27,60 @@ impl TryFrom<DataType> for DatumType {
     }
 }
 
+impl TryFrom<DatumType> for DataType {
+    type Error = TractError;
+    fn try_from(t: DatumType) -> TractResult<DataType> {
+        match t {
+            DatumType::Bool => Ok(DataType::Bool),
+            DatumType::U8 => Ok(DataType::Uint8),
+            DatumType::U16 => Ok(DataType::Uint16),
+            DatumType::U32 => Ok(DataType::Uint32),
+            DatumType::U64 => Ok(DataType::Uint64),
+            DatumType::I8 => Ok(DataType::Int8),
+            DatumType::I16 => Ok(DataType::Int16),
+            DatumType::I32 => Ok(DataType::Int32),
+            DatumType::I64 | DatumType::TDim => Ok(DataType::Int64),
+            DatumType::F16 => Ok(DataType::Float16),
+            DatumType::BF16 => Ok(DataType::Bfloat16),
+            DatumType::F32 => Ok(DataType::Float),
+            DatumType::F64 => Ok(DataType::Double),
+            DatumType::String => Ok(DataType::String),
+            _ => bail!("No ONNX equivalent for {:?}", t),
+        }
+    }
+}
+
+/// Translate an ONNX tensor type, creating the symbols for named dimensions in `symbols`.
+pub fn translate_inference_fact(
+    symbols: &SymbolTable,
+    t: &type_proto::Tensor,
+) -> TractResult<InferenceFact> {
+    use tensor_shape_proto::dimension::Value;
+    let mut fact = InferenceFact::default();
+    fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
+    if let Some(shape) = &t.shape {
+        let shape: TVec<DimFact> = shape
+            .dim
+            .iter()
+            .map(|d| match &d.value {
+                Some(Value::DimValue(v)) if *v > 0 => DimFact::from(v.to_dim()),
+                Some(Value::DimParam(name)) if !name.is_empty() => {
+                    DimFact::from(TDim::from(symbols.sym(name)))
+                }
+                _ => DimFact::default(),
+            })
+            .collect();
+        fact = fact.with_shape(ShapeFactoid::closed(shape));
+    }
+    Ok(fact)
+}
+
+/// Named dimensions become symbols of a fresh table: use `translate_inference_fact` to
+/// share them with a model.
 impl<'a> TryFrom<&'a type_proto::Tensor> for InferenceFact {
     type Error = TractError;
     fn try_from(t: &'a type_proto::Tensor) -> TractResult<InferenceFact> {
-        let mut fact = InferenceFact::default();
-        fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
-        if let Some(shape) = &t.shape {
-            let shape: TVec<DimFact> = shape
-                .dim
-                .iter()
-                .map(|d| {
-                    let mut fact = DimFact::default();
-                    if let Some(tensor_shape_proto::dimension::Value::DimValue(v)) = d.value {
-                        if v > 0 {
-                            fact = DimFact::from(v.to_dim())
-                        }
-                    }
-                    fact
-                })
-                .collect();
-            fact = fact.with_shape(ShapeFactoid::closed(shape));
-        }
-        Ok(fact)
+        translate_inference_fact(&SymbolTable::default(), t)
     }
 }
 
@@ -75,6 +108,7 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                     DatumType::I32 => Tensor::from_raw::<i32>(&*shape, &*t.raw_data),
                     DatumType::I64 => Tensor::from_raw::<i64>(&*shape, &*t.raw_data),
                     DatumType::F16 => Tensor::from_raw::<f16>(&*shape, &*t.raw_data),
+                    DatumType::BF16 => Tensor::from_raw::<bf16>(&*shape, &*t.raw_data),
                     DatumType::F32 => Tensor::from_raw::<f32>(&*shape, &*t.raw_data),
                     DatumType::F64 => Tensor::from_raw::<f64>(&*shape, &*t.raw_data),
                     DatumType::Bool => Ok(Tensor::from_raw::<u8>(&*shape, &*t.raw_data)?
@@ -119,6 +153,13 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                 .into(),
                 DatumType::I32 => Array::from_shape_vec(&*shape, t.int32_data.to_vec())?.into(),
                 DatumType::I64 => Array::from_shape_vec(&*shape, t.int64_data.to_vec())?.into(),
+                DatumType::F16 | DatumType::BF16 => {
+                    // half floats come as their bit patterns, one per int32
+                    let bits: Vec<u16> = t.int32_data.iter().map(|&x| x as u16).collect();
+                    let mut tensor: Tensor = Array::from_shape_vec(&*shape, bits)?.into();
+                    unsafe { tensor.set_datum_type(dt) };
+                    tensor
+                }
                 DatumType::F32 => Array::from_shape_vec(&*shape, t.float_data.to_vec())?.into(),
                 DatumType::F64 => Array::from_shape_vec(&*shape, t.double_data.to_vec())?.into(),
                 DatumType::String => {
@@ -145,6 +186,27 @@ impl TryFrom<TensorProto> for Tensor {
     }
 }
 
+impl TryFrom<&Tensor> for TensorProto {
+    type Error = TractError;
+    fn try_from(t: &Tensor) -> TractResult<TensorProto> {
+        if t.datum_type() == DatumType::TDim {
+            return (&*t.cast_to::<i64>()?).try_into();
+        }
+        let mut proto = TensorProto {
+            dims: t.shape().iter().map(|&d| d as i64).collect(),
+            data_type: DataType::try_from(t.datum_type())? as i32,
+            ..TensorProto::default()
+        };
+        if t.datum_type() == DatumType::String {
+            proto.string_data =
+                t.as_slice::<String>()?.iter().map(|s| s.as_bytes().to_vec()).collect();
+        } else {
+            proto.raw_data = unsafe { t.as_bytes() }.to_vec();
+        }
+        Ok(proto)
+    }
+}
+
 pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProto> {
     let mut v = vec![];
     r.read_to_end(&mut v)?;
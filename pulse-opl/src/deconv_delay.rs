@@ -0,0 +1,96 @@
+use tract_core::ndarray::*;
+use tract_nnef::internal::*;
+
+#[derive(Debug, Clone, Default)]
+struct DeconvDelayState {
+    buffer: Option<Tensor>,
+}
+
+impl OpState for DeconvDelayState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let op = op.downcast_ref::<DeconvDelay>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let output = dispatch_numbers!(Self::eval_t(input.datum_type())(self, op, &input))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl DeconvDelayState {
+    fn eval_t<T: Datum + Copy + std::ops::Add<T, Output = T>>(
+        &mut self,
+        op: &DeconvDelay,
+        input: &Tensor,
+    ) -> TractResult<Tensor> {
+        let mut sum = input.to_array_view::<T>()?.to_owned();
+        let output_pulse = input.shape()[op.axis] - op.overlap;
+        if let Some(buffer) = &self.buffer {
+            sum.slice_axis_mut(Axis(op.axis), (..op.overlap).into())
+                .zip_mut_with(&buffer.to_array_view::<T>()?, |s, b| *s = *s + *b);
+        }
+        self.buffer =
+            Some(sum.slice_axis(Axis(op.axis), (output_pulse..).into()).to_owned().into_tensor());
+        Ok(sum.slice_axis(Axis(op.axis), (..output_pulse).into()).to_owned().into_tensor())
+    }
+}
+
+/// Overlap-add of the successive pulses of a transposed convolution along the
+/// streaming axis.
+///
+/// Each input pulse is `overlap` frames longer than the output pulse. These
+/// trailing frames are kept and summed to the beginning of the next pulse.
+/// `delay` and `stream_dim` describe the output stream, once the cropping of
+/// the deconvolution is taken into account.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct DeconvDelay {
+    pub axis: usize,
+    pub overlap: usize,
+    pub delay: usize,
+    pub stream_dim: TDim,
+}
+
+impl_dyn_hash!(DeconvDelay);
+
+impl Op for DeconvDelay {
+    fn name(&self) -> Cow<str> {
+        "DeconvDelay".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {} overlap: {} delay: {} stream_dim: {}",
+            self.axis, self.overlap, self.delay, self.stream_dim
+        )])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvDelay {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(DeconvDelayState::default())))
+    }
+}
+
+impl TypedOp for DeconvDelay {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape.set(self.axis, fact.shape[self.axis].clone() - self.overlap.to_dim());
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+}
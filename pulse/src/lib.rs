@@ -57,7 +57,7 @@ mod tests {
                 "a",
                 TypedFact::dt_shape(
                     f32::datum_type(),
-                    [1.to_dim(), stream_dim(), 3.to_dim()].as_ref(),
+                    [1.to_dim(), stream_dim(&model.symbol_table), 3.to_dim()].as_ref(),
                 ),
             )
             .unwrap();
@@ -77,7 +77,7 @@ mod tests {
                 "a",
                 TypedFact::dt_shape(
                     f32::datum_type(),
-                    [stream_dim(), 2.to_dim(), 3.to_dim()].as_ref(),
+                    [stream_dim(&model.symbol_table), 2.to_dim(), 3.to_dim()].as_ref(),
                 ),
             )
             .unwrap();
@@ -94,4 +94,41 @@ mod tests {
             TypedFact::dt_shape(DatumType::F32, &[4, 2, 3])
         );
     }
+
+    #[test]
+    fn test_const_feeding_gather() {
+        let mut model = TypedModel::default();
+        let s = stream_dim(&model.symbol_table);
+        let fact = TypedFact::dt_shape(f32::datum_type(), [s, 3.to_dim()].as_ref());
+        let a = model.add_source("a", fact).unwrap();
+        let indices = model.add_const("indices", tensor1(&[2i64, 0])).unwrap();
+        let gather = tract_core::ops::array::Gather::new(1);
+        model.wire_node("gather", gather, &[a, indices]).unwrap();
+        model.auto_outputs().unwrap();
+        let pulse = PulsedModel::new(&model, 4).unwrap();
+        assert_eq!(
+            pulse.output_fact(0).unwrap().to_typed_fact().unwrap(),
+            TypedFact::dt_shape(DatumType::F32, &[4, 2])
+        );
+    }
+
+    #[test]
+    fn test_const_outside_gather_is_an_error() {
+        let mut model = TypedModel::default();
+        let s = stream_dim(&model.symbol_table);
+        let fact = TypedFact::dt_shape(f32::datum_type(), [s, 3.to_dim()].as_ref());
+        let a = model.add_source("a", fact).unwrap();
+        let k = model.add_const("k", rctensor2(&[[1f32, 2., 3.]])).unwrap();
+        model.wire_node("add", tract_core::ops::math::add::bin_typed(), &[a, k]).unwrap();
+        model.auto_outputs().unwrap();
+        assert!(PulsedModel::new(&model, 4).is_err());
+
+        let mut model = TypedModel::default();
+        let s = stream_dim(&model.symbol_table);
+        let fact = TypedFact::dt_shape(f32::datum_type(), [s, 3.to_dim()].as_ref());
+        model.add_source("a", fact).unwrap();
+        let k = model.add_const("k", rctensor1(&[1f32])).unwrap();
+        model.set_output_outlets(&[k]).unwrap();
+        assert!(PulsedModel::new(&model, 4).is_err());
+    }
 }
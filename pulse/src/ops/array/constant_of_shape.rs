@@ -0,0 +1,63 @@
+use crate::internal::*;
+use tract_core::ops::array::ConstantOfShape;
+
+submit_op_pulsifier!(ConstantOfShape, pulsify);
+
+fn pulsify(
+    op: &ConstantOfShape,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    _mapping: &HashMap<OutletId, OutletId>,
+    pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let fact = PulsedFact::from_tensor_fact_pulse(
+        &node.outputs[0].fact,
+        &stream_symbol(&source.symbol_table),
+        pulse,
+    )?;
+    let op = PulsedConstantOfShape { fact, scalar: op.scalar.clone() };
+    target.wire_node(&*node.name, op, &[])
+}
+
+/// ConstantOfShape producing one pulse of its streaming output at each turn.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedConstantOfShape {
+    pub fact: PulsedFact,
+    pub scalar: Arc<Tensor>,
+}
+
+impl_dyn_hash!(PulsedConstantOfShape);
+
+impl Op for PulsedConstantOfShape {
+    fn name(&self) -> Cow<str> {
+        "PulsedConstantOfShape".into()
+    }
+
+    op_pulse!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for PulsedConstantOfShape {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, _inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let shape =
+            self.fact.shape.iter().map(|d| d.to_usize()).collect::<TractResult<TVec<_>>>()?;
+        Ok(tvec!(self.scalar.broadcast_scalar_to_shape(&*shape)?.into_arc_tensor()))
+    }
+}
+
+impl PulsedOp for PulsedConstantOfShape {
+    fn pulsed_output_facts(&self, _inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(self.fact.clone()))
+    }
+
+    fn to_typed(&self) -> Box<dyn TypedOp> {
+        Box::new(ConstantOfShape::new(self.fact.shape.clone(), self.scalar.clone()))
+    }
+
+    as_op!();
+}
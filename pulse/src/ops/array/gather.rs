@@ -0,0 +1,137 @@
+use crate::internal::*;
+use tract_core::ops::array::Gather;
+use tract_core::ops::konst::Const;
+
+submit_op_pulsifier!(Gather, pulsify);
+submit_op_pulsifier!(Const, pulsify_const);
+
+/// Constants have no streaming axis, so they get no pulsed counterpart: Gather, their
+/// only supported consumer, picks them from the source model.
+fn pulsify_const(
+    _op: &Const,
+    source: &TypedModel,
+    node: &TypedNode,
+    _target: &mut PulsedModel,
+    _mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let successors = &node.outputs[0].successors;
+    if successors.is_empty()
+        || source.output_outlets()?.contains(&OutletId::new(node.id, 0))
+        || successors.iter().any(|s| !source.node(s.node).op_is::<Gather>())
+    {
+        bail!("Can not pulsify {}: constants can only feed a Gather", node);
+    }
+    Ok(tvec!())
+}
+
+fn pulsify(
+    op: &Gather,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let data = source.outlet_fact(node.inputs[0])?.konst.clone();
+    let indices = source.outlet_fact(node.inputs[1])?.konst.clone();
+    let (op, input) = match (data, indices) {
+        (None, Some(indices)) => {
+            let input = mapping[&node.inputs[0]];
+            if target.outlet_fact(input)?.axis == op.axis {
+                bail!("Can not gather along the streaming axis");
+            }
+            (PulsedGather { axis: op.axis, data: None, indices: Some(indices) }, input)
+        }
+        (Some(data), None) => (
+            PulsedGather { axis: op.axis, data: Some(data), indices: None },
+            mapping[&node.inputs[1]],
+        ),
+        _ => bail!("Gather pulsification requires exactly one constant input"),
+    };
+    target.wire_node(&*node.name, op, &[input])
+}
+
+/// Gather with one of its input (data or indices) constant, the other one streaming.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedGather {
+    pub axis: usize,
+    pub data: Option<Arc<Tensor>>,
+    pub indices: Option<Arc<Tensor>>,
+}
+
+impl_dyn_hash!(PulsedGather);
+
+impl Op for PulsedGather {
+    fn name(&self) -> Cow<str> {
+        "PulsedGather".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let (name, konst) = if let Some(data) = &self.data {
+            ("data", data)
+        } else {
+            ("indices", self.indices.as_ref().unwrap())
+        };
+        Ok(vec![format!("axis: {} constant {}: {:?}", self.axis, name, konst)])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedGather {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let inputs = if let Some(data) = &self.data {
+            tvec!(data.clone(), input)
+        } else {
+            tvec!(input, self.indices.clone().unwrap())
+        };
+        Gather::new(self.axis).eval(inputs)
+    }
+}
+
+impl PulsedGather {
+    fn shapes<D: DimLike>(&self, input: &[D]) -> (TVec<D>, TVec<D>) {
+        let konst = |t: &Arc<Tensor>| t.shape().iter().map(|d| D::from(*d)).collect();
+        if let Some(data) = &self.data {
+            (konst(data), input.into())
+        } else {
+            (input.into(), konst(self.indices.as_ref().unwrap()))
+        }
+    }
+}
+
+impl TypedOp for PulsedGather {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let dt = self.data.as_ref().map(|d| d.datum_type()).unwrap_or(inputs[0].datum_type);
+        let (data, indices) = self.shapes(&*inputs[0].shape.to_tvec());
+        let shape = Gather::new(self.axis).compute_output_shape(&*data, &*indices)?;
+        Ok(tvec!(TypedFact::dt_shape(dt, &*shape)))
+    }
+
+    as_op!();
+}
+
+impl PulsedOp for PulsedGather {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        let (data, indices) = self.shapes(&*fact.shape);
+        fact.shape = Gather::new(self.axis).compute_output_shape(&*data, &*indices)?;
+        if let Some(data) = &self.data {
+            fact.datum_type = data.datum_type();
+            fact.axis += self.axis;
+        } else if fact.axis > self.axis {
+            fact.axis = fact.axis + self.indices.as_ref().unwrap().rank() - 1;
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
@@ -1,3 +1,6 @@
 mod concat;
+mod constant_of_shape;
+mod gather;
 mod pad;
 mod slice;
+mod tile;
@@ -0,0 +1,33 @@
+use crate::internal::*;
+use tract_core::ops::array::Tile;
+
+submit_op_pulsifier!(Tile, pulsify);
+
+fn pulsify(
+    op: &Tile,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let input = mapping[&node.inputs[0]];
+    let axis = target.outlet_fact(input)?.axis;
+    if op.multipliers[axis] != 1 {
+        bail!("Can not tile along the streaming axis");
+    }
+    target.wire_node(&*node.name, op.clone(), &[input])
+}
+
+impl PulsedOp for Tile {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        for (d, &m) in fact.shape.iter_mut().zip(self.multipliers.iter()) {
+            *d = d.clone() * m;
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
@@ -0,0 +1,143 @@
+use crate::internal::*;
+use tract_core::ops::array::PadMode;
+use tract_core::ops::cnn::{DeconvUnary, PaddingSpec, PoolSpec};
+use tract_pulse_opl::ops::{DeconvDelay, PulsePad};
+
+submit_op_pulsifier!(DeconvUnary, pulsify);
+
+fn pulsify(
+    op: &DeconvUnary,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    fn zero<D: Datum>() -> Tensor {
+        tensor0(D::default())
+    }
+    let mut wire = mapping[&node.inputs[0]];
+    let fact = target.outlet_fact(wire)?.clone();
+    let input_shape = op.pool_spec.data_format.shape(&*fact.shape)?;
+    if Some(fact.axis) == input_shape.n_axis() {
+        return target.wire_node(&node.name, op.clone(), &[wire]);
+    }
+    if fact.axis == input_shape.c_axis() {
+        bail!("Can not pulsify deconvolution along the input channel axis");
+    }
+    let geo_axis = fact.axis - input_shape.h_axis();
+    let stride = op.pool_spec.stride(geo_axis);
+    let kernel_field =
+        (op.pool_spec.kernel_shape[geo_axis] - 1) * op.pool_spec.dilation(geo_axis) + 1;
+    let computed = op.pool_spec.padding.compute_one_for_deconv(
+        geo_axis,
+        &fact.dim,
+        op.pool_spec.kernel_shape[geo_axis],
+        op.pool_spec.dilation(geo_axis),
+        stride,
+        op.adjustments[geo_axis],
+    );
+
+    // whatever comes before and after the actual stream would leak in the
+    // output through the overlapping kernels: zero it.
+    let pad = PulsePad {
+        axis: fact.axis,
+        pulse: fact.pulse(),
+        before: fact.delay,
+        after: ((kernel_field + op.adjustments[geo_axis] + stride - 1) / stride).to_dim(),
+        begin_input: fact.delay,
+        end_input: fact.delay.to_dim() + &fact.dim,
+        mode: PadMode::Constant(dispatch_numbers!(zero(fact.datum_type)()).into_arc_tensor()),
+    };
+    wire = target.wire_node(format!("{}.zero-outside", node.name), pad, &[wire])?[0];
+
+    // each pulse is deconvoluted without cropping, and padded up to a stride
+    // multiple on the streaming axis
+    let mut before = tvec!();
+    let mut after = tvec!();
+    let mut adjustments = op.adjustments.clone();
+    for ix in 0..input_shape.hw_rank() {
+        if ix == geo_axis {
+            before.push(0);
+            after.push(0);
+            adjustments[ix] = stride.saturating_sub(kernel_field);
+        } else {
+            let c = op.pool_spec.padding.compute_one_for_deconv(
+                ix,
+                &input_shape.hw_dims()[ix],
+                op.pool_spec.kernel_shape[ix],
+                op.pool_spec.dilation(ix),
+                op.pool_spec.stride(ix),
+                op.adjustments[ix],
+            );
+            before.push(c.pad_before.to_usize()?);
+            after.push(c.pad_after.to_usize()?);
+        }
+    }
+    let pool_spec =
+        PoolSpec { padding: PaddingSpec::Explicit(before, after, false), ..op.pool_spec.clone() };
+    let deconv = DeconvUnary { pool_spec, adjustments, bias: None, ..op.clone() };
+    wire = target.wire_node(format!("{}.deconv", node.name), deconv, &[wire])?[0];
+
+    let overlap_add = DeconvDelay {
+        axis: fact.axis,
+        overlap: kernel_field.saturating_sub(stride),
+        delay: fact.delay * stride + computed.pad_before.to_usize()?,
+        stream_dim: computed.output,
+    };
+    if let Some(bias) = &op.bias {
+        wire = target.wire_node(format!("{}.overlap-add", node.name), overlap_add, &[wire])?[0];
+        let mut bias_shape = tvec!(1; input_shape.rank());
+        bias_shape[input_shape.c_axis()] = bias.len();
+        let bias = bias.clone().into_tensor().into_shape(&bias_shape)?;
+        target.wire_node(
+            &*node.name,
+            tract_core::ops::math::add::unary(bias.into_arc_tensor()),
+            &[wire],
+        )
+    } else {
+        target.wire_node(&*node.name, overlap_add, &[wire])
+    }
+}
+
+impl PulsedOp for DeconvUnary {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        let input_shape = self.pool_spec.data_format.shape(&*inputs[0].shape)?;
+        fact.shape = self.output_facts(&[&inputs[0].into()])?.remove(0).shape.to_tvec();
+        if Some(fact.axis) != input_shape.n_axis() {
+            let geo_axis = fact.axis - input_shape.h_axis();
+            let stride = self.pool_spec.stride(geo_axis);
+            fact.dim = self
+                .pool_spec
+                .padding
+                .compute_one_for_deconv(
+                    geo_axis,
+                    &fact.dim,
+                    self.pool_spec.kernel_shape[geo_axis],
+                    self.pool_spec.dilation(geo_axis),
+                    stride,
+                    self.adjustments[geo_axis],
+                )
+                .output;
+            fact.delay *= stride;
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+impl PulsedOp for DeconvDelay {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape[self.axis] -= self.overlap;
+        fact.delay = self.delay;
+        fact.dim = self.stream_dim.clone();
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
@@ -1,2 +1,3 @@
 mod conv;
+mod deconv;
 mod pools;
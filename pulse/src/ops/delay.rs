@@ -41,7 +41,7 @@ mod test {
             datum_type: u8::datum_type(),
             shape: tvec![pulse.to_dim()],
             axis: 0,
-            dim: stream_dim(),
+            dim: stream_dim(&model.symbol_table),
             delay: 0,
         };
         let source = model.add_source("source", fact1.clone()).unwrap();
@@ -92,7 +92,7 @@ mod test {
             datum_type: u8::datum_type(),
             shape: tvec![pulse.to_dim()],
             axis: 0,
-            dim: stream_dim(),
+            dim: stream_dim(&model.symbol_table),
             delay: 0,
         };
         let source = model.add_source("source", fact_0.clone()).unwrap();
@@ -1 +1,3 @@
+mod layer_norm;
 mod reduce;
+mod softmax;
@@ -1,7 +1,8 @@
 use crate::internal::*;
-use tract_core::ops::nn::Reduce;
+use tract_core::ops::nn::{CumulativeReduce, Reduce};
 
 submit_op_pulsifier!(Reduce, pulsify);
+submit_op_pulsifier!(CumulativeReduce, pulsify_cumulative);
 
 fn pulsify(
     op: &Reduce,
@@ -12,9 +13,9 @@ fn pulsify(
     _pulse: usize,
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
-    let axis = target.outlet_fact(input)?.axis;
-    if op.axes.contains(&axis) {
-        bail!("Can not reduce over streaming axis");
+    let fact = target.outlet_fact(input)?.clone();
+    if op.axes.contains(&fact.axis) {
+        bail!("Can not reduce over the streaming axis, only a CumulativeReduce can");
     }
     target.wire_node(&*node.name, op.clone(), &[input])
 }
@@ -31,3 +32,219 @@ impl PulsedOp for Reduce {
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+fn pulsify_cumulative(
+    op: &CumulativeReduce,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let input = mapping[&node.inputs[0]];
+    let fact = target.outlet_fact(input)?.clone();
+    if op.axis != fact.axis {
+        return target.wire_node(&*node.name, op.clone(), &[input]);
+    }
+    let op = PulsedCumulativeReduce {
+        op: op.clone(),
+        pulse: fact.pulse(),
+        begin_input: fact.delay,
+        end_input: fact.delay.to_dim() + &fact.dim,
+    };
+    target.wire_node(&*node.name, op, &[input])
+}
+
+impl PulsedOp for CumulativeReduce {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+/// CumulativeReduce along the streaming axis, keeping the running value across pulses.
+///
+/// The output stream has the input delay and length. Frames before the first valid input
+/// frame are zeros.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedCumulativeReduce {
+    pub op: CumulativeReduce,
+    pub pulse: usize,
+    pub begin_input: usize,
+    pub end_input: TDim,
+}
+
+impl_dyn_hash!(PulsedCumulativeReduce);
+
+impl Op for PulsedCumulativeReduce {
+    fn name(&self) -> Cow<str> {
+        format!("PulsedCumulativeReduce<{:?}>", self.op.reducer).into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {} valid input: {}..{}",
+            self.op.axis, self.begin_input, self.end_input
+        )])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedCumulativeReduce {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(PulsedCumulativeReduceState::default())))
+    }
+}
+
+impl TypedOp for PulsedCumulativeReduce {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        self.op.output_facts(inputs)
+    }
+
+    as_op!();
+}
+
+impl PulsedOp for PulsedCumulativeReduce {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[derive(Clone, Debug, Default)]
+struct PulsedCumulativeReduceState {
+    current_pos: usize,
+    accumulator: Option<Tensor>,
+}
+
+impl OpState for PulsedCumulativeReduceState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let op = op
+            .downcast_ref::<PulsedCumulativeReduce>()
+            .ok_or_else(|| format_err!("Wrong Op type"))?;
+        let axis = op.op.axis;
+        let end_input =
+            op.end_input.eval(&session.resolved_symbols).to_usize().unwrap_or(std::usize::MAX);
+        let mut frames = tvec!();
+        for i in 0..op.pulse {
+            let pos = self.current_pos + i;
+            let frame = input.slice(axis, i, i + 1)?;
+            let shape: TVec<usize> = frame.shape().into();
+            if pos >= op.begin_input && pos < end_input {
+                self.accumulator = Some(op.op.accumulate(self.accumulator.take(), frame)?);
+            }
+            frames.push(if let Some(acc) = &self.accumulator {
+                acc.clone()
+            } else {
+                Tensor::zero_dt(input.datum_type(), &shape)?
+            });
+        }
+        self.current_pos += op.pulse;
+        Ok(tvec!(Tensor::stack_tensors(axis, &frames)?.into_arc_tensor()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::nn::Reducer;
+
+    /// Channel sums, then a running reduction over time. Streaming if `frames` is None.
+    fn model(frames: Option<usize>, reducer: Reducer, delay: usize) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let len = frames.map(|f| f.to_dim()).unwrap_or_else(|| stream_dim(&model.symbol_table));
+        let fact = TypedFact::dt_shape(f32::datum_type(), [len.clone(), 2.to_dim()].as_ref());
+        let source = model.add_source("source", fact)?;
+        let mut wire = tvec!(source);
+        if delay > 0 {
+            let slice = tract_core::ops::array::Slice::new(0, delay, len);
+            wire = model.wire_node("slice", slice, &wire)?;
+        }
+        wire = model.wire_node("sum", Reduce::new(tvec!(1), Reducer::Sum), &wire)?;
+        model.wire_node("running", CumulativeReduce::new(0, reducer), &wire)?;
+        model.auto_outputs()?;
+        Ok(model)
+    }
+
+    /// Compare the pulsed model against the plain one run on the whole input.
+    fn check(reducer: Reducer, pulse: usize, delay: usize) -> TractResult<()> {
+        let frames = 10;
+        let input = tract_ndarray::Array2::from_shape_fn((frames + pulse, 2), |(t, c)| {
+            ((t * 2 + c) % 5) as f32 - 1.5
+        })
+        .into_tensor();
+        let expected = SimplePlan::new(model(Some(frames), reducer, delay)?)?
+            .run(tvec!(input.slice(0, 0, frames)?))?
+            .remove(0);
+
+        let model = model(None, reducer, delay)?;
+        let s = stream_symbol(&model.symbol_table);
+        let pulsed = PulsedModel::new(&model, pulse)?;
+        let output_fact = pulsed.output_fact(0)?.clone();
+        assert_eq!(output_fact.delay, delay);
+        assert_eq!(pulsed.into_typed()?.output_fact(0)?.shape[0], pulse.to_dim());
+        let values = SymbolValues::default().with(s, frames as i64);
+        let len = output_fact.dim.eval(&values).to_usize()?;
+        assert_eq!(expected.shape(), &[len, 1]);
+
+        let pulsed = PulsedModel::new(&model, pulse)?;
+        let mut state = SimpleState::new(pulsed.into_typed()?.into_runnable()?)?;
+        state.session_state.resolved_symbols = values;
+        let mut outputs = vec![];
+        for i in 0.. {
+            if outputs.len() >= delay + len {
+                break;
+            }
+            let chunk = input.slice(0, i * pulse, (i + 1) * pulse)?;
+            outputs.extend(state.run(tvec!(chunk))?[0].as_slice::<f32>()?.iter().cloned());
+        }
+        assert_eq!(&outputs[delay..delay + len], expected.as_slice::<f32>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn running_sum() -> TractResult<()> {
+        check(Reducer::Sum, 4, 0)
+    }
+
+    #[test]
+    fn running_reducers_after_delay() -> TractResult<()> {
+        for reducer in &[Reducer::Sum, Reducer::Max, Reducer::Min, Reducer::Prod] {
+            check(*reducer, 4, 5)?;
+            check(*reducer, 3, 1)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reduce_over_stream_is_an_error() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = stream_symbol(&model.symbol_table);
+        let fact = TypedFact::dt_shape(f32::datum_type(), [s.to_dim(), 2.to_dim()].as_ref());
+        let source = model.add_source("source", fact)?;
+        model.wire_node("reduce", Reduce::new(tvec!(0), Reducer::Max), &[source])?;
+        model.auto_outputs()?;
+        assert!(PulsedModel::new(&model, 4).is_err());
+        Ok(())
+    }
+}
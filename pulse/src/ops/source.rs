@@ -5,13 +5,17 @@ submit_op_pulsifier!(TypedSource, pulsify);
 
 fn pulsify(
     _op: &TypedSource,
-    _source: &TypedModel,
+    source: &TypedModel,
     node: &TypedNode,
     target: &mut PulsedModel,
     _mapping: &HashMap<OutletId, OutletId>,
     pulse: usize,
 ) -> TractResult<TVec<OutletId>> {
-    let pulsed_fact = PulsedFact::from_tensor_fact_pulse(&node.outputs[0].fact, pulse)?;
+    let pulsed_fact = PulsedFact::from_tensor_fact_pulse(
+        &node.outputs[0].fact,
+        &stream_symbol(&source.symbol_table),
+        pulse,
+    )?;
     let id = target.add_source(node.name.clone(), pulsed_fact)?;
     Ok(tvec!(id))
 }
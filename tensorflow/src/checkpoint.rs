@@ -0,0 +1,513 @@
+//! TensorFlow checkpoint (tensor bundle) reader.
+//!
+//! A checkpoint is made of an index file, which is a LevelDB-style table
+//! mapping tensor names to `BundleEntryProto`, and of one or several data
+//! shards holding the raw tensor bytes.
+
+use crate::tfpb::tensorflow::bundle_header_proto::Endianness;
+use crate::tfpb::tensorflow::{
+    BundleEntryProto, BundleHeaderProto, DataType, GraphDef, TensorProto, TrackableObjectGraph,
+};
+use prost::Message;
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::{fs, path};
+use tract_hir::internal::*;
+
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const FOOTER_LEN: usize = 48;
+const OBJECT_GRAPH_KEY: &str = "_CHECKPOINTABLE_OBJECT_GRAPH";
+
+fn varint(bytes: &[u8], pos: &mut usize) -> TractResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte =
+            *bytes.get(*pos).ok_or_else(|| format_err!("Truncated varint in checkpoint index"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Invalid varint in checkpoint index")
+}
+
+fn block_handle(bytes: &[u8], pos: &mut usize) -> TractResult<(usize, usize)> {
+    let offset = varint(bytes, pos)? as usize;
+    let size = varint(bytes, pos)? as usize;
+    Ok((offset, size))
+}
+
+/// Reads a table block, without its trailer, into (key, value) pairs.
+fn block_entries(
+    table: &[u8],
+    (offset, size): (usize, usize),
+) -> TractResult<Vec<(Vec<u8>, &[u8])>> {
+    if offset + size + 5 > table.len() {
+        bail!("Checkpoint index block out of bounds");
+    }
+    if table[offset + size] != 0 {
+        bail!("Compressed checkpoint index blocks are not supported");
+    }
+    let block = &table[offset..offset + size];
+    if block.len() < 4 {
+        bail!("Checkpoint index block too short");
+    }
+    let restarts = u32::from_le_bytes([
+        block[block.len() - 4],
+        block[block.len() - 3],
+        block[block.len() - 2],
+        block[block.len() - 1],
+    ]) as usize;
+    let end = block
+        .len()
+        .checked_sub(4 * (restarts + 1))
+        .ok_or_else(|| format_err!("Invalid restart count in checkpoint index"))?;
+    let mut entries: Vec<(Vec<u8>, &[u8])> = vec![];
+    let mut pos = 0;
+    let mut key: Vec<u8> = vec![];
+    while pos < end {
+        let shared = varint(block, &mut pos)? as usize;
+        let non_shared = varint(block, &mut pos)? as usize;
+        let value_len = varint(block, &mut pos)? as usize;
+        if shared > key.len() || pos + non_shared + value_len > end {
+            bail!("Corrupted checkpoint index entry");
+        }
+        key.truncate(shared);
+        key.extend_from_slice(&block[pos..pos + non_shared]);
+        pos += non_shared;
+        entries.push((key.clone(), &block[pos..pos + value_len]));
+        pos += value_len;
+    }
+    Ok(entries)
+}
+
+/// All (key, value) pairs of a LevelDB-style table.
+fn table_entries(table: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if table.len() < FOOTER_LEN {
+        bail!("Checkpoint index too short");
+    }
+    let footer = &table[table.len() - FOOTER_LEN..];
+    let mut magic = [0u8; 8];
+    magic.copy_from_slice(&footer[40..]);
+    if u64::from_le_bytes(magic) != TABLE_MAGIC {
+        bail!("Checkpoint index has wrong magic number");
+    }
+    let mut pos = 0;
+    let _metaindex = block_handle(footer, &mut pos)?;
+    let index = block_handle(footer, &mut pos)?;
+    let mut entries = vec![];
+    for (_, handle) in block_entries(table, index)? {
+        let handle = block_handle(handle, &mut 0)?;
+        for (k, v) in block_entries(table, handle)? {
+            entries.push((k, v.to_vec()));
+        }
+    }
+    Ok(entries)
+}
+
+/// A checkpoint, as a set of named tensors.
+pub struct Checkpoint {
+    prefix: path::PathBuf,
+    header: BundleHeaderProto,
+    entries: HashMap<String, BundleEntryProto>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint with the given prefix (like `variables/variables`
+    /// in a SavedModel directory).
+    pub fn open(prefix: impl AsRef<path::Path>) -> TractResult<Checkpoint> {
+        let prefix = prefix.as_ref().to_path_buf();
+        let index_path = prefix.with_file_name(format!(
+            "{}.index",
+            prefix.file_name().and_then(|f| f.to_str()).unwrap_or("")
+        ));
+        let index = fs::read(&index_path)
+            .with_context(|| format!("Reading checkpoint index {:?}", index_path))?;
+        Self::from_index(prefix, &index)
+    }
+
+    fn from_index(prefix: path::PathBuf, index: &[u8]) -> TractResult<Checkpoint> {
+        let mut header = None;
+        let mut entries = HashMap::new();
+        for (key, value) in table_entries(index)? {
+            if key.len() == 0 {
+                header = Some(BundleHeaderProto::decode(&*value)?);
+            } else {
+                let key = String::from_utf8(key)?;
+                entries.insert(key, BundleEntryProto::decode(&*value)?);
+            }
+        }
+        let header = header.ok_or_else(|| format_err!("Checkpoint index has no header"))?;
+        if header.endianness != Endianness::Little as i32 {
+            bail!("Only little endian checkpoints are supported");
+        }
+        Ok(Checkpoint { prefix, header, entries })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|k| &**k)
+    }
+
+    fn shard_path(&self, shard_id: i32) -> path::PathBuf {
+        self.prefix.with_file_name(format!(
+            "{}.data-{:05}-of-{:05}",
+            self.prefix.file_name().and_then(|f| f.to_str()).unwrap_or(""),
+            shard_id,
+            self.header.num_shards
+        ))
+    }
+
+    /// Reads the bytes of an entry from its shard, without loading the rest
+    /// of the shard.
+    fn read_entry(&self, name: &str, entry: &BundleEntryProto) -> TractResult<Vec<u8>> {
+        let path = self.shard_path(entry.shard_id);
+        let mut file = fs::File::open(&path)
+            .with_context(|| format!("Opening checkpoint data {:?} for {}", path, name))?;
+        let (offset, size) = (entry.offset as u64, entry.size as u64);
+        if offset + size > file.metadata()?.len() {
+            bail!("Checkpoint data for {} is out of bounds", name);
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = vec![0u8; size as usize];
+        file.read_exact(&mut data)
+            .with_context(|| format!("Reading checkpoint data for {}", name))?;
+        Ok(data)
+    }
+
+    /// The tensor saved under `name`, as a TensorProto with raw content.
+    pub fn tensor_proto(&self, name: &str) -> TractResult<Option<TensorProto>> {
+        let entry = if let Some(entry) = self.entries.get(name) {
+            entry
+        } else {
+            return Ok(None);
+        };
+        if entry.slices.len() > 0 {
+            bail!("Partitioned variable {} is not supported", name);
+        }
+        let dt = DataType::from_i32(entry.dtype)
+            .ok_or_else(|| format_err!("Unknown data type for {}", name))?;
+        if dt == DataType::DtString {
+            bail!("String variable {} is not supported", name);
+        }
+        let mut proto = crate::tensor::empty_tensor_proto();
+        proto.dtype = entry.dtype;
+        proto.tensor_shape = entry.shape.clone();
+        proto.tensor_content = self.read_entry(name, entry)?;
+        Ok(Some(proto))
+    }
+
+    /// The object graph saved by TF2 object-based checkpoints, if any.
+    ///
+    /// It is stored as a scalar string: the varint length, a four bytes
+    /// checksum, then the serialized TrackableObjectGraph.
+    pub fn object_graph(&self) -> TractResult<Option<TrackableObjectGraph>> {
+        let entry = if let Some(entry) = self.entries.get(OBJECT_GRAPH_KEY) {
+            entry
+        } else {
+            return Ok(None);
+        };
+        let data = self.read_entry(OBJECT_GRAPH_KEY, entry)?;
+        let mut pos = 0;
+        let len = varint(&data, &mut pos)? as usize;
+        let start = pos + 4;
+        if start + len > data.len() {
+            bail!("Truncated checkpoint object graph");
+        }
+        Ok(Some(TrackableObjectGraph::decode(&data[start..start + len])?))
+    }
+
+    /// Maps variable names to their keys in the checkpoint.
+    ///
+    /// Name-based (TF1) checkpoints use the variable names as keys. TF2
+    /// checkpoints use object paths like
+    /// `layer-0/kernel/.ATTRIBUTES/VARIABLE_VALUE`, resolved through the
+    /// object graph.
+    pub fn variable_keys(&self) -> TractResult<HashMap<String, String>> {
+        let mut keys = HashMap::new();
+        if let Some(graph) = self.object_graph()? {
+            for node in graph.nodes {
+                for attr in node.attributes {
+                    if !attr.full_name.is_empty() && self.entries.contains_key(&attr.checkpoint_key)
+                    {
+                        keys.insert(attr.full_name, attr.checkpoint_key);
+                    }
+                }
+            }
+        }
+        for name in self.entries.keys() {
+            keys.insert(name.clone(), name.clone());
+        }
+        Ok(keys)
+    }
+}
+
+/// Replaces the variables of `graph` by constants holding their values from
+/// the checkpoint.
+///
+/// VariableV2 nodes are looked up by name, VarHandleOp by shared name (or
+/// name), and the ReadVariableOp consuming the handles become Identity.
+/// Names are resolved to checkpoint keys by `Checkpoint::variable_keys`.
+pub fn restore_variables(graph: &mut GraphDef, checkpoint: &Checkpoint) -> TractResult<()> {
+    let keys = checkpoint.variable_keys()?;
+    let mut handles = HashSet::new();
+    for node in &mut graph.node {
+        if node.op != "VariableV2" && node.op != "VarHandleOp" {
+            continue;
+        }
+        let key = match node.get_attr_opt_str("shared_name")? {
+            Some(shared) if node.op == "VarHandleOp" && shared != "" => shared,
+            _ => node.name.clone(),
+        };
+        let value = keys
+            .get(&key)
+            .map(|k| checkpoint.tensor_proto(k))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| format_err!("Variable {} not found in checkpoint", key))?;
+        if node.op == "VarHandleOp" {
+            handles.insert(node.name.clone());
+        }
+        let dtype = node.attr["dtype"].clone();
+        node.op = "Const".to_string();
+        node.input.clear();
+        node.attr.clear();
+        node.attr.insert("dtype".to_string(), dtype);
+        node.attr.insert("value".to_string(), value.into());
+    }
+    for node in &mut graph.node {
+        if node.op != "ReadVariableOp" || node.input.len() == 0 {
+            continue;
+        }
+        let (handle, _) = crate::model::Tensorflow::parse_input(&node.input[0])?;
+        if handles.contains(handle) {
+            let dtype = node.attr["dtype"].clone();
+            node.op = "Identity".to_string();
+            node.attr.clear();
+            node.attr.insert("T".to_string(), dtype);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    // one entry per restart point, no prefix compression
+    fn block(entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut buf = vec![];
+        let mut restarts = vec![];
+        for (k, v) in entries {
+            restarts.push(buf.len() as u32);
+            put_varint(&mut buf, 0);
+            put_varint(&mut buf, k.len() as u64);
+            put_varint(&mut buf, v.len() as u64);
+            buf.extend_from_slice(k);
+            buf.extend_from_slice(v);
+        }
+        for r in &restarts {
+            buf.extend_from_slice(&r.to_le_bytes());
+        }
+        buf.extend_from_slice(&(restarts.len() as u32).to_le_bytes());
+        buf
+    }
+
+    fn table(entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut file = vec![];
+        let data = block(entries);
+        let data_handle = (file.len(), data.len());
+        file.extend_from_slice(&data);
+        file.extend_from_slice(&[0; 5]);
+        let mut handle = vec![];
+        put_varint(&mut handle, data_handle.0 as u64);
+        put_varint(&mut handle, data_handle.1 as u64);
+        let last_key = entries.last().unwrap().0;
+        let index = block(&[(last_key, &handle)]);
+        let index_handle = (file.len(), index.len());
+        file.extend_from_slice(&index);
+        file.extend_from_slice(&[0; 5]);
+        let mut footer = vec![];
+        put_varint(&mut footer, 0);
+        put_varint(&mut footer, 0);
+        put_varint(&mut footer, index_handle.0 as u64);
+        put_varint(&mut footer, index_handle.1 as u64);
+        footer.resize(40, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+        file.extend_from_slice(&footer);
+        file
+    }
+
+    const TF2_BIAS_KEY: &str = "layer_with_weights-0/bias/.ATTRIBUTES/VARIABLE_VALUE";
+
+    // TF2 bundles key variables by object path, and carry the object graph
+    fn write_bundle(name: &str, tf2: bool) -> TractResult<path::PathBuf> {
+        use crate::tfpb::tensorflow::trackable_object_graph::trackable_object::SerializedTensor;
+        use crate::tfpb::tensorflow::trackable_object_graph::TrackableObject;
+        let dir = std::env::temp_dir().join(format!("tract-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let mut header = vec![];
+        BundleHeaderProto { num_shards: 1, endianness: 0, version: None }.encode(&mut header)?;
+        let mut data: Vec<u8> =
+            [0f32, 1.5, -2.0].iter().flat_map(|f| f.to_le_bytes().to_vec()).collect();
+        let mut shape = TensorProto::try_from(&tensor1(&[0f32, 0.]))?.tensor_shape;
+        let mut entry = vec![];
+        BundleEntryProto {
+            dtype: DataType::DtFloat as i32,
+            shape: shape.take(),
+            shard_id: 0,
+            offset: 4,
+            size: 8,
+            crc32c: 0,
+            slices: vec![],
+        }
+        .encode(&mut entry)?;
+        let index = if tf2 {
+            let mut object_graph = vec![];
+            TrackableObjectGraph {
+                nodes: vec![TrackableObject {
+                    children: vec![],
+                    attributes: vec![SerializedTensor {
+                        name: "VARIABLE_VALUE".to_string(),
+                        full_name: "dense/bias".to_string(),
+                        checkpoint_key: TF2_BIAS_KEY.to_string(),
+                        optional_restore: false,
+                    }],
+                    slot_variables: vec![],
+                }],
+            }
+            .encode(&mut object_graph)?;
+            let offset = data.len();
+            put_varint(&mut data, object_graph.len() as u64);
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&object_graph);
+            let mut graph_entry = vec![];
+            BundleEntryProto {
+                dtype: DataType::DtString as i32,
+                shape: Some(Default::default()),
+                shard_id: 0,
+                offset: offset as i64,
+                size: (data.len() - offset) as i64,
+                crc32c: 0,
+                slices: vec![],
+            }
+            .encode(&mut graph_entry)?;
+            table(&[
+                (b"", &header),
+                (OBJECT_GRAPH_KEY.as_bytes(), &graph_entry),
+                (TF2_BIAS_KEY.as_bytes(), &entry),
+            ])
+        } else {
+            table(&[(b"", &header), (b"dense/bias", &entry)])
+        };
+        fs::write(dir.join("vars.index"), index)?;
+        fs::write(dir.join("vars.data-00000-of-00001"), data)?;
+        Ok(dir)
+    }
+
+    #[test]
+    fn read_bundle() -> TractResult<()> {
+        let dir = write_bundle("read-bundle", false)?;
+        let checkpoint = Checkpoint::open(dir.join("vars"))?;
+        assert_eq!(checkpoint.names().collect::<Vec<_>>(), vec!["dense/bias"]);
+        let proto = checkpoint.tensor_proto("dense/bias")?.unwrap();
+        assert_eq!(Tensor::try_from(&proto)?, tensor1(&[1.5f32, -2.0]));
+        assert!(checkpoint.tensor_proto("nope")?.is_none());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_object_graph() -> TractResult<()> {
+        let dir = write_bundle("read-object-graph", true)?;
+        let checkpoint = Checkpoint::open(dir.join("vars"))?;
+        let keys = checkpoint.variable_keys()?;
+        assert_eq!(keys["dense/bias"], TF2_BIAS_KEY);
+        let proto = checkpoint.tensor_proto(&keys["dense/bias"])?.unwrap();
+        assert_eq!(Tensor::try_from(&proto)?, tensor1(&[1.5f32, -2.0]));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn restore_variable() -> TractResult<()> {
+        use crate::tfpb::tensorflow::TensorShapeProto;
+        use crate::tfpb::{graph, node};
+        let dir = write_bundle("restore-variable", false)?;
+        let checkpoint = Checkpoint::open(dir.join("vars"))?;
+        let mut graph = graph()
+            .node(node().name("x").op("Placeholder").attr("dtype", DataType::DtFloat))
+            .node(
+                node()
+                    .name("dense/bias")
+                    .op("VariableV2")
+                    .attr("dtype", DataType::DtFloat)
+                    .attr("shape", TensorShapeProto { dim: vec![], unknown_rank: true })
+                    .attr("container", "")
+                    .attr("shared_name", ""),
+            )
+            .node(
+                node()
+                    .name("y")
+                    .op("Add")
+                    .input("x")
+                    .input("dense/bias")
+                    .attr("T", DataType::DtFloat),
+            );
+        restore_variables(&mut graph, &checkpoint)?;
+        fs::remove_dir_all(&dir)?;
+        let model = crate::tensorflow().model_for_proto_model(&graph)?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 1.])))?;
+        assert_eq!(outputs[0], rctensor1(&[2.5f32, -1.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn restore_tf2_resource_variable() -> TractResult<()> {
+        use crate::tfpb::tensorflow::TensorShapeProto;
+        use crate::tfpb::{graph, node};
+        let dir = write_bundle("restore-tf2-variable", true)?;
+        let checkpoint = Checkpoint::open(dir.join("vars"))?;
+        let mut graph = graph()
+            .node(node().name("x").op("Placeholder").attr("dtype", DataType::DtFloat))
+            .node(
+                node()
+                    .name("dense/bias")
+                    .op("VarHandleOp")
+                    .attr("dtype", DataType::DtFloat)
+                    .attr("shape", TensorShapeProto { dim: vec![], unknown_rank: true })
+                    .attr("container", "")
+                    .attr("shared_name", "dense/bias"),
+            )
+            .node(
+                node()
+                    .name("dense/bias/read")
+                    .op("ReadVariableOp")
+                    .input("dense/bias")
+                    .attr("dtype", DataType::DtFloat),
+            )
+            .node(
+                node()
+                    .name("y")
+                    .op("Add")
+                    .input("x")
+                    .input("dense/bias/read")
+                    .attr("T", DataType::DtFloat),
+            );
+        restore_variables(&mut graph, &checkpoint)?;
+        fs::remove_dir_all(&dir)?;
+        let model = crate::tensorflow().model_for_proto_model(&graph)?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 1.])))?;
+        assert_eq!(outputs[0], rctensor1(&[2.5f32, -1.0]));
+        Ok(())
+    }
+}
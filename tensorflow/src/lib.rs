@@ -51,6 +51,7 @@ pub extern crate tract_hir;
 #[cfg(feature = "conform")]
 pub mod conform;
 
+pub mod checkpoint;
 pub mod model;
 pub mod ops;
 pub mod tensor;
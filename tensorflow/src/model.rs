@@ -1,5 +1,6 @@
-use crate::tfpb::tensorflow::{GraphDef, NodeDef, SavedModel};
+use crate::tfpb::tensorflow::{tensor_info, GraphDef, NodeDef, SavedModel, TensorInfo};
 use prost::Message;
+use std::collections::HashSet;
 use std::{fs, path};
 use tract_hir::internal::*;
 
@@ -62,7 +63,7 @@ impl Tensorflow {
     // "src_output" indicating which output tensor to use from "node". If
     // "src_output" is 0 the ":0" suffix can be omitted. Regular inputs may
     // optionally be followed by control inputs that have the format "^node".
-    fn parse_input(i: &str) -> TractResult<(&str, usize)> {
+    pub(crate) fn parse_input(i: &str) -> TractResult<(&str, usize)> {
         let pair = if i.starts_with("^") {
             (&i[1..], 0)
         } else {
@@ -109,12 +110,111 @@ impl Tensorflow {
     }
 
     /// Convenience method: will read the first model in the saved model
-    /// container. Use open_avec_model for more control.
+    /// container. Use model_for_saved_model_dir to pick a signature and
+    /// restore variables.
     pub fn read_saved_model(&self, r: &mut dyn std::io::Read) -> TractResult<GraphDef> {
         let mut saved = self.open_saved_model(r)?;
         Ok(saved.meta_graphs.remove(0).graph_def.unwrap())
     }
 
+    /// Loads a SavedModel directory: picks the meta graph matching `tags` and
+    /// the signature `signature_key`, restores variables from the
+    /// `variables/` checkpoint, and maps model inputs and outputs to the
+    /// signature ones, in signature key order.
+    pub fn model_for_saved_model_dir(
+        &self,
+        dir: impl AsRef<path::Path>,
+        tags: &[&str],
+        signature_key: &str,
+    ) -> TractResult<InferenceModel> {
+        let dir = dir.as_ref();
+        let saved = self.open_saved_model(&mut fs::File::open(dir.join("saved_model.pb"))?)?;
+        let mut wanted: Vec<&str> = tags.to_vec();
+        wanted.sort();
+        let mut available = vec![];
+        let mut meta_graph = None;
+        for mg in saved.meta_graphs {
+            let mut mg_tags: Vec<String> =
+                mg.meta_info_def.as_ref().map(|info| info.tags.clone()).unwrap_or_default();
+            mg_tags.sort();
+            if mg_tags.iter().map(|t| &**t).eq(wanted.iter().cloned()) {
+                meta_graph = Some(mg);
+                break;
+            }
+            available.push(mg_tags);
+        }
+        let mut meta_graph = meta_graph.ok_or_else(|| {
+            format_err!("No meta graph with tags {:?}. Available: {:?}", tags, available)
+        })?;
+        let signature = meta_graph.signature_def.remove(signature_key).ok_or_else(|| {
+            format_err!(
+                "No signature {:?}. Available: {:?}",
+                signature_key,
+                meta_graph.signature_def.keys().collect::<Vec<_>>()
+            )
+        })?;
+        let mut graph =
+            meta_graph.graph_def.ok_or_else(|| format_err!("Meta graph has no graph"))?;
+        let tensor_names =
+            |map: &HashMap<String, TensorInfo>| -> TractResult<Vec<(String, String)>> {
+                let mut names = map
+                    .iter()
+                    .map(|(k, info)| match &info.encoding {
+                        Some(tensor_info::Encoding::Name(name)) => Ok((k.clone(), name.clone())),
+                        _ => bail!("Signature tensor {} is not a dense tensor", k),
+                    })
+                    .collect::<TractResult<Vec<_>>>()?;
+                names.sort();
+                Ok(names)
+            };
+        let inputs = tensor_names(&signature.inputs)?;
+        let outputs = tensor_names(&signature.outputs)?;
+
+        // only keep what the signature outputs depend on
+        let mut keep: HashSet<String> = HashSet::new();
+        let mut todo: Vec<String> = inputs
+            .iter()
+            .chain(outputs.iter())
+            .map(|(_, name)| Ok(Self::parse_input(name)?.0.to_string()))
+            .collect::<TractResult<_>>()?;
+        let by_name: HashMap<String, &NodeDef> =
+            graph.node.iter().map(|n| (n.name.clone(), n)).collect();
+        while let Some(name) = todo.pop() {
+            if keep.contains(&name) {
+                continue;
+            }
+            let node = by_name.get(&name).ok_or_else(|| format_err!("Node {} not found", name))?;
+            for i in &node.input {
+                todo.push(Self::parse_input(i)?.0.to_string());
+            }
+            keep.insert(name);
+        }
+        graph.node.retain(|n| keep.contains(&n.name));
+
+        if graph.node.iter().any(|n| n.op == "VariableV2" || n.op == "VarHandleOp") {
+            let checkpoint = crate::checkpoint::Checkpoint::open(dir.join("variables/variables"))?;
+            crate::checkpoint::restore_variables(&mut graph, &checkpoint)?;
+        }
+
+        let mut model = self.parse_graph(&graph)?.0;
+        let mut outlets = |names: &[(String, String)]| -> TractResult<Vec<OutletId>> {
+            names
+                .iter()
+                .map(|(key, name)| {
+                    let (node, slot) = Self::parse_input(name)?;
+                    let outlet = OutletId::new(model.node_id_by_name(node)?, slot);
+                    model.set_outlet_label(outlet, key.clone())?;
+                    Ok(outlet)
+                })
+                .collect()
+        };
+        let input_outlets = outlets(&inputs)?;
+        let output_outlets = outlets(&outputs)?;
+        model.set_input_outlets(&input_outlets)?;
+        model.set_output_outlets(&output_outlets)?;
+        Ok(model)
+    }
+
     pub fn parse_graph(&self, graph: &GraphDef) -> TractResult<TfModelAndExtensions> {
         use crate::ops::control_flow as cf;
 
@@ -123,8 +223,10 @@ impl Tensorflow {
         let mut context = ParsingContext::default();
         let mut control_inputs = vec![];
 
+        let cf::LoweredFrames { nodes, mut loops } = cf::lower_while_loops(self, graph)?;
+
         // compute min output arity for all nodes
-        for pbnode in &graph.node {
+        for pbnode in &nodes {
             for i in &pbnode.input {
                 let (node, slot) = Self::parse_input(i)?;
                 let arity = context.node_output_arities.entry(node.to_string()).or_insert(1);
@@ -132,19 +234,11 @@ impl Tensorflow {
             }
         }
 
-        for pbnode in &graph.node {
+        for pbnode in &nodes {
             let name = &pbnode.name;
 
-            if pbnode.op == "NextIteration" {
-                let source_op = cf::NextIteration::new(name.clone(), cf::NextIterationRole::Source);
-                let sink_op = cf::NextIteration::new(name.clone(), cf::NextIterationRole::Sink);
-                let _source =
-                    model.add_node(name.clone(), source_op, tvec!(InferenceFact::default()))?;
-                let _sink = model.add_node(format!("{}-Sink", name), sink_op, tvec!())?;
-                continue;
-            }
-
             let op = match self.op_register.0.get(&pbnode.op) {
+                _ if loops.contains_key(name) => loops.remove(name).unwrap(),
                 Some(builder) => (builder)(&context, pbnode)?,
                 None => tract_hir::ops::unimpl::UnimplementedOp::new(
                     context.node_output_arities.get(name).cloned().unwrap_or(1),
@@ -182,12 +276,8 @@ impl Tensorflow {
             }
         }
 
-        for pbnode in &graph.node {
-            let node_id = if pbnode.op == "NextIteration" {
-                model.node_by_name(&*format!("{}-Sink", &pbnode.name))?.id
-            } else {
-                model.node_by_name(&pbnode.name)?.id
-            };
+        for pbnode in &nodes {
+            let node_id = model.node_by_name(&pbnode.name)?.id;
             for (ix, i) in pbnode.input.iter().filter(|n| !n.starts_with("^")).enumerate() {
                 let input = Self::parse_input(i)?;
                 let prec = model.node_by_name(input.0)?.id;
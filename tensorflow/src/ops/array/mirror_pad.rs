@@ -0,0 +1,65 @@
+use tract_hir::internal::*;
+use tract_hir::ops::array::{Pad, PadMode};
+use tract_ndarray::Ix2;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn mirror_pad(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    match &*pb.get_attr_str("mode")? {
+        "REFLECT" => Ok(expand(MirrorPad)),
+        mode => bail!("MirrorPad mode {} is not supported", mode),
+    }
+}
+
+fn pads(paddings: &Tensor) -> TractResult<Vec<(usize, usize)>> {
+    let paddings = paddings.cast_to::<i64>()?;
+    let paddings = paddings.to_array_view::<i64>()?.into_dimensionality::<Ix2>()?;
+    Ok(paddings.outer_iter().map(|p| (p[0] as usize, p[1] as usize)).collect())
+}
+
+/// Reflecting pad, without repeating the edge (TensorFlow REFLECT mode).
+#[derive(Debug, Clone, new, Hash)]
+pub struct MirrorPad;
+
+impl_dyn_hash!(MirrorPad);
+
+impl Expansion for MirrorPad {
+    fn name(&self) -> Cow<str> {
+        "MirrorPad".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[0], inputs[0].rank.bex().to_dim())?;
+        s.equals(&inputs[1].shape[1], 2.to_dim())?;
+        s.given_2(&inputs[0].shape, &inputs[1].value, move |s, shape, paddings| {
+            for (ix, (before, after)) in pads(&paddings)?.into_iter().enumerate() {
+                s.equals(&outputs[0].shape[ix], shape[ix].clone() + before + after)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let paddings =
+            target.outlet_fact(inputs[1])?.konst.clone().context("Expect constant paddings")?;
+        target.wire_node(prefix, Pad::new(pads(&paddings)?, PadMode::Reflect), &[inputs[0]])
+    }
+}
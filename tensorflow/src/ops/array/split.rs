@@ -0,0 +1,257 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn split(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let num_split = pb.get_attr_int("num_split")?;
+    Ok(expand(Split::new(num_split)))
+}
+
+pub fn split_v(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let num_split = pb.get_attr_int("num_split")?;
+    Ok(expand(SplitV::new(num_split)))
+}
+
+pub fn unpack(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let num = pb.get_attr_int("num")?;
+    let axis = pb.get_attr_opt_int("axis")?.unwrap_or(0);
+    Ok(expand(Unpack::new(num, axis)))
+}
+
+fn resolve_axis(axis: &Tensor, rank: usize) -> TractResult<usize> {
+    let axis = axis.cast_to_scalar::<i64>()?;
+    Ok(if axis < 0 { axis + rank as i64 } else { axis } as usize)
+}
+
+/// Split sizes, with the (optional) -1 entry inferred from the axis length.
+fn split_sizes(sizes: &Tensor, dim: &TDim) -> TractResult<TVec<TDim>> {
+    let sizes = sizes.cast_to::<i64>()?;
+    let sizes = sizes.as_slice::<i64>()?;
+    let known: TDim = sizes.iter().filter(|s| **s >= 0).map(|s| s.to_dim()).sum();
+    Ok(sizes
+        .iter()
+        .map(|&s| if s < 0 { dim.clone() - known.clone() } else { s.to_dim() })
+        .collect())
+}
+
+fn wire_slices(
+    prefix: &str,
+    target: &mut TypedModel,
+    input: OutletId,
+    axis: usize,
+    sizes: &[TDim],
+) -> TractResult<TVec<OutletId>> {
+    let mut outputs = tvec!();
+    let mut current = 0.to_dim();
+    for (ix, len) in sizes.iter().enumerate() {
+        let end = current.clone() + len;
+        outputs.push(
+            target.wire_node(
+                format!("{}.{}", prefix, ix),
+                tract_hir::ops::array::Slice::new(axis, current, end.clone()),
+                &[input],
+            )?[0],
+        );
+        current = end;
+    }
+    Ok(outputs)
+}
+
+/// TensorFlow Split: axis first, then the input, split in equal parts.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Split {
+    num_split: usize,
+}
+
+impl_dyn_hash!(Split);
+
+impl Expansion for Split {
+    fn name(&self) -> Cow<str> {
+        "Split".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.num_split)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, self.num_split)?;
+        s.equals(&inputs[0].rank, 0)?;
+        for o in outputs {
+            s.equals(&inputs[1].datum_type, &o.datum_type)?;
+            s.equals(&inputs[1].rank, &o.rank)?;
+        }
+        s.given_2(&inputs[0].value, &inputs[1].shape, move |s, axis, shape| {
+            let axis = resolve_axis(&axis, shape.len())?;
+            for o in outputs {
+                let mut shape = shape.clone();
+                shape[axis] = shape[axis].clone() / self.num_split as u64;
+                s.equals(&o.shape, shape)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = target.outlet_fact(inputs[0])?.konst.clone().context("Expect constant axis")?;
+        let input = target.outlet_fact(inputs[1])?.clone();
+        let axis = resolve_axis(&axis, input.rank())?;
+        let len = input.shape[axis].clone() / self.num_split as u64;
+        wire_slices(prefix, target, inputs[1], axis, &*tvec!(len; self.num_split))
+    }
+}
+
+/// TensorFlow SplitV: input, sizes and axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct SplitV {
+    num_split: usize,
+}
+
+impl_dyn_hash!(SplitV);
+
+impl Expansion for SplitV {
+    fn name(&self) -> Cow<str> {
+        "SplitV".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.num_split)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, self.num_split)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[1].shape[0], self.num_split.to_dim())?;
+        s.equals(&inputs[2].rank, 0)?;
+        for o in outputs {
+            s.equals(&inputs[0].datum_type, &o.datum_type)?;
+            s.equals(&inputs[0].rank, &o.rank)?;
+        }
+        s.given_3(
+            &inputs[0].shape,
+            &inputs[1].value,
+            &inputs[2].value,
+            move |s, shape, sizes, axis| {
+                let axis = resolve_axis(&axis, shape.len())?;
+                let sizes = split_sizes(&sizes, &shape[axis])?;
+                for (o, size) in outputs.iter().zip(sizes.into_iter()) {
+                    let mut shape = shape.clone();
+                    shape[axis] = size;
+                    s.equals(&o.shape, shape)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let sizes =
+            target.outlet_fact(inputs[1])?.konst.clone().context("Expect constant sizes")?;
+        let axis = target.outlet_fact(inputs[2])?.konst.clone().context("Expect constant axis")?;
+        let input = target.outlet_fact(inputs[0])?.clone();
+        let axis = resolve_axis(&axis, input.rank())?;
+        let sizes = split_sizes(&sizes, &input.shape[axis])?;
+        wire_slices(prefix, target, inputs[0], axis, &sizes)
+    }
+}
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct Unpack {
+    num: usize,
+    axis: i64,
+}
+
+impl_dyn_hash!(Unpack);
+
+impl Unpack {
+    fn axis(&self, rank: usize) -> usize {
+        if self.axis < 0 {
+            (self.axis + rank as i64) as usize
+        } else {
+            self.axis as usize
+        }
+    }
+}
+
+impl Expansion for Unpack {
+    fn name(&self) -> Cow<str> {
+        "Unpack".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.num)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, self.num)?;
+        for o in outputs {
+            s.equals(&inputs[0].datum_type, &o.datum_type)?;
+            s.equals(inputs[0].rank.bex(), o.rank.bex() + 1)?;
+        }
+        s.given(&inputs[0].shape, move |s, shape| {
+            let axis = self.axis(shape.len());
+            let mut shape = shape.clone();
+            shape.remove(axis);
+            for o in outputs {
+                s.equals(&o.shape, shape.clone())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = self.axis(target.outlet_fact(inputs[0])?.rank());
+        let slices = wire_slices(prefix, target, inputs[0], axis, &*tvec!(1.to_dim(); self.num))?;
+        slices
+            .iter()
+            .enumerate()
+            .map(|(ix, slice)| {
+                Ok(target.wire_node(
+                    format!("{}.rm-{}", prefix, ix),
+                    AxisOp::Rm(axis),
+                    &[*slice],
+                )?[0])
+            })
+            .collect()
+    }
+}
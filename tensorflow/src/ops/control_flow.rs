@@ -1,119 +1,489 @@
-use tract_hir::internal::*;
+//! TensorFlow 1 while loops.
+//!
+//! `tf.while_loop` graphs are made of Enter, Merge, Switch, LoopCond,
+//! NextIteration and Exit nodes tied together by a `frame_name`. They can
+//! not run as a dataflow graph in tract, so each frame is extracted before
+//! the graph is parsed, its body turned into a sub-model, and the whole
+//! frame replaced by a single `Loop` op.
 
-use crate::model::TfOpRegister;
+use crate::model::Tensorflow;
+use crate::tfpb::tensorflow::{DataType, GraphDef, NodeDef};
+use std::collections::HashSet;
+use tract_hir::internal::*;
+use tract_hir::ops::scan::{InferenceLoop, InputMapping, OutputMapping, StateInitializer};
 
-pub fn register_all_ops(reg: &mut TfOpRegister) {
-    reg.insert("Enter", |_, node| {
-        Ok(Box::new(LoopGate(LoopGateRole::Enter(node.get_attr_str("frame_name")?))))
-    });
-    reg.insert("Exit", |_, _| Ok(Box::new(LoopGate(LoopGateRole::Exit))));
-    reg.insert("LoopCond", |_, _| Ok(Box::new(LoopGate(LoopGateRole::LoopCond))));
+/// Graph nodes once the while frames have been collapsed, along with the
+/// loop ops standing for them.
+pub struct LoweredFrames {
+    pub nodes: Vec<NodeDef>,
+    pub loops: HashMap<String, Box<dyn InferenceOp>>,
 }
 
-#[derive(Debug, Clone, Hash)]
-pub enum LoopGateRole {
-    Enter(String),
-    Exit,
-    LoopCond,
+/// One loop variable: Enter -> Merge -> Switch -> (body) -> NextIteration
+/// back to the Merge, and Switch -> Exit out of the loop.
+struct LoopVar<'a> {
+    enter: &'a NodeDef,
+    merge: &'a NodeDef,
+    switch: &'a NodeDef,
+    next: String,
+    exit: Option<&'a NodeDef>,
 }
 
-#[derive(Debug, Clone, Hash)]
-pub struct LoopGate(LoopGateRole);
+struct Frame<'a> {
+    name: String,
+    vars: Vec<LoopVar<'a>>,
+    invariants: Vec<&'a NodeDef>,
+    pred: String,
+    nodes: Vec<&'a NodeDef>,
+    controls: HashSet<String>,
+}
 
-impl_dyn_hash!(LoopGate);
+fn node_name(input: &str) -> TractResult<&str> {
+    Ok(Tensorflow::parse_input(input)?.0)
+}
 
-impl Op for LoopGate {
-    fn name(&self) -> Cow<str> {
-        format!("{:?}", self.0).into()
+/// Assigns every node to its frame ("" for the root one), and computes the
+/// parent of every frame.
+fn assign_frames(
+    nodes: &[NodeDef],
+) -> TractResult<(HashMap<String, String>, HashMap<String, String>)> {
+    let mut frames: HashMap<String, String> = HashMap::new();
+    let mut parents: HashMap<String, String> = HashMap::new();
+    loop {
+        let mut changed = false;
+        for node in nodes {
+            if frames.contains_key(&node.name) {
+                continue;
+            }
+            let mut input_frame = None;
+            for input in &node.input {
+                if let Some(frame) = frames.get(node_name(input)?) {
+                    input_frame = Some(frame.clone());
+                    break;
+                }
+            }
+            let frame = if node.input.len() == 0 {
+                String::new()
+            } else if let Some(outer) = input_frame {
+                if node.op == "Enter" {
+                    let inner = node.get_attr_str("frame_name")?;
+                    parents.insert(inner.clone(), outer);
+                    inner
+                } else if node.op == "Exit" {
+                    parents
+                        .get(&outer)
+                        .cloned()
+                        .ok_or_else(|| format_err!("Exit node {} is not in a loop", node.name))?
+                } else {
+                    outer
+                }
+            } else {
+                continue;
+            };
+            frames.insert(node.name.clone(), frame);
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
     }
+    Ok((frames, parents))
+}
 
-    op_tf!();
-    not_a_typed_op!();
+fn is_within(frame: &str, ancestor: &str, parents: &HashMap<String, String>) -> bool {
+    let mut frame = frame;
+    loop {
+        if frame == ancestor {
+            return true;
+        }
+        if let Some(parent) = parents.get(frame) {
+            frame = parent;
+        } else {
+            return false;
+        }
+    }
 }
 
-impl EvalOp for LoopGate {
-    fn is_stateless(&self) -> bool {
-        true
+impl<'a> Frame<'a> {
+    fn analyse(
+        name: &str,
+        nodes: &'a [NodeDef],
+        frames: &HashMap<String, String>,
+        parents: &HashMap<String, String>,
+    ) -> TractResult<Frame<'a>> {
+        let by_name: HashMap<&str, &NodeDef> = nodes.iter().map(|n| (&*n.name, n)).collect();
+        let in_frame = |n: &NodeDef| frames.get(&n.name).map(|f| f == name).unwrap_or(false);
+        let op_of = |input: &str| -> TractResult<&str> {
+            let name = node_name(input)?;
+            Ok(by_name.get(name).map(|n| &*n.op).unwrap_or(""))
+        };
+        let body: Vec<&NodeDef> = nodes
+            .iter()
+            .filter(|n| frames.get(&n.name).map(|f| is_within(f, name, parents)).unwrap_or(false))
+            .collect();
+        let mut vars = vec![];
+        let mut controls = HashSet::new();
+        for &merge in &body {
+            // merges from tf.cond inside the loop body are left alone
+            if merge.op != "Merge" || !in_frame(merge) || merge.input.len() != 2 {
+                continue;
+            }
+            let (enter, next) = if op_of(&merge.input[0])? == "Enter"
+                && op_of(&merge.input[1])? == "NextIteration"
+            {
+                (&merge.input[0], &merge.input[1])
+            } else if op_of(&merge.input[1])? == "Enter"
+                && op_of(&merge.input[0])? == "NextIteration"
+            {
+                (&merge.input[1], &merge.input[0])
+            } else {
+                continue;
+            };
+            let enter = by_name[node_name(enter)?];
+            let next = by_name[node_name(next)?];
+            let mut switch = None;
+            for &node in &body {
+                if node.op == "Switch"
+                    && node_name(&node.input[0])? == merge.name
+                    && op_of(&node.input[1])? == "LoopCond"
+                {
+                    switch = Some(node);
+                }
+            }
+            let switch = switch
+                .ok_or_else(|| format_err!("No switch found for loop merge {}", merge.name))?;
+            let mut exit = None;
+            for node in nodes {
+                if node.op == "Exit"
+                    && Tensorflow::parse_input(&node.input[0])? == (&*switch.name, 0)
+                {
+                    exit = Some(node);
+                }
+            }
+            for n in &[enter, merge, switch, next] {
+                controls.insert(n.name.clone());
+            }
+            vars.push(LoopVar { enter, merge, switch, next: next.input[0].clone(), exit });
+        }
+        let invariants: Vec<&NodeDef> = body
+            .iter()
+            .filter(|n| n.op == "Enter" && in_frame(n) && !controls.contains(&n.name))
+            .cloned()
+            .collect();
+        controls.extend(invariants.iter().map(|n| n.name.clone()));
+        let conds: Vec<&NodeDef> =
+            body.iter().filter(|n| n.op == "LoopCond" && in_frame(n)).cloned().collect();
+        if conds.len() != 1 {
+            bail!("Expected one LoopCond in frame {}, found {}", name, conds.len());
+        }
+        controls.insert(conds[0].name.clone());
+        Ok(Frame {
+            name: name.to_string(),
+            vars,
+            invariants,
+            pred: conds[0].input[0].clone(),
+            nodes: body,
+            controls,
+        })
     }
 
-    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
-        Ok(inputs)
+    fn is_control(&self, node: &NodeDef) -> bool {
+        self.controls.contains(&node.name)
     }
-}
 
-impl InferenceRulesOp for LoopGate {
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        s: &mut Solver<'r>,
-        inputs: &'p [TensorProxy],
-        outputs: &'p [TensorProxy],
-    ) -> InferenceResult {
-        check_input_arity(&inputs, 1)?;
-        check_output_arity(&outputs, 1)?;
-        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
-        s.equals(&inputs[0].shape, &outputs[0].shape)?;
-        Ok(())
+    /// Rewrites inputs in the body: the Switch true branch of a loop
+    /// variable is its current value, that is the Merge placeholder.
+    fn body_input(&self, input: &str) -> TractResult<String> {
+        let (name, slot) = Tensorflow::parse_input(input)?;
+        if let Some(var) = self.vars.iter().find(|v| v.switch.name == name) {
+            if slot != 1 {
+                bail!("Unexpected use of loop switch output {} in loop body", input);
+            }
+            Ok(var.merge.name.clone())
+        } else {
+            Ok(input.to_string())
+        }
     }
 
-    as_op!();
-}
+    /// Copies the loop condition subgraph, with `var_value` and
+    /// `invariant_value` providing the values of the loop variables and
+    /// invariants. Returns the copied nodes and the condition.
+    fn clone_condition(
+        &self,
+        prefix: &str,
+        var_value: impl Fn(&LoopVar) -> String,
+        invariant_value: impl Fn(&NodeDef) -> String,
+    ) -> TractResult<(Vec<NodeDef>, String)> {
+        let mut todo = vec![self.pred.clone()];
+        let mut closure: Vec<&NodeDef> = vec![];
+        while let Some(input) = todo.pop() {
+            let name = node_name(&input)?;
+            if self.vars.iter().any(|v| v.merge.name == name)
+                || self.invariants.iter().any(|e| e.name == name)
+                || closure.iter().any(|n| n.name == name)
+            {
+                continue;
+            }
+            let node = self.nodes.iter().find(|n| n.name == name).ok_or_else(|| {
+                format_err!("Loop condition depends on {} outside of the loop", name)
+            })?;
+            if node.op == "Switch" || node.op == "Enter" || node.op == "Merge" {
+                bail!("Unsupported loop condition, depending on {} ({})", name, node.op);
+            }
+            todo.extend(node.input.iter().filter(|i| !i.starts_with("^")).cloned());
+            closure.push(node);
+        }
+        let rename = |input: &str| -> TractResult<String> {
+            let (name, slot) = Tensorflow::parse_input(input)?;
+            if let Some(var) = self.vars.iter().find(|v| v.merge.name == name) {
+                Ok(var_value(var))
+            } else if let Some(inv) = self.invariants.iter().find(|e| e.name == name) {
+                Ok(invariant_value(inv))
+            } else if slot > 0 {
+                Ok(format!("{}{}:{}", prefix, name, slot))
+            } else {
+                Ok(format!("{}{}", prefix, name))
+            }
+        };
+        let mut nodes = vec![];
+        for node in closure {
+            let mut node = node.clone();
+            node.name = format!("{}{}", prefix, node.name);
+            node.input = node
+                .input
+                .iter()
+                .filter(|i| !i.starts_with("^"))
+                .map(|i| rename(i))
+                .collect::<TractResult<_>>()?;
+            nodes.push(node);
+        }
+        Ok((nodes, rename(&self.pred)?))
+    }
 
-#[derive(Debug, Copy, Clone, PartialEq, Hash)]
-pub enum NextIterationRole {
-    Source,
-    Sink,
+    fn body(&self, tf: &Tensorflow) -> TractResult<InferenceModel> {
+        fn placeholder(name: &str, dt: impl Into<crate::tfpb::tensorflow::AttrValue>) -> NodeDef {
+            crate::tfpb::node().name(name).op("Placeholder").attr("dtype", dt)
+        }
+        let mut graph = crate::tfpb::graph();
+        let iteration = format!("{}/iteration", self.name);
+        let condition = format!("{}/condition", self.name);
+        graph = graph.node(placeholder(&iteration, DataType::DtInt64));
+        graph = graph.node(placeholder(&condition, DataType::DtBool));
+        let mut inputs = vec![iteration, condition];
+        for var in &self.vars {
+            graph = graph.node(placeholder(&var.merge.name, var.enter.attr["T"].clone()));
+            inputs.push(var.merge.name.clone());
+        }
+        for inv in &self.invariants {
+            graph = graph.node(placeholder(&inv.name, inv.attr["T"].clone()));
+            inputs.push(inv.name.clone());
+        }
+        let kept: HashSet<&str> = self
+            .nodes
+            .iter()
+            .filter(|n| !self.is_control(n))
+            .map(|n| &*n.name)
+            .chain(inputs.iter().map(|s| &**s))
+            .collect();
+        for node in self.nodes.iter().filter(|n| !self.is_control(n)) {
+            let mut node = (*node).clone();
+            let mut rewired = vec![];
+            for input in &node.input {
+                if input.starts_with("^") {
+                    let name = node_name(input)?;
+                    if let Some(var) = self.vars.iter().find(|v| v.switch.name == name) {
+                        rewired.push(format!("^{}", var.merge.name));
+                    } else if kept.contains(name) {
+                        rewired.push(input.clone());
+                    }
+                } else {
+                    rewired.push(self.body_input(input)?);
+                }
+            }
+            node.input = rewired;
+            graph = graph.node(node);
+        }
+        let (cond_nodes, next_cond) = self.clone_condition(
+            &format!("{}/next_condition/", self.name),
+            |var| var.next.clone(),
+            |inv| inv.name.clone(),
+        )?;
+        for node in cond_nodes {
+            graph = graph.node(node);
+        }
+        let mut outputs = vec![next_cond];
+        for var in &self.vars {
+            outputs.push(self.body_input(&var.next)?);
+        }
+        let mut body = tf.parse_graph(&graph)?.0;
+        let outlet = |body: &InferenceModel, input: &str| -> TractResult<OutletId> {
+            let (name, slot) = Tensorflow::parse_input(input)?;
+            Ok(OutletId::new(body.node_by_name(name)?.id, slot))
+        };
+        let inputs = inputs.iter().map(|i| outlet(&body, i)).collect::<TractResult<Vec<_>>>()?;
+        let outputs = outputs.iter().map(|o| outlet(&body, o)).collect::<TractResult<Vec<_>>>()?;
+        body.set_input_outlets(&inputs)?;
+        body.set_output_outlets(&outputs)?;
+        Ok(body)
+    }
+
+    fn to_loop(&self, tf: &Tensorflow) -> TractResult<(Box<dyn InferenceOp>, Vec<NodeDef>)> {
+        let body = self.body(tf)?;
+        let (mut nodes, initial_cond) = self.clone_condition(
+            &format!("{}/initial_condition/", self.name),
+            |var| var.enter.input[0].clone(),
+            |inv| inv.input[0].clone(),
+        )?;
+        let mut node = crate::tfpb::node().name(&self.name).op("Loop").input(initial_cond);
+        let mut input_mapping = vec![];
+        let mut output_mapping = vec![];
+        for (ix, var) in self.vars.iter().enumerate() {
+            node = node.input(&var.enter.input[0]);
+            input_mapping
+                .push(InputMapping::State { initializer: StateInitializer::FromInput(1 + ix) });
+            output_mapping.push(OutputMapping {
+                state: true,
+                last_value_slot: Some(ix),
+                full_slot: None,
+                axis: 0,
+                chunk: 1,
+                full_dim_hint: None,
+            });
+        }
+        for (ix, inv) in self.invariants.iter().enumerate() {
+            node = node.input(&inv.input[0]);
+            input_mapping.push(InputMapping::Full { slot: 1 + self.vars.len() + ix });
+        }
+        nodes.push(node);
+        let op = InferenceLoop::new(body, None, Some(0), input_mapping, output_mapping);
+        Ok((Box::new(op), nodes))
+    }
 }
 
-#[derive(Debug, Clone, new, Hash)]
-pub struct NextIteration {
-    name: String,
-    role: NextIterationRole,
+/// Replaces all outermost while frames of the graph by loop ops. Nested
+/// frames are dealt with when parsing the loop bodies.
+pub fn lower_while_loops(tf: &Tensorflow, graph: &GraphDef) -> TractResult<LoweredFrames> {
+    let (frames, parents) = assign_frames(&graph.node)?;
+    let mut outermost: Vec<&str> = vec![];
+    for node in &graph.node {
+        if node.op == "Enter" {
+            let frame = &frames[&node.name];
+            if parents.get(frame).map(|p| p == "").unwrap_or(false)
+                && !outermost.contains(&&**frame)
+            {
+                outermost.push(frame);
+            }
+        }
+    }
+    let mut lowered = LoweredFrames { nodes: vec![], loops: HashMap::new() };
+    let mut exits = HashMap::new();
+    for name in outermost {
+        let frame = Frame::analyse(name, &graph.node, &frames, &parents)
+            .with_context(|| format!("Analysing while loop {}", name))?;
+        let (op, nodes) =
+            frame.to_loop(tf).with_context(|| format!("Building while loop {}", name))?;
+        lowered.loops.insert(name.to_string(), op);
+        lowered.nodes.extend(nodes);
+        for (ix, var) in frame.vars.iter().enumerate() {
+            if let Some(exit) = var.exit {
+                exits.insert(exit.name.clone(), format!("{}:{}", name, ix));
+            }
+        }
+    }
+    for node in &graph.node {
+        if let Some(output) = exits.get(&node.name) {
+            // the exit becomes an identity on the loop output
+            let mut identity = crate::tfpb::node().name(&node.name).op("Identity").input(output);
+            identity.attr = node.attr.clone();
+            lowered.nodes.push(identity);
+        } else if frames.get(&node.name).map(|f| f == "").unwrap_or(true) {
+            lowered.nodes.push(node.clone());
+        }
+    }
+    Ok(lowered)
 }
 
-impl_dyn_hash!(NextIteration);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb::*;
+    use std::convert::TryFrom;
 
-impl Op for NextIteration {
-    fn name(&self) -> Cow<str> {
-        format!("{:?}({})", self.role, self.name).into()
+    fn konst(name: &str, t: Tensor) -> TractResult<NodeDef> {
+        let dt = tensorflow::DataType::try_from(t.datum_type())?;
+        Ok(node()
+            .name(name)
+            .op("Const")
+            .attr("dtype", dt)
+            .attr("value", tensorflow::TensorProto::try_from(&t)?))
     }
 
-    op_tf!();
-    not_a_typed_op!();
-}
+    fn enter(name: &str, input: &str, dt: DataType, constant: bool) -> NodeDef {
+        node()
+            .name(name)
+            .op("Enter")
+            .input(input)
+            .attr("T", dt)
+            .attr("frame_name", "while/while_context")
+            .attr("is_constant", constant)
+    }
 
-impl EvalOp for NextIteration {
-    fn is_stateless(&self) -> bool {
-        false
+    fn op(name: &str, op: &str, inputs: &[&str]) -> NodeDef {
+        inputs.iter().fold(node().name(name).op(op), |n, i| n.input(i))
     }
 
-    fn state(
-        &self,
-        _state: &mut SessionState,
-        _id: usize,
-    ) -> TractResult<Option<Box<dyn OpState>>> {
-        unimplemented!();
+    // i = 0; while i < n { x = x * 2; i = i + 1 }
+    fn doubling(n: i32) -> TractResult<GraphDef> {
+        use DataType::*;
+        Ok(graph()
+            .node(node().name("x").op("Placeholder").attr("dtype", DtFloat))
+            .node(konst("zero", tensor0(0i32))?)
+            .node(konst("n", tensor0(n))?)
+            .node(konst("one", tensor0(1i32))?)
+            .node(konst("two", tensor0(2f32))?)
+            .node(enter("while/Enter", "x", DtFloat, false))
+            .node(enter("while/Enter_1", "zero", DtInt32, false))
+            .node(enter("while/Enter_2", "n", DtInt32, true))
+            .node(enter("while/Enter_3", "one", DtInt32, true))
+            .node(enter("while/Enter_4", "two", DtFloat, true))
+            .node(op("while/Merge", "Merge", &["while/Enter", "while/NextIteration"]).attr("N", 2))
+            .node(
+                op("while/Merge_1", "Merge", &["while/Enter_1", "while/NextIteration_1"])
+                    .attr("N", 2),
+            )
+            .node(op("while/Less", "Less", &["while/Merge_1", "while/Enter_2"]))
+            .node(op("while/LoopCond", "LoopCond", &["while/Less"]))
+            .node(op("while/Switch", "Switch", &["while/Merge", "while/LoopCond"]))
+            .node(op("while/Switch_1", "Switch", &["while/Merge_1", "while/LoopCond"]))
+            .node(op("while/Identity", "Identity", &["while/Switch:1"]))
+            .node(op("while/Identity_1", "Identity", &["while/Switch_1:1"]))
+            .node(op("while/mul", "Mul", &["while/Identity", "while/Enter_4"]))
+            .node(op("while/add", "Add", &["while/Identity_1", "while/Enter_3"]))
+            .node(op("while/NextIteration", "NextIteration", &["while/mul"]))
+            .node(op("while/NextIteration_1", "NextIteration", &["while/add"]))
+            .node(op("while/Exit", "Exit", &["while/Switch"]))
+            .node(op("while/Exit_1", "Exit", &["while/Switch_1"]))
+            .node(op("y", "Identity", &["while/Exit"])))
     }
-}
 
-impl InferenceRulesOp for NextIteration {
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        _s: &mut Solver<'r>,
-        inputs: &'p [TensorProxy],
-        outputs: &'p [TensorProxy],
-    ) -> InferenceResult {
-        match self.role {
-            NextIterationRole::Source => {
-                check_input_arity(&inputs, 0)?;
-                check_output_arity(&outputs, 1)?;
-            }
-            NextIterationRole::Sink => {
-                check_input_arity(&inputs, 1)?;
-                check_output_arity(&outputs, 0)?;
-            }
-        }
+    fn run(graph: &GraphDef) -> TractResult<Arc<Tensor>> {
+        let mut model = crate::tensorflow().model_for_proto_model(graph)?;
+        model.set_output_names(&["y"])?;
+        model.set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let model = model.into_typed()?.declutter()?;
+        let mut outputs = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 3.0])))?;
+        Ok(outputs.remove(0))
+    }
+
+    #[test]
+    fn while_loop() -> TractResult<()> {
+        assert_eq!(*run(&doubling(5)?)?, tensor1(&[32f32, 96.0]));
         Ok(())
     }
 
-    as_op!();
+    #[test]
+    fn while_loop_no_iteration() -> TractResult<()> {
+        assert_eq!(*run(&doubling(0)?)?, tensor1(&[1f32, 3.0]));
+        Ok(())
+    }
 }
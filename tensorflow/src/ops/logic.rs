@@ -15,9 +15,66 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("LogicalAnd", |_, _| Ok(ops::logic::And.into_hir()));
     reg.insert("LogicalOr", |_, _| Ok(ops::logic::Or.into_hir()));
     reg.insert("Merge", merge);
+    reg.insert("Select", |_, _| Ok(expand(Select)));
+    reg.insert("SelectV2", |_, _| Ok(Box::new(ops::logic::Iff)));
     reg.insert("Switch", |_, _| Ok(Box::new(Switch)));
 }
 
+/// Select, where the condition can be a vector picking whole rows of
+/// higher rank inputs. SelectV2 broadcasts, and is plain Iff.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Select;
+
+impl_dyn_hash!(Select);
+
+impl Expansion for Select {
+    fn name(&self) -> Cow<str> {
+        "Select".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, bool::datum_type())?;
+        s.equals(&inputs[1].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[1].shape, &outputs[0].shape)?;
+        s.equals(&inputs[2].shape, &outputs[0].shape)?;
+        s.given_2(&inputs[0].rank, &inputs[1].rank, move |s, crank, rank| {
+            if crank == 1 && rank > 1 {
+                s.equals(&inputs[0].shape[0], &inputs[1].shape[0])
+            } else {
+                s.equals(&inputs[0].shape, &inputs[1].shape)
+            }
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(inputs[1])?.rank();
+        let mut cond = inputs[0];
+        for axis in target.outlet_fact(cond)?.rank()..rank {
+            cond = target.wire_node(
+                format!("{}.cond-{}", prefix, axis),
+                AxisOp::Add(axis),
+                &[cond],
+            )?[0];
+        }
+        target.wire_node(prefix, ops::logic::Iff, &[cond, inputs[1], inputs[2]])
+    }
+}
+
 #[derive(Debug, Clone, new, Hash)]
 pub struct Switch;
 
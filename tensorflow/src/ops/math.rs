@@ -5,16 +5,24 @@ use crate::model::ParsingContext;
 use crate::model::TfOpRegister;
 use crate::tfpb::tensorflow::NodeDef;
 
+mod arg_max;
+mod cumsum;
 mod reduce;
+mod squared_difference;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Abs", |_, _| Ok(Box::new(ops::math::abs())));
     reg.insert("Add", |_, _| Ok(ops::math::Add.into_hir()));
     reg.insert("AddN", add_n);
     reg.insert("AddV2", |_, _| Ok(ops::math::Add.into_hir()));
+    reg.insert("ArgMax", arg_max::arg_max);
+    reg.insert("BatchMatMul", batch_mat_mul);
+    reg.insert("BatchMatMulV2", batch_mat_mul);
     reg.insert("BiasAdd", |_, _| Ok(ops::math::Add.into_hir()));
     reg.insert("Ceil", |_, _| Ok(Box::new(ops::math::ceil())));
+    reg.insert("Cumsum", cumsum::cumsum);
     reg.insert("Div", |_, _| Ok(ops::math::Div.into_hir()));
+    reg.insert("Exp", |_, _| Ok(Box::new(ops::math::exp())));
     reg.insert("FloorMod", |_, _| Ok(ops::math::Rem.into_hir()));
     reg.insert("MatMul", mat_mul);
     reg.insert("Max", reduce::max);
@@ -31,6 +39,9 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Neg", |_, _| Ok(Box::new(ops::math::neg())));
     reg.insert("RealDiv", |_, _| Ok(ops::math::Div.into_hir()));
     reg.insert("Rsqrt", |_, _| Ok(Box::new(ops::math::rsqrt())));
+    reg.insert("Sqrt", |_, _| Ok(Box::new(ops::math::sqrt())));
+    reg.insert("Square", |_, _| Ok(Box::new(ops::math::square())));
+    reg.insert("SquaredDifference", |_, _| Ok(expand(squared_difference::SquaredDifference)));
     reg.insert("Sub", |_, _| Ok(ops::math::Sub.into_hir()));
     reg.insert("Tanh", |_, _| Ok(Box::new(ops::math::tanh())));
 }
@@ -44,3 +55,9 @@ pub fn mat_mul(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn Infer
     let trans_b = pb.get_attr_bool("transpose_b")?;
     Ok(expand(ops::matmul::MatMulInference::default().with_a_trans(trans_a).with_b_trans(trans_b)))
 }
+
+pub fn batch_mat_mul(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let adj_x = pb.get_attr_opt_bool("adj_x")?.unwrap_or(false);
+    let adj_y = pb.get_attr_opt_bool("adj_y")?.unwrap_or(false);
+    Ok(expand(ops::matmul::MatMulInference::default().with_a_trans(adj_x).with_b_trans(adj_y)))
+}
@@ -0,0 +1,139 @@
+use tract_hir::internal::*;
+use tract_ndarray::Axis;
+use tract_num_traits::Zero;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn cumsum(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let exclusive = pb.get_attr_opt_bool("exclusive")?.unwrap_or(false);
+    let reverse = pb.get_attr_opt_bool("reverse")?.unwrap_or(false);
+    Ok(expand(CumsumInference::new(exclusive, reverse)))
+}
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct CumsumInference {
+    exclusive: bool,
+    reverse: bool,
+}
+
+impl_dyn_hash!(CumsumInference);
+
+impl Expansion for CumsumInference {
+    fn name(&self) -> Cow<str> {
+        "Cumsum".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = target.outlet_fact(inputs[1])?.konst.clone().context("Expect constant axis")?;
+        let axis = axis.cast_to_scalar::<i64>()?;
+        let rank = target.outlet_fact(inputs[0])?.rank() as i64;
+        let axis = if axis < 0 { axis + rank } else { axis } as usize;
+        target.wire_node(prefix, Cumsum::new(axis, self.exclusive, self.reverse), &[inputs[0]])
+    }
+}
+
+/// Cumulative sum along an axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Cumsum {
+    axis: usize,
+    exclusive: bool,
+    reverse: bool,
+}
+
+impl_dyn_hash!(Cumsum);
+
+impl Cumsum {
+    fn eval_t<T: Datum + Copy + Zero>(&self, input: Arc<Tensor>) -> TractResult<Arc<Tensor>> {
+        let mut array = input.into_tensor().into_array::<T>()?;
+        for mut lane in array.lanes_mut(Axis(self.axis)) {
+            let len = lane.len();
+            let mut acc = T::zero();
+            for i in 0..len {
+                let ix = if self.reverse { len - 1 - i } else { i };
+                let value = lane[ix];
+                if self.exclusive {
+                    lane[ix] = acc;
+                    acc = acc + value;
+                } else {
+                    acc = acc + value;
+                    lane[ix] = acc;
+                }
+            }
+        }
+        Ok(array.into_arc_tensor())
+    }
+}
+
+impl Op for Cumsum {
+    fn name(&self) -> Cow<str> {
+        "Cumsum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {} exclusive: {} reverse: {}",
+            self.axis, self.exclusive, self.reverse
+        )])
+    }
+
+    op_tf!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Cumsum {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        Ok(tvec!(dispatch_numbers!(Self::eval_t(input.datum_type())(self, input))?))
+    }
+}
+
+impl TypedOp for Cumsum {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cumsum_modes() -> TractResult<()> {
+        let input = rctensor2(&[[1i32, 2, 3], [4, 5, 6]]);
+        let run = |exclusive, reverse| {
+            Cumsum::new(1, exclusive, reverse).eval(tvec!(input.clone())).map(|mut o| o.remove(0))
+        };
+        assert_eq!(run(false, false)?, rctensor2(&[[1i32, 3, 6], [4, 9, 15]]));
+        assert_eq!(run(true, false)?, rctensor2(&[[0i32, 1, 3], [0, 4, 9]]));
+        assert_eq!(run(false, true)?, rctensor2(&[[6i32, 5, 3], [15, 11, 6]]));
+        assert_eq!(run(true, true)?, rctensor2(&[[5i32, 3, 0], [11, 6, 0]]));
+        Ok(())
+    }
+}
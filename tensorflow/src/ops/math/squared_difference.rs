@@ -0,0 +1,41 @@
+use tract_hir::internal::*;
+use tract_hir::ops;
+use tract_hir::ops::binary::InferenceBinOp;
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct SquaredDifference;
+
+impl_dyn_hash!(SquaredDifference);
+
+impl Expansion for SquaredDifference {
+    fn name(&self) -> Cow<str> {
+        "SquaredDifference".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ops::binary::rules(s, inputs, outputs, move |a, b| {
+            a.common_super_type(b).with_context(|| format!("No super type for {:?} and {:?}", a, b))
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let diff = InferenceBinOp(Box::new(ops::math::Sub)).wire(
+            &format!("{}.sub", prefix),
+            target,
+            inputs,
+        )?;
+        target.wire_node(prefix, ops::math::square(), &diff)
+    }
+}
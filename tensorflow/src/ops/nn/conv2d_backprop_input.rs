@@ -0,0 +1,95 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{Deconv, PaddingSpec};
+use tract_hir::ops::nn::DataFormat;
+use tract_hir::tract_core::ops::cnn::KernelFormat;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn conv2d_backprop_input(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let data_format = super::data_format(pb)?;
+    let spatial = if data_format == DataFormat::NHWC { 1..3 } else { 2..4 };
+    let strides: Vec<usize> = pb.get_attr_list_int("strides")?;
+    let dilations: Vec<usize> = pb.get_attr_opt_list_int("dilations")?.unwrap_or(vec![1; 4]);
+    if strides.len() != 4 || dilations.len() != 4 {
+        bail!("Expected 4 strides and dilations, got {:?} and {:?}", strides, dilations)
+    }
+    Ok(expand(Conv2DBackpropInput {
+        data_format,
+        padding: super::padding(pb)?,
+        strides: strides[spatial.clone()].into(),
+        dilations: dilations[spatial].into(),
+    }))
+}
+
+/// Gradient of Conv2D wrt its input, that is a deconvolution. Inputs are the
+/// output shape, the (HWIO, as in the forward conv) filter, and the data.
+#[derive(Debug, Clone, Hash)]
+pub struct Conv2DBackpropInput {
+    data_format: DataFormat,
+    padding: PaddingSpec,
+    strides: TVec<usize>,
+    dilations: TVec<usize>,
+}
+
+impl_dyn_hash!(Conv2DBackpropInput);
+
+impl Expansion for Conv2DBackpropInput {
+    fn name(&self) -> Cow<str> {
+        "Conv2DBackpropInput".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&inputs[0].shape[0], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[2].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&inputs[1].datum_type, &inputs[2].datum_type)?;
+        s.equals(&outputs[0].datum_type, &inputs[2].datum_type)?;
+        s.given(&inputs[0].value, move |s, sizes| {
+            let sizes = sizes.cast_to::<i64>()?;
+            for (ix, d) in sizes.as_slice::<i64>()?.iter().enumerate() {
+                s.equals(&outputs[0].shape[ix], d.to_dim())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let sizes =
+            target.outlet_fact(inputs[0])?.konst.clone().context("Expect constant input sizes")?;
+        let sizes = sizes.cast_to::<i64>()?;
+        let sizes: TVec<usize> = sizes.as_slice::<i64>()?.iter().map(|d| *d as usize).collect();
+        let output_shape = self.data_format.shape(sizes)?.hw_dims().into();
+        let deconv = Deconv {
+            data_format: self.data_format,
+            kernel_format: KernelFormat::HWIO,
+            padding: self.padding.clone(),
+            strides: Some(self.strides.clone()),
+            dilations: Some(self.dilations.clone()),
+            adjustments: None,
+            output_shape: Some(output_shape),
+            group: 1,
+            bias_input: None,
+        };
+        deconv.wire(prefix, target, &[inputs[2], inputs[1]])
+    }
+}
@@ -0,0 +1,195 @@
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn resize_bilinear(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    resize(pb, true)
+}
+
+pub fn resize_nearest_neighbor(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    resize(pb, false)
+}
+
+fn resize(pb: &NodeDef, bilinear: bool) -> TractResult<Box<dyn InferenceOp>> {
+    let align_corners = pb.get_attr_opt_bool("align_corners")?.unwrap_or(false);
+    let half_pixel_centers = pb.get_attr_opt_bool("half_pixel_centers")?.unwrap_or(false);
+    Ok(expand(Resize::new(bilinear, align_corners, half_pixel_centers)))
+}
+
+/// NHWC image resizing, to the size given by the second input.
+///
+/// It is wired as gathers along H and W, with constant indices (and constant
+/// interpolation weights for bilinear), so it requires known input dims.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Resize {
+    bilinear: bool,
+    align_corners: bool,
+    half_pixel_centers: bool,
+}
+
+impl_dyn_hash!(Resize);
+
+impl Resize {
+    fn scale(&self, len_in: usize, len_out: usize) -> f32 {
+        if self.align_corners && len_out > 1 {
+            (len_in - 1) as f32 / (len_out - 1) as f32
+        } else {
+            len_in as f32 / len_out as f32
+        }
+    }
+
+    fn nearest_indices(&self, len_in: usize, len_out: usize) -> Vec<i64> {
+        let scale = self.scale(len_in, len_out);
+        (0..len_out)
+            .map(|o| {
+                let x = if self.half_pixel_centers {
+                    (o as f32 + 0.5) * scale
+                } else {
+                    o as f32 * scale
+                };
+                let x = if self.align_corners { x.round() } else { x.floor() };
+                (x.max(0.0) as i64).min(len_in as i64 - 1)
+            })
+            .collect()
+    }
+
+    /// Lower indices, upper indices and weights of the upper ones.
+    fn bilinear_weights(&self, len_in: usize, len_out: usize) -> (Vec<i64>, Vec<i64>, Vec<f32>) {
+        let scale = self.scale(len_in, len_out);
+        let mut lower = vec![];
+        let mut upper = vec![];
+        let mut weights = vec![];
+        for o in 0..len_out {
+            let x = if self.half_pixel_centers {
+                (o as f32 + 0.5) * scale - 0.5
+            } else {
+                o as f32 * scale
+            };
+            lower.push((x.floor() as i64).max(0));
+            upper.push((x.ceil() as i64).min(len_in as i64 - 1));
+            weights.push(x - x.floor());
+        }
+        (lower, upper, weights)
+    }
+
+    fn wire_axis(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        input: OutletId,
+        axis: usize,
+        len_out: usize,
+    ) -> TractResult<OutletId> {
+        let len_in = target.outlet_fact(input)?.shape[axis].to_usize()?;
+        let gather = |target: &mut TypedModel, name: &str, indices: Vec<i64>| -> TractResult<_> {
+            let indices = target.add_const(format!("{}.{}", prefix, name), tensor1(&indices))?;
+            Ok(target.wire_node(
+                format!("{}.gather-{}", prefix, name),
+                ops::array::Gather::new(axis),
+                &[input, indices],
+            )?[0])
+        };
+        if !self.bilinear {
+            return gather(target, "indices", self.nearest_indices(len_in, len_out));
+        }
+        let (lower, upper, weights) = self.bilinear_weights(len_in, len_out);
+        let lower = gather(target, "lower", lower)?;
+        let upper = gather(target, "upper", upper)?;
+        let mut shape = tvec!(1; 4);
+        shape[axis] = len_out;
+        let weights = tensor1(&weights).into_shape(&shape)?;
+        let delta = target.wire_node(
+            format!("{}.delta", prefix),
+            ops::math::sub::bin_typed(),
+            &[upper, lower],
+        )?;
+        let delta = target.wire_node(
+            format!("{}.weighted", prefix),
+            ops::math::mul::unary(weights.into_arc_tensor()),
+            &delta,
+        )?;
+        Ok(target.wire_node(prefix, ops::math::add::bin_typed(), &[lower, delta[0]])?[0])
+    }
+}
+
+impl Expansion for Resize {
+    fn name(&self) -> Cow<str> {
+        if self.bilinear { "ResizeBilinear" } else { "ResizeNearestNeighbor" }.into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        if self.bilinear {
+            s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        } else {
+            s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        }
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[1].shape[0], 2.to_dim())?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[3], &outputs[0].shape[3])?;
+        s.given(&inputs[1].value, move |s, size| {
+            let size = size.cast_to::<i64>()?;
+            let size = size.as_slice::<i64>()?;
+            s.equals(&outputs[0].shape[1], size[0].to_dim())?;
+            s.equals(&outputs[0].shape[2], size[1].to_dim())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let size = target.outlet_fact(inputs[1])?.konst.clone().context("Expect constant size")?;
+        let size = size.cast_to::<i64>()?;
+        let size = size.as_slice::<i64>()?;
+        let mut wire = inputs[0];
+        if self.bilinear && target.outlet_fact(wire)?.datum_type != f32::datum_type() {
+            wire = target.wire_node(
+                format!("{}.cast", prefix),
+                ops::cast(f32::datum_type()),
+                &[wire],
+            )?[0];
+        }
+        wire = self.wire_axis(&format!("{}.h", prefix), target, wire, 1, size[0] as usize)?;
+        wire = self.wire_axis(&format!("{}.w", prefix), target, wire, 2, size[1] as usize)?;
+        Ok(tvec!(wire))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bilinear_half_pixel_weights() {
+        let op = Resize::new(true, false, true);
+        let (lower, upper, weights) = op.bilinear_weights(2, 4);
+        assert_eq!(lower, vec![0, 0, 0, 1]);
+        assert_eq!(upper, vec![0, 1, 1, 1]);
+        assert_eq!(weights, vec![0.75, 0.25, 0.75, 0.25]);
+    }
+
+    #[test]
+    fn nearest_align_corners() {
+        let op = Resize::new(false, true, false);
+        assert_eq!(op.nearest_indices(3, 5), vec![0, 1, 1, 2, 2]);
+    }
+}
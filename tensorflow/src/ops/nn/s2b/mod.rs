@@ -210,7 +210,8 @@ mod tests {
 
     #[test]
     fn space_to_batch_nd_infer_2() {
-        use tract_pulse::internal::stream_dim as s;
+        let table = SymbolTable::default();
+        let s = || table.sym("S").to_dim();
         let mut op = SpaceToBatch::new(f32::datum_type());
         let data = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, (s() - 4), 16));
         let block_shape = InferenceFact::from(Tensor::from(arr1(&[2])));
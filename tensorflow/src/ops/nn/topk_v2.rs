@@ -0,0 +1,77 @@
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+use tract_hir::internal::*;
+
+pub fn topk_v2(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let sorted = pb.get_attr_opt_bool("sorted")?.unwrap_or(true);
+    Ok(expand(TopKV2 { sorted }))
+}
+
+/// TopK along the last axis, with i32 indices.
+#[derive(Debug, Clone, new, Hash)]
+pub struct TopKV2 {
+    sorted: bool,
+}
+
+impl_dyn_hash!(TopKV2);
+
+impl Expansion for TopKV2 {
+    fn name(&self) -> Cow<str> {
+        "TopKV2".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[1].datum_type, i32::datum_type())?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&outputs[1].datum_type, i32::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[0].rank, &outputs[1].rank)?;
+        s.equals(&outputs[0].shape, &outputs[1].shape)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            for ix in 0..rank - 1 {
+                s.equals(&inputs[0].shape[ix], &outputs[0].shape[ix])?;
+            }
+            s.given(&inputs[1].value, move |s, k| {
+                let k = k.cast_to_scalar::<i64>()?;
+                s.equals(&outputs[0].shape[rank - 1], k.to_dim())
+            })
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(inputs[0])?.rank();
+        let k_dim_hint = if target.outlet_fact(inputs[1])?.konst.is_none() {
+            Some(target.symbol_table.new_with_prefix("K").into())
+        } else {
+            None
+        };
+        let op = tract_hir::ops::array::TopK::new(rank - 1, true, self.sorted, k_dim_hint);
+        let topk = target.wire_node(prefix, op, inputs)?;
+        let indices = target.wire_node(
+            format!("{}.indices", prefix),
+            tract_hir::ops::cast(i32::datum_type()),
+            &[topk[1]],
+        )?;
+        Ok(tvec!(topk[0], indices[0]))
+    }
+}
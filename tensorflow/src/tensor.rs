@@ -20,6 +20,7 @@ impl TryFrom<DataType> for DatumType {
             DataType::DtInt32 => Ok(DatumType::I32),
             DataType::DtInt64 => Ok(DatumType::I64),
             DataType::DtHalf => Ok(DatumType::F16),
+            DataType::DtBfloat16 => Ok(DatumType::BF16),
             DataType::DtFloat => Ok(DatumType::F32),
             DataType::DtDouble => Ok(DatumType::F64),
             DataType::DtString => Ok(DatumType::Blob),
@@ -59,6 +60,7 @@ impl TryFrom<DatumType> for DataType {
             DatumType::I32 => Ok(DataType::DtInt32),
             DatumType::I64 => Ok(DataType::DtInt64),
             DatumType::F16 => Ok(DataType::DtHalf),
+            DatumType::BF16 => Ok(DataType::DtBfloat16),
             DatumType::F32 => Ok(DataType::DtFloat),
             DatumType::F64 => Ok(DataType::DtDouble),
             DatumType::Blob => Ok(DataType::DtString),
@@ -86,15 +88,11 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
         let content = &t.tensor_content;
         let dtype = DataType::from_i32(t.dtype).unwrap();
         let mat: Tensor = if content.len() != 0 {
-            unsafe {
-                match dtype {
-                    DataType::DtFloat => Self::from_raw::<f32>(&dims, content)?,
-                    DataType::DtDouble => Self::from_raw::<f64>(&dims, content)?,
-                    DataType::DtInt32 => Self::from_raw::<i32>(&dims, content)?,
-                    DataType::DtInt64 => Self::from_raw::<i64>(&dims, content)?,
-                    _ => unimplemented!("missing type (for get_tensor_content) {:?}", dtype),
-                }
+            let dt = DatumType::try_from(dtype)?;
+            if !dt.is_copy() {
+                bail!("Raw tensor content is not supported for {:?}", dtype);
             }
+            unsafe { Self::from_raw_dt(dt, &dims, content)? }
         } else {
             match dtype {
                 DataType::DtInt32 => tensor_from_repeated_field(&*dims, t.int_val.to_vec())?,
@@ -114,7 +112,7 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
     }
 }
 
-fn empty_tensor_proto() -> TensorProto {
+pub(crate) fn empty_tensor_proto() -> TensorProto {
     TensorProto {
         dtype: 0,
         tensor_shape: None,
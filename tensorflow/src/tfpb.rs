@@ -236,6 +236,12 @@ impl<'a> From<&'a str> for AttrValue {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(t: bool) -> AttrValue {
+        AttrValue { value: Some(Value::B(t)) }
+    }
+}
+
 impl From<i32> for AttrValue {
     fn from(t: i32) -> AttrValue {
         AttrValue::from(t as i64)
@@ -0,0 +1,88 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::DtFloat;
+
+fn strat() -> BoxedStrategy<(Tensor, usize, usize, usize)> {
+    // rank, axis, number of splits, picked output
+    (1usize..4)
+        .prop_flat_map(|r| (vec(1usize..4, r..r + 1), 0..r, 1usize..4))
+        .prop_flat_map(|(mut dims, axis, n)| {
+            dims[axis] *= n;
+            (Just(dims), Just(axis), Just(n), 0..n)
+        })
+        .prop_map(|(dims, axis, n, pick)| {
+            let size = dims.iter().product::<usize>();
+            let t = tract_ndarray::Array::from_shape_vec(
+                dims,
+                (0..size).map(|i| i as f32).collect::<Vec<_>>(),
+            )
+            .unwrap();
+            (t.into(), axis, n, pick)
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn split((ref input, axis, n, pick) in strat()) {
+        let graph = tfpb::graph()
+            .node(placeholder_f32("input"))
+            .node(const_i32("axis", &tensor0(axis as i32)))
+            .node(tfpb::node().name("split").op("Split").input("axis").input("input")
+                  .attr("num_split", n as i64).attr("T", DtFloat))
+            .node(tfpb::node().name("op").op("Identity").input(format!("split:{}", pick))
+                  .attr("T", DtFloat));
+        let graph = graph.write_to_bytes().unwrap();
+        compare(&graph, vec!(("input", input.clone())), "op")?
+    }
+
+    #[test]
+    fn unpack((ref input, axis, _n, _pick) in strat()) {
+        let num = input.shape()[axis];
+        let graph = tfpb::graph()
+            .node(placeholder_f32("input"))
+            .node(tfpb::node().name("unpack").op("Unpack").input("input")
+                  .attr("num", num as i64).attr("axis", axis as i64).attr("T", DtFloat))
+            .node(tfpb::node().name("op").op("Identity").input(format!("unpack:{}", num - 1))
+                  .attr("T", DtFloat));
+        let graph = graph.write_to_bytes().unwrap();
+        compare(&graph, vec!(("input", input.clone())), "op")?
+    }
+}
+
+#[test]
+fn split_v() {
+    let input = tensor2(&[[0f32, 1., 2., 3., 4.], [5., 6., 7., 8., 9.]]);
+    let graph = tfpb::graph()
+        .node(placeholder_f32("input"))
+        .node(const_i32("sizes", &tensor1(&[2, -1])))
+        .node(const_i32("axis", &tensor0(1)))
+        .node(
+            tfpb::node()
+                .name("split")
+                .op("SplitV")
+                .input("input")
+                .input("sizes")
+                .input("axis")
+                .attr("num_split", 2i64)
+                .attr("T", DtFloat),
+        )
+        .node(tfpb::node().name("op").op("Identity").input("split:1").attr("T", DtFloat));
+    let graph = graph.write_to_bytes().unwrap();
+    compare(&graph, vec![("input", input)], "op").unwrap()
+}
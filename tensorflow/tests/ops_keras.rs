@@ -0,0 +1,248 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::{DtBool, DtFloat, DtInt32};
+use tract_tensorflow::tfpb::tensorflow::NodeDef;
+
+fn check(nodes: Vec<NodeDef>, inputs: Vec<(&str, Tensor)>) {
+    let mut graph = tfpb::graph();
+    for n in nodes {
+        graph = graph.node(n);
+    }
+    compare(&graph.write_to_bytes().unwrap(), inputs, "op").unwrap()
+}
+
+fn op(name: &str) -> NodeDef {
+    tfpb::node().name("op").op(name)
+}
+
+fn data() -> Tensor {
+    tensor2(&[[1.5f32, -2., 3., 0.5], [-1., 4., -0.5, 2.]])
+}
+
+#[test]
+fn arg_max() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            const_i32("axis", &tensor0(-1)),
+            op("ArgMax").input("x").input("axis").attr("output_type", DtInt32).attr("T", DtFloat),
+        ],
+        vec![("x", data())],
+    )
+}
+
+#[test]
+fn select() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            placeholder("c", DtBool, tensor_shape(&[2])),
+            tfpb::node().name("n").op("Neg").input("x").attr("T", DtFloat),
+            op("Select").input("c").input("x").input("n").attr("T", DtFloat),
+        ],
+        vec![("x", data()), ("c", tensor1(&[false, true]))],
+    )
+}
+
+#[test]
+fn select_v2() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            placeholder("c", DtBool, tensor_shape(&[4])),
+            tfpb::node().name("n").op("Neg").input("x").attr("T", DtFloat),
+            op("SelectV2").input("c").input("x").input("n").attr("T", DtFloat),
+        ],
+        vec![("x", data()), ("c", tensor1(&[false, true, true, false]))],
+    )
+}
+
+#[test]
+fn unary() {
+    for name in &["Exp", "Square", "LeakyRelu"] {
+        check(
+            vec![placeholder_f32("x"), op(name).input("x").attr("T", DtFloat)],
+            vec![("x", data())],
+        )
+    }
+    check(
+        vec![placeholder_f32("x"), op("Sqrt").input("x").attr("T", DtFloat)],
+        vec![("x", tensor1(&[0f32, 0.5, 4.]))],
+    )
+}
+
+#[test]
+fn squared_difference() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            const_f32("y", &tensor1(&[1f32, 2., 3., 4.])),
+            op("SquaredDifference").input("x").input("y").attr("T", DtFloat),
+        ],
+        vec![("x", data())],
+    )
+}
+
+#[test]
+fn one_hot() {
+    check(
+        vec![
+            placeholder_i32("i"),
+            const_i32("depth", &tensor0(4)),
+            const_f32("on", &tensor0(5f32)),
+            const_f32("off", &tensor0(-1f32)),
+            op("OneHot")
+                .input("i")
+                .input("depth")
+                .input("on")
+                .input("off")
+                .attr("axis", 0i64)
+                .attr("T", DtFloat)
+                .attr("TI", DtInt32),
+        ],
+        vec![("i", tensor2(&[[0i32, 3, -1], [2, 7, 1]]))],
+    )
+}
+
+#[test]
+fn cumsum() {
+    for &(exclusive, reverse) in &[(false, false), (true, false), (false, true), (true, true)] {
+        check(
+            vec![
+                placeholder_f32("x"),
+                const_i32("axis", &tensor0(1)),
+                op("Cumsum")
+                    .input("x")
+                    .input("axis")
+                    .attr("exclusive", exclusive)
+                    .attr("reverse", reverse)
+                    .attr("T", DtFloat),
+            ],
+            vec![("x", data())],
+        )
+    }
+}
+
+#[test]
+fn mirror_pad() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            const_i32("pads", &tensor2(&[[1, 1], [2, 3]])),
+            op("MirrorPad").input("x").input("pads").attr("mode", "REFLECT").attr("T", DtFloat),
+        ],
+        vec![("x", data())],
+    )
+}
+
+#[test]
+fn batch_mat_mul_v2() {
+    let b: Tensor =
+        tract_ndarray::Array::from_shape_fn((3, 4, 2), |(i, j, k)| (i + j * k) as f32).into();
+    check(
+        vec![
+            placeholder_f32("a"),
+            const_f32("b", &b),
+            op("BatchMatMulV2")
+                .input("a")
+                .input("b")
+                .attr("adj_x", false)
+                .attr("adj_y", false)
+                .attr("T", DtFloat),
+        ],
+        vec![("a", data())],
+    )
+}
+
+#[test]
+fn fused_batch_norm_v3() {
+    check(
+        vec![
+            placeholder_f32("x"),
+            const_f32("scale", &tensor1(&[0.5f32, 2.])),
+            const_f32("offset", &tensor1(&[1f32, -1.])),
+            const_f32("mean", &tensor1(&[0.2f32, 0.1])),
+            const_f32("var", &tensor1(&[1.5f32, 0.25])),
+            op("FusedBatchNormV3")
+                .input("x")
+                .input("scale")
+                .input("offset")
+                .input("mean")
+                .input("var")
+                .attr("epsilon", 0.001f32)
+                .attr("is_training", false)
+                .attr("T", DtFloat)
+                .attr("U", DtFloat),
+        ],
+        vec![("x", data().into_shape(&[1, 2, 2, 2]).unwrap())],
+    )
+}
+
+#[test]
+fn conv3d() {
+    let kernel: Tensor = tract_ndarray::Array::from_shape_fn((2, 2, 2, 2, 3), |(a, b, c, d, e)| {
+        (a + 2 * b + 3 * c + d * e) as f32
+    })
+    .into();
+    let input: Tensor = tract_ndarray::Array::from_shape_fn((1, 3, 4, 3, 2), |(_, a, b, c, d)| {
+        (a * b + c) as f32 - d as f32
+    })
+    .into();
+    for padding in &["VALID", "SAME"] {
+        check(
+            vec![
+                placeholder_f32("x"),
+                const_f32("k", &kernel),
+                op("Conv3D")
+                    .input("x")
+                    .input("k")
+                    .attr("strides", vec![1i64, 1, 2, 1, 1])
+                    .attr("padding", *padding)
+                    .attr("T", DtFloat),
+            ],
+            vec![("x", input.clone())],
+        )
+    }
+}
+
+#[test]
+fn conv2d_backprop_input() {
+    let kernel: Tensor = tract_ndarray::Array::from_shape_fn((3, 3, 2, 3), |(a, b, c, d)| {
+        (a + 2 * b + c * d) as f32
+    })
+    .into();
+    let input: Tensor =
+        tract_ndarray::Array::from_shape_fn((1, 3, 4, 3), |(_, a, b, c)| (a * b + c) as f32).into();
+    for (padding, sizes) in
+        &[("VALID", [1, 7, 9, 2]), ("SAME", [1, 6, 8, 2]), ("SAME", [1, 5, 7, 2])]
+    {
+        check(
+            vec![
+                placeholder_f32("x"),
+                const_i32("sizes", &tensor1(sizes)),
+                const_f32("k", &kernel),
+                op("Conv2DBackpropInput")
+                    .input("sizes")
+                    .input("k")
+                    .input("x")
+                    .attr("strides", vec![1i64, 2, 2, 1])
+                    .attr("padding", *padding)
+                    .attr("T", DtFloat),
+            ],
+            vec![("x", input.clone())],
+        )
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::DtFloat;
+
+fn img_and_size() -> BoxedStrategy<(Tensor, (usize, usize))> {
+    (1usize..3, 1usize..6, 1usize..6, 1usize..3, 1usize..10, 1usize..10)
+        .prop_map(|(n, h, w, c, oh, ow)| {
+            let size = n * h * w * c;
+            let t = tract_ndarray::Array::from_shape_vec(
+                (n, h, w, c),
+                (0..size).map(|i| i as f32).collect::<Vec<_>>(),
+            )
+            .unwrap();
+            (t.into(), (oh, ow))
+        })
+        .boxed()
+}
+
+fn resize_pb(op: &str, size: (usize, usize), align_corners: bool, half_pixel: bool) -> Vec<u8> {
+    tfpb::graph()
+        .node(placeholder_f32("input"))
+        .node(const_i32("size", &tensor1(&[size.0 as i32, size.1 as i32])))
+        .node(
+            tfpb::node()
+                .name("op")
+                .op(op)
+                .input("input")
+                .input("size")
+                .attr("align_corners", align_corners)
+                .attr("half_pixel_centers", half_pixel)
+                .attr("T", DtFloat),
+        )
+        .write_to_bytes()
+        .unwrap()
+}
+
+proptest! {
+    #[test]
+    fn resize_bilinear((ref i, size) in img_and_size(), mode in 0usize..3) {
+        let graph = resize_pb("ResizeBilinear", size, mode == 1, mode == 2);
+        compare(&graph, vec!(("input", i.clone())), "op")?;
+    }
+
+    #[test]
+    fn resize_nearest_neighbor((ref i, size) in img_and_size(), mode in 0usize..3) {
+        let graph = resize_pb("ResizeNearestNeighbor", size, mode == 1, mode == 2);
+        compare(&graph, vec!(("input", i.clone())), "op")?;
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::{DtFloat, DtInt32};
+
+fn strat() -> BoxedStrategy<(Tensor, usize)> {
+    // input and k. values are distinct so that ties do not make indices ambiguous
+    (1usize..4)
+        .prop_flat_map(|r| vec(1usize..5, r..r + 1))
+        .prop_flat_map(|dims| {
+            let last = dims[dims.len() - 1];
+            (Just(dims), 0..last + 1)
+        })
+        .prop_map(|(dims, k)| {
+            let size = dims.iter().product::<usize>();
+            let t = tract_ndarray::Array::from_shape_vec(
+                dims,
+                (0..size).map(|i| ((i * 7919) % size) as f32).collect::<Vec<_>>(),
+            )
+            .unwrap();
+            (t.into(), k)
+        })
+        .boxed()
+}
+
+fn topk_pb(k: usize, output: usize, dt: tfpb::tensorflow::DataType) -> Vec<u8> {
+    tfpb::graph()
+        .node(placeholder_f32("input"))
+        .node(const_i32("k", &tensor0(k as i32)))
+        .node(
+            tfpb::node()
+                .name("topk")
+                .op("TopKV2")
+                .input("input")
+                .input("k")
+                .attr("sorted", true)
+                .attr("T", DtFloat),
+        )
+        .node(
+            tfpb::node().name("op").op("Identity").input(format!("topk:{}", output)).attr("T", dt),
+        )
+        .write_to_bytes()
+        .unwrap()
+}
+
+proptest! {
+    #[test]
+    fn topk_v2_values((ref input, k) in strat()) {
+        let graph = topk_pb(k, 0, DtFloat);
+        compare(&graph, vec!(("input", input.clone())), "op")?
+    }
+
+    #[test]
+    fn topk_v2_indices((ref input, k) in strat()) {
+        let graph = topk_pb(k, 1, DtInt32);
+        compare(&graph, vec!(("input", input.clone())), "op")?
+    }
+}